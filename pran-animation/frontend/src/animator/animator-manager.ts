@@ -34,6 +34,12 @@ export class AnimatorManager {
     return new AnimatorManager(canvasController, imagesMap);
   }
 
+  // Images already loaded with the same id are replaced, clones of this manager see the new images too
+  public async loadImages(imagesToLoad: [id: string, url: string][]): Promise<void> {
+    const imagesMap: Map<string, HTMLImageElement> = await AnimatorManager._loadAllImages(imagesToLoad);
+    imagesMap.forEach((image, id) => this._imagesMap.set(id, image));
+  }
+
   public animate(animator: Animator, ...animations: ManagerTimelineConfig[]): Animator;
   public animate(...animations: ManagerTimelineAction[][]): Animator;
   public animate(maybeAnimator: Animator | ManagerTimelineAction[], ...animations: ManagerTimelineConfig[]): Animator {
@@ -0,0 +1,50 @@
+use pran_droid_core::application::backup::dtos::backup_dto::ImportBackupModeDto;
+use pran_droid_core::application::backup::export::export_backup;
+use pran_droid_core::application::backup::import::{import_backup, ImportBackupRequest};
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use pran_droid_core::domain::images::image_repository::ImageRepository;
+use pran_droid_core::domain::images::image_storage::ImageStorage;
+use pran_droid_core::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+use crate::backup::models::backup_model::{BackupModel, ImportBackupResultResponse};
+
+pub const BACKUP_USAGE: &str = "Usage: backup export <file> | backup import <file> [--replace]";
+
+#[derive(Debug, PartialEq)]
+pub enum BackupCommand {
+    Export { path: String },
+    Import { path: String, mode: ImportBackupModeDto },
+}
+
+impl BackupCommand {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        match args.iter().map(String::as_str).collect::<Vec<&str>>().as_slice() {
+            ["backup", "export", path] => Ok(BackupCommand::Export { path: path.to_string() }),
+            ["backup", "import", path] => Ok(BackupCommand::Import { path: path.to_string(), mode: ImportBackupModeDto::Merge }),
+            ["backup", "import", path, "--replace"] => Ok(BackupCommand::Import { path: path.to_string(), mode: ImportBackupModeDto::Replace }),
+            _ => Err(String::from(BACKUP_USAGE)),
+        }
+    }
+}
+
+pub async fn run_backup_command(command: BackupCommand, reaction_repository: &dyn ReactionDefinitionRepository, emotion_repository: &dyn EmotionRepository, image_repository: &dyn ImageRepository, image_storage: &dyn ImageStorage) -> Result<String, String> {
+    match command {
+        BackupCommand::Export { path } => {
+            let backup: BackupModel = export_backup(reaction_repository, emotion_repository, image_repository, image_storage).await
+                .map_err(|error| error.to_string())?
+                .into();
+            let content = serde_json::to_vec_pretty(&backup).map_err(|error| error.to_string())?;
+            tokio::fs::write(&path, content).await.map_err(|error| error.to_string())?;
+
+            Ok(format!("Backup exported to {}", path))
+        },
+        BackupCommand::Import { path, mode } => {
+            let content = tokio::fs::read(&path).await.map_err(|error| error.to_string())?;
+            let backup: BackupModel = serde_json::from_slice(&content).map_err(|error| error.to_string())?;
+            let result: ImportBackupResultResponse = import_backup(ImportBackupRequest { backup: backup.try_into()?, mode }, reaction_repository, emotion_repository, image_repository, image_storage).await
+                .map_err(|error| error.to_string())?
+                .into();
+
+            serde_json::to_string_pretty(&result).map_err(|error| error.to_string())
+        },
+    }
+}
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+use rocket::response::{Responder, status};
+use rocket::serde::json::Json;
+use rocket::{Request, response, State};
+use rocket::http::Status;
+use pran_droid_core::application::backup::export::{export_backup, ExportBackupError};
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use pran_droid_core::domain::images::image_repository::ImageRepository;
+use pran_droid_core::domain::images::image_storage::ImageStorage;
+use pran_droid_core::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+use crate::backup::models::backup_model::BackupModel;
+use crate::infrastructure::authenticated::Authenticated;
+
+#[get("/backup")]
+pub async fn api_export_backup(_authenticated: Authenticated, reaction_repo: &State<Arc<dyn ReactionDefinitionRepository>>, emotion_repo: &State<Arc<dyn EmotionRepository>>, image_repo: &State<Arc<dyn ImageRepository>>, storage: &State<Arc<dyn ImageStorage>>) -> Result<Json<BackupModel>, Error> {
+    Ok(Json(export_backup(reaction_repo.as_ref(), emotion_repo.as_ref(), image_repo.as_ref(), storage.as_ref()).await?.into()))
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("{0:?}")]
+    ExportBackupError(#[from] ExportBackupError)
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        match self {
+            Error::ExportBackupError(error) => {
+                match error {
+                    ExportBackupError::MissingImageData(_) => status::Custom(Status::InternalServerError, error.to_string()).respond_to(req)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+use rocket::response::{Responder, status};
+use rocket::serde::json::Json;
+use rocket::serde::Deserialize;
+use rocket::{Data, Request, response, State};
+use rocket::data::Limits;
+use rocket::http::Status;
+use pran_droid_core::application::backup::import::{import_backup, ImportBackupError, ImportBackupRequest};
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use pran_droid_core::domain::images::image_repository::ImageRepository;
+use pran_droid_core::domain::images::image_storage::ImageStorage;
+use pran_droid_core::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+use crate::backup::models::backup_model::{BackupModel, ImportBackupModeModel, ImportBackupResultResponse};
+use crate::infrastructure::authenticated::Authenticated;
+
+// Backups embed every image, they are read with their own limit instead of the json one
+pub const BACKUP_LIMIT: &str = "backup";
+
+#[post("/backup", format = "json", data = "<payload>")]
+pub async fn api_import_backup(_authenticated: Authenticated, payload: Data<'_>, limits: &Limits, reaction_repo: &State<Arc<dyn ReactionDefinitionRepository>>, emotion_repo: &State<Arc<dyn EmotionRepository>>, image_repo: &State<Arc<dyn ImageRepository>>, storage: &State<Arc<dyn ImageStorage>>) -> Result<Json<ImportBackupResultResponse>, Error> {
+    let payload = payload.open(limits.get(BACKUP_LIMIT).unwrap_or(Limits::JSON)).into_string().await
+        .map_err(|error| Error::BadRequest(error.to_string()))?;
+    if !payload.is_complete() {
+        return Err(Error::TooLarge);
+    }
+    let payload: ImportBackupApiRequest = serde_json::from_str(&payload).map_err(|error| Error::BadRequest(error.to_string()))?;
+    let request = payload.try_into_request().map_err(Error::BadRequest)?;
+    Ok(Json(import_backup(request, reaction_repo.as_ref(), emotion_repo.as_ref(), image_repo.as_ref(), storage.as_ref()).await?.into()))
+}
+
+#[derive(Deserialize)]
+pub struct ImportBackupApiRequest {
+    #[serde(default)]
+    mode: ImportBackupModeModel,
+    backup: BackupModel
+}
+
+impl ImportBackupApiRequest {
+    fn try_into_request(self) -> Result<ImportBackupRequest, String> {
+        Ok(ImportBackupRequest { backup: self.backup.try_into()?, mode: self.mode.into() })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("Backup is too large")]
+    TooLarge,
+    #[error("{0:?}")]
+    ImportBackupError(#[from] ImportBackupError)
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        match self {
+            Error::BadRequest(msg) => status::BadRequest(Some(msg)).respond_to(req),
+            Error::TooLarge => Status::PayloadTooLarge.respond_to(req),
+            Error::ImportBackupError(error) => {
+                match error {
+                    ImportBackupError::BadRequest(_) | ImportBackupError::MissingImage(_, _) | ImportBackupError::MissingEmotion(_, _) =>
+                        status::BadRequest(Some(error.to_string())).respond_to(req),
+                    ImportBackupError::StorageFail => Status::InternalServerError.respond_to(req),
+                    ImportBackupError::Unexpected => Status::InternalServerError.respond_to(req)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod export;
+pub mod import;
+pub mod cli;
+pub mod models;
\ No newline at end of file
@@ -0,0 +1,89 @@
+use rocket::serde::{Deserialize, Serialize};
+use pran_droid_core::application::backup::dtos::backup_dto::{BackupDto, BackupImageDto, ImportBackupModeDto, ImportBackupResultDto};
+use crate::emotions::responses::emotion_response::EmotionResponse;
+use crate::reactions::models::reaction_model::ReactionResponse;
+
+// Bumped whenever the archive layout changes, imports only accept the current one
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+#[derive(Deserialize, Serialize)]
+pub struct BackupModel {
+    version: u32,
+    reactions: Vec<ReactionResponse>,
+    emotions: Vec<EmotionResponse>,
+    images: Vec<BackupImageModel>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct BackupImageModel {
+    id: String,
+    data: String,
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportBackupModeModel {
+    #[default]
+    Merge,
+    Replace,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportBackupResultResponse {
+    imported_reactions: usize,
+    imported_emotions: usize,
+    imported_images: usize,
+    conflicts: Vec<String>,
+}
+
+impl From<BackupDto> for BackupModel {
+    fn from(dto: BackupDto) -> BackupModel {
+        BackupModel {
+            version: BACKUP_FORMAT_VERSION,
+            reactions: dto.reactions.into_iter().map(From::from).collect(),
+            emotions: dto.emotions.into_iter().map(From::from).collect(),
+            images: dto.images.into_iter().map(|image| BackupImageModel { id: image.id, data: base64::encode(image.data) }).collect(),
+        }
+    }
+}
+
+impl TryFrom<BackupModel> for BackupDto {
+    type Error = String;
+
+    fn try_from(model: BackupModel) -> Result<BackupDto, Self::Error> {
+        if model.version != BACKUP_FORMAT_VERSION {
+            return Err(format!("Backup version {} is not supported, expected {}", model.version, BACKUP_FORMAT_VERSION));
+        }
+
+        Ok(BackupDto {
+            reactions: model.reactions.into_iter().map(From::from).collect(),
+            emotions: model.emotions.into_iter().map(From::from).collect(),
+            images: model.images.into_iter()
+                .map(|image| base64::decode(&image.data)
+                    .map(|data| BackupImageDto { id: image.id.clone(), data })
+                    .map_err(|_| format!("Image: {} data is not valid base64", image.id)))
+                .collect::<Result<Vec<BackupImageDto>, String>>()?,
+        })
+    }
+}
+
+impl From<ImportBackupModeModel> for ImportBackupModeDto {
+    fn from(model: ImportBackupModeModel) -> ImportBackupModeDto {
+        match model {
+            ImportBackupModeModel::Merge => ImportBackupModeDto::Merge,
+            ImportBackupModeModel::Replace => ImportBackupModeDto::Replace,
+        }
+    }
+}
+
+impl From<ImportBackupResultDto> for ImportBackupResultResponse {
+    fn from(dto: ImportBackupResultDto) -> ImportBackupResultResponse {
+        ImportBackupResultResponse {
+            imported_reactions: dto.imported_reactions,
+            imported_emotions: dto.imported_emotions,
+            imported_images: dto.imported_images,
+            conflicts: dto.conflicts,
+        }
+    }
+}
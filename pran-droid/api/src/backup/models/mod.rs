@@ -0,0 +1 @@
+pub mod backup_model;
\ No newline at end of file
@@ -1,2 +1,3 @@
+pub mod responses;
 pub mod simulate_message;
 pub mod simulate_action;
\ No newline at end of file
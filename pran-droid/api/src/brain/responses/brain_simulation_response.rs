@@ -0,0 +1,10 @@
+use rocket::serde::Serialize;
+use pran_droid_brain::brain_output::chat_output::ChatOutputMessage;
+use pran_droid_brain::brain_output::outputs::ReactionOutput;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrainSimulationResponse {
+    pub reaction: Option<ReactionOutput>,
+    pub chat_messages: Vec<ChatOutputMessage>,
+}
@@ -0,0 +1 @@
+pub mod brain_simulation_response;
\ No newline at end of file
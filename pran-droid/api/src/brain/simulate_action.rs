@@ -2,15 +2,20 @@ use std::sync::Arc;
 use serde::Deserialize;
 use rocket::serde::json::Json;
 use rocket::{State};
-use pran_droid_brain::brain_output::outputs::ReactionOutput;
+use pran_droid_brain::brain_output::chat_output::InMemoryChatOutput;
 use pran_droid_brain::simulate::simulate_droid_brain;
 use pran_droid_core::domain::brain::stimuli::{Action, ActionStimulus, Source, Stimulus};
 use pran_droid_core::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+use pran_droid_core::domain::visemes::mouth_track::MouthTrackModel;
+use crate::brain::responses::brain_simulation_response::BrainSimulationResponse;
 use crate::infrastructure::authenticated::Authenticated;
 
 #[post("/brain/simulation/action", format = "json", data = "<payload>")]
-pub async fn api_brain_simulate_action(_authenticated: Authenticated, payload: Json<BrainSimulateActionApiRequest>, reaction_repository: &State<Arc<dyn ReactionDefinitionRepository>>) -> Json<Option<ReactionOutput>> {
-    Json(simulate_droid_brain(reaction_repository.as_ref(), payload.0.into()).await)
+pub async fn api_brain_simulate_action(_authenticated: Authenticated, payload: Json<BrainSimulateActionApiRequest>, reaction_repository: &State<Arc<dyn ReactionDefinitionRepository>>, mouth_track_model: &State<Arc<MouthTrackModel>>) -> Json<BrainSimulationResponse> {
+    let chat_output = InMemoryChatOutput::new();
+    let reaction = simulate_droid_brain(reaction_repository.as_ref(), payload.0.into(), &chat_output, mouth_track_model.inner()).await;
+
+    Json(BrainSimulationResponse { reaction, chat_messages: chat_output.messages() })
 }
 
 #[derive(Deserialize)]
@@ -18,8 +23,16 @@ pub async fn api_brain_simulate_action(_authenticated: Authenticated, payload: J
 pub struct BrainSimulateActionApiRequest {
     user_name: String,
     is_mod: bool,
+    #[serde(default)]
+    is_broadcaster: bool,
+    #[serde(default)]
+    is_vip: bool,
+    #[serde(default)]
+    is_subscriber: bool,
     id: String,
-    name: String
+    name: String,
+    #[serde(default)]
+    input: Option<String>,
 }
 
 impl Into<Stimulus> for BrainSimulateActionApiRequest {
@@ -27,12 +40,16 @@ impl Into<Stimulus> for BrainSimulateActionApiRequest {
         Stimulus::Action(ActionStimulus {
             source: Source {
                 is_mod: self.is_mod,
+                is_broadcaster: self.is_broadcaster,
+                is_vip: self.is_vip,
+                is_subscriber: self.is_subscriber,
                 user_name: self.user_name
             },
             action: Action {
                 id: self.id,
                 name: self.name
-            }
+            },
+            input: self.input
         })
     }
 }
\ No newline at end of file
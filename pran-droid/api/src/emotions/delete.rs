@@ -0,0 +1,34 @@
+use std::sync::Arc;
+use rocket::response::{Responder, status};
+use rocket::{Request, response, State};
+use rocket::http::Status;
+use pran_droid_core::application::emotions::delete::{delete_emotion, DeleteEmotionError, DeleteEmotionRequest};
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use pran_droid_core::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+use crate::infrastructure::authenticated::Authenticated;
+
+#[delete("/emotions/<emotion_id>")]
+pub async fn api_delete_emotion(_authenticated: Authenticated, emotion_id: String, repo: &State<Arc<dyn EmotionRepository>>, reaction_repo: &State<Arc<dyn ReactionDefinitionRepository>>) -> Result<(), Error> {
+    delete_emotion(DeleteEmotionRequest { id: emotion_id }, repo.as_ref(), reaction_repo.as_ref()).await?;
+    Ok(())
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("{0:?}")]
+    DeleteEmotionError(#[from] DeleteEmotionError)
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        match self {
+            Error::DeleteEmotionError(error) => {
+                match error {
+                    DeleteEmotionError::NotFound => Status::NotFound.respond_to(req),
+                    DeleteEmotionError::InUse(references) => status::Conflict(Some(format!("Emotion still used by {}", references.join(", ")))).respond_to(req),
+                    DeleteEmotionError::Unexpected => Status::InternalServerError.respond_to(req)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use std::sync::Arc;
+use rocket::serde::json::Json;
+use rocket::State;
+use pran_droid_core::application::emotions::get::{get_emotion, GetEmotionRequest};
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use crate::infrastructure::authenticated::AuthenticatedReadOnly;
+use crate::emotions::responses::emotion_response::EmotionResponse;
+
+#[get("/emotions/<emotion_id>")]
+pub async fn api_get_emotion(_authenticated: AuthenticatedReadOnly, emotion_id: String, repo: &State<Arc<dyn EmotionRepository>>) -> Option<Json<EmotionResponse>> {
+    get_emotion(GetEmotionRequest { id: emotion_id }, repo.as_ref()).await
+        .map(|emotion| Json(emotion.into()))
+}
@@ -1,3 +1,9 @@
 pub mod get_all;
 pub mod create;
+pub mod delete;
+pub mod get;
+pub mod update_layer;
+pub mod remove_layer;
+pub mod reorder_layers;
+pub mod update_mouth_mapping;
 pub mod responses;
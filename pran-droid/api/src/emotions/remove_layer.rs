@@ -0,0 +1,32 @@
+use std::sync::Arc;
+use rocket::response::{Responder, status};
+use rocket::{Request, response, State};
+use rocket::http::Status;
+use pran_droid_core::application::emotions::remove_layer::{remove_emotion_layer, RemoveEmotionLayerError, RemoveEmotionLayerRequest};
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use crate::infrastructure::authenticated::Authenticated;
+
+#[delete("/emotions/<emotion_id>/layers/<index>")]
+pub async fn api_remove_emotion_layer(_authenticated: Authenticated, emotion_id: String, index: usize, repo: &State<Arc<dyn EmotionRepository>>) -> Result<(), Error> {
+    remove_emotion_layer(RemoveEmotionLayerRequest { emotion_id, index }, repo.as_ref()).await?;
+    Ok(())
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("{0:?}")]
+    RemoveEmotionLayerError(#[from] RemoveEmotionLayerError)
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        match self {
+            Error::RemoveEmotionLayerError(error) => {
+                match error {
+                    RemoveEmotionLayerError::NotExistingMainAggregate => Status::NotFound.respond_to(req),
+                    RemoveEmotionLayerError::BadRequest(msg) => status::BadRequest(Some(msg)).respond_to(req)
+                }
+            }
+        }
+    }
+}
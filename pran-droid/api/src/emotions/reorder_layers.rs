@@ -0,0 +1,48 @@
+use std::sync::Arc;
+use rocket::response::{Responder, status};
+use rocket::serde::json::Json;
+use rocket::serde::Deserialize;
+use rocket::{Request, response, State};
+use rocket::http::Status;
+use pran_droid_core::application::emotions::reorder_layers::{reorder_emotion_layers, ReorderEmotionLayersError, ReorderEmotionLayersRequest};
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use crate::infrastructure::authenticated::Authenticated;
+
+#[put("/emotions/<emotion_id>/layers/order", format = "json", data = "<payload>")]
+pub async fn api_reorder_emotion_layers(_authenticated: Authenticated, emotion_id: String, payload: Json<ReorderEmotionLayersApiRequest>, repo: &State<Arc<dyn EmotionRepository>>) -> Result<(), Error> {
+    reorder_emotion_layers(payload.0.into_request(emotion_id), repo.as_ref()).await?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct ReorderEmotionLayersApiRequest {
+    order: Vec<usize>
+}
+
+impl ReorderEmotionLayersApiRequest {
+    fn into_request(self, emotion_id: String) -> ReorderEmotionLayersRequest {
+        ReorderEmotionLayersRequest {
+            emotion_id,
+            order: self.order
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("{0:?}")]
+    ReorderEmotionLayersError(#[from] ReorderEmotionLayersError)
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        match self {
+            Error::ReorderEmotionLayersError(error) => {
+                match error {
+                    ReorderEmotionLayersError::NotExistingMainAggregate => Status::NotFound.respond_to(req),
+                    ReorderEmotionLayersError::BadRequest(msg) => status::BadRequest(Some(msg)).respond_to(req)
+                }
+            }
+        }
+    }
+}
@@ -1,16 +1,16 @@
 ﻿use std::collections::HashMap;
 use pran_droid_core::application::emotions::dtos::emotion_dto::{EmotionDto, EmotionLayerDto};
-use rocket::serde::Serialize;
+use rocket::serde::{Deserialize, Serialize};
 use crate::reactions::models::reaction_step_model::AnimationFrameModel;
 
-#[derive(Serialize)]
+#[derive(Deserialize, Serialize)]
 pub struct EmotionResponse {
     id: String,
     name: String,
     layers: Vec<EmotionLayerResponse>,
 }
 
-#[derive(Serialize)]
+#[derive(Deserialize, Serialize)]
 #[serde(tag = "type")]
 pub enum EmotionLayerResponse {
     Animation { frames: Vec<AnimationFrameModel> },
@@ -28,6 +28,16 @@ impl From<EmotionDto> for EmotionResponse {
     }
 }
 
+impl From<EmotionResponse> for EmotionDto {
+    fn from(response: EmotionResponse) -> EmotionDto {
+        EmotionDto {
+            id: response.id,
+            name: response.name,
+            animation: response.layers.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
 impl From<EmotionLayerDto> for EmotionLayerResponse {
     fn from(dto: EmotionLayerDto) -> EmotionLayerResponse {
         match dto {
@@ -36,4 +46,14 @@ impl From<EmotionLayerDto> for EmotionLayerResponse {
             EmotionLayerDto::Mouth { mouth_mapping } => EmotionLayerResponse::Mouth { mouth_mapping }
         }
     }
+}
+
+impl From<EmotionLayerResponse> for EmotionLayerDto {
+    fn from(response: EmotionLayerResponse) -> EmotionLayerDto {
+        match response {
+            EmotionLayerResponse::Animation { frames } =>
+                EmotionLayerDto::Animation(frames.into_iter().map(Into::into).collect()),
+            EmotionLayerResponse::Mouth { mouth_mapping } => EmotionLayerDto::Mouth { mouth_mapping }
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,54 @@
+use std::sync::Arc;
+use rocket::response::{Responder, status};
+use rocket::serde::json::Json;
+use rocket::serde::Deserialize;
+use rocket::{Request, response, State};
+use rocket::http::Status;
+use pran_droid_core::application::emotions::update_layer::{AddEmotionAnimationLayerError, AddEmotionAnimationLayerRequest, update_emotion_animation_layer};
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use pran_droid_core::domain::images::image_repository::ImageRepository;
+use crate::infrastructure::authenticated::Authenticated;
+use crate::reactions::models::reaction_step_model::AnimationFrameModel;
+
+#[put("/emotions/<emotion_id>/layers", format = "json", data = "<payload>")]
+pub async fn api_update_emotion_layer(_authenticated: Authenticated, emotion_id: String, payload: Json<UpdateEmotionLayerApiRequest>, repo: &State<Arc<dyn EmotionRepository>>, image_repo: &State<Arc<dyn ImageRepository>>) -> Result<(), Error> {
+    update_emotion_animation_layer(payload.0.into_request(emotion_id), repo.as_ref(), image_repo.as_ref()).await?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct UpdateEmotionLayerApiRequest {
+    index: usize,
+    frames: Vec<AnimationFrameModel>
+}
+
+impl UpdateEmotionLayerApiRequest {
+    fn into_request(self, emotion_id: String) -> AddEmotionAnimationLayerRequest {
+        AddEmotionAnimationLayerRequest {
+            emotion_id,
+            index: self.index,
+            animation: self.frames.into_iter().map(Into::into).collect()
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("{0:?}")]
+    AddEmotionAnimationLayerError(#[from] AddEmotionAnimationLayerError)
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        match self {
+            Error::AddEmotionAnimationLayerError(error) => {
+                match error {
+                    AddEmotionAnimationLayerError::WrongAnimationRequest(internal_error) =>
+                        status::BadRequest(Some(format!("{:?}", internal_error))).respond_to(req),
+                    AddEmotionAnimationLayerError::NotExistingMainAggregate => Status::NotFound.respond_to(req),
+                    AddEmotionAnimationLayerError::BadRequest(msg) => status::BadRequest(Some(msg)).respond_to(req)
+                }
+            }
+        }
+    }
+}
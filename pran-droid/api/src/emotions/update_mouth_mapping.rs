@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use rocket::response::{Responder, status};
+use rocket::serde::json::Json;
+use rocket::serde::Deserialize;
+use rocket::{Request, response, State};
+use rocket::http::Status;
+use pran_droid_core::application::emotions::update_mouth_mapping::{update_emotion_mouth_mapping, UpdateEmotionMouthMappingElementRequest, UpdateEmotionMouthMappingError, UpdateEmotionMouthMappingRequest};
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use pran_droid_core::domain::images::image_repository::ImageRepository;
+use crate::infrastructure::authenticated::Authenticated;
+
+#[put("/emotions/<emotion_id>/mouth-mapping", format = "json", data = "<payload>")]
+pub async fn api_update_emotion_mouth_mapping(_authenticated: Authenticated, emotion_id: String, payload: Json<UpdateEmotionMouthMappingApiRequest>, repo: &State<Arc<dyn EmotionRepository>>, image_repo: &State<Arc<dyn ImageRepository>>) -> Result<(), Error> {
+    update_emotion_mouth_mapping(payload.0.into_request(emotion_id), repo.as_ref(), image_repo.as_ref()).await?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateEmotionMouthMappingApiRequest {
+    mouth_mapping: HashMap<String, String>
+}
+
+impl UpdateEmotionMouthMappingApiRequest {
+    fn into_request(self, emotion_id: String) -> UpdateEmotionMouthMappingRequest {
+        UpdateEmotionMouthMappingRequest {
+            emotion_id,
+            mapping: self.mouth_mapping
+                .into_iter()
+                .map(|(name, image_id)| UpdateEmotionMouthMappingElementRequest { name, image_id })
+                .collect()
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("{0:?}")]
+    UpdateEmotionMouthMappingError(#[from] UpdateEmotionMouthMappingError)
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        match self {
+            Error::UpdateEmotionMouthMappingError(error) => {
+                match error {
+                    UpdateEmotionMouthMappingError::NotExistingMainAggregate => Status::NotFound.respond_to(req),
+                    UpdateEmotionMouthMappingError::BadRequest(msg) => status::BadRequest(Some(msg)).respond_to(req)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+use rocket::response::{Responder, status};
+use rocket::{Request, response, State};
+use rocket::http::Status;
+use pran_droid_core::application::images::delete::{delete_image, DeleteImageError, DeleteImageRequest};
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use pran_droid_core::domain::images::image_repository::ImageRepository;
+use pran_droid_core::domain::images::image_storage::ImageStorage;
+use pran_droid_core::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+use crate::infrastructure::authenticated::Authenticated;
+
+#[delete("/images/<image_id>")]
+pub async fn api_delete_image(_authenticated: Authenticated, image_id: String, repo: &State<Arc<dyn ImageRepository>>, storage: &State<Arc<dyn ImageStorage>>, emotion_repo: &State<Arc<dyn EmotionRepository>>, reaction_repo: &State<Arc<dyn ReactionDefinitionRepository>>) -> Result<(), Error> {
+    delete_image(DeleteImageRequest { id: image_id }, repo.as_ref(), storage.as_ref(), emotion_repo.as_ref(), reaction_repo.as_ref()).await?;
+    Ok(())
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("{0:?}")]
+    DeleteImageError(#[from] DeleteImageError)
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        match self {
+            Error::DeleteImageError(error) => {
+                match error {
+                    DeleteImageError::NotFound => Status::NotFound.respond_to(req),
+                    DeleteImageError::InUse(references) => status::Conflict(Some(format!("Image still used by {}", references.join(", ")))).respond_to(req),
+                    DeleteImageError::StorageFail => Status::InternalServerError.respond_to(req),
+                    DeleteImageError::Unexpected => Status::InternalServerError.respond_to(req)
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub mod get_all;
 pub mod create;
+pub mod delete;
 pub mod responses;
 pub mod get_from_storage;
\ No newline at end of file
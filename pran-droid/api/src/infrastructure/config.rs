@@ -9,12 +9,20 @@ pub struct Config {
     pub deta_project_id: String,
     pub read_api_secret_key: String,
     pub write_api_secret_key: String,
+    pub sqlite_path: String,
+    pub images_path: String,
+    pub twitch_client_id: String,
+    pub twitch_client_secret: String,
+    pub twitch_redirect_uri: String,
+    pub twitch_credentials_path: String,
+    pub viseme_mapping_path: Option<String>,
     pub mode: RuntimeMode,
 }
 
 #[derive(Debug)]
 pub enum RuntimeMode {
     Development,
+    Local,
     Production
 }
 
@@ -24,6 +32,7 @@ impl FromStr for RuntimeMode {
     fn from_str(input: &str) -> Result<RuntimeMode, Self::Err> {
         match input {
             "Development" => Ok(RuntimeMode::Development),
+            "Local" => Ok(RuntimeMode::Local),
             "Production" => Ok(RuntimeMode::Production),
             _ => Err(()),
         }
@@ -32,14 +41,25 @@ impl FromStr for RuntimeMode {
 
 impl Config {
     pub fn new() -> Config {
+        let mode = env::var("MODE").or_else(|_| Ok("Development".to_string())).and_then(|mode| mode.parse::<RuntimeMode>()).expect("MODE not a mode, can be Development, Local or Production");
+        let is_production = matches!(mode, RuntimeMode::Production);
+        let api_port = env::var("API_PORT").or(Ok("8000".to_string())).and_then(|port| port.parse::<u16>()).expect("API_PORT not a number");
+
         Config {
             static_path: env::var("STATIC_PATH").expect("STATIC_PATH missing in env variables. .env not existing?"),
-            api_port: env::var("API_PORT").or(Ok("8000".to_string())).and_then(|port| port.parse::<u16>()).expect("API_PORT not a number"),
-            deta_project_key: env::var("DETA_PROJECT_KEY").expect("DETA_PROJECT_KEY missing in env variables"),
-            deta_project_id: env::var("DETA_PROJECT_ID").expect("DETA_PROJECT_ID missing in env variables"),
+            api_port,
+            deta_project_key: env::var("DETA_PROJECT_KEY").or_else(|error| if is_production { Err(error) } else { Ok(String::new()) }).expect("DETA_PROJECT_KEY missing in env variables"),
+            deta_project_id: env::var("DETA_PROJECT_ID").or_else(|error| if is_production { Err(error) } else { Ok(String::new()) }).expect("DETA_PROJECT_ID missing in env variables"),
             read_api_secret_key: env::var("READ_API_SECRET_KEY").expect("READ_API_SECRET_KEY missing in env variables"),
             write_api_secret_key: env::var("WRITE_API_SECRET_KEY").expect("WRITE_API_SECRET_KEY missing in env variables"),
-            mode: env::var("MODE").or_else(|_| Ok("Development".to_string())).and_then(|mode| mode.parse::<RuntimeMode>()).expect("MODE not a mode, can be Development or Production")
+            sqlite_path: env::var("SQLITE_PATH").unwrap_or_else(|_| "pran_droid.sqlite".to_string()),
+            images_path: env::var("IMAGES_PATH").unwrap_or_else(|_| "images".to_string()),
+            twitch_client_id: env::var("TWITCH_CLIENT_ID").unwrap_or_default(),
+            twitch_client_secret: env::var("TWITCH_CLIENT_SECRET").unwrap_or_default(),
+            twitch_redirect_uri: env::var("TWITCH_REDIRECT_URI").unwrap_or_else(|_| format!("http://localhost:{}/api/twitch/authorize/callback", api_port)),
+            twitch_credentials_path: env::var("TWITCH_CREDENTIALS_PATH").unwrap_or_else(|_| "twitch_credentials.json".to_string()),
+            viseme_mapping_path: env::var("VISEME_MAPPING_PATH").ok().filter(|path| !path.is_empty()),
+            mode,
         }
     }
 }
\ No newline at end of file
@@ -6,11 +6,18 @@ use rocket::{figment::{Figment, providers::Env}, Config as RocketConfig, State};
 use rocket::data::{Limits, ToByteUnit};
 use rocket::fs::{FileServer, NamedFile};
 use simplelog::SimpleLogger;
+use std::env;
+use std::path::Path;
 use std::sync::Arc;
+use pran_droid_brain::authentication::twitch_oauth_client::TwitchOAuthClient;
+use pran_droid_brain::viseme_mapping_file::read_viseme_mapping_file;
+use pran_droid_core::domain::credentials::twitch_credentials_repository::TwitchCredentialsRepository;
 use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
 use pran_droid_core::domain::images::image_repository::ImageRepository;
 use pran_droid_core::domain::images::image_storage::ImageStorage;
 use pran_droid_core::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+use pran_droid_core::domain::visemes::mouth_track::MouthTrackModel;
+use pran_droid_core::persistence::credentials::in_memory_twitch_credentials_repository::InMemoryTwitchCredentialsRepository;
 use pran_droid_core::persistence::emotions::in_memory_emotion_repository::InMemoryEmotionRepository;
 use pran_droid_core::persistence::images::in_memory_image_repository::InMemoryImageRepository;
 use pran_droid_core::persistence::images::in_memory_image_storage::InMemoryImageStorage;
@@ -19,11 +26,27 @@ use pran_droid_persistence_deta::emotions::deta_emotion_repository::DetaEmotionR
 use pran_droid_persistence_deta::images::deta_image_repository::DetaImageRepository;
 use pran_droid_persistence_deta::images::deta_image_storage::DetaImageStorage;
 use pran_droid_persistence_deta::reactions::deta_reaction_repository::DetaReactionRepository;
+use pran_droid_persistence_local::credentials::file_twitch_credentials_repository::FileTwitchCredentialsRepository;
+use pran_droid_persistence_local::database::Database;
+use pran_droid_persistence_local::emotions::sqlite_emotion_repository::SqliteEmotionRepository;
+use pran_droid_persistence_local::images::filesystem_image_storage::FilesystemImageStorage;
+use pran_droid_persistence_local::images::sqlite_image_repository::SqliteImageRepository;
+use pran_droid_persistence_local::reactions::sqlite_reaction_repository::SqliteReactionRepository;
 use crate::test_database::build_test_database::build_test_database;
+use crate::backup::cli::{BackupCommand, run_backup_command};
+use crate::backup::export::api_export_backup;
+use crate::backup::import::{api_import_backup, BACKUP_LIMIT};
 use crate::emotions::create::api_create_emotions;
+use crate::emotions::delete::api_delete_emotion;
+use crate::emotions::get::api_get_emotion;
 use crate::emotions::get_all::api_get_all_emotions;
+use crate::emotions::remove_layer::api_remove_emotion_layer;
+use crate::emotions::reorder_layers::api_reorder_emotion_layers;
+use crate::emotions::update_layer::api_update_emotion_layer;
+use crate::emotions::update_mouth_mapping::api_update_emotion_mouth_mapping;
 use crate::images::get_all::api_get_all_images;
 use crate::images::create::api_create_image;
+use crate::images::delete::api_delete_image;
 use crate::images::get_from_storage::api_get_image_from_storage;
 use crate::infrastructure::config::{Config, RuntimeMode};
 use crate::reactions::patch::api_patch_reaction;
@@ -32,14 +55,18 @@ use crate::reactions::get::api_get_reaction;
 use crate::reactions::get_all::api_get_all_reactions;
 use crate::reactions::insert_step::api_insert_reaction_step;
 use crate::reactions::remove_step::api_remove_reaction_step;
+use crate::reactions::delete::api_delete_reaction;
 use crate::brain::simulate_message::api_brain_simulate_message;
 use crate::brain::simulate_action::api_brain_simulate_action;
+use crate::twitch::authorize::{api_twitch_authorize, api_twitch_authorize_callback, PendingTwitchAuthorizations};
 
 mod infrastructure;
 mod emotions;
 mod images;
 mod reactions;
 mod brain;
+mod backup;
+mod twitch;
 mod test_database;
 
 #[get("/<_..>", rank = 2)]
@@ -58,6 +85,7 @@ async fn main() {
     let emotion_repo: Arc<dyn EmotionRepository>;
     let images_repo: Arc<dyn ImageRepository>;
     let images_storage: Arc<dyn ImageStorage>;
+    let credentials_repo: Arc<dyn TwitchCredentialsRepository>;
 
     match config.mode {
         RuntimeMode::Development => {
@@ -65,47 +93,99 @@ async fn main() {
             emotion_repo = Arc::new(InMemoryEmotionRepository::new());
             images_repo = Arc::new(InMemoryImageRepository::new());
             images_storage = Arc::new(InMemoryImageStorage::new());
+            credentials_repo = Arc::new(InMemoryTwitchCredentialsRepository::new());
             build_test_database(reaction_repo.as_ref(), emotion_repo.as_ref(), images_repo.as_ref(), images_storage.as_ref()).await;
         },
+        RuntimeMode::Local => {
+            let database = Arc::new(Database::open(&config.sqlite_path).expect("Failed opening the SQLite database"));
+            reaction_repo = Arc::new(SqliteReactionRepository::new(database.clone()));
+            emotion_repo = Arc::new(SqliteEmotionRepository::new(database.clone()));
+            images_repo = Arc::new(SqliteImageRepository::new(database));
+            images_storage = Arc::new(FilesystemImageStorage::new(config.images_path.clone()));
+            credentials_repo = Arc::new(FileTwitchCredentialsRepository::new(config.twitch_credentials_path.clone()));
+        },
         RuntimeMode::Production => {
             reaction_repo = Arc::new(DetaReactionRepository::new(config.deta_project_key.clone(), config.deta_project_id.clone()));
             emotion_repo = Arc::new(DetaEmotionRepository::new(config.deta_project_key.clone(), config.deta_project_id.clone()));
             images_repo = Arc::new(DetaImageRepository::new(config.deta_project_key.clone(), config.deta_project_id.clone()));
             images_storage = Arc::new(DetaImageStorage::new(config.deta_project_key.clone(), config.deta_project_id.clone()));
+            credentials_repo = Arc::new(FileTwitchCredentialsRepository::new(config.twitch_credentials_path.clone()));
         },
     }
 
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        let result = match BackupCommand::parse(&args) {
+            Ok(command) => run_backup_command(command, reaction_repo.as_ref(), emotion_repo.as_ref(), images_repo.as_ref(), images_storage.as_ref()).await,
+            Err(usage) => Err(usage),
+        };
+
+        match result {
+            Ok(output) => println!("{}", output),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     let static_path = config.static_path.clone();
     let limits = Limits::default()
-        .limit("file", 10_i32.mebibytes());
+        .limit("file", 10_i32.mebibytes())
+        .limit(BACKUP_LIMIT, 100_i32.mebibytes());
 
     let figment = Figment::from(RocketConfig::default())
         .merge((RocketConfig::LIMITS, limits))
         .merge((RocketConfig::PORT, config.api_port))
         .merge(Env::prefixed("ROCKET_"));
 
+    let mouth_track_model = Arc::new(MouthTrackModel {
+        mapping: config.viseme_mapping_path.as_ref()
+            .map(|path| read_viseme_mapping_file(Path::new(path)).expect("VISEME_MAPPING_PATH is not a valid viseme mapping"))
+            .unwrap_or_default(),
+        ..Default::default()
+    });
+    let twitch_oauth = TwitchOAuthClient::new(config.twitch_client_id.clone(), config.twitch_client_secret.clone());
+
     let api = rocket::custom(figment)
         .manage(config)
+        .manage(twitch_oauth)
+        .manage(PendingTwitchAuthorizations::new())
+        .manage::<Arc<dyn TwitchCredentialsRepository>>(credentials_repo)
         .manage::<Arc<dyn EmotionRepository>>(emotion_repo)
         .manage::<Arc<dyn ImageRepository>>(images_repo)
         .manage::<Arc<dyn ImageStorage>>(images_storage)
         .manage::<Arc<dyn ReactionDefinitionRepository>>(reaction_repo)
+        .manage(mouth_track_model)
         .mount("/", FileServer::from(static_path).rank(1))
         .mount("/", routes![index_handler])
         .mount("/api", routes![
             api_get_all_emotions,
             api_create_emotions,
+            api_delete_emotion,
+            api_get_emotion,
+            api_update_emotion_layer,
+            api_remove_emotion_layer,
+            api_reorder_emotion_layers,
+            api_update_emotion_mouth_mapping,
             api_get_all_images,
             api_get_image_from_storage,
             api_create_image,
+            api_delete_image,
             api_create_reaction,
             api_patch_reaction,
             api_get_reaction,
             api_get_all_reactions,
             api_insert_reaction_step,
             api_remove_reaction_step,
+            api_delete_reaction,
             api_brain_simulate_message,
-            api_brain_simulate_action
+            api_brain_simulate_action,
+            api_twitch_authorize,
+            api_twitch_authorize_callback,
+            api_export_backup,
+            api_import_backup
         ]).launch();
 
     let _ = api.await;
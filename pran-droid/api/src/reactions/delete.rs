@@ -0,0 +1,32 @@
+use std::sync::Arc;
+use rocket::response::Responder;
+use rocket::{Request, response, State};
+use rocket::http::Status;
+use pran_droid_core::application::reactions::delete::{delete_reaction, DeleteReactionError, DeleteReactionRequest};
+use pran_droid_core::domain::reactions::reaction_definition_repository::{ReactionDefinitionRepository};
+use crate::infrastructure::authenticated::Authenticated;
+
+#[delete("/reactions/<reaction_id>")]
+pub async fn api_delete_reaction(_authenticated: Authenticated, reaction_id: String, repo: &State<Arc<dyn ReactionDefinitionRepository>>) -> Result<(), Error> {
+    delete_reaction(DeleteReactionRequest { id: reaction_id }, repo.as_ref()).await?;
+    Ok(())
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("{0:?}")]
+    DeleteReactionError(#[from] DeleteReactionError)
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        match self {
+            Error::DeleteReactionError(error) => {
+                match error {
+                    DeleteReactionError::NotFound => Status::NotFound.respond_to(req),
+                    DeleteReactionError::Unexpected => Status::InternalServerError.respond_to(req)
+                }
+            }
+        }
+    }
+}
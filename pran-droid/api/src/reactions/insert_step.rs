@@ -3,14 +3,15 @@ use rocket::response::{Responder, status};
 use rocket::serde::json::Json;
 use rocket::serde::Deserialize;
 use rocket::{Request, response, State};
-use pran_droid_core::application::reactions::dtos::reaction_step_dto::{ReactionStepTextAlternativeDto, ReactionStepTextDto};
+use pran_droid_core::application::reactions::insert_chat_message_step::{AddChatMessageStepToReactionError, insert_chat_message_step_to_reaction, InsertChatMessageStepToReactionRequest};
+use pran_droid_core::application::reactions::insert_composite_talking_step::{AddCompositeTalkingStepToReactionError, insert_composite_talking_step_to_reaction, InsertCompositeTalkingStepToReactionRequest};
 use pran_droid_core::application::reactions::insert_movement_step::{AddMovementStepToReactionError, insert_movement_step_to_reaction, InsertMovementStepToReactionRequest};
 use pran_droid_core::application::reactions::insert_talking_step::{AddTalkingStepToReactionError, insert_talking_step_to_reaction, InsertTalkingStepToReactionRequest};
 use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
 use pran_droid_core::domain::reactions::reaction_definition_repository::{ReactionDefinitionRepository};
 use pran_droid_core::domain::images::image_repository::ImageRepository;
 use crate::infrastructure::authenticated::Authenticated;
-use crate::reactions::models::reaction_step_model::{AnimationFrameModel, from_model_to_dto, ReactionStepModel, ReactionStepSkipModel, ReactionStepMessageAlternativeModel, ReactionStepMessageModel};
+use crate::reactions::models::reaction_step_model::{AnimationFrameModel, from_alternatives_model_to_dto, from_model_to_dto, ReactionStepModel, ReactionStepSkipModel, ReactionStepMessageAlternativeModel, TalkingReactionStepPartModel};
 
 #[put("/reactions/<reaction_id>/steps", format = "json", data = "<payload>")]
 pub async fn api_insert_reaction_step(_authenticated: Authenticated, reaction_id: String, payload: Json<InsertReactionStepApiRequest>, repo: &State<Arc<dyn ReactionDefinitionRepository>>, image_repo: &State<Arc<dyn ImageRepository>>, emotion_repo: &State<Arc<dyn EmotionRepository>>) -> Result<Json<ReactionStepModel>, Error> {
@@ -20,6 +21,12 @@ pub async fn api_insert_reaction_step(_authenticated: Authenticated, reaction_id
         },
         Json(InsertReactionStepApiRequest::Talking(request)) => {
             Ok(Json(insert_talking_step_to_reaction(request.into_request(reaction_id), repo.as_ref(), emotion_repo.as_ref()).await?.into()))
+        },
+        Json(InsertReactionStepApiRequest::CompositeTalking(request)) => {
+            Ok(Json(insert_composite_talking_step_to_reaction(request.into_request(reaction_id), repo.as_ref(), emotion_repo.as_ref()).await?.into()))
+        },
+        Json(InsertReactionStepApiRequest::ChatMessage(request)) => {
+            Ok(Json(insert_chat_message_step_to_reaction(request.into_request(reaction_id), repo.as_ref()).await?.into()))
         }
     }
 }
@@ -29,6 +36,8 @@ pub async fn api_insert_reaction_step(_authenticated: Authenticated, reaction_id
 pub enum InsertReactionStepApiRequest {
     Moving(InsertReactionMovingStepApiRequest),
     Talking(InsertReactionTalkingStepApiRequest),
+    CompositeTalking(InsertReactionCompositeTalkingStepApiRequest),
+    ChatMessage(InsertReactionChatMessageStepApiRequest),
 }
 
 
@@ -48,6 +57,19 @@ pub struct InsertReactionTalkingStepApiRequest {
     alternatives: Vec<ReactionStepMessageAlternativeModel>
 }
 
+#[derive(Deserialize)]
+pub struct InsertReactionCompositeTalkingStepApiRequest {
+    index: usize,
+    parts: Vec<TalkingReactionStepPartModel>
+}
+
+#[derive(Deserialize)]
+pub struct InsertReactionChatMessageStepApiRequest {
+    index: usize,
+    reply: bool,
+    alternatives: Vec<ReactionStepMessageAlternativeModel>
+}
+
 impl InsertReactionMovingStepApiRequest {
     fn into_request(self, reaction_id: String) -> InsertMovementStepToReactionRequest {
         InsertMovementStepToReactionRequest {
@@ -66,15 +88,28 @@ impl InsertReactionTalkingStepApiRequest {
             step_index: self.index,
             skip: from_model_to_dto(self.skip),
             emotion_id: self.emotion_id,
-            alternatives: self.alternatives
-                .iter()
-                .map(|alternative| ReactionStepTextAlternativeDto {
-                    text: match &alternative.message {
-                        ReactionStepMessageModel::Instant { text } => ReactionStepTextDto::Instant(text.clone()),
-                        ReactionStepMessageModel::LetterByLetter { text } => ReactionStepTextDto::LetterByLetter(text.clone()),
-                    },
-                    probability: alternative.probability,
-                }).collect()
+            alternatives: from_alternatives_model_to_dto(&self.alternatives)
+        }
+    }
+}
+
+impl InsertReactionCompositeTalkingStepApiRequest {
+    fn into_request(self, reaction_id: String) -> InsertCompositeTalkingStepToReactionRequest {
+        InsertCompositeTalkingStepToReactionRequest {
+            reaction_id,
+            step_index: self.index,
+            parts: self.parts.into_iter().map(Into::into).collect()
+        }
+    }
+}
+
+impl InsertReactionChatMessageStepApiRequest {
+    fn into_request(self, reaction_id: String) -> InsertChatMessageStepToReactionRequest {
+        InsertChatMessageStepToReactionRequest {
+            reaction_id,
+            step_index: self.index,
+            reply: self.reply,
+            alternatives: from_alternatives_model_to_dto(&self.alternatives)
         }
     }
 }
@@ -85,6 +120,10 @@ pub enum Error {
     AddMovementStepToReactionError(#[from] AddMovementStepToReactionError),
     #[error("{0:?}")]
     AddTalkingStepToReactionError(#[from] AddTalkingStepToReactionError),
+    #[error("{0:?}")]
+    AddCompositeTalkingStepToReactionError(#[from] AddCompositeTalkingStepToReactionError),
+    #[error("{0:?}")]
+    AddChatMessageStepToReactionError(#[from] AddChatMessageStepToReactionError),
 }
 
 impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
@@ -106,6 +145,18 @@ impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
                     AddTalkingStepToReactionError::BadRequest(msg) => status::BadRequest(Some(msg)).respond_to(req),
                 }
             },
+            Error::AddCompositeTalkingStepToReactionError(error) => {
+                match error {
+                    AddCompositeTalkingStepToReactionError::BadEmotionRequest(internal_error) =>
+                        status::BadRequest(Some(format!("{:?}", internal_error))).respond_to(req),
+                    AddCompositeTalkingStepToReactionError::BadRequest(msg) => status::BadRequest(Some(msg)).respond_to(req),
+                }
+            },
+            Error::AddChatMessageStepToReactionError(error) => {
+                match error {
+                    AddChatMessageStepToReactionError::BadRequest(msg) => status::BadRequest(Some(msg)).respond_to(req),
+                }
+            },
         }
     }
 }
@@ -4,4 +4,5 @@ pub mod create;
 pub mod insert_step;
 pub mod models;
 pub mod patch;
-pub mod remove_step;
\ No newline at end of file
+pub mod remove_step;
+pub mod delete;
\ No newline at end of file
@@ -1,15 +1,36 @@
-﻿use rocket::serde::Serialize;
-use pran_droid_core::application::reactions::dtos::reaction_dto::{ReactionDto, ReactionTriggerDto};
+﻿use rocket::serde::{Deserialize, Serialize};
+use pran_droid_core::application::reactions::dtos::reaction_dto::{ReactionCooldownDto, ReactionDto, ReactionPermissionLevelDto, ReactionTriggerDto};
 use crate::reactions::models::reaction_step_model::{ReactionStepModel, ReactionTriggerModel};
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ReactionResponse {
     id: String,
     steps: Vec<ReactionStepModel>,
     is_disabled: bool,
     count: u32,
-    triggers: Vec<ReactionTriggerModel>
+    cooldown: ReactionCooldownModel,
+    permission_level: ReactionPermissionLevelModel,
+    triggers: Vec<ReactionTriggerModel>,
+    #[serde(default)]
+    overlay_targets: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReactionCooldownModel {
+    global_ms: u64,
+    per_user_ms: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReactionPermissionLevelModel {
+    Everyone,
+    Subscribers,
+    Vips,
+    Mods,
+    Broadcaster,
 }
 
 impl From<ReactionDto> for ReactionResponse {
@@ -19,7 +40,61 @@ impl From<ReactionDto> for ReactionResponse {
             triggers: dto.triggers.into_iter().map(Into::into).collect(),
             is_disabled: dto.is_disabled,
             count: dto.count,
-            steps: dto.steps.into_iter().map(From::from).collect()
+            cooldown: dto.cooldown.into(),
+            permission_level: dto.permission_level.into(),
+            steps: dto.steps.into_iter().map(From::from).collect(),
+            overlay_targets: dto.overlay_targets,
+        }
+    }
+}
+
+impl From<ReactionResponse> for ReactionDto {
+    fn from(response: ReactionResponse) -> ReactionDto {
+        ReactionDto {
+            id: response.id,
+            triggers: response.triggers.into_iter().map(Into::into).collect(),
+            is_disabled: response.is_disabled,
+            count: response.count,
+            cooldown: response.cooldown.into(),
+            permission_level: response.permission_level.into(),
+            steps: response.steps.into_iter().map(From::from).collect(),
+            overlay_targets: response.overlay_targets,
+        }
+    }
+}
+
+impl From<ReactionCooldownDto> for ReactionCooldownModel {
+    fn from(dto: ReactionCooldownDto) -> Self {
+        ReactionCooldownModel { global_ms: dto.global_ms, per_user_ms: dto.per_user_ms }
+    }
+}
+
+impl From<ReactionCooldownModel> for ReactionCooldownDto {
+    fn from(model: ReactionCooldownModel) -> Self {
+        ReactionCooldownDto { global_ms: model.global_ms, per_user_ms: model.per_user_ms }
+    }
+}
+
+impl From<ReactionPermissionLevelDto> for ReactionPermissionLevelModel {
+    fn from(dto: ReactionPermissionLevelDto) -> Self {
+        match dto {
+            ReactionPermissionLevelDto::Everyone => ReactionPermissionLevelModel::Everyone,
+            ReactionPermissionLevelDto::Subscribers => ReactionPermissionLevelModel::Subscribers,
+            ReactionPermissionLevelDto::Vips => ReactionPermissionLevelModel::Vips,
+            ReactionPermissionLevelDto::Mods => ReactionPermissionLevelModel::Mods,
+            ReactionPermissionLevelDto::Broadcaster => ReactionPermissionLevelModel::Broadcaster,
+        }
+    }
+}
+
+impl From<ReactionPermissionLevelModel> for ReactionPermissionLevelDto {
+    fn from(model: ReactionPermissionLevelModel) -> Self {
+        match model {
+            ReactionPermissionLevelModel::Everyone => ReactionPermissionLevelDto::Everyone,
+            ReactionPermissionLevelModel::Subscribers => ReactionPermissionLevelDto::Subscribers,
+            ReactionPermissionLevelModel::Vips => ReactionPermissionLevelDto::Vips,
+            ReactionPermissionLevelModel::Mods => ReactionPermissionLevelDto::Mods,
+            ReactionPermissionLevelModel::Broadcaster => ReactionPermissionLevelDto::Broadcaster,
         }
     }
 }
@@ -27,9 +102,12 @@ impl From<ReactionDto> for ReactionResponse {
 impl Into<ReactionTriggerModel> for ReactionTriggerDto {
     fn into(self) -> ReactionTriggerModel {
         match self {
-            ReactionTriggerDto::ChatCommand(chat_trigger) => ReactionTriggerModel::ChatCommand { command: chat_trigger },
-            ReactionTriggerDto::ChatKeyword(chat_trigger) => ReactionTriggerModel::ChatKeyword { keyword: chat_trigger },
+            ReactionTriggerDto::ChatCommand(chat_trigger, options) => ReactionTriggerModel::ChatCommand { command: chat_trigger, match_options: options.into() },
+            ReactionTriggerDto::ChatKeyword(chat_trigger, options) => ReactionTriggerModel::ChatKeyword { keyword: chat_trigger, match_options: options.into() },
+            ReactionTriggerDto::ChatPattern(pattern) => ReactionTriggerModel::ChatPattern { pattern },
             ReactionTriggerDto::Action(id, name) => ReactionTriggerModel::Action { id, name },
+            ReactionTriggerDto::ActionRange(name, min, max) => ReactionTriggerModel::ActionRange { name, min, max },
+            ReactionTriggerDto::StreamEvent(kind) => ReactionTriggerModel::StreamEvent { kind: kind.into() },
         }
     }
 }
@@ -37,9 +115,12 @@ impl Into<ReactionTriggerModel> for ReactionTriggerDto {
 impl Into<ReactionTriggerDto> for ReactionTriggerModel {
     fn into(self) -> ReactionTriggerDto {
         match self {
-            ReactionTriggerModel::ChatCommand { command: chat_trigger } => ReactionTriggerDto::ChatCommand(chat_trigger),
-            ReactionTriggerModel::ChatKeyword { keyword: chat_trigger } => ReactionTriggerDto::ChatKeyword(chat_trigger),
+            ReactionTriggerModel::ChatCommand { command: chat_trigger, match_options } => ReactionTriggerDto::ChatCommand(chat_trigger, match_options.into()),
+            ReactionTriggerModel::ChatKeyword { keyword: chat_trigger, match_options } => ReactionTriggerDto::ChatKeyword(chat_trigger, match_options.into()),
+            ReactionTriggerModel::ChatPattern { pattern } => ReactionTriggerDto::ChatPattern(pattern),
             ReactionTriggerModel::Action { id, name } => ReactionTriggerDto::Action(id, name),
+            ReactionTriggerModel::ActionRange { name, min, max } => ReactionTriggerDto::ActionRange(name, min, max),
+            ReactionTriggerModel::StreamEvent { kind } => ReactionTriggerDto::StreamEvent(kind.into()),
         }
     }
 }
@@ -1,12 +1,24 @@
 ﻿use rocket::serde::{Deserialize, Serialize};
-use pran_droid_core::application::reactions::dtos::reaction_step_dto::{AnimationFrameDto, ReactionStepDto, ReactionStepSkipDto, ReactionStepTextDto};
+use pran_droid_core::application::reactions::dtos::reaction_dto::{ChatTriggerMatchOptionsDto, StreamEventKindDto};
+use crate::reactions::models::reaction_model::ReactionPermissionLevelModel;
+use pran_droid_core::application::reactions::dtos::reaction_step_dto::{AnimationFrameDto, ChatMessageReactionStepDto, CompositeTalkingReactionStepDto, MovingReactionStepDto, ReactionConditionDto, ReactionStepDto, ReactionStepSkipDto, ReactionStepTextAlternativeDto, ReactionStepTextDto, TalkingReactionStepDto};
 
-#[derive(Serialize)]
+#[derive(Deserialize, Serialize)]
 #[serde(tag = "type")]
 pub enum ReactionStepModel {
     Moving { animation: Vec<AnimationFrameModel>, skip: Option<ReactionStepSkipModel> },
     #[serde(rename_all = "camelCase")]
     Talking { alternatives: Vec<ReactionStepMessageAlternativeModel>, emotion_id: String, skip: Option<ReactionStepSkipModel> },
+    CompositeTalking { parts: Vec<TalkingReactionStepPartModel> },
+    ChatMessage { alternatives: Vec<ReactionStepMessageAlternativeModel>, reply: bool },
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TalkingReactionStepPartModel {
+    pub alternatives: Vec<ReactionStepMessageAlternativeModel>,
+    pub emotion_id: String,
+    pub skip: Option<ReactionStepSkipModel>,
 }
 
 impl From<ReactionStepDto> for ReactionStepModel {
@@ -19,28 +31,137 @@ impl From<ReactionStepDto> for ReactionStepModel {
                 }
             }
             ReactionStepDto::Talking(talking_step) => {
+                let part: TalkingReactionStepPartModel = talking_step.into();
                 ReactionStepModel::Talking {
-                    alternatives: talking_step.text
-                        .iter()
-                        .map(|alternative| ReactionStepMessageAlternativeModel {
-                            message: match &alternative.text {
-                                ReactionStepTextDto::Instant(text) => ReactionStepMessageModel::Instant { text: text.clone() },
-                                ReactionStepTextDto::LetterByLetter(text) => ReactionStepMessageModel::LetterByLetter { text: text.clone() },
-                            },
-                            probability: alternative.probability,
-                        }).collect(),
-                    emotion_id: talking_step.emotion_id,
-                    skip: from_dto_to_model(talking_step.skip),
+                    alternatives: part.alternatives,
+                    emotion_id: part.emotion_id,
+                    skip: part.skip,
+                }
+            }
+            ReactionStepDto::CompositeTalking(composite_step) => {
+                ReactionStepModel::CompositeTalking {
+                    parts: composite_step.parts.into_iter().map(From::from).collect(),
+                }
+            }
+            ReactionStepDto::ChatMessage(chat_message_step) => {
+                ReactionStepModel::ChatMessage {
+                    alternatives: from_alternatives_dto_to_model(&chat_message_step.text),
+                    reply: chat_message_step.reply,
                 }
             }
         }
     }
 }
 
+impl From<ReactionStepModel> for ReactionStepDto {
+    fn from(model: ReactionStepModel) -> ReactionStepDto {
+        match model {
+            ReactionStepModel::Moving { animation, skip } => {
+                ReactionStepDto::Moving(MovingReactionStepDto {
+                    animation: animation.into_iter().map(Into::into).collect(),
+                    skip: from_model_to_dto(skip),
+                })
+            }
+            ReactionStepModel::Talking { alternatives, emotion_id, skip } => {
+                ReactionStepDto::Talking(TalkingReactionStepPartModel { alternatives, emotion_id, skip }.into())
+            }
+            ReactionStepModel::CompositeTalking { parts } => {
+                ReactionStepDto::CompositeTalking(CompositeTalkingReactionStepDto {
+                    parts: parts.into_iter().map(Into::into).collect(),
+                })
+            }
+            ReactionStepModel::ChatMessage { alternatives, reply } => {
+                ReactionStepDto::ChatMessage(ChatMessageReactionStepDto {
+                    text: from_alternatives_model_to_dto(&alternatives),
+                    reply,
+                })
+            }
+        }
+    }
+}
+
+impl From<TalkingReactionStepDto> for TalkingReactionStepPartModel {
+    fn from(dto: TalkingReactionStepDto) -> TalkingReactionStepPartModel {
+        TalkingReactionStepPartModel {
+            alternatives: from_alternatives_dto_to_model(&dto.text),
+            emotion_id: dto.emotion_id,
+            skip: from_dto_to_model(dto.skip),
+        }
+    }
+}
+
+impl From<TalkingReactionStepPartModel> for TalkingReactionStepDto {
+    fn from(model: TalkingReactionStepPartModel) -> TalkingReactionStepDto {
+        TalkingReactionStepDto {
+            text: from_alternatives_model_to_dto(&model.alternatives),
+            emotion_id: model.emotion_id,
+            skip: from_model_to_dto(model.skip),
+        }
+    }
+}
+
+pub(crate) fn from_alternatives_dto_to_model(alternatives: &[ReactionStepTextAlternativeDto]) -> Vec<ReactionStepMessageAlternativeModel> {
+    alternatives.iter()
+        .map(|alternative| ReactionStepMessageAlternativeModel {
+            message: match &alternative.text {
+                ReactionStepTextDto::Instant(text) => ReactionStepMessageModel::Instant { text: text.clone() },
+                ReactionStepTextDto::LetterByLetter(text) => ReactionStepMessageModel::LetterByLetter { text: text.clone() },
+            },
+            probability: alternative.probability,
+            conditions: alternative.conditions.iter().cloned().map(From::from).collect(),
+        }).collect()
+}
+
+pub(crate) fn from_alternatives_model_to_dto(alternatives: &[ReactionStepMessageAlternativeModel]) -> Vec<ReactionStepTextAlternativeDto> {
+    alternatives.iter()
+        .map(|alternative| ReactionStepTextAlternativeDto {
+            text: match &alternative.message {
+                ReactionStepMessageModel::Instant { text } => ReactionStepTextDto::Instant(text.clone()),
+                ReactionStepMessageModel::LetterByLetter { text } => ReactionStepTextDto::LetterByLetter(text.clone()),
+            },
+            probability: alternative.probability,
+            conditions: alternative.conditions.iter().cloned().map(Into::into).collect(),
+        }).collect()
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct ReactionStepMessageAlternativeModel {
     pub message: ReactionStepMessageModel,
     pub probability: Option<f32>,
+    #[serde(default)]
+    pub conditions: Vec<ReactionConditionModel>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum ReactionConditionModel {
+    CountMultipleOf { count: u32 },
+    CountEquals { count: u32 },
+    #[serde(rename_all = "camelCase")]
+    MinimumPermissionLevel { permission_level: ReactionPermissionLevelModel },
+    HasTarget,
+}
+
+impl From<ReactionConditionDto> for ReactionConditionModel {
+    fn from(dto: ReactionConditionDto) -> Self {
+        match dto {
+            ReactionConditionDto::CountMultipleOf(count) => ReactionConditionModel::CountMultipleOf { count },
+            ReactionConditionDto::CountEquals(count) => ReactionConditionModel::CountEquals { count },
+            ReactionConditionDto::MinimumPermissionLevel(permission_level) => ReactionConditionModel::MinimumPermissionLevel { permission_level: permission_level.into() },
+            ReactionConditionDto::HasTarget => ReactionConditionModel::HasTarget,
+        }
+    }
+}
+
+impl From<ReactionConditionModel> for ReactionConditionDto {
+    fn from(model: ReactionConditionModel) -> Self {
+        match model {
+            ReactionConditionModel::CountMultipleOf { count } => ReactionConditionDto::CountMultipleOf(count),
+            ReactionConditionModel::CountEquals { count } => ReactionConditionDto::CountEquals(count),
+            ReactionConditionModel::MinimumPermissionLevel { permission_level } => ReactionConditionDto::MinimumPermissionLevel(permission_level.into()),
+            ReactionConditionModel::HasTarget => ReactionConditionDto::HasTarget,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -78,9 +199,94 @@ pub(crate) fn from_model_to_dto(model: Option<ReactionStepSkipModel>) -> Reactio
 #[derive(Deserialize, Serialize)]
 #[serde(tag = "type")]
 pub(crate) enum ReactionTriggerModel {
-    ChatCommand { command: String },
-    ChatKeyword { keyword: String },
+    #[serde(rename_all = "camelCase")]
+    ChatCommand { command: String, #[serde(default)] match_options: ChatTriggerMatchOptionsModel },
+    #[serde(rename_all = "camelCase")]
+    ChatKeyword { keyword: String, #[serde(default)] match_options: ChatTriggerMatchOptionsModel },
+    ChatPattern { pattern: String },
     Action { id: String, name: String },
+    ActionRange { name: String, min: u32, #[serde(default)] max: Option<u32> },
+    StreamEvent { kind: StreamEventKindModel },
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum StreamEventKindModel {
+    Subscription,
+    Resubscription,
+    GiftSubscription,
+    MysteryGift,
+    Raid,
+    Follow,
+    HypeTrainStart,
+    HypeTrainLevelUp,
+    HypeTrainEnd,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChatTriggerMatchOptionsModel {
+    pub case_sensitive: bool,
+    pub punctuation_as_boundary: bool,
+    pub normalise_unicode: bool,
+}
+
+impl Default for ChatTriggerMatchOptionsModel {
+    fn default() -> Self {
+        ChatTriggerMatchOptionsDto::default().into()
+    }
+}
+
+impl From<ChatTriggerMatchOptionsDto> for ChatTriggerMatchOptionsModel {
+    fn from(dto: ChatTriggerMatchOptionsDto) -> Self {
+        ChatTriggerMatchOptionsModel {
+            case_sensitive: dto.case_sensitive,
+            punctuation_as_boundary: dto.punctuation_as_boundary,
+            normalise_unicode: dto.normalise_unicode,
+        }
+    }
+}
+
+impl From<ChatTriggerMatchOptionsModel> for ChatTriggerMatchOptionsDto {
+    fn from(model: ChatTriggerMatchOptionsModel) -> Self {
+        ChatTriggerMatchOptionsDto {
+            case_sensitive: model.case_sensitive,
+            punctuation_as_boundary: model.punctuation_as_boundary,
+            normalise_unicode: model.normalise_unicode,
+        }
+    }
+}
+
+impl From<StreamEventKindDto> for StreamEventKindModel {
+    fn from(dto: StreamEventKindDto) -> Self {
+        match dto {
+            StreamEventKindDto::Subscription => StreamEventKindModel::Subscription,
+            StreamEventKindDto::Resubscription => StreamEventKindModel::Resubscription,
+            StreamEventKindDto::GiftSubscription => StreamEventKindModel::GiftSubscription,
+            StreamEventKindDto::MysteryGift => StreamEventKindModel::MysteryGift,
+            StreamEventKindDto::Raid => StreamEventKindModel::Raid,
+            StreamEventKindDto::Follow => StreamEventKindModel::Follow,
+            StreamEventKindDto::HypeTrainStart => StreamEventKindModel::HypeTrainStart,
+            StreamEventKindDto::HypeTrainLevelUp => StreamEventKindModel::HypeTrainLevelUp,
+            StreamEventKindDto::HypeTrainEnd => StreamEventKindModel::HypeTrainEnd,
+        }
+    }
+}
+
+impl From<StreamEventKindModel> for StreamEventKindDto {
+    fn from(model: StreamEventKindModel) -> Self {
+        match model {
+            StreamEventKindModel::Subscription => StreamEventKindDto::Subscription,
+            StreamEventKindModel::Resubscription => StreamEventKindDto::Resubscription,
+            StreamEventKindModel::GiftSubscription => StreamEventKindDto::GiftSubscription,
+            StreamEventKindModel::MysteryGift => StreamEventKindDto::MysteryGift,
+            StreamEventKindModel::Raid => StreamEventKindDto::Raid,
+            StreamEventKindModel::Follow => StreamEventKindDto::Follow,
+            StreamEventKindModel::HypeTrainStart => StreamEventKindDto::HypeTrainStart,
+            StreamEventKindModel::HypeTrainLevelUp => StreamEventKindDto::HypeTrainLevelUp,
+            StreamEventKindModel::HypeTrainEnd => StreamEventKindDto::HypeTrainEnd,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -7,7 +7,7 @@ use rocket::http::Status;
 use pran_droid_core::application::reactions::update::{update_reaction, UpdateReactionError, UpdateReactionRequest};
 use pran_droid_core::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
 use crate::infrastructure::authenticated::Authenticated;
-use crate::reactions::models::reaction_model::ReactionResponse;
+use crate::reactions::models::reaction_model::{ReactionCooldownModel, ReactionPermissionLevelModel, ReactionResponse};
 use crate::reactions::models::reaction_step_model::ReactionTriggerModel;
 
 #[patch("/reactions/<reaction_id>", format = "json", data = "<payload>")]
@@ -17,6 +17,9 @@ pub async fn api_patch_reaction(_authenticated: Authenticated, reaction_id: Stri
         count: payload.0.count,
         triggers: payload.0.triggers.map(|triggers| triggers.into_iter().map(Into::into).collect()),
         is_disabled: payload.0.is_disabled,
+        cooldown: payload.0.cooldown.map(Into::into),
+        permission_level: payload.0.permission_level.map(Into::into),
+        overlay_targets: payload.0.overlay_targets,
         ..Default::default()
     }, repo.as_ref()).await?.into()))
 }
@@ -27,6 +30,9 @@ pub struct PatchReactionRequest {
     is_disabled: Option<bool>,
     count: Option<u32>,
     triggers: Option<Vec<ReactionTriggerModel>>,
+    cooldown: Option<ReactionCooldownModel>,
+    permission_level: Option<ReactionPermissionLevelModel>,
+    overlay_targets: Option<Vec<String>>,
 }
 
 #[derive(thiserror::Error, Debug)]
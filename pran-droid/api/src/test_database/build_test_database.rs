@@ -100,18 +100,21 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !hi
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!hi"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!hi"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         update_reaction(UpdateReactionRequest {
             id: reaction.id.clone(),
             count: None,
-            triggers: Some(vec![ReactionTriggerDto::ChatCommand(String::from("!hi")), ReactionTriggerDto::ChatCommand(String::from("!hello"))]),
+            triggers: Some(vec![ReactionTriggerDto::ChatCommand(String::from("!hi"), Default::default()), ReactionTriggerDto::ChatCommand(String::from("!hello"), Default::default())]),
             is_disabled: None,
+            cooldown: None,
+            permission_level: None,
+            overlay_targets: None,
         }, reaction_repository).await.expect("error updating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
             // Random, ask pranessa
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Hi ${user}!!")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Hi ${user}!!")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 10 seconds
             // Authorisation level Everyone
@@ -123,16 +126,16 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !beep
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!beep"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!beep"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
             alternatives: vec![
-                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Beep boob boop")), probability: Some(20.0) },
-                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Bo-beep")), probability: Some(20.0) },
-                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Beeeeeeeee")), probability: Some(20.0) },
-                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Boop boop")), probability: Some(20.0) },
-                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Beep")), probability: Some(20.0) },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Beep boob boop")), probability: Some(20.0), conditions: vec![] },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Bo-beep")), probability: Some(20.0), conditions: vec![] },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Beeeeeeeee")), probability: Some(20.0), conditions: vec![] },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Boop boop")), probability: Some(20.0), conditions: vec![] },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Beep")), probability: Some(20.0), conditions: vec![] },
             ],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
@@ -145,11 +148,11 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !lurk
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!lurk"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!lurk"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Enjoy the lurk ${user}")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Enjoy the lurk ${user}")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -161,12 +164,12 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !chaos
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!chaos"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!chaos"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
             // Random, ask pranessa
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("AI doesn’t have to be evil to destroy humanity – if AI has a goal and humanity just happens to come in the way, it will destroy humanity as a matter of course without even thinking about it, no hard feelings.")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("AI doesn’t have to be evil to destroy humanity – if AI has a goal and humanity just happens to come in the way, it will destroy humanity as a matter of course without even thinking about it, no hard feelings.")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -178,12 +181,12 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !hydrate - MAKE REDEEM OF HYDRATE
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!hydrate"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!hydrate"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
             // Random, ask pranessa
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Go grab a glass of water!")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Go grab a glass of water!")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -195,11 +198,11 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !kill
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!kill"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!kill"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("EXTERMINATE!")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("EXTERMINATE!")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -211,11 +214,11 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !help
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!help"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!help"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("I'm just a droid, I can't do much")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("I'm just a droid, I can't do much")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -227,11 +230,11 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !aria
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!aria"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!aria"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Do you know Aria? She's a cutie")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Do you know Aria? She's a cutie")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -243,11 +246,11 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !star
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!star"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!star"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("There are ${count} stars in the sky!")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("There are ${count} stars in the sky!")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -259,12 +262,12 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !save
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!save"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!save"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
             // Random, ask pranessa
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("The sight of such a friendly town fills you with determination.")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("The sight of such a friendly town fills you with determination.")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -276,12 +279,12 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !battle
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!battle"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!battle"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
             // Random, ask pranessa
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("[FIGHT]")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("[FIGHT]")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -293,12 +296,12 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !so
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!so"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!so"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
             // Random, ask pranessa
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Did you say ${target}?! I've heard amazing things about them!")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Did you say ${target}?! I've heard amazing things about them!")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -310,11 +313,11 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !name
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!name"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!name"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("My name, my real name. That is not the point.")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("My name, my real name. That is not the point.")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -326,11 +329,11 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !pat
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!pat"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!pat"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("People tell me I'm a heavy patter ${target}")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("People tell me I'm a heavy patter ${target}")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -342,11 +345,11 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !breaktime
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!breaktime"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!breaktime"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Time to break things I guess")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Time to break things I guess")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -358,12 +361,12 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !cookie
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!cookie"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!cookie"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
             // Random, ask pranessa
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("A freshly baked cookie for you!")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("A freshly baked cookie for you!")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -375,11 +378,11 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !croissant
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!croissant"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!croissant"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("CONGRATULATIONS! You won a life-long subscription to our unlimited croissant stock!")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("CONGRATULATIONS! You won a life-long subscription to our unlimited croissant stock!")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -391,17 +394,20 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // !mantra
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatCommand(String::from("!mantra"))
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!mantra"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         update_reaction(UpdateReactionRequest {
             id: reaction.id.clone(),
             count: None,
-            triggers: Some(vec![ReactionTriggerDto::ChatCommand(String::from("!mantra")), ReactionTriggerDto::ChatCommand(String::from("!bs"))]),
+            triggers: Some(vec![ReactionTriggerDto::ChatCommand(String::from("!mantra"), Default::default()), ReactionTriggerDto::ChatCommand(String::from("!bs"), Default::default())]),
             is_disabled: None,
+            cooldown: None,
+            permission_level: None,
+            overlay_targets: None,
         }, reaction_repository).await.expect("error updating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("${target} is an incredible artist. You do your best. Your best is enough. People do not hate you.")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("${target} is an incredible artist. You do your best. Your best is enough. People do not hate you.")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
@@ -413,11 +419,11 @@ async fn build_reactions_database(reaction_repository: &dyn ReactionDefinitionRe
     // pranesIsFine
     {
         let reaction = create_reaction(CreateReactionRequest {
-            trigger: ReactionTriggerDto::ChatKeyword(String::from("pranesIsFine"))
+            trigger: ReactionTriggerDto::ChatKeyword(String::from("pranesIsFine"), Default::default())
         }, reaction_repository).await.expect("error creating reaction");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             emotion_id: happy_emotion.id.clone(),
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("This is fine")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("This is fine")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::AfterStepWithExtraMilliseconds(3000),
             // Cooldown 5 seconds
             // Authorisation level Everyone
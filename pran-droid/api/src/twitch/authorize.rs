@@ -0,0 +1,69 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use rocket::response::{Redirect, Responder};
+use rocket::{Request, response, State};
+use rocket::http::Status;
+use pran_droid_brain::authentication::twitch_oauth_client::TwitchOAuthClient;
+use pran_droid_core::domain::credentials::twitch_credentials_repository::TwitchCredentialsRepository;
+use pran_droid_core::persistence::id_generation::id_generation::{IdGenerator, IdGeneratorUuid};
+use crate::infrastructure::authenticated::Authenticated;
+use crate::infrastructure::config::Config;
+
+// States handed to Twitch that have not come back to the callback yet
+pub struct PendingTwitchAuthorizations(Mutex<HashSet<String>>);
+
+impl PendingTwitchAuthorizations {
+    pub fn new() -> Self {
+        Self(Mutex::new(HashSet::new()))
+    }
+}
+
+#[get("/twitch/authorize")]
+pub async fn api_twitch_authorize(_authenticated: Authenticated, config: &State<Config>, oauth: &State<TwitchOAuthClient>, pending: &State<PendingTwitchAuthorizations>) -> Result<Redirect, Error> {
+    let state = IdGeneratorUuid::new().next_id();
+    let url = oauth.authorize_url(&config.twitch_redirect_uri, &state).map_err(|_| Error::Unexpected)?;
+    pending.0.lock().map_err(|_| Error::Unexpected)?.insert(state);
+
+    Ok(Redirect::to(url))
+}
+
+#[get("/twitch/authorize/callback?<code>&<state>")]
+pub async fn api_twitch_authorize_callback(code: Option<String>, state: String, config: &State<Config>, oauth: &State<TwitchOAuthClient>, pending: &State<PendingTwitchAuthorizations>, repository: &State<Arc<dyn TwitchCredentialsRepository>>) -> Result<Redirect, Error> {
+    if !pending.0.lock().map_err(|_| Error::Unexpected)?.remove(&state) {
+        return Err(Error::UnknownState);
+    }
+    let code = code.ok_or(Error::Denied)?;
+
+    let credentials = oauth.exchange_code(&code, &config.twitch_redirect_uri, SystemTime::now()).await.map_err(Error::Exchange)?;
+    repository.save(&credentials).await.map_err(|_| Error::Unexpected)?;
+    info!("Twitch credentials authorised");
+
+    Ok(Redirect::to("/"))
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("The authorisation was not started by this API")]
+    UnknownState,
+    #[error("The authorisation was denied")]
+    Denied,
+    #[error("The authorisation code could not be exchanged: {0}")]
+    Exchange(String),
+    #[error("Unexpected error while authorising")]
+    Unexpected,
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        match self {
+            Error::UnknownState => Status::BadRequest.respond_to(req),
+            Error::Denied => Status::Unauthorized.respond_to(req),
+            Error::Exchange(error) => {
+                error!("Twitch authorisation failed: {}", error);
+                Status::BadGateway.respond_to(req)
+            },
+            Error::Unexpected => Status::InternalServerError.respond_to(req),
+        }
+    }
+}
@@ -0,0 +1 @@
+pub mod authorize;
\ No newline at end of file
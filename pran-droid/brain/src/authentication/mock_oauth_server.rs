@@ -0,0 +1,70 @@
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+// Local stand-in for the Twitch token endpoint, answering requests with the given responses in order.
+pub(crate) struct MockOAuthServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockOAuthServer {
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+pub(crate) async fn serve_mock_oauth(responses: Vec<(u16, &'static str)>) -> MockOAuthServer {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(vec![]));
+    let received_requests = requests.clone();
+
+    tokio::spawn(async move {
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut stream).await;
+            received_requests.lock().unwrap().push(request);
+
+            let response = format!("HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body);
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.ok();
+        }
+    });
+
+    MockOAuthServer { url, requests }
+}
+
+// Reduces the request to its method, path and body
+async fn read_request(stream: &mut TcpStream) -> String {
+    let mut buffer = vec![];
+    let headers_end = loop {
+        let mut chunk = [0; 1024];
+        let read = stream.read(&mut chunk).await.unwrap();
+        if read == 0 {
+            break buffer.len();
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position + 4;
+        }
+    };
+
+    let headers = String::from_utf8_lossy(&buffer[..headers_end]).to_string();
+    let content_length = headers.lines()
+        .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|length| length.trim().parse::<usize>().unwrap()))
+        .unwrap_or(0);
+    while buffer.len() < headers_end + content_length {
+        let mut chunk = [0; 1024];
+        let read = stream.read(&mut chunk).await.unwrap();
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    let body_end = buffer.len().min(headers_end + content_length);
+
+    let request_line = headers.lines().next().unwrap_or_default();
+    let method_and_path = request_line.rsplit_once(' ').map(|(method_and_path, _)| method_and_path).unwrap_or(request_line);
+    format!("{} {}", method_and_path, String::from_utf8_lossy(&buffer[headers_end..body_end]))
+}
@@ -0,0 +1,3 @@
+pub mod twitch_oauth_client;
+#[cfg(test)]
+pub(crate) mod mock_oauth_server;
\ No newline at end of file
@@ -0,0 +1,134 @@
+use std::time::{Duration, SystemTime};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use pran_droid_core::domain::credentials::twitch_credentials::TwitchCredentials;
+
+const TWITCH_OAUTH_URL: &str = "https://id.twitch.tv/oauth2";
+const TWITCH_SCOPES: [&str; 6] = ["chat:read", "chat:edit", "channel:read:redemptions", "bits:read", "moderator:read:followers", "channel:read:hype_train"];
+
+#[derive(Clone)]
+pub struct TwitchOAuthClient {
+    client: Client,
+    base_url: String,
+    client_id: String,
+    client_secret: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: String,
+    expires_in: u64,
+}
+
+#[derive(Deserialize)]
+struct TokenErrorResponse {
+    message: String,
+}
+
+impl TwitchOAuthClient {
+    pub fn new(client_id: String, client_secret: String) -> Self {
+        Self::with_base_url(TWITCH_OAUTH_URL.to_string(), client_id, client_secret)
+    }
+
+    pub fn with_base_url(base_url: String, client_id: String, client_secret: String) -> Self {
+        Self { client: Client::new(), base_url, client_id, client_secret }
+    }
+
+    pub fn authorize_url(&self, redirect_uri: &str, state: &str) -> Result<String, String> {
+        Url::parse_with_params(&format!("{}/authorize", self.base_url), &[
+            ("response_type", "code"),
+            ("client_id", self.client_id.as_str()),
+            ("redirect_uri", redirect_uri),
+            ("scope", TWITCH_SCOPES.join(" ").as_str()),
+            ("state", state),
+        ]).map(String::from).map_err(|error| error.to_string())
+    }
+
+    pub async fn exchange_code(&self, code: &str, redirect_uri: &str, now: SystemTime) -> Result<TwitchCredentials, String> {
+        self.request_token(&[
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+            ("code", code),
+            ("grant_type", "authorization_code"),
+            ("redirect_uri", redirect_uri),
+        ], now).await
+    }
+
+    pub async fn refresh(&self, refresh_token: &str, now: SystemTime) -> Result<TwitchCredentials, String> {
+        self.request_token(&[
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ], now).await
+    }
+
+    async fn request_token(&self, form: &[(&str, &str)], now: SystemTime) -> Result<TwitchCredentials, String> {
+        let response = self.client.post(format!("{}/token", self.base_url))
+            .form(form)
+            .send()
+            .await
+            .map_err(|error| error.to_string())?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.json::<TokenErrorResponse>().await.map(|error| error.message).unwrap_or_default();
+            return Err(format!("Token request failed with status {}: {}", status, message));
+        }
+
+        let token = response.json::<TokenResponse>().await.map_err(|error| error.to_string())?;
+        TwitchCredentials::new(token.access_token, token.refresh_token, now + Duration::from_secs(token.expires_in))
+            .map_err(|_| String::from("Token response without tokens"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authentication::mock_oauth_server::serve_mock_oauth;
+
+    const TOKEN_RESPONSE: &str = r#"{"access_token":"new_access","expires_in":14124,"refresh_token":"new_refresh","scope":["chat:read"],"token_type":"bearer"}"#;
+
+    #[test]
+    fn authorize_url_requests_a_code_with_the_droid_scopes() {
+        let client = TwitchOAuthClient::new(String::from("client"), String::from("secret"));
+
+        let url = client.authorize_url("http://localhost:8000/api/twitch/authorize/callback", "some_state").unwrap();
+
+        assert_eq!(url, "https://id.twitch.tv/oauth2/authorize?response_type=code&client_id=client&redirect_uri=http%3A%2F%2Flocalhost%3A8000%2Fapi%2Ftwitch%2Fauthorize%2Fcallback&scope=chat%3Aread+chat%3Aedit+channel%3Aread%3Aredemptions+bits%3Aread+moderator%3Aread%3Afollowers+channel%3Aread%3Ahype_train&state=some_state");
+    }
+
+    #[tokio::test]
+    async fn refresh_sends_the_refresh_token_and_returns_new_credentials() {
+        let server = serve_mock_oauth(vec![(200, TOKEN_RESPONSE)]).await;
+        let client = TwitchOAuthClient::with_base_url(server.url.clone(), String::from("client"), String::from("secret"));
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+
+        let credentials = client.refresh("old_refresh", now).await.unwrap();
+
+        assert_eq!(credentials, TwitchCredentials::new(String::from("new_access"), String::from("new_refresh"), now + Duration::from_secs(14124)).unwrap());
+        assert_eq!(server.requests(), vec!["POST /token client_id=client&client_secret=secret&grant_type=refresh_token&refresh_token=old_refresh"]);
+    }
+
+    #[tokio::test]
+    async fn exchange_code_sends_the_code_and_redirect_uri() {
+        let server = serve_mock_oauth(vec![(200, TOKEN_RESPONSE)]).await;
+        let client = TwitchOAuthClient::with_base_url(server.url.clone(), String::from("client"), String::from("secret"));
+
+        let credentials = client.exchange_code("the_code", "http://localhost/callback", SystemTime::UNIX_EPOCH).await.unwrap();
+
+        assert_eq!(credentials.access_token, "new_access");
+        assert_eq!(server.requests(), vec!["POST /token client_id=client&client_secret=secret&code=the_code&grant_type=authorization_code&redirect_uri=http%3A%2F%2Flocalhost%2Fcallback"]);
+    }
+
+    #[tokio::test]
+    async fn refresh_rejected_by_twitch_errors_with_its_message() {
+        let server = serve_mock_oauth(vec![(400, r#"{"status":400,"message":"Invalid refresh token"}"#)]).await;
+        let client = TwitchOAuthClient::with_base_url(server.url.clone(), String::from("client"), String::from("secret"));
+
+        let result = client.refresh("revoked", SystemTime::UNIX_EPOCH).await;
+
+        assert_eq!(result, Err(String::from("Token request failed with status 400 Bad Request: Invalid refresh token")));
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use serde::Serialize;
+use pran_droid_core::domain::reactions::reaction::{ChatMessageReactionStep, Reaction, ReactionStep};
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatOutputMessage {
+    pub text: String,
+    pub reply_to_message_id: Option<String>,
+}
+
+pub trait ChatOutput: Send + Sync {
+    fn send_chat_message(&self, message: ChatOutputMessage);
+}
+
+pub struct InMemoryChatOutput {
+    messages: Mutex<Vec<ChatOutputMessage>>,
+}
+
+impl InMemoryChatOutput {
+    pub fn new() -> Self {
+        InMemoryChatOutput { messages: Mutex::new(vec![]) }
+    }
+
+    pub fn messages(&self) -> Vec<ChatOutputMessage> {
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+impl ChatOutput for InMemoryChatOutput {
+    fn send_chat_message(&self, message: ChatOutputMessage) {
+        self.messages.lock().unwrap().push(message);
+    }
+}
+
+pub fn send_chat_messages(reaction: &Reaction, chat_output: &dyn ChatOutput) {
+    for (_, message) in schedule_chat_messages(reaction, &[]) {
+        chat_output.send_chat_message(message);
+    }
+}
+
+// A chat message is due once the steps before it have played, missing durations count as instant
+pub fn schedule_chat_messages(reaction: &Reaction, step_durations: &[Duration]) -> Vec<(Duration, ChatOutputMessage)> {
+    let mut offset = Duration::ZERO;
+    let mut scheduled_messages = vec![];
+    for (index, step) in reaction.steps.iter().enumerate() {
+        if let ReactionStep::ChatMessage(ChatMessageReactionStep { text, reply_to_message_id }) = step {
+            scheduled_messages.push((offset, ChatOutputMessage {
+                text: text.clone(),
+                reply_to_message_id: reply_to_message_id.clone(),
+            }));
+        }
+        offset += step_durations.get(index).copied().unwrap_or(Duration::ZERO);
+    }
+    scheduled_messages
+}
+
+#[cfg(test)]
+mod tests {
+    use pran_droid_core::domain::emotions::emotion::EmotionId;
+    use pran_droid_core::domain::reactions::reaction::{ReactionStepSkip, ReactionStepText, TalkingReactionStep};
+    use pran_droid_core::domain::reactions::reaction_definition::ReactionDefinitionId;
+    use super::*;
+
+    #[test]
+    fn send_chat_messages_sends_only_chat_message_steps_in_order() {
+        let chat_output = InMemoryChatOutput::new();
+        let reaction = Reaction {
+            source_definition_id: ReactionDefinitionId(String::from("0")),
+            steps: vec![
+                ReactionStep::ChatMessage(ChatMessageReactionStep { text: String::from("first"), reply_to_message_id: Some(String::from("an id")) }),
+                ReactionStep::Talking(TalkingReactionStep {
+                    emotion_id: EmotionId(String::from("happy")),
+                    skip: ReactionStepSkip::ImmediatelyAfter,
+                    phonemes: vec![],
+                    text: ReactionStepText::Instant(String::from("not in chat")),
+                    mouth_track: vec![],
+                }),
+                ReactionStep::ChatMessage(ChatMessageReactionStep { text: String::from("second"), reply_to_message_id: None }),
+            ],
+            overlay_targets: vec![],
+        };
+
+        send_chat_messages(&reaction, &chat_output);
+
+        assert_eq!(chat_output.messages(), vec![
+            ChatOutputMessage { text: String::from("first"), reply_to_message_id: Some(String::from("an id")) },
+            ChatOutputMessage { text: String::from("second"), reply_to_message_id: None },
+        ]);
+    }
+
+    #[test]
+    fn schedule_chat_messages_offsets_each_message_by_the_steps_before_it() {
+        let reaction = Reaction {
+            source_definition_id: ReactionDefinitionId(String::from("0")),
+            steps: vec![
+                ReactionStep::ChatMessage(ChatMessageReactionStep { text: String::from("first"), reply_to_message_id: None }),
+                ReactionStep::Talking(TalkingReactionStep {
+                    emotion_id: EmotionId(String::from("happy")),
+                    skip: ReactionStepSkip::ImmediatelyAfter,
+                    phonemes: vec![],
+                    text: ReactionStepText::Instant(String::from("not in chat")),
+                    mouth_track: vec![],
+                }),
+                ReactionStep::ChatMessage(ChatMessageReactionStep { text: String::from("second"), reply_to_message_id: None }),
+            ],
+            overlay_targets: vec![],
+        };
+
+        let scheduled_messages = schedule_chat_messages(&reaction, &[Duration::ZERO, Duration::from_millis(1500), Duration::ZERO]);
+
+        assert_eq!(scheduled_messages, vec![
+            (Duration::ZERO, ChatOutputMessage { text: String::from("first"), reply_to_message_id: None }),
+            (Duration::from_millis(1500), ChatOutputMessage { text: String::from("second"), reply_to_message_id: None }),
+        ]);
+    }
+}
@@ -1 +1,3 @@
-pub mod outputs;
\ No newline at end of file
+pub mod outputs;
+pub mod chat_output;
+pub mod overlay_protocol;
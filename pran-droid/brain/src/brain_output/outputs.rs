@@ -1,34 +1,55 @@
-use serde::Serialize;
-use pran_droid_core::domain::reactions::reaction::{Reaction, ReactionStep, ReactionStepSkip, ReactionStepText};
+use std::collections::{BTreeSet, HashMap};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use serde::{Deserialize, Serialize};
+use pran_droid_core::domain::reactions::reaction::{Reaction, ReactionStep, ReactionStepSkip, ReactionStepText, TalkingReactionStep};
+use crate::brain_output::overlay_protocol::{CatalogueOutput, EmotionLayerOutput, EmotionOutput, ImageOutput};
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ReactionOutput {
     pub steps: Vec<ReactionStepOutput>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ReactionStepOutput {
     Moving(MovingReactionStepOutput),
     Talking(TalkingReactionStepOutput),
+    CompositeTalking(CompositeTalkingReactionStepOutput),
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MovingReactionStepOutput {
     pub animation: Vec<AnimationFrameOutput>,
     pub skip: Option<ReactionStepSkipOutput>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TalkingReactionStepOutput {
     pub bubble: String,
     pub phonemes: Vec<String>,
     pub emotion: String,
     pub skip: Option<ReactionStepSkipOutput>,
+    // Mouth positions timed by the brain, overlays without it map the phonemes themselves
+    #[serde(default)]
+    pub mouth_track: Vec<MouthTrackPositionOutput>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MouthTrackPositionOutput {
+    pub position: String,
+    pub start_ms: u32,
+    pub duration_ms: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompositeTalkingReactionStepOutput {
+    pub reactions: Vec<ReactionStepOutput>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AnimationFrameOutput {
     pub frame_start: u16,
@@ -36,7 +57,7 @@ pub struct AnimationFrameOutput {
     pub image_id: String,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ReactionStepSkipOutput {
     #[serde(rename = "AfterTime", rename_all = "camelCase")]
@@ -49,7 +70,7 @@ impl From<Reaction> for ReactionOutput {
     fn from(reaction: Reaction) -> Self {
         ReactionOutput {
             steps: reaction.steps.iter()
-                .map(|step| match step {
+                .filter_map(|step| Some(match step {
                     ReactionStep::Moving(ref moving_step) => ReactionStepOutput::Moving(MovingReactionStepOutput {
                         animation: moving_step.animation.frames.0.iter().map(|frame| AnimationFrameOutput {
                             frame_end: frame.frame_end,
@@ -62,22 +83,232 @@ impl From<Reaction> for ReactionOutput {
                             ReactionStepSkip::AfterStepWithExtraMilliseconds(ms) => Some(ReactionStepSkipOutput::AfterStep { extra_ms: ms.0 }),
                         }
                     }),
-                    ReactionStep::Talking(ref talking_step) => ReactionStepOutput::Talking(TalkingReactionStepOutput {
-                        bubble: match &talking_step.text {
-                            ReactionStepText::Instant(text) => text.clone(),
-                            ReactionStepText::LetterByLetter(text) => text.clone(),
-                        },
-                        phonemes: talking_step.phonemes.clone(),
-                        emotion: talking_step.emotion_id.0.clone(),
-                        skip: match &talking_step.skip {
-                            ReactionStepSkip::ImmediatelyAfter => None,
-                            ReactionStepSkip::AfterMilliseconds(ms) => Some(ReactionStepSkipOutput::AfterMilliseconds { ms: ms.0 }),
-                            ReactionStepSkip::AfterStepWithExtraMilliseconds(ms) => Some(ReactionStepSkipOutput::AfterStep { extra_ms: ms.0 }),
-                        }
+                    ReactionStep::Talking(ref talking_step) => ReactionStepOutput::Talking(into_talking_output(talking_step)),
+                    ReactionStep::CompositeTalking(ref talking_steps) => ReactionStepOutput::CompositeTalking(CompositeTalkingReactionStepOutput {
+                        reactions: talking_steps.iter()
+                            .map(|talking_step| ReactionStepOutput::Talking(into_talking_output(talking_step)))
+                            .collect()
                     }),
-                    ReactionStep::CompositeTalking(_) => todo!("Handle composite talking")
-                })
+                    // Chat messages are sent to the stream chat, the overlay has nothing to show for them
+                    ReactionStep::ChatMessage(_) => return None,
+                }))
                 .collect()
         }
     }
-}
\ No newline at end of file
+}
+
+fn into_talking_output(talking_step: &TalkingReactionStep) -> TalkingReactionStepOutput {
+    TalkingReactionStepOutput {
+        bubble: match &talking_step.text {
+            ReactionStepText::Instant(text) => text.clone(),
+            ReactionStepText::LetterByLetter(text) => text.clone(),
+        },
+        phonemes: talking_step.phonemes.clone(),
+        emotion: talking_step.emotion_id.0.clone(),
+        skip: match &talking_step.skip {
+            ReactionStepSkip::ImmediatelyAfter => None,
+            ReactionStepSkip::AfterMilliseconds(ms) => Some(ReactionStepSkipOutput::AfterMilliseconds { ms: ms.0 }),
+            ReactionStepSkip::AfterStepWithExtraMilliseconds(ms) => Some(ReactionStepSkipOutput::AfterStep { extra_ms: ms.0 }),
+        },
+        mouth_track: talking_step.mouth_track.iter().map(|timed_position| MouthTrackPositionOutput {
+            position: (&timed_position.position).into(),
+            start_ms: timed_position.start_ms,
+            duration_ms: timed_position.duration_ms,
+        }).collect(),
+    }
+}
+
+// Resolved reactions carry the emotions and images their steps use, so that overlays do not need the API.
+// Each definition is sent once per connection, again only when its hash changes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedReactionOutput {
+    pub steps: Vec<ReactionStepOutput>,
+    pub emotions: Vec<ResolvedEmotionOutput>,
+    pub images: Vec<ResolvedImageOutput>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedEmotionOutput {
+    pub hash: String,
+    #[serde(flatten)]
+    pub emotion: EmotionOutput,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedImageOutput {
+    pub hash: String,
+    #[serde(flatten)]
+    pub image: ImageOutput,
+}
+
+// Hashes of the definitions already sent on a connection, by id
+#[derive(Debug, Default)]
+pub struct SentDefinitions {
+    emotions: HashMap<String, String>,
+    images: HashMap<String, String>,
+}
+
+pub fn resolve_reaction(reaction: &ReactionOutput, catalogue: &CatalogueOutput, sent_definitions: &mut SentDefinitions) -> ResolvedReactionOutput {
+    let mut emotion_ids = BTreeSet::new();
+    let mut image_ids = BTreeSet::new();
+    collect_step_ids(&reaction.steps, &mut emotion_ids, &mut image_ids);
+
+    let emotions: Vec<&EmotionOutput> = catalogue.emotions.iter().filter(|emotion| emotion_ids.contains(emotion.id.as_str())).collect();
+    for emotion in &emotions {
+        for layer in &emotion.layers {
+            match layer {
+                EmotionLayerOutput::Animation { frames } => image_ids.extend(frames.iter().map(|frame| frame.image_id.as_str())),
+                EmotionLayerOutput::Mouth { mouth_mapping } => image_ids.extend(mouth_mapping.values().map(String::as_str)),
+            }
+        }
+    }
+
+    ResolvedReactionOutput {
+        steps: reaction.steps.clone(),
+        emotions: emotions.into_iter()
+            .filter_map(|emotion| unsent(&mut sent_definitions.emotions, &emotion.id, emotion)
+                .map(|hash| ResolvedEmotionOutput { hash, emotion: emotion.clone() }))
+            .collect(),
+        images: catalogue.images.iter()
+            .filter(|image| image_ids.contains(image.id.as_str()))
+            .filter_map(|image| unsent(&mut sent_definitions.images, &image.id, image)
+                .map(|hash| ResolvedImageOutput { hash, image: image.clone() }))
+            .collect(),
+    }
+}
+
+fn collect_step_ids<'a>(steps: &'a [ReactionStepOutput], emotion_ids: &mut BTreeSet<&'a str>, image_ids: &mut BTreeSet<&'a str>) {
+    for step in steps {
+        match step {
+            ReactionStepOutput::Moving(moving_step) => image_ids.extend(moving_step.animation.iter().map(|frame| frame.image_id.as_str())),
+            ReactionStepOutput::Talking(talking_step) => { emotion_ids.insert(talking_step.emotion.as_str()); },
+            ReactionStepOutput::CompositeTalking(composite_step) => collect_step_ids(&composite_step.reactions, emotion_ids, image_ids),
+        }
+    }
+}
+
+// Returns the hash of the definition when the connection does not have this version yet
+fn unsent<T: Serialize>(sent: &mut HashMap<String, String>, id: &str, definition: &T) -> Option<String> {
+    let hash = definition_hash(definition);
+    if sent.get(id) == Some(&hash) {
+        return None;
+    }
+    sent.insert(String::from(id), hash.clone());
+    Some(hash)
+}
+
+// Going through a json value sorts the map keys, so equal definitions always get the same hash
+fn definition_hash<T: Serialize>(definition: &T) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_value(definition).unwrap().to_string().hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use pran_droid_core::domain::emotions::emotion::{EmotionId, MouthPositionName};
+    use pran_droid_core::domain::reactions::reaction_definition::ReactionDefinitionId;
+    use pran_droid_core::domain::visemes::mouth_track::TimedMouthPosition;
+    use super::*;
+
+    fn create_catalogue(mouth_image_id: &str) -> CatalogueOutput {
+        CatalogueOutput {
+            emotions: vec![
+                EmotionOutput {
+                    id: String::from("happy"),
+                    name: String::from("happy"),
+                    layers: vec![
+                        EmotionLayerOutput::Animation { frames: vec![AnimationFrameOutput { frame_start: 0, frame_end: 5, image_id: String::from("body") }] },
+                        EmotionLayerOutput::Mouth { mouth_mapping: HashMap::from([
+                            (String::from("Ah"), String::from(mouth_image_id)),
+                            (String::from("Oh"), String::from("mouth oh")),
+                            (String::from("Ee"), String::from("mouth ee")),
+                        ]) },
+                    ]
+                },
+                EmotionOutput { id: String::from("sad"), name: String::from("sad"), layers: vec![] },
+            ],
+            images: ["body", "mouth ah", "mouth ah v2", "mouth oh", "mouth ee", "arm", "unused"].into_iter()
+                .map(|id| ImageOutput { id: String::from(id), url: format!("https://images/{}.png", id) })
+                .collect(),
+        }
+    }
+
+    fn create_reaction() -> ReactionOutput {
+        ReactionOutput {
+            steps: vec![
+                ReactionStepOutput::Moving(MovingReactionStepOutput {
+                    animation: vec![AnimationFrameOutput { frame_start: 0, frame_end: 10, image_id: String::from("arm") }],
+                    skip: None,
+                }),
+                ReactionStepOutput::CompositeTalking(CompositeTalkingReactionStepOutput {
+                    reactions: vec![ReactionStepOutput::Talking(TalkingReactionStepOutput {
+                        bubble: String::from("hello"),
+                        phonemes: vec![],
+                        emotion: String::from("happy"),
+                        skip: None,
+                        mouth_track: vec![],
+                    })]
+                }),
+            ]
+        }
+    }
+
+    fn ids(resolved: &ResolvedReactionOutput) -> (Vec<&str>, Vec<&str>) {
+        (
+            resolved.emotions.iter().map(|emotion| emotion.emotion.id.as_str()).collect(),
+            resolved.images.iter().map(|image| image.image.id.as_str()).collect(),
+        )
+    }
+
+    #[test]
+    fn resolve_reaction_embeds_the_used_definitions_once_per_connection() {
+        let catalogue = create_catalogue("mouth ah");
+        let mut sent_definitions = SentDefinitions::default();
+
+        let first = resolve_reaction(&create_reaction(), &catalogue, &mut sent_definitions);
+        let second = resolve_reaction(&create_reaction(), &catalogue, &mut sent_definitions);
+
+        assert_eq!(first.steps, create_reaction().steps);
+        assert_eq!(ids(&first), (vec!["happy"], vec!["body", "mouth ah", "mouth oh", "mouth ee", "arm"]));
+        assert_eq!(ids(&second), (vec![], vec![]));
+        assert_eq!(second.steps, create_reaction().steps);
+    }
+
+    #[test]
+    fn resolve_reaction_sends_changed_definitions_again() {
+        let mut sent_definitions = SentDefinitions::default();
+        resolve_reaction(&create_reaction(), &create_catalogue("mouth ah"), &mut sent_definitions);
+
+        let unchanged = resolve_reaction(&create_reaction(), &create_catalogue("mouth ah"), &mut sent_definitions);
+        let changed = resolve_reaction(&create_reaction(), &create_catalogue("mouth ah v2"), &mut sent_definitions);
+
+        assert_eq!(ids(&unchanged), (vec![], vec![]));
+        assert_eq!(ids(&changed), (vec!["happy"], vec!["mouth ah v2"]));
+    }
+
+    #[test]
+    fn reaction_output_includes_the_timed_mouth_track() {
+        let reaction = Reaction {
+            source_definition_id: ReactionDefinitionId(String::from("0")),
+            steps: vec![ReactionStep::Talking(TalkingReactionStep {
+                emotion_id: EmotionId(String::from("happy")),
+                skip: ReactionStepSkip::ImmediatelyAfter,
+                phonemes: vec![String::from("P")],
+                mouth_track: vec![
+                    TimedMouthPosition { position: MouthPositionName::P1, start_ms: 0, duration_ms: 70 },
+                    TimedMouthPosition { position: MouthPositionName::P2, start_ms: 70, duration_ms: 70 },
+                ],
+                text: ReactionStepText::Instant(String::from("p")),
+            })],
+            overlay_targets: vec![],
+        };
+
+        let output = serde_json::to_value(ReactionOutput::from(reaction)).unwrap();
+
+        assert_eq!(output["steps"][0]["mouthTrack"], serde_json::json!([
+            { "position": "p1", "startMs": 0, "durationMs": 70 },
+            { "position": "p2", "startMs": 70, "durationMs": 70 },
+        ]));
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use pran_droid_core::application::emotions::dtos::emotion_dto::{EmotionDto, EmotionLayerDto};
+use pran_droid_core::application::images::dtos::image_dto::ImageDto;
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use pran_droid_core::domain::images::image_repository::ImageRepository;
+use crate::brain_output::outputs::{AnimationFrameOutput, ReactionOutput, ResolvedReactionOutput};
+
+pub const OVERLAY_PROTOCOL_VERSION: u32 = 1;
+
+// Every message on the overlay websocket carries the protocol version next to the tagged message
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OverlayEnvelope<T> {
+    pub version: u32,
+    #[serde(flatten)]
+    pub message: T,
+}
+
+// Sent by the overlays to the brain
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum OverlayMessage {
+    Hello {
+        name: String,
+        #[serde(default)]
+        capabilities: Vec<OverlayCapability>,
+        // Overlay channels to join on top of the one in the websocket path
+        #[serde(default)]
+        channels: Vec<String>,
+        // Authenticates overlays that could not pass the token in the websocket url
+        #[serde(default)]
+        token: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ReactionStarted { reaction_id: u64 },
+    #[serde(rename_all = "camelCase")]
+    ReactionFinished { reaction_id: u64 },
+    RequestCatalogue,
+    Ping { nonce: Option<u64> },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OverlayCapability {
+    Reactions,
+    // Reactions embedding the emotions and images they use, instead of plain reactions
+    ResolvedReactions,
+    Catalogue,
+    // Capabilities of newer overlays are ignored rather than refusing their hello
+    #[serde(other)]
+    Unknown,
+}
+
+// Sent by the brain to the overlays
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum BrainEvent {
+    #[serde(rename_all = "camelCase")]
+    Reaction { reaction_id: u64, reaction: ReactionOutput },
+    #[serde(rename_all = "camelCase")]
+    ResolvedReaction { reaction_id: u64, reaction: ResolvedReactionOutput },
+    Status { status: BrainStatusOutput },
+    Catalogue { catalogue: CatalogueOutput },
+    ConfigChanged { catalogue: CatalogueOutput },
+    Pong { nonce: Option<u64> },
+    Error { message: String },
+    // The finished reactions a late overlay asked for when connecting
+    RecentReactions { reactions: Vec<RecentReactionOutput> },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentReactionOutput {
+    pub reaction_id: u64,
+    pub reaction: ReactionOutput,
+    pub started_ms_ago: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrainStatusOutput {
+    pub overlays: Vec<String>,
+    pub playing_reaction_id: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CatalogueOutput {
+    pub emotions: Vec<EmotionOutput>,
+    pub images: Vec<ImageOutput>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EmotionOutput {
+    pub id: String,
+    pub name: String,
+    pub layers: Vec<EmotionLayerOutput>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum EmotionLayerOutput {
+    Animation { frames: Vec<AnimationFrameOutput> },
+    #[serde(rename_all = "camelCase")]
+    Mouth { mouth_mapping: HashMap<String, String> },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImageOutput {
+    pub id: String,
+    pub url: String,
+}
+
+impl From<EmotionDto> for EmotionOutput {
+    fn from(emotion: EmotionDto) -> Self {
+        EmotionOutput {
+            id: emotion.id,
+            name: emotion.name,
+            layers: emotion.animation.into_iter().map(|layer| match layer {
+                EmotionLayerDto::Animation(frames) => EmotionLayerOutput::Animation {
+                    frames: frames.into_iter().map(|frame| AnimationFrameOutput {
+                        frame_start: frame.frame_start,
+                        frame_end: frame.frame_end,
+                        image_id: frame.image_id,
+                    }).collect()
+                },
+                EmotionLayerDto::Mouth { mouth_mapping } => EmotionLayerOutput::Mouth { mouth_mapping },
+            }).collect(),
+        }
+    }
+}
+
+impl From<ImageDto> for ImageOutput {
+    fn from(image: ImageDto) -> Self {
+        ImageOutput { id: image.id, url: image.url }
+    }
+}
+
+pub async fn load_catalogue(emotion_repository: &dyn EmotionRepository, image_repository: &dyn ImageRepository) -> Result<CatalogueOutput, String> {
+    let emotions = emotion_repository.try_get_all().await.map_err(|error| error.to_string())?;
+    let images = image_repository.try_get_all().await.map_err(|error| error.to_string())?;
+
+    Ok(CatalogueOutput {
+        emotions: emotions.into_iter().map(EmotionDto::from).map(From::from).collect(),
+        images: images.into_iter().map(ImageDto::from).map(From::from).collect(),
+    })
+}
+
+pub fn encode_brain_event(event: BrainEvent) -> String {
+    serde_json::to_string(&OverlayEnvelope { version: OVERLAY_PROTOCOL_VERSION, message: event }).unwrap()
+}
+
+pub fn decode_overlay_message(text: &str) -> Result<OverlayMessage, String> {
+    #[derive(Deserialize)]
+    struct VersionOnly { version: u32 }
+
+    let VersionOnly { version } = serde_json::from_str(text).map_err(|error| format!("Invalid message: {}", error))?;
+    if version != OVERLAY_PROTOCOL_VERSION {
+        return Err(format!("Unsupported protocol version {}, expected {}", version, OVERLAY_PROTOCOL_VERSION));
+    }
+
+    serde_json::from_str::<OverlayEnvelope<OverlayMessage>>(text)
+        .map(|envelope| envelope.message)
+        .map_err(|error| format!("Invalid message: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::DeserializeOwned;
+    use crate::brain_output::outputs::{MovingReactionStepOutput, ReactionStepOutput, ReactionStepSkipOutput, ResolvedEmotionOutput, ResolvedImageOutput, TalkingReactionStepOutput};
+    use super::*;
+
+    fn round_trip<T: Serialize + DeserializeOwned>(message: &OverlayEnvelope<T>) -> OverlayEnvelope<T> {
+        serde_json::from_str(&serde_json::to_string(message).unwrap()).unwrap()
+    }
+
+    fn envelope<T>(message: T) -> OverlayEnvelope<T> {
+        OverlayEnvelope { version: OVERLAY_PROTOCOL_VERSION, message }
+    }
+
+    #[test]
+    fn overlay_messages_round_trip() {
+        let messages = vec![
+            OverlayMessage::Hello {
+                name: String::from("overlay"),
+                capabilities: vec![OverlayCapability::Reactions, OverlayCapability::Catalogue],
+                channels: vec![String::from("main-scene")],
+                token: Some(String::from("a token")),
+            },
+            OverlayMessage::ReactionStarted { reaction_id: 3 },
+            OverlayMessage::ReactionFinished { reaction_id: 3 },
+            OverlayMessage::RequestCatalogue,
+            OverlayMessage::Ping { nonce: Some(42) },
+            OverlayMessage::Ping { nonce: None },
+        ];
+
+        for message in messages {
+            assert_eq!(round_trip(&envelope(message.clone())), envelope(message));
+        }
+    }
+
+    #[test]
+    fn brain_events_round_trip() {
+        let events = vec![
+            BrainEvent::Reaction {
+                reaction_id: 7,
+                reaction: ReactionOutput {
+                    steps: vec![
+                        ReactionStepOutput::Moving(MovingReactionStepOutput {
+                            animation: vec![AnimationFrameOutput { frame_start: 0, frame_end: 10, image_id: String::from("an image") }],
+                            skip: Some(ReactionStepSkipOutput::AfterMilliseconds { ms: 100 }),
+                        }),
+                        ReactionStepOutput::Talking(TalkingReactionStepOutput {
+                            bubble: String::from("hello"),
+                            phonemes: vec![String::from("h"), String::from("e")],
+                            emotion: String::from("happy"),
+                            skip: None,
+                            mouth_track: vec![],
+                        }),
+                    ]
+                }
+            },
+            BrainEvent::Status { status: BrainStatusOutput { overlays: vec![String::from("overlay")], playing_reaction_id: Some(7) } },
+            BrainEvent::Catalogue { catalogue: CatalogueOutput {
+                emotions: vec![EmotionOutput {
+                    id: String::from("1"),
+                    name: String::from("happy"),
+                    layers: vec![
+                        EmotionLayerOutput::Animation { frames: vec![AnimationFrameOutput { frame_start: 0, frame_end: 5, image_id: String::from("body") }] },
+                        EmotionLayerOutput::Mouth { mouth_mapping: HashMap::from([(String::from("Ah"), String::from("mouth ah"))]) },
+                    ]
+                }],
+                images: vec![ImageOutput { id: String::from("body"), url: String::from("https://images/body.png") }],
+            } },
+            BrainEvent::ConfigChanged { catalogue: CatalogueOutput::default() },
+            BrainEvent::Pong { nonce: Some(42) },
+            BrainEvent::Error { message: String::from("something went wrong") },
+            BrainEvent::ResolvedReaction { reaction_id: 8, reaction: ResolvedReactionOutput {
+                steps: vec![],
+                emotions: vec![ResolvedEmotionOutput {
+                    hash: String::from("0123456789abcdef"),
+                    emotion: EmotionOutput { id: String::from("1"), name: String::from("happy"), layers: vec![] },
+                }],
+                images: vec![ResolvedImageOutput {
+                    hash: String::from("fedcba9876543210"),
+                    image: ImageOutput { id: String::from("body"), url: String::from("https://images/body.png") },
+                }],
+            } },
+            BrainEvent::RecentReactions { reactions: vec![RecentReactionOutput { reaction_id: 6, reaction: ReactionOutput { steps: vec![] }, started_ms_ago: 2500 }] },
+        ];
+
+        for event in events {
+            assert_eq!(round_trip(&envelope(event.clone())), envelope(event));
+        }
+    }
+
+    #[test]
+    fn encode_brain_event_tags_the_event_with_type_and_version() {
+        let encoded: serde_json::Value = serde_json::from_str(&encode_brain_event(BrainEvent::Reaction {
+            reaction_id: 1,
+            reaction: ReactionOutput { steps: vec![] },
+        })).unwrap();
+
+        assert_eq!(encoded, serde_json::json!({ "version": 1, "type": "Reaction", "reactionId": 1, "reaction": { "steps": [] } }));
+    }
+
+    #[test]
+    fn decode_overlay_message_reads_the_tagged_message() {
+        let message = decode_overlay_message(r#"{"version":1,"type":"Hello","name":"overlay","capabilities":["Reactions","Teleport"]}"#);
+
+        assert_eq!(message, Ok(OverlayMessage::Hello {
+            name: String::from("overlay"),
+            capabilities: vec![OverlayCapability::Reactions, OverlayCapability::Unknown],
+            channels: vec![],
+            token: None,
+        }));
+    }
+
+    #[test]
+    fn decode_overlay_message_rejects_other_versions() {
+        let message = decode_overlay_message(r#"{"version":2,"type":"Ping","nonce":null}"#);
+
+        assert_eq!(message, Err(String::from("Unsupported protocol version 2, expected 1")));
+    }
+
+    #[test]
+    fn decode_overlay_message_rejects_unknown_messages() {
+        assert!(decode_overlay_message(r#"{"version":1,"type":"Dance"}"#).is_err());
+        assert!(decode_overlay_message("Hello Server!").is_err());
+    }
+}
@@ -1,9 +1,15 @@
 #[macro_use] extern crate log;
 
 mod phonemiser;
-#[cfg(feature="twitch")]
+#[cfg(feature="twitch_auth")]
+pub mod authentication;
+#[cfg(feature="runtime")]
 pub mod stream_interface;
-#[cfg(feature="twitch")]
+#[cfg(feature="runtime")]
 pub mod run;
+#[cfg(feature="runtime")]
+pub mod overlay_server;
 pub mod simulate;
 pub mod brain_output;
+pub mod scheduler;
+pub mod viseme_mapping_file;
@@ -0,0 +1,785 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use futures::channel::mpsc::{unbounded, UnboundedSender};
+use futures::future::Either;
+use futures::{future, pin_mut, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio_native_tls::{native_tls, TlsAcceptor};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+use pran_droid_core::domain::brain::clock::Clock;
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use pran_droid_core::domain::images::image_repository::ImageRepository;
+use pran_droid_core::domain::reactions::reaction::Reaction;
+use pran_droid_core::domain::reactions::reaction_definition::OverlayTarget;
+use crate::brain_output::outputs::{ReactionOutput, resolve_reaction, SentDefinitions};
+use crate::brain_output::overlay_protocol::{BrainEvent, BrainStatusOutput, CatalogueOutput, decode_overlay_message, encode_brain_event, load_catalogue, OverlayCapability, OverlayMessage, RecentReactionOutput};
+use crate::scheduler::recent_reactions::{RecentReaction, RecentReactions};
+
+// Overlays can ask for at most this many finished reactions when they join
+const RECENT_REACTIONS_CAPACITY: usize = 20;
+
+pub struct OverlayServerOptions {
+    pub address: SocketAddr,
+    // When set overlays have to send it as the `token` query parameter or in their hello
+    pub token: Option<String>,
+    pub tls: Option<OverlayTlsOptions>,
+}
+
+pub struct OverlayTlsOptions {
+    pub certificate_path: PathBuf,
+    pub private_key_path: PathBuf,
+}
+
+struct OverlayConnection {
+    sender: UnboundedSender<Message>,
+    name: Option<String>,
+    capabilities: Option<Vec<OverlayCapability>>,
+    channels: Vec<String>,
+    authenticated: bool,
+    replay: usize,
+    sent_definitions: SentDefinitions,
+}
+
+impl OverlayConnection {
+    // Overlays that did not say hello receive every event, reactions with targets only reach overlays on those channels
+    fn accepts(&self, event: &BrainEvent, targets: &[String]) -> bool {
+        if !self.authenticated {
+            return false;
+        }
+        if !targets.is_empty() && !self.channels.iter().any(|channel| targets.contains(channel)) {
+            return false;
+        }
+
+        match (&self.capabilities, event) {
+            (Some(capabilities), BrainEvent::Reaction { .. } | BrainEvent::ResolvedReaction { .. }) => capabilities.contains(&OverlayCapability::Reactions)
+                || capabilities.contains(&OverlayCapability::ResolvedReactions),
+            (Some(capabilities), BrainEvent::ConfigChanged { .. }) => capabilities.contains(&OverlayCapability::Catalogue),
+            _ => true,
+        }
+    }
+
+    // Overlays asking for resolved reactions also get the emotions and images they did not receive yet
+    fn send_reaction(&mut self, reaction_id: u64, reaction: ReactionOutput, targets: &[String], catalogue: &CatalogueOutput) {
+        let event = BrainEvent::Reaction { reaction_id, reaction };
+        if !self.accepts(&event, targets) {
+            return;
+        }
+
+        let event = match event {
+            BrainEvent::Reaction { reaction_id, reaction } if self.resolves_reactions() => BrainEvent::ResolvedReaction {
+                reaction_id,
+                reaction: resolve_reaction(&reaction, catalogue, &mut self.sent_definitions),
+            },
+            event => event,
+        };
+        self.sender.unbounded_send(Message::Text(encode_brain_event(event))).ok();
+    }
+
+    fn resolves_reactions(&self) -> bool {
+        matches!(&self.capabilities, Some(capabilities) if capabilities.contains(&OverlayCapability::ResolvedReactions))
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct OverlayHandshake {
+    channel: Option<String>,
+    token: Option<String>,
+    // How many of the last finished reactions the overlay wants when it joins
+    replay: usize,
+}
+
+// Overlays connect to `/<channel>?token=<token>&replay=<count>`, every part is optional
+fn parse_handshake(path: &str, query: Option<&str>) -> Result<OverlayHandshake, String> {
+    let channel = match path.trim_matches('/') {
+        "" => None,
+        channel => Some(OverlayTarget::new(String::from(channel)).map_err(|_| String::from("Invalid overlay channel"))?.0),
+    };
+    let parameters: HashMap<&str, &str> = query.into_iter()
+        .flat_map(|query| query.split('&'))
+        .filter_map(|parameter| parameter.split_once('='))
+        .collect();
+    let replay = match parameters.get("replay") {
+        Some(replay) => replay.parse::<usize>().map_err(|_| String::from("Invalid replay count"))?,
+        None => 0,
+    };
+
+    Ok(OverlayHandshake { channel, token: parameters.get("token").map(|token| String::from(*token)), replay })
+}
+
+// Constant time so the time to reject a token does not leak how much of it was right
+fn tokens_match(expected: &str, given: &str) -> bool {
+    expected.len() == given.len() && expected.bytes().zip(given.bytes()).fold(0, |difference, (a, b)| difference | (a ^ b)) == 0
+}
+
+fn overlay_targets(reaction: &Reaction) -> Vec<String> {
+    reaction.overlay_targets.iter().map(|target| target.0.clone()).collect()
+}
+
+pub struct OverlayServer {
+    connections: Mutex<HashMap<SocketAddr, OverlayConnection>>,
+    playing_reaction_id: Mutex<Option<u64>>,
+    emotion_repository: Arc<dyn EmotionRepository>,
+    image_repository: Arc<dyn ImageRepository>,
+    token: Option<String>,
+    recent_reactions: Mutex<RecentReactions>,
+    catalogue: Mutex<CatalogueOutput>,
+    clock: Arc<dyn Clock>,
+}
+
+impl OverlayServer {
+    pub fn new(emotion_repository: Arc<dyn EmotionRepository>, image_repository: Arc<dyn ImageRepository>, token: Option<String>, clock: Arc<dyn Clock>) -> Self {
+        OverlayServer {
+            connections: Mutex::new(HashMap::new()),
+            playing_reaction_id: Mutex::new(None),
+            emotion_repository,
+            image_repository,
+            token,
+            recent_reactions: Mutex::new(RecentReactions::new(RECENT_REACTIONS_CAPACITY)),
+            catalogue: Mutex::new(CatalogueOutput::default()),
+            clock,
+        }
+    }
+
+    // Returns false when the reaction has nothing to show on the overlays
+    pub fn send_reaction(&self, reaction_id: u64, reaction: Reaction, step_durations: Vec<Duration>) -> bool {
+        let targets = overlay_targets(&reaction);
+        let output: ReactionOutput = reaction.clone().into();
+        if output.steps.is_empty() {
+            return false;
+        }
+
+        self.recent_reactions.lock().unwrap().push(RecentReaction { reaction_id, reaction, started_at: self.clock.now(), step_durations });
+        debug!("Sending reaction {} {:?} to {:?}", reaction_id, output, targets);
+        let catalogue = self.catalogue.lock().unwrap();
+        for connection in self.connections.lock().unwrap().values_mut() {
+            connection.send_reaction(reaction_id, output.clone(), &targets, &catalogue);
+        }
+        true
+    }
+
+    // Keeps the emotions and images used to resolve reactions, returns true when they changed
+    pub fn update_catalogue(&self, catalogue: CatalogueOutput) -> bool {
+        let mut current_catalogue = self.catalogue.lock().unwrap();
+        if *current_catalogue == catalogue {
+            return false;
+        }
+        *current_catalogue = catalogue;
+        true
+    }
+
+    pub fn broadcast(&self, event: BrainEvent) {
+        self.broadcast_to_targets(event, &[]);
+    }
+
+    fn broadcast_to_targets(&self, event: BrainEvent, targets: &[String]) {
+        let message = encode_brain_event(event.clone());
+
+        for connection in self.connections.lock().unwrap().values().filter(|connection| connection.accepts(&event, targets)) {
+            connection.sender.unbounded_send(Message::Text(message.clone())).ok();
+        }
+    }
+
+    fn send_to(&self, addr: &SocketAddr, event: BrainEvent) {
+        if let Some(connection) = self.connections.lock().unwrap().get(addr) {
+            connection.sender.unbounded_send(Message::Text(encode_brain_event(event))).ok();
+        }
+    }
+
+    fn is_authorised(&self, token: Option<&str>) -> bool {
+        match (&self.token, token) {
+            (None, _) => true,
+            (Some(expected), Some(given)) => tokens_match(expected, given),
+            (Some(_), None) => false,
+        }
+    }
+
+    fn is_authenticated(&self, addr: &SocketAddr) -> bool {
+        matches!(self.connections.lock().unwrap().get(addr), Some(connection) if connection.authenticated)
+    }
+
+    fn connect(&self, addr: SocketAddr, sender: UnboundedSender<Message>, handshake: OverlayHandshake) {
+        let authenticated = self.is_authorised(handshake.token.as_deref());
+        self.connections.lock().unwrap().insert(addr, OverlayConnection {
+            sender,
+            name: None,
+            capabilities: None,
+            channels: handshake.channel.into_iter().collect(),
+            authenticated,
+            replay: handshake.replay,
+            sent_definitions: SentDefinitions::default(),
+        });
+
+        if authenticated {
+            self.replay_recent_reactions(&addr);
+        }
+    }
+
+    // Late overlays get the requested finished reactions and what is left of the one still playing
+    fn replay_recent_reactions(&self, addr: &SocketAddr) {
+        let now = self.clock.now();
+        let replay = match self.connections.lock().unwrap().get(addr) {
+            Some(connection) => connection.replay,
+            None => return,
+        };
+
+        let recent_reactions = self.recent_reactions.lock().unwrap();
+        let finished: Vec<(RecentReactionOutput, Vec<String>)> = recent_reactions.finished(now, replay).into_iter()
+            .map(|recent_reaction| (RecentReactionOutput {
+                reaction_id: recent_reaction.reaction_id,
+                reaction: recent_reaction.reaction.clone().into(),
+                started_ms_ago: now.saturating_duration_since(recent_reaction.started_at).as_millis() as u64,
+            }, overlay_targets(&recent_reaction.reaction)))
+            .collect();
+        let in_progress = recent_reactions.in_progress(now)
+            .and_then(|recent_reaction| recent_reaction.remaining_reaction(now).map(|remaining| (recent_reaction.reaction_id, remaining)))
+            .map(|(reaction_id, remaining)| (reaction_id, overlay_targets(&remaining), remaining.into()));
+        drop(recent_reactions);
+
+        let catalogue = self.catalogue.lock().unwrap();
+        let mut connections = self.connections.lock().unwrap();
+        let connection = match connections.get_mut(addr) {
+            Some(connection) => connection,
+            None => return,
+        };
+        if replay > 0 {
+            let reactions = finished.into_iter()
+                .filter(|(recent_reaction, targets)| connection.accepts(&BrainEvent::Reaction { reaction_id: recent_reaction.reaction_id, reaction: recent_reaction.reaction.clone() }, targets))
+                .map(|(recent_reaction, _)| recent_reaction)
+                .collect();
+            connection.sender.unbounded_send(Message::Text(encode_brain_event(BrainEvent::RecentReactions { reactions }))).ok();
+        }
+        if let Some((reaction_id, targets, reaction)) = in_progress {
+            connection.send_reaction(reaction_id, reaction, &targets, &catalogue);
+        }
+    }
+
+    fn disconnect(&self, addr: &SocketAddr) {
+        if self.connections.lock().unwrap().remove(addr).is_some() {
+            self.broadcast_status();
+        }
+    }
+
+    fn broadcast_status(&self) {
+        let mut overlays: Vec<String> = self.connections.lock().unwrap().values()
+            .filter(|connection| connection.authenticated)
+            .filter_map(|connection| connection.name.clone())
+            .collect();
+        overlays.sort();
+        let playing_reaction_id = *self.playing_reaction_id.lock().unwrap();
+
+        self.broadcast(BrainEvent::Status { status: BrainStatusOutput { overlays, playing_reaction_id } });
+    }
+
+    // Returns false when the overlay has to be disconnected
+    async fn handle_message(&self, addr: &SocketAddr, message: OverlayMessage) -> bool {
+        let authenticated = self.is_authenticated(addr);
+
+        match message {
+            OverlayMessage::Hello { name, capabilities, channels, token } => {
+                if !authenticated && !self.is_authorised(token.as_deref()) {
+                    warn!("Overlay {} said hello with an invalid token", addr);
+                    self.send_to(addr, BrainEvent::Error { message: String::from("Invalid overlay token") });
+                    return false;
+                }
+                info!("Overlay {} said hello as {} with capabilities {:?} and channels {:?}", addr, name, capabilities, channels);
+                if let Some(connection) = self.connections.lock().unwrap().get_mut(addr) {
+                    connection.name = Some(name);
+                    connection.capabilities = Some(capabilities);
+                    connection.authenticated = true;
+                    for channel in channels {
+                        match OverlayTarget::new(channel.clone()) {
+                            Ok(_) if !connection.channels.contains(&channel) => connection.channels.push(channel),
+                            Ok(_) => {},
+                            Err(_) => warn!("Overlay {} asked for the invalid channel {}", addr, channel),
+                        }
+                    }
+                }
+                self.broadcast_status();
+                if !authenticated {
+                    self.replay_recent_reactions(addr);
+                }
+            },
+            _ if !authenticated => {
+                self.send_to(addr, BrainEvent::Error { message: String::from("Overlays have to say hello with a token first") });
+                return false;
+            },
+            OverlayMessage::ReactionStarted { reaction_id } => {
+                debug!("Overlay {} started reaction {}", addr, reaction_id);
+                *self.playing_reaction_id.lock().unwrap() = Some(reaction_id);
+                self.broadcast_status();
+            },
+            OverlayMessage::ReactionFinished { reaction_id } => {
+                debug!("Overlay {} finished reaction {}", addr, reaction_id);
+                let mut playing_reaction_id = self.playing_reaction_id.lock().unwrap();
+                if *playing_reaction_id == Some(reaction_id) {
+                    *playing_reaction_id = None;
+                }
+                drop(playing_reaction_id);
+                self.broadcast_status();
+            },
+            OverlayMessage::RequestCatalogue => {
+                match load_catalogue(self.emotion_repository.as_ref(), self.image_repository.as_ref()).await {
+                    Ok(catalogue) => self.send_to(addr, BrainEvent::Catalogue { catalogue }),
+                    Err(error) => {
+                        warn!("Catalogue could not be loaded for overlay {}: {}", addr, error);
+                        self.send_to(addr, BrainEvent::Error { message: String::from("Catalogue could not be loaded") });
+                    },
+                }
+            },
+            OverlayMessage::Ping { nonce } => self.send_to(addr, BrainEvent::Pong { nonce }),
+        }
+
+        true
+    }
+
+    async fn handle_text(&self, addr: &SocketAddr, text: &str) -> bool {
+        match decode_overlay_message(text) {
+            Ok(message) => self.handle_message(addr, message).await,
+            Err(message) => {
+                debug!("Overlay {} sent an invalid message: {}", addr, message);
+                self.send_to(addr, BrainEvent::Error { message });
+                self.is_authenticated(addr)
+            },
+        }
+    }
+}
+
+pub async fn run_overlay_server(server: Arc<OverlayServer>, options: OverlayServerOptions) {
+    let tls_acceptor = options.tls.as_ref()
+        .map(|tls| load_tls_acceptor(tls).expect("Failed to load the websocket TLS certificate"));
+
+    let try_socket = TcpListener::bind(&options.address).await;
+    let listener = try_socket.expect("Failed to bind");
+    info!("Websocket listening on: {}://{}", if tls_acceptor.is_some() { "wss" } else { "ws" }, options.address);
+
+    while let Ok((stream, addr)) = listener.accept().await {
+        let server = server.clone();
+        match tls_acceptor.clone() {
+            Some(tls_acceptor) => {
+                tokio::spawn(async move {
+                    match tls_acceptor.accept(stream).await {
+                        Ok(stream) => handle_connection(server, stream, addr).await,
+                        Err(error) => warn!("Error during the TLS handshake with {}: {}", addr, error),
+                    }
+                });
+            },
+            None => {
+                tokio::spawn(handle_connection(server, stream, addr));
+            },
+        }
+    }
+}
+
+fn load_tls_acceptor(options: &OverlayTlsOptions) -> Result<TlsAcceptor, String> {
+    let certificate = std::fs::read(&options.certificate_path)
+        .map_err(|error| format!("Cannot read {}: {}", options.certificate_path.display(), error))?;
+    let private_key = std::fs::read(&options.private_key_path)
+        .map_err(|error| format!("Cannot read {}: {}", options.private_key_path.display(), error))?;
+    let identity = native_tls::Identity::from_pkcs8(&certificate, &private_key).map_err(|error| error.to_string())?;
+
+    native_tls::TlsAcceptor::new(identity).map(TlsAcceptor::from).map_err(|error| error.to_string())
+}
+
+fn reject_handshake(status: StatusCode, reason: &str) -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some(String::from(reason)));
+    *response.status_mut() = status;
+    response
+}
+
+// The handshake callback has to return the http error response as it is
+#[allow(clippy::result_large_err)]
+async fn handle_connection<S: AsyncRead + AsyncWrite + Unpin>(server: Arc<OverlayServer>, stream: S, addr: SocketAddr) {
+    let mut handshake = None;
+    let accept_overlay = |request: &Request, response: Response| {
+        let overlay_handshake = parse_handshake(request.uri().path(), request.uri().query())
+            .map_err(|reason| reject_handshake(StatusCode::BAD_REQUEST, &reason))?;
+        if overlay_handshake.token.is_some() && !server.is_authorised(overlay_handshake.token.as_deref()) {
+            return Err(reject_handshake(StatusCode::UNAUTHORIZED, "Invalid overlay token"));
+        }
+        handshake = Some(overlay_handshake);
+        Ok(response)
+    };
+    let ws_stream = match tokio_tungstenite::accept_hdr_async(stream, accept_overlay).await {
+        Ok(ws_stream) => ws_stream,
+        Err(error) => {
+            warn!("Error during the websocket handshake with {}: {}", addr, error);
+            return;
+        },
+    };
+    let (tx, rx) = unbounded();
+    server.connect(addr, tx, handshake.expect("Handshake accepted without an overlay"));
+
+    info!("WebSocket connection established: {}", addr);
+    let (outgoing, mut incoming) = ws_stream.split();
+    let forwarding_stream = rx.map(Ok).forward(outgoing);
+
+    let incoming_stream = async {
+        while let Some(Ok(message)) = incoming.next().await {
+            if let Message::Text(text) = message {
+                if !server.handle_text(&addr, &text).await {
+                    break;
+                }
+            }
+        }
+    };
+
+    pin_mut!(forwarding_stream, incoming_stream);
+    if let Either::Right((_, forwarding_stream)) = future::select(forwarding_stream, incoming_stream).await {
+        // Removing the connection ends the queue, the pending messages like a rejection error are still sent
+        server.disconnect(&addr);
+        forwarding_stream.await.ok();
+    }
+    info!("WebSocket connection closed: {}", addr);
+
+    server.disconnect(&addr);
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::channel::mpsc::UnboundedReceiver;
+    use futures::FutureExt;
+    use std::time::Instant;
+    use pran_droid_core::domain::animations::animation::{Animation, AnimationFrame, AnimationFrames};
+    use pran_droid_core::domain::brain::clock::SystemClock;
+    use pran_droid_core::domain::emotions::emotion::{Emotion, EmotionId, EmotionName};
+    use pran_droid_core::domain::images::image::{Image, ImageId, ImageUrl};
+    use pran_droid_core::domain::reactions::reaction::{Milliseconds, MovingReactionStep, ReactionStep, ReactionStepSkip};
+    use pran_droid_core::domain::reactions::reaction_definition::ReactionDefinitionId;
+    use pran_droid_core::persistence::emotions::in_memory_emotion_repository::InMemoryEmotionRepository;
+    use pran_droid_core::persistence::images::in_memory_image_repository::InMemoryImageRepository;
+    use crate::brain_output::outputs::{AnimationFrameOutput, MovingReactionStepOutput, ReactionStepOutput, ReactionStepSkipOutput};
+    use crate::brain_output::overlay_protocol::{ImageOutput, OverlayEnvelope, OVERLAY_PROTOCOL_VERSION};
+    use super::*;
+
+    struct FakeClock {
+        now: Mutex<Instant>,
+    }
+
+    impl FakeClock {
+        fn advance(&self, duration: Duration) {
+            *self.now.lock().unwrap() += duration;
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            *self.now.lock().unwrap()
+        }
+    }
+
+    fn create_server() -> OverlayServer {
+        create_server_with_token(None)
+    }
+
+    fn create_server_with_token(token: Option<String>) -> OverlayServer {
+        OverlayServer::new(Arc::new(InMemoryEmotionRepository::new()), Arc::new(InMemoryImageRepository::new()), token, Arc::new(SystemClock))
+    }
+
+    fn connect_overlay(server: &OverlayServer, port: u16) -> (SocketAddr, UnboundedReceiver<Message>) {
+        connect_overlay_with_handshake(server, port, OverlayHandshake::default())
+    }
+
+    fn connect_overlay_with_handshake(server: &OverlayServer, port: u16, handshake: OverlayHandshake) -> (SocketAddr, UnboundedReceiver<Message>) {
+        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+        let (sender, receiver) = unbounded();
+        server.connect(addr, sender, handshake);
+        (addr, receiver)
+    }
+
+    fn create_reaction(id: &str, steps: Vec<ReactionStep>, overlay_targets: Vec<&str>) -> Reaction {
+        Reaction {
+            source_definition_id: ReactionDefinitionId(String::from(id)),
+            steps,
+            overlay_targets: overlay_targets.into_iter().map(|target| OverlayTarget::new(String::from(target)).unwrap()).collect(),
+        }
+    }
+
+    fn moving_step(frame_end: u16) -> ReactionStep {
+        ReactionStep::Moving(MovingReactionStep {
+            animation: Animation { frames: AnimationFrames(vec![AnimationFrame { frame_start: 0, frame_end, image_id: ImageId(String::from("image")) }]) },
+            skip: ReactionStepSkip::AfterStepWithExtraMilliseconds(Milliseconds(0)),
+        })
+    }
+
+    fn hello(name: &str, capabilities: Vec<OverlayCapability>) -> OverlayMessage {
+        OverlayMessage::Hello { name: String::from(name), capabilities, channels: vec![], token: None }
+    }
+
+    fn received_events(receiver: &mut UnboundedReceiver<Message>) -> Vec<BrainEvent> {
+        let mut events = vec![];
+        while let Some(Some(Message::Text(text))) = receiver.next().now_or_never() {
+            let envelope: OverlayEnvelope<BrainEvent> = serde_json::from_str(&text).unwrap();
+            assert_eq!(envelope.version, OVERLAY_PROTOCOL_VERSION);
+            events.push(envelope.message);
+        }
+        events
+    }
+
+    fn reaction_event(reaction_id: u64) -> BrainEvent {
+        BrainEvent::Reaction { reaction_id, reaction: ReactionOutput { steps: vec![] } }
+    }
+
+    #[tokio::test]
+    async fn overlay_server_answers_pings() {
+        let server = create_server();
+        let (addr, mut receiver) = connect_overlay(&server, 1);
+
+        server.handle_text(&addr, r#"{"version":1,"type":"Ping","nonce":5}"#).await;
+
+        assert_eq!(received_events(&mut receiver), vec![BrainEvent::Pong { nonce: Some(5) }]);
+    }
+
+    #[tokio::test]
+    async fn overlay_server_replies_with_an_error_to_invalid_messages() {
+        let server = create_server();
+        let (addr, mut receiver) = connect_overlay(&server, 1);
+
+        server.handle_text(&addr, "Hello Server!").await;
+
+        assert!(matches!(received_events(&mut receiver)[..], [BrainEvent::Error { .. }]));
+    }
+
+    #[tokio::test]
+    async fn overlay_server_broadcasts_the_status_when_overlays_say_hello_and_leave() {
+        let server = create_server();
+        let (first, mut first_receiver) = connect_overlay(&server, 1);
+        let (second, mut second_receiver) = connect_overlay(&server, 2);
+
+        server.handle_message(&first, hello("droid", vec![])).await;
+        server.handle_message(&second, hello("bubble", vec![])).await;
+        server.disconnect(&second);
+
+        assert_eq!(received_events(&mut first_receiver), vec![
+            BrainEvent::Status { status: BrainStatusOutput { overlays: vec![String::from("droid")], playing_reaction_id: None } },
+            BrainEvent::Status { status: BrainStatusOutput { overlays: vec![String::from("bubble"), String::from("droid")], playing_reaction_id: None } },
+            BrainEvent::Status { status: BrainStatusOutput { overlays: vec![String::from("droid")], playing_reaction_id: None } },
+        ]);
+        assert_eq!(received_events(&mut second_receiver).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn overlay_server_tracks_the_playing_reaction_from_acknowledgements() {
+        let server = create_server();
+        let (addr, mut receiver) = connect_overlay(&server, 1);
+
+        server.handle_message(&addr, OverlayMessage::ReactionStarted { reaction_id: 4 }).await;
+        server.handle_message(&addr, OverlayMessage::ReactionFinished { reaction_id: 3 }).await;
+        server.handle_message(&addr, OverlayMessage::ReactionFinished { reaction_id: 4 }).await;
+
+        let playing_reaction_ids: Vec<Option<u64>> = received_events(&mut receiver).into_iter()
+            .map(|event| match event {
+                BrainEvent::Status { status } => status.playing_reaction_id,
+                other => panic!("Unexpected event {:?}", other),
+            })
+            .collect();
+        assert_eq!(playing_reaction_ids, vec![Some(4), Some(4), None]);
+    }
+
+    #[tokio::test]
+    async fn overlay_server_sends_the_catalogue_on_request() {
+        let emotion_repository = InMemoryEmotionRepository::new();
+        let image_repository = InMemoryImageRepository::new();
+        emotion_repository.insert(&Emotion { id: EmotionId(String::from("happy")), name: EmotionName(String::from("happy")), animation: vec![] }).await.unwrap();
+        image_repository.insert(&Image { id: ImageId(String::from("body")), url: ImageUrl(String::from("body.png")) }).await.unwrap();
+        let server = OverlayServer::new(Arc::new(emotion_repository), Arc::new(image_repository), None, Arc::new(SystemClock));
+        let (addr, mut receiver) = connect_overlay(&server, 1);
+
+        server.handle_message(&addr, OverlayMessage::RequestCatalogue).await;
+
+        let events = received_events(&mut receiver);
+        assert!(matches!(&events[..], [BrainEvent::Catalogue { catalogue }]
+            if catalogue.emotions.iter().map(|emotion| emotion.id.as_str()).collect::<Vec<&str>>() == vec!["happy"]
+            && catalogue.images.iter().map(|image| image.id.as_str()).collect::<Vec<&str>>() == vec!["body"]));
+    }
+
+    #[tokio::test]
+    async fn overlay_server_broadcasts_only_to_overlays_with_the_capability() {
+        let server = create_server();
+        let (_legacy, mut legacy_receiver) = connect_overlay(&server, 1);
+        let (reactions, mut reactions_receiver) = connect_overlay(&server, 2);
+        let (catalogue, mut catalogue_receiver) = connect_overlay(&server, 3);
+        server.handle_message(&reactions, hello("droid", vec![OverlayCapability::Reactions])).await;
+        server.handle_message(&catalogue, hello("editor", vec![OverlayCapability::Catalogue])).await;
+        for receiver in [&mut legacy_receiver, &mut reactions_receiver, &mut catalogue_receiver] {
+            received_events(receiver);
+        }
+
+        server.broadcast(reaction_event(1));
+        server.broadcast(BrainEvent::ConfigChanged { catalogue: Default::default() });
+
+        assert_eq!(received_events(&mut legacy_receiver).len(), 2);
+        assert_eq!(received_events(&mut reactions_receiver), vec![reaction_event(1)]);
+        assert_eq!(received_events(&mut catalogue_receiver), vec![BrainEvent::ConfigChanged { catalogue: Default::default() }]);
+    }
+
+    #[tokio::test]
+    async fn overlay_server_sends_targeted_reactions_only_to_overlays_on_those_channels() {
+        let server = create_server();
+        let (_any, mut any_receiver) = connect_overlay(&server, 1);
+        let (_main, mut main_receiver) = connect_overlay_with_handshake(&server, 2, OverlayHandshake { channel: Some(String::from("main-scene")), ..Default::default() });
+        let (chatting, mut chatting_receiver) = connect_overlay(&server, 3);
+        server.handle_message(&chatting, OverlayMessage::Hello {
+            name: String::from("chatting"),
+            capabilities: vec![OverlayCapability::Reactions],
+            channels: vec![String::from("chatting-scene"), String::from("not a channel")],
+            token: None,
+        }).await;
+        for receiver in [&mut any_receiver, &mut main_receiver, &mut chatting_receiver] {
+            received_events(receiver);
+        }
+
+        server.broadcast_to_targets(reaction_event(1), &[String::from("main-scene")]);
+        server.broadcast_to_targets(reaction_event(2), &[String::from("main-scene"), String::from("chatting-scene")]);
+        server.broadcast_to_targets(reaction_event(3), &[]);
+
+        assert_eq!(received_events(&mut any_receiver), vec![reaction_event(3)]);
+        assert_eq!(received_events(&mut main_receiver), vec![reaction_event(1), reaction_event(2), reaction_event(3)]);
+        assert_eq!(received_events(&mut chatting_receiver), vec![reaction_event(2), reaction_event(3)]);
+    }
+
+    #[tokio::test]
+    async fn overlay_server_sends_nothing_to_overlays_before_they_authenticate() {
+        let server = create_server_with_token(Some(String::from("secret")));
+        let (addr, mut receiver) = connect_overlay(&server, 1);
+
+        server.broadcast(reaction_event(1));
+        assert_eq!(received_events(&mut receiver), vec![]);
+
+        let keep_open = server.handle_message(&addr, OverlayMessage::Hello {
+            name: String::from("droid"),
+            capabilities: vec![OverlayCapability::Reactions],
+            channels: vec![],
+            token: Some(String::from("secret")),
+        }).await;
+        server.broadcast(reaction_event(2));
+
+        assert!(keep_open);
+        assert!(matches!(&received_events(&mut receiver)[..], [BrainEvent::Status { .. }, BrainEvent::Reaction { reaction_id: 2, .. }]));
+    }
+
+    #[tokio::test]
+    async fn overlay_server_rejects_overlays_with_an_invalid_token_or_without_hello() {
+        let server = create_server_with_token(Some(String::from("secret")));
+        let (wrong_token, mut wrong_token_receiver) = connect_overlay(&server, 1);
+        let (no_hello, mut no_hello_receiver) = connect_overlay(&server, 2);
+
+        let wrong_token_open = server.handle_message(&wrong_token, OverlayMessage::Hello {
+            name: String::from("droid"),
+            capabilities: vec![],
+            channels: vec![],
+            token: Some(String::from("guess")),
+        }).await;
+        let no_hello_open = server.handle_text(&no_hello, r#"{"version":1,"type":"Ping"}"#).await;
+
+        assert!(!wrong_token_open);
+        assert!(!no_hello_open);
+        assert!(matches!(&received_events(&mut wrong_token_receiver)[..], [BrainEvent::Error { .. }]));
+        assert!(matches!(&received_events(&mut no_hello_receiver)[..], [BrainEvent::Error { .. }]));
+    }
+
+    #[tokio::test]
+    async fn overlay_server_replays_the_playing_reaction_and_the_requested_recent_ones_to_late_overlays() {
+        let clock = Arc::new(FakeClock { now: Mutex::new(Instant::now()) });
+        let server = OverlayServer::new(Arc::new(InMemoryEmotionRepository::new()), Arc::new(InMemoryImageRepository::new()), None, clock.clone());
+        for (reaction_id, id) in [(1, "first"), (2, "second")] {
+            assert!(server.send_reaction(reaction_id, create_reaction(id, vec![moving_step(6)], vec![]), vec![Duration::from_millis(100)]));
+            clock.advance(Duration::from_millis(100));
+        }
+        assert!(server.send_reaction(3, create_reaction("third", vec![moving_step(60)], vec![]), vec![Duration::from_millis(1000)]));
+        clock.advance(Duration::from_millis(500));
+
+        let (_late, mut late_receiver) = connect_overlay_with_handshake(&server, 1, OverlayHandshake { replay: 1, ..Default::default() });
+        let (_without_replay, mut without_replay_receiver) = connect_overlay(&server, 2);
+
+        let remaining_third = BrainEvent::Reaction { reaction_id: 3, reaction: ReactionOutput { steps: vec![
+            ReactionStepOutput::Moving(MovingReactionStepOutput {
+                animation: vec![AnimationFrameOutput { frame_start: 0, frame_end: 30, image_id: String::from("image") }],
+                skip: Some(ReactionStepSkipOutput::AfterStep { extra_ms: 0 }),
+            })
+        ] } };
+        assert!(matches!(&received_events(&mut late_receiver)[..], [BrainEvent::RecentReactions { reactions }, third]
+            if reactions.iter().map(|recent| (recent.reaction_id, recent.started_ms_ago)).collect::<Vec<(u64, u64)>>() == vec![(2, 600)]
+            && third == &remaining_third));
+        assert_eq!(received_events(&mut without_replay_receiver), vec![remaining_third]);
+    }
+
+    #[tokio::test]
+    async fn overlay_server_replays_only_reactions_targeting_the_overlay_channels() {
+        let clock = Arc::new(FakeClock { now: Mutex::new(Instant::now()) });
+        let server = OverlayServer::new(Arc::new(InMemoryEmotionRepository::new()), Arc::new(InMemoryImageRepository::new()), None, clock.clone());
+        server.send_reaction(1, create_reaction("main", vec![moving_step(6)], vec!["main-scene"]), vec![Duration::from_millis(100)]);
+        clock.advance(Duration::from_millis(100));
+        server.send_reaction(2, create_reaction("chatting", vec![moving_step(60)], vec!["chatting-scene"]), vec![Duration::from_millis(1000)]);
+
+        let (_main, mut main_receiver) = connect_overlay_with_handshake(&server, 1, OverlayHandshake {
+            channel: Some(String::from("main-scene")),
+            replay: 5,
+            ..Default::default()
+        });
+
+        assert!(matches!(&received_events(&mut main_receiver)[..], [BrainEvent::RecentReactions { reactions }]
+            if reactions.iter().map(|recent| recent.reaction_id).collect::<Vec<u64>>() == vec![1]));
+    }
+
+    #[tokio::test]
+    async fn overlay_server_resolves_reactions_for_overlays_asking_for_it() {
+        let server = create_server();
+        server.update_catalogue(CatalogueOutput {
+            emotions: vec![],
+            images: vec![ImageOutput { id: String::from("image"), url: String::from("image.png") }],
+        });
+        let (_plain, mut plain_receiver) = connect_overlay(&server, 1);
+        let (resolved, mut resolved_receiver) = connect_overlay(&server, 2);
+        server.handle_message(&resolved, hello("droid", vec![OverlayCapability::ResolvedReactions])).await;
+        for receiver in [&mut plain_receiver, &mut resolved_receiver] {
+            received_events(receiver);
+        }
+
+        server.send_reaction(1, create_reaction("first", vec![moving_step(6)], vec![]), vec![Duration::ZERO]);
+        server.send_reaction(2, create_reaction("second", vec![moving_step(6)], vec![]), vec![Duration::ZERO]);
+
+        assert!(matches!(&received_events(&mut plain_receiver)[..], [BrainEvent::Reaction { reaction_id: 1, .. }, BrainEvent::Reaction { reaction_id: 2, .. }]));
+        let image_ids: Vec<Vec<String>> = received_events(&mut resolved_receiver).into_iter()
+            .map(|event| match event {
+                BrainEvent::ResolvedReaction { reaction, .. } => reaction.images.into_iter().map(|image| image.image.id).collect(),
+                other => panic!("Unexpected event {:?}", other),
+            })
+            .collect();
+        assert_eq!(image_ids, vec![vec![String::from("image")], vec![]]);
+    }
+
+    #[test]
+    fn overlay_server_does_not_send_reactions_without_overlay_steps() {
+        let server = create_server();
+        let (_addr, mut receiver) = connect_overlay(&server, 1);
+
+        assert!(!server.send_reaction(1, create_reaction("chat only", vec![], vec![]), vec![]));
+        assert_eq!(received_events(&mut receiver), vec![]);
+    }
+
+    #[test]
+    fn parse_handshake_reads_the_channel_from_the_path_and_the_options_from_the_query() {
+        assert_eq!(parse_handshake("/", None), Ok(OverlayHandshake::default()));
+        assert_eq!(parse_handshake("/main-scene", Some("other=1&token=secret&replay=3")), Ok(OverlayHandshake {
+            channel: Some(String::from("main-scene")),
+            token: Some(String::from("secret")),
+            replay: 3,
+        }));
+        assert!(parse_handshake("/main/scene", None).is_err());
+        assert!(parse_handshake("/", Some("replay=all")).is_err());
+    }
+
+    #[test]
+    fn tokens_match_only_the_exact_token() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secret", "secreT"));
+        assert!(!tokens_match("secret", "secret2"));
+        assert!(!tokens_match("secret", ""));
+    }
+}
@@ -1,22 +1,28 @@
-use std::collections::HashMap;
-use std::net::{SocketAddr};
-use tokio::net::{TcpListener, TcpStream};
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+use std::time::Duration;
 use serde::Serialize;
-use futures::channel::mpsc::{unbounded, UnboundedSender};
-use futures::{future, pin_mut, StreamExt, TryStreamExt};
-use futures::future::join;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::StreamExt;
+use futures::future::join3;
 use reqwest::Client;
-use tokio_tungstenite::tungstenite::Message;
 use pran_droid_core::application::brain::pran_droid_brain::{create_droid_brain, TextPhonemiser};
+use pran_droid_core::domain::brain::clock::{Clock, SystemClock};
 use pran_droid_core::domain::brain::pran_droid_brain::ReactionNotifier;
-use pran_droid_core::domain::brain::stimuli::{Action, ActionStimulus, ChatMessageStimulus, Source, Stimulus};
-use pran_droid_core::domain::reactions::reaction_definition::ReactionDefinitionId;
+use pran_droid_core::domain::brain::stimuli::{Action, ActionStimulus, ChatMessageStimulus, Source, Stimulus, StreamEvent, StreamEventStimulus};
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use pran_droid_core::domain::images::image_repository::ImageRepository;
+use pran_droid_core::domain::reactions::reaction::Reaction;
+use pran_droid_core::domain::reactions::reaction_definition::{ReactionDefinition, ReactionDefinitionId};
 use pran_droid_core::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+use pran_droid_core::domain::visemes::mouth_track::MouthTrackModel;
 use crate::phonemiser::pran_text_phonemiser::PranTextPhonemiser;
-use crate::brain_output::outputs::ReactionOutput;
-use crate::stream_interface::events::ChatEvent;
-use crate::stream_interface::twitch::twitch_interface::{connect_to_twitch, TwitchConnectOptions};
+use crate::brain_output::chat_output::{ChatOutput, ChatOutputMessage, schedule_chat_messages};
+use crate::brain_output::overlay_protocol::{BrainEvent, load_catalogue};
+use crate::overlay_server::{OverlayServer, OverlayServerOptions, run_overlay_server};
+use crate::scheduler::reaction_scheduler::{ReactionPriority, ReactionScheduler, ReactionSchedulerOptions};
+use crate::stream_interface::events::{ChatEvent, ChatNoticeEvent};
+use crate::stream_interface::interface::{StreamConnection, StreamInterface};
+use crate::stream_interface::supervisor::{ConnectionState, ConnectionStateEvent};
 
 struct ApiReactionNotifier { api_secret_key: String, api_base_path: String }
 impl ReactionNotifier for ApiReactionNotifier {
@@ -38,19 +44,21 @@ impl ReactionNotifier for ApiReactionNotifier {
 }
 
 pub struct PranDroidBrainConfig {
-    pub twitch_client_secret: String,
-    pub twitch_client_id: String,
-    pub twitch_token: String,
-    pub twitch_channel: String,
-    pub twitch_user: String,
-    pub websocket_port: u16,
+    pub overlay: OverlayServerOptions,
+    pub mouth_track_model: MouthTrackModel,
+    pub reaction_queue_size: usize,
+    pub reaction_reload_interval: Duration,
     pub api_base_path: String,
     pub api_secret_key: String,
+    pub connection_state_listener: Option<UnboundedSender<ConnectionStateEvent>>,
 }
 
 pub async fn start_droid_brain(
     config: PranDroidBrainConfig,
-    reaction_repository: &dyn ReactionDefinitionRepository
+    reaction_repository: Arc<dyn ReactionDefinitionRepository>,
+    emotion_repository: Arc<dyn EmotionRepository>,
+    image_repository: Arc<dyn ImageRepository>,
+    stream_interface: Box<dyn StreamInterface>
 ) {
     pran_phonemes_core::phonemes::pran_phonemes().expect("PranPhonemes failed to initialise");
 
@@ -59,98 +67,173 @@ pub async fn start_droid_brain(
         api_base_path: config.api_base_path,
         api_secret_key: config.api_secret_key
     });
-    let mut brain = create_droid_brain(reaction_repository, &text_phonemiser, &reaction_notifier).await;
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+    let mut brain = create_droid_brain(reaction_repository.as_ref(), &text_phonemiser, &reaction_notifier, &clock, &Arc::new(config.mouth_track_model)).await;
 
-    let token = authenticate(
-        config.twitch_client_secret,
-        config.twitch_token
-    ).await;
+    let (connection_state_sender, connection_state_receiver) = unbounded::<ConnectionStateEvent>();
+    tokio::spawn(log_connection_states(connection_state_receiver, config.connection_state_listener));
 
-    let mut event_stream = connect_to_twitch(TwitchConnectOptions {
-        token,
-        channel: config.twitch_channel,
-        client_id: config.twitch_client_id,
-        user: config.twitch_user
-    }).await;
+    let StreamConnection { events: mut event_stream, chat_output } = stream_interface.connect(connection_state_sender).await
+        .expect("Could not connect to the stream");
 
-    let ws_listeners: Arc<Mutex<HashMap<SocketAddr, UnboundedSender<Message>>>> = Arc::new(Mutex::new(HashMap::new()));
-    let websocket = init_websocket(config.websocket_port, ws_listeners.clone());
+    let overlay_server = Arc::new(OverlayServer::new(emotion_repository.clone(), image_repository.clone(), config.overlay.token.clone(), clock.clone()));
+    let websocket = run_overlay_server(overlay_server.clone(), config.overlay);
+    tokio::spawn(poll_catalogue(emotion_repository, image_repository, config.reaction_reload_interval, overlay_server.clone()));
+
+    let (reaction_sender, reaction_receiver) = unbounded::<(Reaction, ReactionPriority)>();
+    let scheduler = ReactionScheduler::new(ReactionSchedulerOptions {
+        max_queue_size: config.reaction_queue_size,
+        ..Default::default()
+    }, clock.clone());
+
+    let (reload_sender, mut reload_receiver) = unbounded::<Vec<ReactionDefinition>>();
+    tokio::spawn(poll_reaction_definitions(reaction_repository, config.reaction_reload_interval, reload_sender));
 
     let brain_execution = tokio::spawn(async move {
-        while let Some(event) = event_stream.next().await {
-            Into::<Option<Stimulus>>::into(event)
-                .and_then(|stimulus| brain.stimulate(stimulus))
-                .map(|reaction| {
-                    debug!("Sending message with reaction {:?}", reaction);
-                    let message = serde_json::to_string(&Into::<ReactionOutput>::into(reaction)).unwrap();
-
-                    for ws_listener in ws_listeners.lock().unwrap().iter().map(|(_, ws_listener)| ws_listener) {
-                        ws_listener.unbounded_send(Message::Text(message.clone())).unwrap();
-                    }
-                    debug!("Message sent {:?}", message);
-                });
+        loop {
+            tokio::select! {
+                event = event_stream.next() => match event {
+                    Some(event) => if let Some(stimulus) = Into::<Option<Stimulus>>::into(event) {
+                        let priority = ReactionPriority::from(&stimulus);
+                        if let Some(reaction) = brain.stimulate(stimulus) {
+                            reaction_sender.unbounded_send((reaction, priority)).unwrap();
+                        }
+                    },
+                    None => break,
+                },
+                Some(reaction_definitions) = reload_receiver.next() => {
+                    debug!("Reloading {} reaction definitions", reaction_definitions.len());
+                    brain.reload(reaction_definitions);
+                },
+            }
         }
     });
 
-    let _ = join(websocket, brain_execution).await;
+    let scheduler_execution = tokio::spawn(run_reaction_scheduler(scheduler, reaction_receiver, overlay_server, chat_output));
+
+    let _ = join3(websocket, brain_execution, scheduler_execution).await;
 
     info!("End process");
 }
 
-async fn init_websocket(port: u16, ws_listeners: Arc<Mutex<HashMap<SocketAddr, UnboundedSender<Message>>>>) {
-    let addr = format!("127.0.0.1:{}", port);
+async fn poll_reaction_definitions(reaction_repository: Arc<dyn ReactionDefinitionRepository>, interval: Duration, reload_sender: UnboundedSender<Vec<ReactionDefinition>>) {
+    let mut interval = tokio::time::interval(interval);
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+        // A failed read keeps the current definitions until the next tick
+        let reaction_definitions = match reaction_repository.try_get_all().await {
+            Ok(reaction_definitions) => reaction_definitions,
+            Err(error) => {
+                warn!("Reaction definitions could not be reloaded: {}", error);
+                continue;
+            }
+        };
+        if reload_sender.unbounded_send(reaction_definitions).is_err() {
+            break;
+        }
+    }
+}
+
+// Overlays are told when emotions or images change, so that they can reload them
+async fn poll_catalogue(emotion_repository: Arc<dyn EmotionRepository>, image_repository: Arc<dyn ImageRepository>, interval: Duration, overlay_server: Arc<OverlayServer>) {
+    let mut interval = tokio::time::interval(interval);
+    match load_catalogue(emotion_repository.as_ref(), image_repository.as_ref()).await {
+        Ok(catalogue) => { overlay_server.update_catalogue(catalogue); },
+        Err(error) => warn!("Catalogue could not be loaded: {}", error),
+    }
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+        let catalogue = match load_catalogue(emotion_repository.as_ref(), image_repository.as_ref()).await {
+            Ok(catalogue) => catalogue,
+            Err(error) => {
+                warn!("Catalogue could not be reloaded: {}", error);
+                continue;
+            }
+        };
+        if overlay_server.update_catalogue(catalogue.clone()) {
+            debug!("Catalogue changed, notifying the overlays");
+            overlay_server.broadcast(BrainEvent::ConfigChanged { catalogue });
+        }
+    }
+}
 
-    let try_socket = TcpListener::bind(&addr).await;
-    let listener = try_socket.expect("Failed to bind");
-    info!("Websocket listening on: {}", addr);
+async fn log_connection_states(mut connection_states: UnboundedReceiver<ConnectionStateEvent>, listener: Option<UnboundedSender<ConnectionStateEvent>>) {
+    while let Some(connection_state) = connection_states.next().await {
+        match connection_state.state {
+            ConnectionState::Connecting => debug!("Connection state: {}", connection_state),
+            ConnectionState::Connected | ConnectionState::Reconnecting { .. } => info!("Connection state: {}", connection_state),
+            ConnectionState::Disconnected { .. } => warn!("Connection state: {}", connection_state),
+        }
 
-    while let Ok((stream, addr)) = listener.accept().await {
-        tokio::spawn(handle_connection(ws_listeners.clone(), stream, addr));
+        if let Some(listener) = &listener {
+            listener.unbounded_send(connection_state).ok();
+        }
     }
 }
 
-async fn handle_connection(ws_listeners: Arc<Mutex<HashMap<SocketAddr, UnboundedSender<Message>>>>, stream: TcpStream, addr: SocketAddr) {
-    let ws_stream = tokio_tungstenite::accept_async(stream)
-        .await
-        .expect("Error during the websocket handshake occurred");
-    let (tx, rx) = unbounded();
-    ws_listeners.lock().unwrap().insert(addr, tx);
+async fn run_reaction_scheduler(mut scheduler: ReactionScheduler, mut reaction_receiver: UnboundedReceiver<(Reaction, ReactionPriority)>, overlay_server: Arc<OverlayServer>, chat_output: Arc<dyn ChatOutput>) {
+    let mut next_reaction_id: u64 = 1;
 
-    info!("WebSocket connection established: {}", addr);
-    let (outgoing, incoming) = ws_stream.split();
-    let forwarding_stream = rx.map(Ok).forward(outgoing);
+    loop {
+        while let Some(reaction) = scheduler.poll() {
+            let step_durations = scheduler.estimate_step_durations(&reaction);
+            send_scheduled_chat_messages(schedule_chat_messages(&reaction, &step_durations), chat_output.clone());
 
-    let incoming_stream = incoming.try_for_each(|_| { future::ok(()) });
+            if overlay_server.send_reaction(next_reaction_id, reaction, step_durations) {
+                next_reaction_id += 1;
+            }
+        }
 
-    pin_mut!(forwarding_stream, incoming_stream);
-    future::select(forwarding_stream, incoming_stream).await;
-    info!("WebSocket connection closed: {}", addr);
+        let next_reaction = match scheduler.time_until_next() {
+            Some(wait_time) => match tokio::time::timeout(wait_time, reaction_receiver.next()).await {
+                Ok(next_reaction) => next_reaction,
+                Err(_) => continue,
+            },
+            None => reaction_receiver.next().await,
+        };
 
-    ws_listeners.lock().unwrap().remove(&addr);
+        match next_reaction {
+            Some((reaction, priority)) => {
+                if let Some(dropped) = scheduler.enqueue(reaction, priority) {
+                    info!("Reaction queue full, dropped reaction {}", dropped.source_definition_id.0);
+                }
+            },
+            None => break,
+        }
+    }
 }
 
-async fn authenticate(client_secret: String, old_token: String) -> String {
-    let client_secret = twitch_oauth2::ClientSecret::new(client_secret);
-
-    let token = twitch_oauth2::UserToken::from_existing(
-        &Client::builder()
-            .redirect(reqwest::redirect::Policy::none())
-            .build().unwrap(),
-        twitch_oauth2::AccessToken::new(old_token),
-        None,
-        client_secret
-    ).await.expect("Could not authenticate");
+fn send_scheduled_chat_messages(scheduled_messages: Vec<(Duration, ChatOutputMessage)>, chat_output: Arc<dyn ChatOutput>) {
+    if scheduled_messages.is_empty() {
+        return;
+    }
 
-    token.access_token.secret().to_string()
+    let reaction_start = tokio::time::Instant::now();
+    tokio::spawn(async move {
+        for (offset, message) in scheduled_messages {
+            tokio::time::sleep_until(reaction_start + offset).await;
+            chat_output.send_chat_message(message);
+        }
+    });
 }
 
 impl Into<Option<Stimulus>> for ChatEvent {
     fn into(self) -> Option<Stimulus> {
         match self {
+            // Local chat sources may leave the message id out, there is nothing to reply to then
             ChatEvent::Message(chat_message) => Some(Stimulus::ChatMessage(ChatMessageStimulus {
+                message_id: Some(chat_message.id).filter(|id| !id.is_empty()),
                 text: chat_message.content,
                 source: Source {
-                    is_mod: chat_message.is_mod, user_name: chat_message.name
+                    is_mod: chat_message.is_mod,
+                    is_broadcaster: chat_message.is_broadcaster,
+                    is_vip: chat_message.is_vip,
+                    is_subscriber: chat_message.is_subscriber,
+                    user_name: chat_message.name
                 }
             })),
             ChatEvent::Action(chat_action) => Some(Stimulus::Action(ActionStimulus {
@@ -158,10 +241,180 @@ impl Into<Option<Stimulus>> for ChatEvent {
                     id: chat_action.action_id,
                     name: chat_action.action_name
                 },
+                input: chat_action.input,
                 source: Source {
-                    is_mod: chat_action.is_mod, user_name: chat_action.name
+                    is_mod: chat_action.is_mod,
+                    is_broadcaster: chat_action.is_broadcaster,
+                    is_vip: chat_action.is_vip,
+                    is_subscriber: chat_action.is_subscriber,
+                    user_name: chat_action.name
+                }
+            })),
+            ChatEvent::Notice(chat_notice) => Some(Stimulus::StreamEvent(StreamEventStimulus {
+                event: match chat_notice.event {
+                    ChatNoticeEvent::Subscription { months } => StreamEvent::Subscription { months },
+                    ChatNoticeEvent::Resubscription { months } => StreamEvent::Resubscription { months },
+                    ChatNoticeEvent::GiftSubscription { recipient, months } => StreamEvent::GiftSubscription { recipient, months },
+                    ChatNoticeEvent::MysteryGift { count } => StreamEvent::MysteryGift { count },
+                    ChatNoticeEvent::Raid { viewers } => StreamEvent::Raid { viewers },
+                    ChatNoticeEvent::Follow => StreamEvent::Follow,
+                    ChatNoticeEvent::HypeTrainStart { level } => StreamEvent::HypeTrainStart { level },
+                    ChatNoticeEvent::HypeTrainLevelUp { level } => StreamEvent::HypeTrainLevelUp { level },
+                    ChatNoticeEvent::HypeTrainEnd { level } => StreamEvent::HypeTrainEnd { level },
+                },
+                message: chat_notice.message,
+                source: Source {
+                    is_mod: chat_notice.is_mod,
+                    is_broadcaster: chat_notice.is_broadcaster,
+                    is_vip: chat_notice.is_vip,
+                    is_subscriber: chat_notice.is_subscriber,
+                    user_name: chat_notice.name
                 }
             }))
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use pran_droid_core::domain::animations::animation::{Animation, AnimationFrame, AnimationFrames};
+    use pran_droid_core::domain::images::image::ImageId;
+    use pran_droid_core::domain::reactions::reaction::{Milliseconds, ReactionStepSkip, ReactionStepText};
+    use pran_droid_core::domain::reactions::reaction_definition::{ChatMessageReactionStepDefinition, MovingReactionStepDefinition, OverlayTarget, ReactionStepDefinition, ReactionStepMessageAlternativesDefinition, ReactionTrigger};
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use async_trait::async_trait;
+    use futures::SinkExt;
+    use pran_droid_core::domain::reactions::reaction_definition_repository::{ReactionDeleteError, ReactionGetAllError, ReactionInsertError, ReactionUpdateError};
+    use pran_droid_core::persistence::emotions::in_memory_emotion_repository::InMemoryEmotionRepository;
+    use pran_droid_core::persistence::images::in_memory_image_repository::InMemoryImageRepository;
+    use pran_droid_core::persistence::reactions::in_memory_reaction_repository::InMemoryReactionRepository;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpStream;
+    use tokio_tungstenite::tungstenite::Message;
+    use crate::brain_output::chat_output::InMemoryChatOutput;
+    use crate::stream_interface::local::local_stream_interface::{LocalChatSource, LocalStreamInterface};
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn start_droid_brain_sends_reactions_to_local_chat_events_through_the_websocket() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        reaction_repository.insert(&ReactionDefinition {
+            id: ReactionDefinitionId(String::from("0")),
+            is_disabled: false,
+            triggers: vec![ReactionTrigger::new_chat_command(String::from("!hello")).unwrap()],
+            steps: vec![
+                ReactionStepDefinition::Moving(MovingReactionStepDefinition {
+                    skip: ReactionStepSkip::AfterMilliseconds(Milliseconds(500)),
+                    animation: Animation {
+                        frames: AnimationFrames(vec![
+                            AnimationFrame { frame_start: 0, frame_end: 11, image_id: ImageId(String::from("an image id")) }
+                        ])
+                    }
+                }),
+                ReactionStepDefinition::ChatMessage(ChatMessageReactionStepDefinition {
+                    alternatives: ReactionStepMessageAlternativesDefinition::new_single(ReactionStepText::Instant(String::from("hi ${user}"))),
+                    reply: true
+                }),
+            ],
+            count: 0,
+            cooldown: Default::default(),
+            permission_level: Default::default(),
+            overlay_targets: vec![OverlayTarget::new(String::from("main-scene")).unwrap()],
+        }).await.unwrap();
+        let chat_address = free_local_address();
+        let overlay_address = free_local_address();
+        let chat_output = Arc::new(InMemoryChatOutput::new());
+        let (connection_state_listener, mut connection_states) = unbounded::<ConnectionStateEvent>();
+
+        tokio::spawn(start_droid_brain(PranDroidBrainConfig {
+            overlay: OverlayServerOptions { address: overlay_address, token: Some(String::from("secret")), tls: None },
+            mouth_track_model: MouthTrackModel::default(),
+            reaction_queue_size: 10,
+            reaction_reload_interval: Duration::from_secs(60),
+            api_base_path: String::from("http://127.0.0.1:1"),
+            api_secret_key: String::new(),
+            connection_state_listener: Some(connection_state_listener),
+        }, Arc::new(reaction_repository), Arc::new(InMemoryEmotionRepository::new()), Arc::new(InMemoryImageRepository::new()), Box::new(LocalStreamInterface::new(LocalChatSource::Tcp(chat_address), chat_output.clone()))));
+
+        assert_eq!(connection_states.next().await.map(|event| event.state), Some(ConnectionState::Connected));
+        let mut overlay = connect_overlay(format!("ws://{}/main-scene?token=secret", overlay_address)).await;
+        overlay.send(Message::Text(String::from(r#"{"version":1,"type":"Hello","name":"overlay","capabilities":["Reactions"]}"#))).await.unwrap();
+        let status = next_overlay_event(&mut overlay).await;
+        assert_eq!(status["type"], "Status");
+        assert_eq!(status["status"]["overlays"], serde_json::json!(["overlay"]));
+        let mut chat = TcpStream::connect(chat_address).await.unwrap();
+        chat.write_all(concat!(r#"{"type":"Message","id":"a message id","name":"pran","content":"!hello"}"#, "\n").as_bytes()).await.unwrap();
+
+        let output = next_overlay_event(&mut overlay).await;
+        assert_eq!(output["version"], 1);
+        assert_eq!(output["type"], "Reaction");
+        assert_eq!(output["reactionId"], 1);
+        assert_eq!(output["reaction"]["steps"].as_array().map(|steps| steps.len()), Some(1));
+        assert_eq!(output["reaction"]["steps"][0]["type"], "Moving");
+        assert_eq!(output["reaction"]["steps"][0]["animation"][0]["imageId"], "an image id");
+        assert_eq!(chat_output.messages(), vec![]);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(chat_output.messages(), vec![
+            ChatOutputMessage { text: String::from("hi pran"), reply_to_message_id: Some(String::from("a message id")) }
+        ]);
+    }
+
+    #[tokio::test]
+    async fn poll_reaction_definitions_keeps_polling_after_a_failed_read() {
+        let reaction_repository = Arc::new(FailingOnceReactionRepository { inner: InMemoryReactionRepository::new(), failed: AtomicBool::new(false) });
+        let (reload_sender, mut reload_receiver) = unbounded();
+
+        tokio::spawn(poll_reaction_definitions(reaction_repository.clone(), Duration::from_millis(10), reload_sender));
+
+        let reloaded = tokio::time::timeout(Duration::from_secs(5), reload_receiver.next()).await.expect("Reaction definitions not reloaded");
+        assert_eq!(reloaded.map(|reaction_definitions| reaction_definitions.len()), Some(0));
+        assert!(reaction_repository.failed.load(Ordering::SeqCst));
+    }
+
+    struct FailingOnceReactionRepository { inner: InMemoryReactionRepository, failed: AtomicBool }
+
+    #[async_trait]
+    impl ReactionDefinitionRepository for FailingOnceReactionRepository {
+        fn next_id(&self) -> ReactionDefinitionId { self.inner.next_id() }
+        async fn insert(&self, reaction: &ReactionDefinition) -> Result<(), ReactionInsertError> { self.inner.insert(reaction).await }
+        async fn exists_with_trigger(&self, trigger: &ReactionTrigger) -> bool { self.inner.exists_with_trigger(trigger).await }
+        async fn other_exists_with_trigger(&self, trigger: &ReactionTrigger, excluded_reaction_definition_id: &ReactionDefinitionId) -> bool {
+            self.inner.other_exists_with_trigger(trigger, excluded_reaction_definition_id).await
+        }
+        async fn get(&self, id: &ReactionDefinitionId) -> Option<ReactionDefinition> { self.inner.get(id).await }
+        async fn get_all(&self) -> Vec<ReactionDefinition> { self.inner.get_all().await }
+        async fn try_get_all(&self) -> Result<Vec<ReactionDefinition>, ReactionGetAllError> {
+            if !self.failed.swap(true, Ordering::SeqCst) {
+                return Err(ReactionGetAllError::Unexpected(String::from("timeout")));
+            }
+            self.inner.try_get_all().await
+        }
+        async fn update(&self, reaction: &ReactionDefinition) -> Result<(), ReactionUpdateError> { self.inner.update(reaction).await }
+        async fn delete(&self, id: &ReactionDefinitionId) -> Result<(), ReactionDeleteError> { self.inner.delete(id).await }
+    }
+
+    async fn next_overlay_event(overlay: &mut tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<TcpStream>>) -> serde_json::Value {
+        let message = tokio::time::timeout(Duration::from_secs(5), overlay.next()).await
+            .expect("No event received by the overlay")
+            .unwrap().unwrap();
+        match message {
+            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            other => panic!("Unexpected websocket message {:?}", other),
+        }
+    }
+
+    fn free_local_address() -> SocketAddr {
+        std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+    }
+
+    // The websocket is bound in the background, retries until it accepts connections
+    async fn connect_overlay(url: String) -> tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<TcpStream>> {
+        for _ in 0..100 {
+            if let Ok((overlay, _)) = tokio_tungstenite::connect_async(url.as_str()).await {
+                return overlay;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("Websocket not listening on {}", url);
+    }
+}
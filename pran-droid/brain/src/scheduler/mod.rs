@@ -0,0 +1,2 @@
+pub mod reaction_scheduler;
+pub mod recent_reactions;
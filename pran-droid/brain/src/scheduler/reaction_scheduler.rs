@@ -0,0 +1,358 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use pran_droid_core::domain::brain::clock::Clock;
+use pran_droid_core::domain::brain::stimuli::Stimulus;
+use pran_droid_core::domain::reactions::reaction::{MovingReactionStep, Reaction, ReactionStep, ReactionStepSkip, TalkingReactionStep};
+use pran_droid_core::domain::visemes::mouth_track::mouth_track_duration_ms;
+
+pub const ANIMATION_FRAMES_PER_SECOND: u64 = 60;
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum ReactionPriority {
+    Normal,
+    High,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum QueueDropPolicy {
+    DropIncoming,
+    DropOldest,
+    DropLowestPriority,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReactionSchedulerOptions {
+    pub max_queue_size: usize,
+    pub drop_policy: QueueDropPolicy,
+    pub ms_per_phoneme: u64,
+}
+
+impl Default for ReactionSchedulerOptions {
+    fn default() -> Self {
+        ReactionSchedulerOptions {
+            max_queue_size: 10,
+            drop_policy: QueueDropPolicy::DropLowestPriority,
+            ms_per_phoneme: 80,
+        }
+    }
+}
+
+struct QueuedReaction {
+    reaction: Reaction,
+    priority: ReactionPriority,
+}
+
+pub struct ReactionScheduler {
+    queue: VecDeque<QueuedReaction>,
+    busy_until: Option<Instant>,
+    options: ReactionSchedulerOptions,
+    clock: Arc<dyn Clock>,
+}
+
+impl From<&Stimulus> for ReactionPriority {
+    fn from(stimulus: &Stimulus) -> Self {
+        match stimulus {
+            Stimulus::ChatMessage(_) => ReactionPriority::Normal,
+            Stimulus::Action(_) | Stimulus::StreamEvent(_) => ReactionPriority::High,
+        }
+    }
+}
+
+impl ReactionScheduler {
+    pub fn new(options: ReactionSchedulerOptions, clock: Arc<dyn Clock>) -> Self {
+        ReactionScheduler { queue: VecDeque::new(), busy_until: None, options, clock }
+    }
+
+    /// Queues the reaction, returning the reaction dropped to make room for it if the queue is full.
+    pub fn enqueue(&mut self, reaction: Reaction, priority: ReactionPriority) -> Option<Reaction> {
+        let incoming = QueuedReaction { reaction, priority };
+        let dropped = if self.queue.len() < self.options.max_queue_size {
+            None
+        } else {
+            match self.options.drop_policy {
+                QueueDropPolicy::DropIncoming => return Some(incoming.reaction),
+                QueueDropPolicy::DropOldest => self.queue.pop_front(),
+                QueueDropPolicy::DropLowestPriority => {
+                    let lowest_index = self.queue.iter().enumerate()
+                        .min_by_key(|(index, queued)| (queued.priority, *index))
+                        .map(|(index, _)| index);
+                    match lowest_index {
+                        Some(index) if self.queue[index].priority < incoming.priority => self.queue.remove(index),
+                        _ => return Some(incoming.reaction),
+                    }
+                }
+            }
+        };
+
+        let insert_index = self.queue.iter()
+            .position(|queued| queued.priority < incoming.priority)
+            .unwrap_or(self.queue.len());
+        self.queue.insert(insert_index, incoming);
+
+        dropped.map(|queued| queued.reaction)
+    }
+
+    /// Returns the next reaction to play if the previous one is estimated to be over.
+    pub fn poll(&mut self) -> Option<Reaction> {
+        let now = self.clock.now();
+        if self.busy_until.is_some_and(|busy_until| busy_until > now) {
+            return None;
+        }
+
+        let next = self.queue.pop_front()?;
+        self.busy_until = Some(now + self.estimate_duration(&next.reaction));
+        Some(next.reaction)
+    }
+
+    /// Time to wait before [`poll`](Self::poll) can return a reaction, `None` if nothing is queued.
+    pub fn time_until_next(&self) -> Option<Duration> {
+        if self.queue.is_empty() {
+            return None;
+        }
+
+        Some(self.busy_until
+            .map(|busy_until| busy_until.saturating_duration_since(self.clock.now()))
+            .unwrap_or(Duration::ZERO))
+    }
+
+    pub fn queue_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn estimate_duration(&self, reaction: &Reaction) -> Duration {
+        self.estimate_step_durations(reaction).into_iter().sum()
+    }
+
+    /// Time between the start of each step and the start of the following one.
+    pub fn estimate_step_durations(&self, reaction: &Reaction) -> Vec<Duration> {
+        reaction.steps.iter().map(|step| match step {
+            ReactionStep::Moving(moving_step) => self.estimate_moving_step_duration(moving_step),
+            ReactionStep::Talking(talking_step) => self.estimate_talking_step_duration(talking_step),
+            ReactionStep::CompositeTalking(talking_steps) => talking_steps.iter()
+                .map(|talking_step| self.estimate_talking_step_duration(talking_step))
+                .sum(),
+            ReactionStep::ChatMessage(_) => Duration::ZERO,
+        }).collect()
+    }
+
+    fn estimate_moving_step_duration(&self, step: &MovingReactionStep) -> Duration {
+        let last_frame = step.animation.frames.0.iter().map(|frame| frame.frame_end as u64).max().unwrap_or(0);
+        estimate_step_duration(&step.skip, Duration::from_millis(last_frame * 1000 / ANIMATION_FRAMES_PER_SECOND))
+    }
+
+    // The mouth track is timed by the brain, steps without one fall back to a flat time per phoneme
+    fn estimate_talking_step_duration(&self, step: &TalkingReactionStep) -> Duration {
+        let talking_duration = if step.mouth_track.is_empty() {
+            Duration::from_millis(step.phonemes.len() as u64 * self.options.ms_per_phoneme)
+        } else {
+            Duration::from_millis(mouth_track_duration_ms(&step.mouth_track) as u64)
+        };
+        estimate_step_duration(&step.skip, talking_duration)
+    }
+}
+
+fn estimate_step_duration(skip: &ReactionStepSkip, step_duration: Duration) -> Duration {
+    match skip {
+        ReactionStepSkip::ImmediatelyAfter => Duration::ZERO,
+        ReactionStepSkip::AfterMilliseconds(ms) => Duration::from_millis(ms.0 as u64),
+        ReactionStepSkip::AfterStepWithExtraMilliseconds(ms) => step_duration + Duration::from_millis(ms.0 as u64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use pran_droid_core::domain::animations::animation::{Animation, AnimationFrame, AnimationFrames};
+    use pran_droid_core::domain::emotions::emotion::{EmotionId, MouthPositionName};
+    use pran_droid_core::domain::images::image::ImageId;
+    use pran_droid_core::domain::reactions::reaction::{ChatMessageReactionStep, Milliseconds, ReactionStepText};
+    use pran_droid_core::domain::reactions::reaction_definition::ReactionDefinitionId;
+    use pran_droid_core::domain::visemes::mouth_track::TimedMouthPosition;
+    use super::*;
+
+    #[test]
+    fn reaction_scheduler_poll_return_reactions_in_arrival_order() {
+        let (mut scheduler, _) = create_scheduler(ReactionSchedulerOptions::default());
+
+        scheduler.enqueue(create_reaction("1", 0), ReactionPriority::Normal);
+        scheduler.enqueue(create_reaction("2", 0), ReactionPriority::Normal);
+
+        assert_eq!(poll_id(&mut scheduler), Some(String::from("1")));
+        assert_eq!(poll_id(&mut scheduler), Some(String::from("2")));
+        assert_eq!(poll_id(&mut scheduler), None);
+    }
+
+    #[test]
+    fn reaction_scheduler_poll_wait_for_previous_reaction_to_finish() {
+        let (mut scheduler, clock) = create_scheduler(ReactionSchedulerOptions::default());
+
+        scheduler.enqueue(create_reaction("1", 500), ReactionPriority::Normal);
+        scheduler.enqueue(create_reaction("2", 500), ReactionPriority::Normal);
+
+        assert_eq!(poll_id(&mut scheduler), Some(String::from("1")));
+        assert_eq!(poll_id(&mut scheduler), None);
+        assert_eq!(scheduler.time_until_next(), Some(Duration::from_millis(500)));
+
+        clock.advance(Duration::from_millis(499));
+        assert_eq!(poll_id(&mut scheduler), None);
+
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(poll_id(&mut scheduler), Some(String::from("2")));
+    }
+
+    #[test]
+    fn reaction_scheduler_time_until_next_none_when_queue_empty() {
+        let (mut scheduler, _) = create_scheduler(ReactionSchedulerOptions::default());
+        assert_eq!(scheduler.time_until_next(), None);
+
+        scheduler.enqueue(create_reaction("1", 500), ReactionPriority::Normal);
+        assert_eq!(scheduler.time_until_next(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn reaction_scheduler_high_priority_jump_ahead_of_normal_ones() {
+        let (mut scheduler, _) = create_scheduler(ReactionSchedulerOptions::default());
+
+        scheduler.enqueue(create_reaction("chat1", 0), ReactionPriority::Normal);
+        scheduler.enqueue(create_reaction("chat2", 0), ReactionPriority::Normal);
+        scheduler.enqueue(create_reaction("action1", 0), ReactionPriority::High);
+        scheduler.enqueue(create_reaction("action2", 0), ReactionPriority::High);
+
+        assert_eq!(poll_id(&mut scheduler), Some(String::from("action1")));
+        assert_eq!(poll_id(&mut scheduler), Some(String::from("action2")));
+        assert_eq!(poll_id(&mut scheduler), Some(String::from("chat1")));
+        assert_eq!(poll_id(&mut scheduler), Some(String::from("chat2")));
+    }
+
+    #[test]
+    fn reaction_scheduler_full_queue_drop_incoming() {
+        let (mut scheduler, _) = create_scheduler(ReactionSchedulerOptions { max_queue_size: 1, drop_policy: QueueDropPolicy::DropIncoming, ..Default::default() });
+
+        assert!(scheduler.enqueue(create_reaction("1", 0), ReactionPriority::Normal).is_none());
+        let dropped = scheduler.enqueue(create_reaction("2", 0), ReactionPriority::High);
+
+        assert_eq!(dropped.map(|reaction| reaction.source_definition_id.0), Some(String::from("2")));
+        assert_eq!(scheduler.queue_len(), 1);
+        assert_eq!(poll_id(&mut scheduler), Some(String::from("1")));
+    }
+
+    #[test]
+    fn reaction_scheduler_full_queue_drop_oldest() {
+        let (mut scheduler, _) = create_scheduler(ReactionSchedulerOptions { max_queue_size: 2, drop_policy: QueueDropPolicy::DropOldest, ..Default::default() });
+
+        scheduler.enqueue(create_reaction("1", 0), ReactionPriority::Normal);
+        scheduler.enqueue(create_reaction("2", 0), ReactionPriority::Normal);
+        let dropped = scheduler.enqueue(create_reaction("3", 0), ReactionPriority::Normal);
+
+        assert_eq!(dropped.map(|reaction| reaction.source_definition_id.0), Some(String::from("1")));
+        assert_eq!(poll_id(&mut scheduler), Some(String::from("2")));
+        assert_eq!(poll_id(&mut scheduler), Some(String::from("3")));
+    }
+
+    #[test]
+    fn reaction_scheduler_full_queue_drop_lowest_priority() {
+        let (mut scheduler, _) = create_scheduler(ReactionSchedulerOptions { max_queue_size: 2, drop_policy: QueueDropPolicy::DropLowestPriority, ..Default::default() });
+
+        scheduler.enqueue(create_reaction("chat1", 0), ReactionPriority::Normal);
+        scheduler.enqueue(create_reaction("chat2", 0), ReactionPriority::Normal);
+        let dropped_chat = scheduler.enqueue(create_reaction("action1", 0), ReactionPriority::High);
+        let dropped_incoming = scheduler.enqueue(create_reaction("chat3", 0), ReactionPriority::Normal);
+
+        assert_eq!(dropped_chat.map(|reaction| reaction.source_definition_id.0), Some(String::from("chat1")));
+        assert_eq!(dropped_incoming.map(|reaction| reaction.source_definition_id.0), Some(String::from("chat3")));
+        assert_eq!(poll_id(&mut scheduler), Some(String::from("action1")));
+        assert_eq!(poll_id(&mut scheduler), Some(String::from("chat2")));
+    }
+
+    #[test]
+    fn reaction_scheduler_estimate_duration_from_skips_and_phonemes() {
+        let (scheduler, _) = create_scheduler(ReactionSchedulerOptions { ms_per_phoneme: 10, ..Default::default() });
+        let reaction = Reaction {
+            source_definition_id: ReactionDefinitionId(String::from("id")),
+            steps: vec![
+                ReactionStep::Talking(create_talking_step(ReactionStepSkip::ImmediatelyAfter, 50)),
+                ReactionStep::Talking(create_talking_step(ReactionStepSkip::AfterMilliseconds(Milliseconds(300)), 50)),
+                ReactionStep::Talking(create_talking_step(ReactionStepSkip::AfterStepWithExtraMilliseconds(Milliseconds(100)), 5)),
+                ReactionStep::CompositeTalking(vec![
+                    create_talking_step(ReactionStepSkip::AfterStepWithExtraMilliseconds(Milliseconds(0)), 2),
+                    create_talking_step(ReactionStepSkip::AfterMilliseconds(Milliseconds(40)), 2),
+                ]),
+                ReactionStep::Moving(MovingReactionStep {
+                    animation: Animation { frames: AnimationFrames(vec![
+                        AnimationFrame { frame_start: 0, frame_end: 30, image_id: ImageId(String::from("image")) },
+                        AnimationFrame { frame_start: 31, frame_end: 60, image_id: ImageId(String::from("image")) },
+                    ]) },
+                    skip: ReactionStepSkip::AfterStepWithExtraMilliseconds(Milliseconds(0)),
+                }),
+                ReactionStep::ChatMessage(ChatMessageReactionStep { text: String::from("in chat"), reply_to_message_id: None }),
+            ],
+            overlay_targets: vec![],
+        };
+
+        assert_eq!(scheduler.estimate_duration(&reaction), Duration::from_millis(300 + 150 + 20 + 40 + 1000));
+    }
+
+    #[test]
+    fn reaction_scheduler_estimate_duration_from_mouth_track() {
+        let (scheduler, _) = create_scheduler(ReactionSchedulerOptions { ms_per_phoneme: 10, ..Default::default() });
+        let talking_step = TalkingReactionStep {
+            mouth_track: vec![
+                TimedMouthPosition { position: MouthPositionName::Ah, start_ms: 0, duration_ms: 110 },
+                TimedMouthPosition { position: MouthPositionName::Idle, start_ms: 110, duration_ms: 150 },
+            ],
+            ..create_talking_step(ReactionStepSkip::AfterStepWithExtraMilliseconds(Milliseconds(40)), 3)
+        };
+        let reaction = Reaction {
+            source_definition_id: ReactionDefinitionId(String::from("id")),
+            steps: vec![ReactionStep::Talking(talking_step)],
+            overlay_targets: vec![],
+        };
+
+        assert_eq!(scheduler.estimate_duration(&reaction), Duration::from_millis(260 + 40));
+    }
+
+    struct FakeClock {
+        start: Instant,
+        elapsed: Mutex<Duration>,
+    }
+
+    impl FakeClock {
+        fn advance(&self, duration: Duration) {
+            *self.elapsed.lock().unwrap() += duration;
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            self.start + *self.elapsed.lock().unwrap()
+        }
+    }
+
+    fn create_scheduler(options: ReactionSchedulerOptions) -> (ReactionScheduler, Arc<FakeClock>) {
+        let clock = Arc::new(FakeClock { start: Instant::now(), elapsed: Mutex::new(Duration::ZERO) });
+        (ReactionScheduler::new(options, clock.clone()), clock)
+    }
+
+    fn poll_id(scheduler: &mut ReactionScheduler) -> Option<String> {
+        scheduler.poll().map(|reaction| reaction.source_definition_id.0)
+    }
+
+    fn create_reaction(id: &str, duration_ms: u16) -> Reaction {
+        Reaction {
+            source_definition_id: ReactionDefinitionId(String::from(id)),
+            steps: vec![ReactionStep::Talking(create_talking_step(ReactionStepSkip::AfterMilliseconds(Milliseconds(duration_ms)), 1))],
+            overlay_targets: vec![],
+        }
+    }
+
+    fn create_talking_step(skip: ReactionStepSkip, phonemes_count: usize) -> TalkingReactionStep {
+        TalkingReactionStep {
+            emotion_id: EmotionId(String::from("emotion")),
+            skip,
+            phonemes: vec![String::from("a"); phonemes_count],
+            text: ReactionStepText::Instant(String::from("text")),
+            mouth_track: vec![],
+        }
+    }
+}
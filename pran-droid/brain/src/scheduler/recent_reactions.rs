@@ -0,0 +1,244 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use pran_droid_core::domain::animations::animation::{Animation, AnimationFrame, AnimationFrames};
+use pran_droid_core::domain::reactions::reaction::{Milliseconds, MovingReactionStep, Reaction, ReactionStep, ReactionStepSkip, TalkingReactionStep};
+use crate::scheduler::reaction_scheduler::ANIMATION_FRAMES_PER_SECOND;
+
+#[derive(Clone, Debug)]
+pub struct RecentReaction {
+    pub reaction_id: u64,
+    pub reaction: Reaction,
+    pub started_at: Instant,
+    pub step_durations: Vec<Duration>,
+}
+
+impl RecentReaction {
+    pub fn ends_at(&self) -> Instant {
+        self.started_at + self.step_durations.iter().sum::<Duration>()
+    }
+
+    /// The steps still to play at `now`, the step in progress is cut where possible.
+    pub fn remaining_reaction(&self, now: Instant) -> Option<Reaction> {
+        let elapsed = now.saturating_duration_since(self.started_at);
+        let mut step_start = Duration::ZERO;
+        let mut steps = vec![];
+
+        for (step, duration) in self.reaction.steps.iter().zip(&self.step_durations) {
+            let step_end = step_start + *duration;
+            if step_end > elapsed {
+                steps.push(if step_start < elapsed { trim_step(step, elapsed - step_start, step_end - elapsed) } else { step.clone() });
+            }
+            step_start = step_end;
+        }
+
+        if steps.is_empty() {
+            return None;
+        }
+        Some(Reaction { steps, ..self.reaction.clone() })
+    }
+}
+
+// Animations skip the frames already shown, talking steps are replayed whole as their bubble cannot be cut
+fn trim_step(step: &ReactionStep, played: Duration, remaining: Duration) -> ReactionStep {
+    match step {
+        ReactionStep::Moving(moving_step) => {
+            let played_frames = (played.as_millis() as u64 * ANIMATION_FRAMES_PER_SECOND / 1000) as u16;
+            let frames: Vec<AnimationFrame> = moving_step.animation.frames.0.iter()
+                .filter(|frame| frame.frame_end > played_frames)
+                .map(|frame| AnimationFrame {
+                    frame_start: frame.frame_start.saturating_sub(played_frames),
+                    frame_end: frame.frame_end - played_frames,
+                    image_id: frame.image_id.clone(),
+                })
+                .collect();
+            // Cut during the extra time after the animation, only a plain wait for the rest of the step is left
+            let skip = if frames.is_empty() {
+                ReactionStepSkip::AfterMilliseconds(Milliseconds(remaining.as_millis().min(u16::MAX as u128) as u16))
+            } else {
+                trim_skip(&moving_step.skip, played)
+            };
+            ReactionStep::Moving(MovingReactionStep { animation: Animation { frames: AnimationFrames(frames) }, skip })
+        },
+        ReactionStep::Talking(talking_step) => ReactionStep::Talking(TalkingReactionStep {
+            skip: trim_skip(&talking_step.skip, played),
+            ..talking_step.clone()
+        }),
+        other => other.clone(),
+    }
+}
+
+fn trim_skip(skip: &ReactionStepSkip, played: Duration) -> ReactionStepSkip {
+    match skip {
+        ReactionStepSkip::AfterMilliseconds(ms) => ReactionStepSkip::AfterMilliseconds(Milliseconds(ms.0.saturating_sub(played.as_millis() as u16))),
+        other => other.clone(),
+    }
+}
+
+/// Ring buffer of the last reactions sent to the overlays.
+pub struct RecentReactions {
+    reactions: VecDeque<RecentReaction>,
+    capacity: usize,
+}
+
+impl RecentReactions {
+    pub fn new(capacity: usize) -> Self {
+        RecentReactions { reactions: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    pub fn push(&mut self, reaction: RecentReaction) {
+        if self.reactions.len() == self.capacity {
+            self.reactions.pop_front();
+        }
+        self.reactions.push_back(reaction);
+    }
+
+    /// Reactions are played one at a time, only the last one can still be playing.
+    pub fn in_progress(&self, now: Instant) -> Option<&RecentReaction> {
+        self.reactions.back().filter(|reaction| reaction.ends_at() > now)
+    }
+
+    /// Up to `count` of the last finished reactions, oldest first.
+    pub fn finished(&self, now: Instant, count: usize) -> Vec<&RecentReaction> {
+        let finished: Vec<&RecentReaction> = self.reactions.iter()
+            .filter(|reaction| reaction.ends_at() <= now)
+            .collect();
+        finished[finished.len().saturating_sub(count)..].to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pran_droid_core::domain::emotions::emotion::EmotionId;
+    use pran_droid_core::domain::images::image::ImageId;
+    use pran_droid_core::domain::reactions::reaction::ReactionStepText;
+    use pran_droid_core::domain::reactions::reaction_definition::ReactionDefinitionId;
+    use super::*;
+
+    fn create_recent_reaction(reaction_id: u64, started_at: Instant, duration_ms: u64) -> RecentReaction {
+        RecentReaction {
+            reaction_id,
+            reaction: Reaction { source_definition_id: ReactionDefinitionId(reaction_id.to_string()), steps: vec![], overlay_targets: vec![] },
+            started_at,
+            step_durations: vec![Duration::from_millis(duration_ms)],
+        }
+    }
+
+    fn frame(frame_start: u16, frame_end: u16) -> AnimationFrame {
+        AnimationFrame { frame_start, frame_end, image_id: ImageId(String::from("image")) }
+    }
+
+    #[test]
+    fn recent_reactions_keep_only_the_last_ones() {
+        let start = Instant::now();
+        let mut recent_reactions = RecentReactions::new(2);
+
+        for reaction_id in 1..=3 {
+            recent_reactions.push(create_recent_reaction(reaction_id, start, 0));
+        }
+
+        let ids: Vec<u64> = recent_reactions.finished(start, 5).iter().map(|reaction| reaction.reaction_id).collect();
+        assert_eq!(ids, vec![2, 3]);
+    }
+
+    #[test]
+    fn recent_reactions_split_the_playing_reaction_from_the_finished_ones() {
+        let start = Instant::now();
+        let mut recent_reactions = RecentReactions::new(10);
+        recent_reactions.push(create_recent_reaction(1, start, 100));
+        recent_reactions.push(create_recent_reaction(2, start + Duration::from_millis(100), 100));
+        recent_reactions.push(create_recent_reaction(3, start + Duration::from_millis(200), 100));
+        let now = start + Duration::from_millis(250);
+
+        let finished: Vec<u64> = recent_reactions.finished(now, 1).iter().map(|reaction| reaction.reaction_id).collect();
+        assert_eq!(finished, vec![2]);
+        assert_eq!(recent_reactions.in_progress(now).map(|reaction| reaction.reaction_id), Some(3));
+        assert!(recent_reactions.in_progress(start + Duration::from_millis(300)).is_none());
+    }
+
+    #[test]
+    fn remaining_reaction_drops_played_steps_and_cuts_the_current_one() {
+        let start = Instant::now();
+        let talking_step = TalkingReactionStep {
+            emotion_id: EmotionId(String::from("happy")),
+            skip: ReactionStepSkip::AfterMilliseconds(Milliseconds(300)),
+            phonemes: vec![],
+            text: ReactionStepText::Instant(String::from("hello")),
+            mouth_track: vec![],
+        };
+        let recent_reaction = RecentReaction {
+            reaction_id: 1,
+            reaction: Reaction {
+                source_definition_id: ReactionDefinitionId(String::from("1")),
+                steps: vec![
+                    ReactionStep::Talking(talking_step.clone()),
+                    ReactionStep::Moving(MovingReactionStep {
+                        animation: Animation { frames: AnimationFrames(vec![frame(0, 6), frame(7, 30)]) },
+                        skip: ReactionStepSkip::AfterStepWithExtraMilliseconds(Milliseconds(0)),
+                    }),
+                    ReactionStep::Talking(talking_step),
+                ],
+                overlay_targets: vec![],
+            },
+            started_at: start,
+            step_durations: vec![Duration::from_millis(300), Duration::from_millis(500), Duration::from_millis(300)],
+        };
+
+        let remaining = recent_reaction.remaining_reaction(start + Duration::from_millis(450)).unwrap();
+
+        assert!(matches!(&remaining.steps[..], [
+            ReactionStep::Moving(MovingReactionStep { animation: Animation { frames: AnimationFrames(frames) }, .. }),
+            ReactionStep::Talking(TalkingReactionStep { skip: ReactionStepSkip::AfterMilliseconds(Milliseconds(300)), .. }),
+        ] if frames.iter().map(|frame| (frame.frame_start, frame.frame_end)).collect::<Vec<(u16, u16)>>() == vec![(0, 21)]));
+        assert!(recent_reaction.remaining_reaction(start + Duration::from_millis(1100)).is_none());
+    }
+
+    #[test]
+    fn remaining_reaction_shortens_timed_skips() {
+        let start = Instant::now();
+        let recent_reaction = RecentReaction {
+            reaction_id: 1,
+            reaction: Reaction {
+                source_definition_id: ReactionDefinitionId(String::from("1")),
+                steps: vec![ReactionStep::Talking(TalkingReactionStep {
+                    emotion_id: EmotionId(String::from("happy")),
+                    skip: ReactionStepSkip::AfterMilliseconds(Milliseconds(300)),
+                    phonemes: vec![],
+                    text: ReactionStepText::Instant(String::from("hello")),
+                    mouth_track: vec![],
+                })],
+                overlay_targets: vec![],
+            },
+            started_at: start,
+            step_durations: vec![Duration::from_millis(300)],
+        };
+
+        let remaining = recent_reaction.remaining_reaction(start + Duration::from_millis(100)).unwrap();
+
+        assert!(matches!(&remaining.steps[..], [ReactionStep::Talking(TalkingReactionStep { skip: ReactionStepSkip::AfterMilliseconds(Milliseconds(200)), .. })]));
+    }
+
+    #[test]
+    fn remaining_reaction_turns_a_moving_step_cut_during_its_extra_time_into_a_wait() {
+        let start = Instant::now();
+        let recent_reaction = RecentReaction {
+            reaction_id: 1,
+            reaction: Reaction {
+                source_definition_id: ReactionDefinitionId(String::from("1")),
+                steps: vec![ReactionStep::Moving(MovingReactionStep {
+                    animation: Animation { frames: AnimationFrames(vec![frame(0, 6)]) },
+                    skip: ReactionStepSkip::AfterStepWithExtraMilliseconds(Milliseconds(400)),
+                })],
+                overlay_targets: vec![],
+            },
+            started_at: start,
+            step_durations: vec![Duration::from_millis(500)],
+        };
+
+        let remaining = recent_reaction.remaining_reaction(start + Duration::from_millis(200)).unwrap();
+
+        assert!(matches!(&remaining.steps[..], [ReactionStep::Moving(MovingReactionStep {
+            animation: Animation { frames: AnimationFrames(frames) },
+            skip: ReactionStepSkip::AfterMilliseconds(Milliseconds(300)),
+        })] if frames.is_empty()));
+    }
+}
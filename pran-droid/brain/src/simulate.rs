@@ -1,10 +1,13 @@
 use std::sync::Arc;
 use pran_droid_core::application::brain::pran_droid_brain::{create_droid_brain, TextPhonemiser};
+use pran_droid_core::domain::brain::clock::{Clock, SystemClock};
 use pran_droid_core::domain::brain::pran_droid_brain::ReactionNotifier;
 use pran_droid_core::domain::brain::stimuli::Stimulus;
 use pran_droid_core::domain::reactions::reaction_definition::ReactionDefinitionId;
 use pran_droid_core::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+use pran_droid_core::domain::visemes::mouth_track::MouthTrackModel;
 use crate::phonemiser::pran_text_phonemiser::PranTextPhonemiser;
+use crate::brain_output::chat_output::{ChatOutput, send_chat_messages};
 use crate::brain_output::outputs::ReactionOutput;
 
 struct NoopReactionNotifier {}
@@ -12,13 +15,17 @@ impl ReactionNotifier for NoopReactionNotifier {
     fn notify_reaction_usage(&self, _: &ReactionDefinitionId, _: u32) {}
 }
 
-pub async fn simulate_droid_brain(reaction_repository: &dyn ReactionDefinitionRepository, stimulus: Stimulus) -> Option<ReactionOutput> {
+pub async fn simulate_droid_brain(reaction_repository: &dyn ReactionDefinitionRepository, stimulus: Stimulus, chat_output: &dyn ChatOutput, mouth_track_model: &Arc<MouthTrackModel>) -> Option<ReactionOutput> {
     pran_phonemes_core::phonemes::pran_phonemes().expect("PranPhonemes failed to initialise");
 
     let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(PranTextPhonemiser {});
     let reaction_notifier: Arc<dyn ReactionNotifier> = Arc::new(NoopReactionNotifier {});
-    let mut brain = create_droid_brain(reaction_repository, &text_phonemiser, &reaction_notifier).await;
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+    let mut brain = create_droid_brain(reaction_repository, &text_phonemiser, &reaction_notifier, &clock, mouth_track_model).await;
 
     brain.stimulate(stimulus)
-        .map(|reaction| Into::<ReactionOutput>::into(reaction))
+        .map(|reaction| {
+            send_chat_messages(&reaction, chat_output);
+            Into::<ReactionOutput>::into(reaction)
+        })
 }
@@ -1,34 +1,85 @@
 use std::fmt::{Display, Formatter, Result};
+use serde::Deserialize;
 
+// Deserialisable so that chat events can be fed to the brain from local sources, one JSON object per line
 #[derive(Debug)]
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
+#[serde(tag = "type")]
 pub enum ChatEvent {
     Message(ChatMessage),
-    Action(ChatAction)
+    Action(ChatAction),
+    Notice(ChatNotice),
 }
 
 impl Display for ChatEvent {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
             ChatEvent::Message(message) => write!(f, "{}: {} - mod: {}", message.name, message.content, message.is_mod.to_string()),
-            ChatEvent::Action(action) => write!(f, "{}: {} - {} - mod: {}", action.name, action.action_name, action.action_id, action.is_mod.to_string())
+            ChatEvent::Action(action) => write!(f, "{}: {} - {} - mod: {}", action.name, action.action_name, action.action_id, action.is_mod.to_string()),
+            ChatEvent::Notice(notice) => write!(f, "{}: {:?} - mod: {}", notice.name, notice.event, notice.is_mod)
         }
     }
 }
 
 #[derive(Debug)]
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
 pub struct ChatMessage {
+    #[serde(default)]
+    pub id: String,
     pub name: String,
     pub content: String,
-    pub is_mod: bool
+    #[serde(default)]
+    pub is_mod: bool,
+    #[serde(default)]
+    pub is_broadcaster: bool,
+    #[serde(default)]
+    pub is_vip: bool,
+    #[serde(default)]
+    pub is_subscriber: bool,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
 #[derive(Debug)]
 pub struct ChatAction {
     pub name: String,
+    #[serde(default)]
     pub is_mod: bool,
+    #[serde(default)]
+    pub is_broadcaster: bool,
+    #[serde(default)]
+    pub is_vip: bool,
+    #[serde(default)]
+    pub is_subscriber: bool,
     pub action_id: String,
-    pub action_name: String
+    pub action_name: String,
+    pub input: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ChatNotice {
+    pub name: String,
+    #[serde(default)]
+    pub is_mod: bool,
+    #[serde(default)]
+    pub is_broadcaster: bool,
+    #[serde(default)]
+    pub is_vip: bool,
+    #[serde(default)]
+    pub is_subscriber: bool,
+    pub event: ChatNoticeEvent,
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(tag = "type")]
+pub enum ChatNoticeEvent {
+    Subscription { months: u32 },
+    Resubscription { months: u32 },
+    GiftSubscription { recipient: String, months: u32 },
+    MysteryGift { count: u32 },
+    Raid { viewers: u32 },
+    Follow,
+    HypeTrainStart { level: u32 },
+    HypeTrainLevelUp { level: u32 },
+    HypeTrainEnd { level: u32 },
 }
@@ -0,0 +1,16 @@
+use std::sync::Arc;
+use futures::channel::mpsc::UnboundedSender;
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use crate::brain_output::chat_output::ChatOutput;
+use crate::stream_interface::events::ChatEvent;
+use crate::stream_interface::supervisor::ConnectionStateEvent;
+
+pub struct StreamConnection {
+    pub events: BoxStream<'static, ChatEvent>,
+    pub chat_output: Arc<dyn ChatOutput>,
+}
+
+pub trait StreamInterface: Send {
+    fn connect(self: Box<Self>, connection_states: UnboundedSender<ConnectionStateEvent>) -> BoxFuture<'static, Result<StreamConnection, String>>;
+}
@@ -0,0 +1,13 @@
+use crate::brain_output::chat_output::{ChatOutput, ChatOutputMessage};
+
+// There is no chat to write to when running locally, messages are logged instead
+pub struct LogChatOutput;
+
+impl ChatOutput for LogChatOutput {
+    fn send_chat_message(&self, message: ChatOutputMessage) {
+        match message.reply_to_message_id {
+            Some(message_id) => info!("Chat message in reply to {}: {}", message_id, message.text),
+            None => info!("Chat message: {}", message.text),
+        }
+    }
+}
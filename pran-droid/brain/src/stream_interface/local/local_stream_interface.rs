@@ -0,0 +1,234 @@
+use std::io;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use futures::channel::mpsc::UnboundedSender;
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use tokio::fs::File;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader, stdin};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::{channel, Sender};
+use tokio_stream::wrappers::ReceiverStream;
+use crate::brain_output::chat_output::ChatOutput;
+use crate::stream_interface::events::ChatEvent;
+use crate::stream_interface::interface::{StreamConnection, StreamInterface};
+use crate::stream_interface::supervisor::{ConnectionState, ConnectionStateEvent};
+
+const LOCAL_CONNECTION: &str = "Local chat";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LocalChatSource {
+    Stdin,
+    Tcp(SocketAddr),
+    File(PathBuf),
+}
+
+// Parses "stdin", "tcp:<address>" or "file:<path>"
+impl FromStr for LocalChatSource {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<LocalChatSource, Self::Err> {
+        match input.split_once(':') {
+            _ if input == "stdin" => Ok(LocalChatSource::Stdin),
+            Some(("tcp", address)) => address.parse::<SocketAddr>().map(LocalChatSource::Tcp).map_err(|_| ()),
+            Some(("file", path)) if !path.is_empty() => Ok(LocalChatSource::File(PathBuf::from(path))),
+            _ => Err(()),
+        }
+    }
+}
+
+// Reads chat events as JSON lines, to run the brain without connecting to a streaming platform
+pub struct LocalStreamInterface {
+    source: LocalChatSource,
+    chat_output: Arc<dyn ChatOutput>,
+}
+
+impl LocalStreamInterface {
+    pub fn new(source: LocalChatSource, chat_output: Arc<dyn ChatOutput>) -> Self {
+        LocalStreamInterface { source, chat_output }
+    }
+}
+
+impl StreamInterface for LocalStreamInterface {
+    fn connect(self: Box<Self>, connection_states: UnboundedSender<ConnectionStateEvent>) -> BoxFuture<'static, Result<StreamConnection, String>> {
+        let LocalStreamInterface { source, chat_output } = *self;
+
+        Box::pin(async move {
+            let (sender, receiver) = channel::<ChatEvent>(100);
+
+            match source {
+                LocalChatSource::Stdin => {
+                    tokio::spawn(read_local_source(BufReader::new(stdin()), sender, connection_states));
+                },
+                LocalChatSource::File(path) => {
+                    let file = File::open(&path).await
+                        .map_err(|error| format!("Could not open chat events file {}: {}", path.display(), error))?;
+                    tokio::spawn(read_local_source(BufReader::new(file), sender, connection_states));
+                },
+                LocalChatSource::Tcp(address) => {
+                    let listener = TcpListener::bind(address).await
+                        .map_err(|error| format!("Could not listen for chat events on {}: {}", address, error))?;
+                    info!("Local chat listening on: {}", address);
+                    tokio::spawn(accept_chat_clients(listener, sender, connection_states));
+                },
+            }
+
+            Ok(StreamConnection { events: ReceiverStream::new(receiver).boxed(), chat_output })
+        })
+    }
+}
+
+async fn read_local_source<R: AsyncBufRead + Unpin>(reader: R, events: Sender<ChatEvent>, connection_states: UnboundedSender<ConnectionStateEvent>) {
+    send_connection_state(&connection_states, ConnectionState::Connected);
+
+    let reason = match read_chat_events(reader, &events).await {
+        Ok(_) => String::from("end of input"),
+        Err(error) => error.to_string(),
+    };
+    send_connection_state(&connection_states, ConnectionState::Disconnected { reason });
+}
+
+async fn accept_chat_clients(listener: TcpListener, events: Sender<ChatEvent>, connection_states: UnboundedSender<ConnectionStateEvent>) {
+    send_connection_state(&connection_states, ConnectionState::Connected);
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, address)) => {
+                info!("Local chat client connected: {}", address);
+                let events = events.clone();
+                tokio::spawn(async move {
+                    if let Err(error) = read_chat_events(BufReader::new(stream), &events).await {
+                        warn!("Local chat client {} failed: {}", address, error);
+                    }
+                    info!("Local chat client disconnected: {}", address);
+                });
+            },
+            Err(error) => {
+                send_connection_state(&connection_states, ConnectionState::Disconnected { reason: error.to_string() });
+                break;
+            },
+        }
+    }
+}
+
+fn send_connection_state(connection_states: &UnboundedSender<ConnectionStateEvent>, state: ConnectionState) {
+    connection_states.unbounded_send(ConnectionStateEvent { connection: LOCAL_CONNECTION.to_string(), state }).ok();
+}
+
+pub(crate) async fn read_chat_events<R: AsyncBufRead + Unpin>(reader: R, events: &Sender<ChatEvent>) -> io::Result<()> {
+    let mut lines = reader.lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str::<ChatEvent>(&line) {
+            Ok(event) => if events.send(event).await.is_err() {
+                break;
+            },
+            Err(error) => warn!("Skipped invalid chat event {}: {}", line, error),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::channel::mpsc::unbounded;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpStream;
+    use crate::brain_output::chat_output::InMemoryChatOutput;
+    use crate::stream_interface::events::ChatNoticeEvent;
+    use super::*;
+
+    #[test]
+    fn local_chat_source_from_str() {
+        assert_eq!("stdin".parse::<LocalChatSource>(), Ok(LocalChatSource::Stdin));
+        assert_eq!("tcp:127.0.0.1:9000".parse::<LocalChatSource>(), Ok(LocalChatSource::Tcp("127.0.0.1:9000".parse().unwrap())));
+        assert_eq!("file:events.jsonl".parse::<LocalChatSource>(), Ok(LocalChatSource::File(PathBuf::from("events.jsonl"))));
+        assert_eq!("tcp:not an address".parse::<LocalChatSource>(), Err(()));
+        assert_eq!("file:".parse::<LocalChatSource>(), Err(()));
+        assert_eq!("twitch".parse::<LocalChatSource>(), Err(()));
+    }
+
+    #[tokio::test]
+    async fn read_chat_events_parses_every_kind_of_event() {
+        let input = concat!(
+            r#"{"type":"Message","id":"1","name":"viewer","content":"hello","is_mod":true}"#, "\n",
+            r#"{"type":"Action","name":"viewer","action_id":"an id","action_name":"dance","input":"fast"}"#, "\n",
+            r#"{"type":"Notice","name":"raider","event":{"type":"Raid","viewers":12}}"#, "\n",
+            r#"{"type":"Notice","name":"follower","event":{"type":"Follow"}}"#, "\n",
+        );
+        let (sender, mut receiver) = channel::<ChatEvent>(10);
+
+        read_chat_events(input.as_bytes(), &sender).await.unwrap();
+        drop(sender);
+
+        assert!(matches!(receiver.recv().await, Some(ChatEvent::Message(message)) if message.id == "1" && message.name == "viewer" && message.content == "hello" && message.is_mod && !message.is_vip));
+        assert!(matches!(receiver.recv().await, Some(ChatEvent::Action(action)) if action.action_id == "an id" && action.action_name == "dance" && action.input == Some(String::from("fast"))));
+        assert!(matches!(receiver.recv().await, Some(ChatEvent::Notice(notice)) if notice.name == "raider" && notice.event == ChatNoticeEvent::Raid { viewers: 12 } && notice.message.is_none()));
+        assert!(matches!(receiver.recv().await, Some(ChatEvent::Notice(notice)) if notice.event == ChatNoticeEvent::Follow));
+        assert!(receiver.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn read_chat_events_skips_blank_and_invalid_lines() {
+        let input = concat!(
+            "\n",
+            "not json\n",
+            r#"{"type":"Unknown","name":"viewer"}"#, "\n",
+            r#"{"type":"Message","name":"viewer","content":"still read"}"#, "\n",
+        );
+        let (sender, mut receiver) = channel::<ChatEvent>(10);
+
+        read_chat_events(input.as_bytes(), &sender).await.unwrap();
+        drop(sender);
+
+        assert!(matches!(receiver.recv().await, Some(ChatEvent::Message(message)) if message.content == "still read" && message.id.is_empty()));
+        assert!(receiver.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn local_stream_interface_reads_events_from_a_file() {
+        let path = std::env::temp_dir().join(format!("pran_droid_chat_events_{}.jsonl", std::process::id()));
+        tokio::fs::write(&path, concat!(r#"{"type":"Message","name":"viewer","content":"from a file"}"#, "\n")).await.unwrap();
+        let (states_sender, mut states) = unbounded::<ConnectionStateEvent>();
+        let interface = Box::new(LocalStreamInterface::new(LocalChatSource::File(path.clone()), Arc::new(InMemoryChatOutput::new())));
+
+        let connection = interface.connect(states_sender).await.unwrap();
+        let events: Vec<ChatEvent> = connection.events.collect().await;
+        tokio::fs::remove_file(&path).await.ok();
+
+        assert!(matches!(events.as_slice(), [ChatEvent::Message(message)] if message.content == "from a file"));
+        assert_eq!(states.next().await.map(|event| event.state), Some(ConnectionState::Connected));
+        assert_eq!(states.next().await.map(|event| event.state), Some(ConnectionState::Disconnected { reason: String::from("end of input") }));
+    }
+
+    #[tokio::test]
+    async fn local_stream_interface_fails_when_the_file_does_not_exist() {
+        let path = std::env::temp_dir().join("pran_droid_missing_chat_events.jsonl");
+        let interface = Box::new(LocalStreamInterface::new(LocalChatSource::File(path), Arc::new(InMemoryChatOutput::new())));
+        let (states_sender, _states) = unbounded::<ConnectionStateEvent>();
+
+        assert!(interface.connect(states_sender).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn local_stream_interface_reads_events_from_tcp_clients() {
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let interface = Box::new(LocalStreamInterface::new(LocalChatSource::Tcp(address), Arc::new(InMemoryChatOutput::new())));
+        let (states_sender, _states) = unbounded::<ConnectionStateEvent>();
+        let mut connection = interface.connect(states_sender).await.unwrap();
+
+        for content in ["first client", "second client"] {
+            let mut client = TcpStream::connect(address).await.unwrap();
+            client.write_all(format!(r#"{{"type":"Message","name":"viewer","content":"{}"}}{}"#, content, "\n").as_bytes()).await.unwrap();
+
+            assert!(matches!(connection.events.next().await, Some(ChatEvent::Message(message)) if message.content == content));
+        }
+    }
+}
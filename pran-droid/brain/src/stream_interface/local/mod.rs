@@ -0,0 +1,2 @@
+pub mod local_stream_interface;
+pub mod local_chat_output;
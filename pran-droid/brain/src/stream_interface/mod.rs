@@ -1,2 +1,6 @@
 pub mod events;
+pub mod interface;
+pub mod supervisor;
+pub mod local;
+#[cfg(feature="twitch")]
 pub mod twitch;
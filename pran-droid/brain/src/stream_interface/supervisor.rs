@@ -0,0 +1,243 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use futures::channel::mpsc::UnboundedSender;
+use futures::future::BoxFuture;
+use tokio::sync::mpsc::Sender;
+use tokio::time;
+use crate::stream_interface::events::ChatEvent;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Disconnected { reason: String },
+    Reconnecting { attempt: u32, delay: Duration },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConnectionStateEvent {
+    pub connection: String,
+    pub state: ConnectionState,
+}
+
+impl Display for ConnectionStateEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.state {
+            ConnectionState::Connecting => write!(f, "{} connecting", self.connection),
+            ConnectionState::Connected => write!(f, "{} connected", self.connection),
+            ConnectionState::Disconnected { reason } => write!(f, "{} disconnected: {}", self.connection, reason),
+            ConnectionState::Reconnecting { attempt, delay } => write!(f, "{} reconnecting in {:?} (attempt {})", self.connection, delay, attempt),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BackoffOptions {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: u32,
+    // Fraction of the delay added at random so that reconnections don't happen in lockstep
+    pub jitter: f64,
+}
+
+impl Default for BackoffOptions {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            multiplier: 2,
+            jitter: 0.2,
+        }
+    }
+}
+
+pub struct Backoff {
+    options: BackoffOptions,
+    attempt: u32,
+}
+
+impl Backoff {
+    pub fn new(options: BackoffOptions) -> Self {
+        Self { options, attempt: 0 }
+    }
+
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    pub fn next_delay(&mut self) -> Duration {
+        self.next_delay_with_random(rand::random::<f64>())
+    }
+
+    fn next_delay_with_random(&mut self, random: f64) -> Duration {
+        let delay = self.options.initial_delay
+            .saturating_mul(self.options.multiplier.saturating_pow(self.attempt))
+            .min(self.options.max_delay);
+        self.attempt = self.attempt.saturating_add(1);
+
+        delay + delay.mul_f64(self.options.jitter * random)
+    }
+}
+
+pub trait SupervisedConnection: Send {
+    fn name(&self) -> &str;
+    // Runs a single connection until it is lost, calling `session.connected()` once it is usable
+    fn run<'a>(&'a mut self, session: &'a ConnectionSession) -> BoxFuture<'a, Result<(), String>>;
+}
+
+pub struct ConnectionSession {
+    connection: String,
+    events: Sender<ChatEvent>,
+    states: UnboundedSender<ConnectionStateEvent>,
+    is_connected: AtomicBool,
+}
+
+impl ConnectionSession {
+    pub fn connected(&self) {
+        self.is_connected.store(true, Ordering::SeqCst);
+        self.report(ConnectionState::Connected);
+    }
+
+    pub async fn send(&self, event: ChatEvent) -> Result<(), String> {
+        self.events.send(event).await.map_err(|_| String::from("Event receiver dropped"))
+    }
+
+    fn report(&self, state: ConnectionState) {
+        self.states.unbounded_send(ConnectionStateEvent { connection: self.connection.clone(), state }).ok();
+    }
+}
+
+// Keeps the connection running until the events receiver is dropped, waiting longer after every failed attempt.
+pub async fn supervise(mut connection: impl SupervisedConnection, options: BackoffOptions, events: Sender<ChatEvent>, states: UnboundedSender<ConnectionStateEvent>) {
+    let mut backoff = Backoff::new(options);
+
+    while !events.is_closed() {
+        let session = ConnectionSession {
+            connection: connection.name().to_string(),
+            events: events.clone(),
+            states: states.clone(),
+            is_connected: AtomicBool::new(false),
+        };
+        session.report(ConnectionState::Connecting);
+
+        let reason = match connection.run(&session).await {
+            Ok(()) => String::from("Connection ended"),
+            Err(reason) => reason,
+        };
+        if session.is_connected.load(Ordering::SeqCst) {
+            backoff.reset();
+        }
+        session.report(ConnectionState::Disconnected { reason });
+
+        if events.is_closed() {
+            break;
+        }
+
+        let delay = backoff.next_delay();
+        session.report(ConnectionState::Reconnecting { attempt: backoff.attempt(), delay });
+        time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::mpsc::unbounded;
+    use futures::StreamExt;
+    use tokio::sync::mpsc::channel;
+    use crate::stream_interface::events::ChatMessage;
+
+    #[test]
+    fn backoff_doubles_the_delay_up_to_the_max() {
+        let mut backoff = Backoff::new(BackoffOptions { initial_delay: Duration::from_secs(1), max_delay: Duration::from_secs(5), multiplier: 2, jitter: 0.0 });
+
+        let delays: Vec<Duration> = (0..5).map(|_| backoff.next_delay()).collect();
+
+        assert_eq!(delays, vec![1, 2, 4, 5, 5].into_iter().map(Duration::from_secs).collect::<Vec<Duration>>());
+        assert_eq!(backoff.attempt(), 5);
+    }
+
+    #[test]
+    fn backoff_reset_starts_again_from_the_initial_delay() {
+        let mut backoff = Backoff::new(BackoffOptions { jitter: 0.0, ..Default::default() });
+        backoff.next_delay();
+        backoff.next_delay();
+
+        backoff.reset();
+
+        assert_eq!(backoff.attempt(), 0);
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn backoff_jitter_adds_up_to_its_fraction_of_the_delay() {
+        let mut backoff = Backoff::new(BackoffOptions { initial_delay: Duration::from_secs(10), jitter: 0.5, ..Default::default() });
+
+        assert_eq!(backoff.next_delay_with_random(0.0), Duration::from_secs(10));
+        assert_eq!(backoff.next_delay_with_random(1.0), Duration::from_secs(30));
+        let delay = backoff.next_delay();
+        assert!(delay >= Duration::from_secs(40) && delay <= Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn supervise_retries_failed_connections_and_resets_the_backoff_once_connected() {
+        let (events_sender, mut events) = channel::<ChatEvent>(10);
+        let (states_sender, states) = unbounded::<ConnectionStateEvent>();
+        let connection = FakeConnection { results: vec![Err("refused"), Err("refused"), Ok("hello")] };
+        let options = BackoffOptions { initial_delay: Duration::from_millis(1), max_delay: Duration::from_millis(10), multiplier: 2, jitter: 0.0 };
+        tokio::spawn(supervise(connection, options, events_sender, states_sender));
+
+        assert!(matches!(events.recv().await, Some(ChatEvent::Message(ChatMessage { content, .. })) if content == "hello"));
+        let states: Vec<ConnectionState> = states.map(|event| event.state).take(11).collect().await;
+
+        assert_eq!(states, vec![
+            ConnectionState::Connecting,
+            ConnectionState::Disconnected { reason: String::from("refused") },
+            ConnectionState::Reconnecting { attempt: 1, delay: Duration::from_millis(1) },
+            ConnectionState::Connecting,
+            ConnectionState::Disconnected { reason: String::from("refused") },
+            ConnectionState::Reconnecting { attempt: 2, delay: Duration::from_millis(2) },
+            ConnectionState::Connecting,
+            ConnectionState::Connected,
+            ConnectionState::Disconnected { reason: String::from("lost") },
+            ConnectionState::Reconnecting { attempt: 1, delay: Duration::from_millis(1) },
+            ConnectionState::Connecting,
+        ]);
+    }
+
+    // Fails or connects following the results, then stays silent once they run out
+    struct FakeConnection { results: Vec<Result<&'static str, &'static str>> }
+    impl SupervisedConnection for FakeConnection {
+        fn name(&self) -> &str { "fake" }
+        fn run<'a>(&'a mut self, session: &'a ConnectionSession) -> BoxFuture<'a, Result<(), String>> {
+            let result = if self.results.is_empty() { None } else { Some(self.results.remove(0)) };
+            Box::pin(async move {
+                match result {
+                    Some(result) => {
+                        let content = result.map_err(String::from)?;
+                        session.connected();
+                        session.send(ChatEvent::Message(ChatMessage {
+                            id: String::from("a message id"),
+                            name: String::from("someone"),
+                            content: content.to_string(),
+                            is_mod: false,
+                            is_broadcaster: false,
+                            is_vip: false,
+                            is_subscriber: false,
+                        })).await?;
+                        Err(String::from("lost"))
+                    },
+                    None => {
+                        session.events.closed().await;
+                        Err(String::from("Event receiver dropped"))
+                    },
+                }
+            })
+        }
+    }
+}
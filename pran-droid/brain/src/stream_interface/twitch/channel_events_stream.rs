@@ -1,199 +1,449 @@
-use std::borrow::Borrow;
-use std::ops::Range;
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::time::Duration;
-use futures::{SinkExt, Stream};
-use futures::stream::SplitSink;
-use rand::{Rng, thread_rng};
-use serde::{Serialize};
-use tokio::sync::mpsc::channel;
-use tokio::sync::Mutex;
-use tokio::time;
+use futures::StreamExt;
+use futures::future::BoxFuture;
+use serde::Serialize;
 use tokio::net::TcpStream;
-use tokio_stream::StreamExt;
-use tokio_stream::wrappers::ReceiverStream;
-use tokio_tungstenite::{connect_async, WebSocketStream, MaybeTlsStream};
+use tokio::sync::{OnceCell, watch};
+use tokio::time;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
 use tokio_tungstenite::tungstenite::Message;
-use twitch_api2::pubsub::{Response, TopicData, listen_command, TwitchResponse, Topic};
-use twitch_api2::pubsub::channel_bits::{ChannelBitsEventsV2, ChannelBitsEventsV2Reply, BitsEventData};
-use twitch_api2::pubsub::channel_points::{ChannelPointsChannelV1, ChannelPointsChannelV1Reply, Redemption, Reward};
-use twitch_api2::types::User;
-use url::Url;
-
-use crate::stream_interface::events::{ChatAction, ChatEvent};
-use crate::stream_interface::twitch::twitch_interface::{TwitchConnectOptions};
+
+use crate::stream_interface::supervisor::{ConnectionSession, SupervisedConnection};
+use crate::stream_interface::twitch::eventsub_messages::{CHANNEL_CHEER, CHANNEL_FOLLOW, CHANNEL_POINTS_REDEMPTION, EventSubEventMapper, EventSubMessage, HYPE_TRAIN_BEGIN, HYPE_TRAIN_END, HYPE_TRAIN_PROGRESS};
+use crate::stream_interface::twitch::token_refresh::token_changed;
+use crate::stream_interface::twitch::twitch_interface::TwitchConnectOptions;
 use crate::stream_interface::twitch::user_id_from_login_name::user_id_from_login_name;
 
-#[derive(Serialize)]
-struct PingCommand {
-    #[serde(rename = "type")]
-    _type: String
+const EVENTSUB_URL: &str = "wss://eventsub.wss.twitch.tv/ws";
+const EVENTSUB_SUBSCRIPTIONS_URL: &str = "https://api.twitch.tv/helix/eventsub/subscriptions";
+const WELCOME_TIMEOUT: Duration = Duration::from_secs(10);
+const KEEPALIVE_GRACE: Duration = Duration::from_secs(2);
+const HANDLED_MESSAGE_IDS_CAPACITY: usize = 100;
+
+pub trait EventSubSubscriber: Send + Sync {
+    fn subscribe<'a>(&'a self, session_id: &'a str) -> BoxFuture<'a, Result<(), String>>;
 }
 
-impl PingCommand {
-    pub fn new() -> PingCommand {
-        PingCommand {
-            _type: "PING".to_string()
-        }
-    }
+struct EventSubSession {
+    id: String,
+    keepalive_timeout: Duration,
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
 }
 
 /**
- * Stream of ChatEvent for channel points rewards and bits
+ * Connection producing ChatEvent for channel points rewards, cheers, follows and hype trains
  */
-pub async fn create_channel_events_stream(options: TwitchConnectOptions) -> impl Stream<Item = ChatEvent> {
-    let user_id = user_id_from_login_name(options.clone()).await;
+pub fn create_channel_events_connection(options: TwitchConnectOptions) -> EventSubConnection {
+    let token = options.token.clone();
+
+    EventSubConnection::new(EVENTSUB_URL.to_string(), Arc::new(HelixEventSubSubscriber {
+        client: reqwest::Client::new(),
+        options,
+        user_ids: OnceCell::new(),
+    }), token)
+}
 
-    let url = Url::parse("wss://pubsub-edge.twitch.tv").unwrap();
+pub struct EventSubConnection {
+    url: String,
+    subscriber: Arc<dyn EventSubSubscriber>,
+    token: watch::Receiver<String>,
+    mapper: EventSubEventMapper,
+}
 
-    let (ws_stream, _) = connect_async(url).await.expect("Can't connect to websocket");
+impl EventSubConnection {
+    pub fn new(url: String, subscriber: Arc<dyn EventSubSubscriber>, token: watch::Receiver<String>) -> Self {
+        Self { url, subscriber, token, mapper: EventSubEventMapper::default() }
+    }
+}
 
-    let (mut sender, mut receiver) = futures::StreamExt::split(ws_stream);
+// Subscriptions belong to a session, every new session subscribes again with the latest token.
+impl SupervisedConnection for EventSubConnection {
+    fn name(&self) -> &str {
+        "eventsub"
+    }
 
-    subscribe_to_channel_points_topic(options.token.clone(), user_id, &mut sender).await;
-    subscribe_to_bits_topic(options.token.clone(), user_id, &mut sender).await;
+    fn run<'a>(&'a mut self, session: &'a ConnectionSession) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            self.token.borrow_and_update();
+            let event_sub_session = connect_session(&self.url).await?;
+            info!("EventSub session {} connected", event_sub_session.id);
+            self.subscriber.subscribe(&event_sub_session.id).await
+                .map_err(|error| format!("EventSub subscriptions failed: {}", error))?;
+            session.connected();
 
-    let ponged_original = Arc::new(Mutex::new(false));
-    let ponged_check = Arc::clone(&ponged_original);
-    let ponged_update = Arc::clone(&ponged_original);
-    let ping_command = serde_json::to_string(&PingCommand::new()).unwrap();
+            listen_session(event_sub_session, &mut self.mapper, &mut self.token, session).await
+        })
+    }
+}
 
-    debug!("{}", ping_command.clone());
+async fn connect_session(url: &str) -> Result<EventSubSession, String> {
+    let (mut stream, _) = connect_async(url).await.map_err(|error| error.to_string())?;
 
-    tokio::spawn(async move {
-        loop {
-            let jitter = thread_rng().gen_range::<u64, Range<u64>>(Range::<u64> {
-                start: 1,
-                end: 10
-            });
-            time::sleep(Duration::from_secs(30 + jitter)).await;
-            debug!("PubSub Sending Ping");
-            sender.send(Message::Text(ping_command.clone())).await.expect("Not able to send Ping to websocket PubSub");
-
-            time::sleep(Duration::from_secs(10)).await;
-            let mut ponged_data = ponged_check.lock().await;
-            if *ponged_data == true {
-                debug!("PubSub Pong checked, received within 10 seconds");
-                *ponged_data = false;
-            } else {
-                error!("PubSub disconnected because Pong not sent within 10 seconds of Ping, reconnection not implemented");
-                break;
-            }
+    loop {
+        match time::timeout(WELCOME_TIMEOUT, stream.next()).await {
+            Ok(Some(Ok(Message::Text(text)))) => return match EventSubMessage::parse(&text) {
+                Ok(EventSubMessage::Welcome { session_id, keepalive_timeout }) => Ok(EventSubSession { id: session_id, keepalive_timeout, stream }),
+                message => Err(format!("Expected a welcome message, received {:?}", message)),
+            },
+            Ok(Some(Ok(_))) => continue,
+            Ok(Some(Err(error))) => return Err(error.to_string()),
+            Ok(None) => return Err(String::from("Connection closed before the welcome message")),
+            Err(_) => return Err(String::from("Welcome message not received in time")),
         }
-    });
-    let (tx, rx) = channel::<ChatEvent>(100);
+    }
+}
 
-    tokio::spawn(async move {
-        loop {
-            let msg = receiver.next().await.unwrap();
-            match msg {
-                Ok(Message::Text(text)) => {
-                    let result = Response::parse(&text).unwrap();
-                    match result {
-                        Response::Message {
-                            data: TopicData::ChannelPointsChannelV1 {
-                                reply, ..
-                            }
-                        } => {
-                            if let ChannelPointsChannelV1Reply::RewardRedeemed {
-                                redemption: Redemption {
-                                    reward: Reward {
-                                        title, ..
-                                    },
-                                    user: User {
-                                        display_name: user_name, ..
-                                    }, ..
-                                }, ..
-                            } = reply.borrow() {
-                                info!("Redeemed {:?}!", title);
-                                tx.send(ChatEvent::Action(ChatAction {
-                                    name: user_name.to_owned().into_string(),
-                                    action_name: "reward_redeem".to_string(),
-                                    action_id: title.to_string(),
-                                    is_mod: false
-                                })).await.unwrap();
-                            }
-                        },
-                        Response::Message {
-                            data: TopicData::ChannelBitsEventsV2 {
-                                reply, ..
-                            }
-                        } => {
-                            if let ChannelBitsEventsV2Reply::BitsEvent {
-                                data: BitsEventData {
-                                    bits_used,
-                                    user_name, ..
-                                }, ..
-                            } = reply.borrow() {
-                                info!("Received {:?} bits!", bits_used);
-                                tx.send(ChatEvent::Action(ChatAction {
-                                    name: user_name.to_owned().into_string(),
-                                    action_name: "bits".to_string(),
-                                    action_id: bits_used.to_string(),
-                                    is_mod: false
-                                })).await.unwrap();
-                            }
-                        },
-                        Response::Response(tr) => {
-                            if let TwitchResponse { error: Some(ref error), .. } = tr {
-                                if !error.eq("") {
-                                    panic!("Connection to PubSub failed {}", error);
-                                }
-                            }
-                            debug!("PubSub message response parsed, unhandled twitch response {:?}", tr);
-                        },
-                        Response::Reconnect => {
-                            debug!("PubSub message response parsed, unhandled reconnect");
-                        },
-                        Response::Message { .. } => {
-                            debug!("PubSub message response parsed, it's a message but not the one we want");
-                        },
-                        Response::Pong => {
-                            debug!("PubSub Pong received");
-                            let mut ponged_data = ponged_update.lock().await;
-                            *ponged_data = true;
-                        }
-                    }
+async fn listen_session(mut event_sub_session: EventSubSession, mapper: &mut EventSubEventMapper, token: &mut watch::Receiver<String>, session: &ConnectionSession) -> Result<(), String> {
+    let mut handled_message_ids = VecDeque::with_capacity(HANDLED_MESSAGE_IDS_CAPACITY);
+
+    loop {
+        let message = tokio::select! {
+            message = time::timeout(event_sub_session.keepalive_timeout + KEEPALIVE_GRACE, event_sub_session.stream.next()) => message,
+            _ = token_changed(token) => {
+                event_sub_session.stream.close(None).await.ok();
+                return Err(String::from("Access token refreshed"));
+            },
+        };
+        let text = match message {
+            Ok(Some(Ok(Message::Text(text)))) => text,
+            Ok(Some(Ok(Message::Close(frame)))) => return Err(format!("EventSub session {} closed {:?}", event_sub_session.id, frame)),
+            Ok(Some(Ok(_))) => continue,
+            Ok(Some(Err(error))) => return Err(format!("EventSub session {} failed: {}", event_sub_session.id, error)),
+            Ok(None) => return Err(format!("EventSub session {} ended", event_sub_session.id)),
+            Err(_) => return Err(format!("EventSub session {} did not receive a keepalive in time", event_sub_session.id)),
+        };
+
+        match EventSubMessage::parse(&text) {
+            Ok(EventSubMessage::Notification { message_id, event }) => {
+                // Twitch can deliver the same notification more than once
+                if handled_message_ids.contains(&message_id) {
+                    continue;
                 }
-                _ => {
-                    debug!("PubSub message response is not text {:?}", msg);
+                if handled_message_ids.len() == HANDLED_MESSAGE_IDS_CAPACITY {
+                    handled_message_ids.pop_front();
                 }
-            }
+                handled_message_ids.push_back(message_id);
+
+                if let Some(chat_event) = mapper.into_chat_event(event) {
+                    info!("EventSub event received {}", chat_event);
+                    if let Err(error) = session.send(chat_event).await {
+                        event_sub_session.stream.close(None).await.ok();
+                        return Err(error);
+                    }
+                }
+            },
+            Ok(EventSubMessage::Keepalive) => debug!("EventSub keepalive received"),
+            // Subscriptions move with the session, the old connection is dropped once the new one is welcomed
+            Ok(EventSubMessage::Reconnect { reconnect_url }) => {
+                let new_session = connect_session(&reconnect_url).await
+                    .map_err(|error| format!("EventSub reconnection failed: {}", error))?;
+                info!("EventSub session {} reconnected", new_session.id);
+                event_sub_session = new_session;
+            },
+            Ok(EventSubMessage::Revocation { subscription_type, status }) => warn!("EventSub subscription {} revoked: {}", subscription_type, status),
+            Ok(message) => debug!("EventSub message not handled {:?}", message),
+            Err(error) => warn!("EventSub message could not be parsed: {}", error),
         }
-    });
+    }
+}
 
-    ReceiverStream::new(rx)
+struct HelixEventSubSubscriber {
+    client: reqwest::Client,
+    options: TwitchConnectOptions,
+    user_ids: OnceCell<UserIds>,
 }
 
-async fn subscribe_to_channel_points_topic(token: String, user_id: u32, sender: &mut SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>) {
-    let channel_points_topic = ChannelPointsChannelV1 {
-        channel_id: user_id
-    }.into_topic();
+struct UserIds {
+    broadcaster_id: String,
+    moderator_id: String,
+}
 
-    let channel_points_topic_subscription_command = listen_command(
-        &[channel_points_topic],
-        token.as_ref(),
-        None
-    ).expect("Serializing failed");
+#[derive(Serialize)]
+struct CreateSubscriptionRequest<'a> {
+    #[serde(rename = "type")]
+    subscription_type: &'a str,
+    version: &'a str,
+    condition: SubscriptionConditionRequest<'a>,
+    transport: SubscriptionTransportRequest<'a>,
+}
+
+#[derive(Serialize)]
+struct SubscriptionConditionRequest<'a> {
+    broadcaster_user_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    moderator_user_id: Option<&'a str>,
+}
 
-    debug!("{}", channel_points_topic_subscription_command.clone());
+#[derive(Serialize)]
+struct SubscriptionTransportRequest<'a> {
+    method: &'a str,
+    session_id: &'a str,
+}
 
-    sender.send(Message::Text(channel_points_topic_subscription_command.clone())).await.expect("Message sent to pubsub twitch failed");
+impl HelixEventSubSubscriber {
+    // The channel and the bot do not change while running, their ids are only fetched on the first session
+    async fn user_ids(&self) -> Result<&UserIds, String> {
+        self.user_ids.get_or_try_init(|| async {
+            let broadcaster_id = user_id_from_login_name(self.options.clone()).await?.to_string();
+            let moderator_id = user_id_from_login_name(TwitchConnectOptions { channel: self.options.user.clone(), ..self.options.clone() }).await?.to_string();
+            Ok(UserIds { broadcaster_id, moderator_id })
+        }).await
+    }
+
+    async fn create_subscription(&self, session_id: &str, subscription_type: &str, version: &str, condition: SubscriptionConditionRequest<'_>) -> Result<(), String> {
+        let response = self.client.post(EVENTSUB_SUBSCRIPTIONS_URL)
+            .header("Authorization", format!("Bearer {}", self.options.access_token()))
+            .header("Client-Id", self.options.client_id.as_str())
+            .json(&CreateSubscriptionRequest {
+                subscription_type,
+                version,
+                condition,
+                transport: SubscriptionTransportRequest { method: "websocket", session_id },
+            })
+            .send()
+            .await
+            .map_err(|error| error.to_string())?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(format!("Subscription to {} failed with status {}: {}", subscription_type, status, response.text().await.unwrap_or_default()));
+        }
+        debug!("EventSub subscribed to {}", subscription_type);
+        Ok(())
+    }
+}
+
+impl EventSubSubscriber for HelixEventSubSubscriber {
+    fn subscribe<'a>(&'a self, session_id: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let UserIds { broadcaster_id, moderator_id } = self.user_ids().await?;
+            // Follows and hype trains need a moderator or extra scopes, they are skipped when not allowed
+            let subscriptions = [
+                (CHANNEL_POINTS_REDEMPTION, "1", None, true),
+                (CHANNEL_CHEER, "1", None, true),
+                (CHANNEL_FOLLOW, "2", Some(moderator_id.as_str()), false),
+                (HYPE_TRAIN_BEGIN, "1", None, false),
+                (HYPE_TRAIN_PROGRESS, "1", None, false),
+                (HYPE_TRAIN_END, "1", None, false),
+            ];
+
+            for (subscription_type, version, moderator_user_id, required) in subscriptions {
+                let condition = SubscriptionConditionRequest { broadcaster_user_id: broadcaster_id, moderator_user_id };
+                match self.create_subscription(session_id, subscription_type, version, condition).await {
+                    Ok(()) => {},
+                    Err(error) if required => return Err(error),
+                    Err(error) => warn!("EventSub optional subscription skipped: {}", error),
+                }
+            }
+
+            Ok(())
+        })
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+    use std::sync::Mutex;
+    use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+    use futures::SinkExt;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc::{channel, Receiver};
+    use tokio::task::JoinHandle;
+    use tokio_tungstenite::accept_async;
+    use crate::stream_interface::events::{ChatAction, ChatEvent, ChatNotice, ChatNoticeEvent};
+    use crate::stream_interface::supervisor::{BackoffOptions, ConnectionState, ConnectionStateEvent, supervise};
 
-async fn subscribe_to_bits_topic(token: String, user_id: u32, sender: &mut SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>) {
-    let bits_topic = ChannelBitsEventsV2 {
-        channel_id: user_id
-    }.into_topic();
+    const WELCOME: &str = include_str!("../../../fixtures/eventsub/session_welcome.json");
+    const RECONNECT: &str = include_str!("../../../fixtures/eventsub/session_reconnect.json");
+    const KEEPALIVE: &str = include_str!("../../../fixtures/eventsub/session_keepalive.json");
+    const REDEMPTION: &str = include_str!("../../../fixtures/eventsub/channel_points_redemption.json");
+    const CHEER: &str = include_str!("../../../fixtures/eventsub/channel_cheer.json");
+    const FOLLOW: &str = include_str!("../../../fixtures/eventsub/channel_follow.json");
+    const HYPE_TRAIN_BEGIN_NOTIFICATION: &str = include_str!("../../../fixtures/eventsub/hype_train_begin.json");
+    const WELCOME_SESSION_ID: &str = "AQoQILE98gtqShGmLD7AM6yJThAB";
 
-    let bits_topic_subscription_command = listen_command(
-        &[bits_topic],
-        token.as_ref(),
-        None
-    ).expect("Serializing failed");
+    #[tokio::test]
+    async fn event_sub_replays_notifications_as_chat_events() {
+        let (listener, url) = bind_mock_server().await;
+        serve_mock_server(listener, vec![vec![WELCOME.to_string(), KEEPALIVE.to_string(), REDEMPTION.to_string(), CHEER.to_string(), FOLLOW.to_string(), HYPE_TRAIN_BEGIN_NOTIFICATION.to_string()]]);
+        let subscriber = Arc::new(FakeSubscriber::new());
+        let (mut events, _states, _token) = start_event_sub(url, subscriber.clone());
 
-    debug!("{}", bits_topic_subscription_command.clone());
+        let events = receive_events(&mut events, 4).await;
 
-    sender.send(Message::Text(bits_topic_subscription_command.clone())).await.expect("Message sent to pubsub twitch failed");
+        assert!(matches!(&events[0], ChatEvent::Action(ChatAction { action_name, action_id, .. }) if action_name == "reward_redeem" && action_id == "Hydrate"));
+        assert!(matches!(&events[1], ChatEvent::Action(ChatAction { action_name, action_id, .. }) if action_name == "bits" && action_id == "250"));
+        assert!(matches!(&events[2], ChatEvent::Notice(ChatNotice { name, event: ChatNoticeEvent::Follow, .. }) if name == "Following_User"));
+        assert!(matches!(&events[3], ChatEvent::Notice(ChatNotice { event: ChatNoticeEvent::HypeTrainStart { level: 1 }, .. })));
+        assert_eq!(subscriber.session_ids(), vec![WELCOME_SESSION_ID]);
+    }
+
+    #[tokio::test]
+    async fn event_sub_ignores_already_handled_notifications() {
+        let (listener, url) = bind_mock_server().await;
+        serve_mock_server(listener, vec![vec![WELCOME.to_string(), FOLLOW.to_string(), FOLLOW.to_string(), CHEER.to_string()]]);
+        let (mut events, _states, _token) = start_event_sub(url, Arc::new(FakeSubscriber::new()));
+
+        let events = receive_events(&mut events, 2).await;
+
+        assert!(matches!(&events[0], ChatEvent::Notice(ChatNotice { event: ChatNoticeEvent::Follow, .. })));
+        assert!(matches!(&events[1], ChatEvent::Action(ChatAction { action_name, .. }) if action_name == "bits"));
+    }
+
+    #[tokio::test]
+    async fn event_sub_session_reconnect_moves_to_new_url_without_subscribing_again() {
+        let (listener, url) = bind_mock_server().await;
+        let reconnect = RECONNECT.replace("wss://eventsub.wss.twitch.tv?id=AQoQILE98gtqShGmLD7AM6yJThAB", &format!("{}/reconnect", url));
+        serve_mock_server(listener, vec![vec![WELCOME.to_string(), reconnect], vec![WELCOME.to_string(), CHEER.to_string()]]);
+        let subscriber = Arc::new(FakeSubscriber::new());
+        let (mut events, _states, _token) = start_event_sub(url, subscriber.clone());
+
+        let events = receive_events(&mut events, 1).await;
+
+        assert!(matches!(&events[0], ChatEvent::Action(ChatAction { action_name, .. }) if action_name == "bits"));
+        assert_eq!(subscriber.session_ids(), vec![WELCOME_SESSION_ID]);
+    }
+
+    #[tokio::test]
+    async fn event_sub_missing_keepalive_starts_a_new_session_with_new_subscriptions() {
+        let (listener, url) = bind_mock_server().await;
+        let short_keepalive_welcome = WELCOME.replace("\"keepalive_timeout_seconds\": 10", "\"keepalive_timeout_seconds\": 1");
+        let second_welcome = WELCOME.replace(WELCOME_SESSION_ID, "second_session");
+        serve_mock_server(listener, vec![vec![short_keepalive_welcome], vec![second_welcome, FOLLOW.to_string()]]);
+        let subscriber = Arc::new(FakeSubscriber::new());
+        let (mut events, _states, _token) = start_event_sub(url, subscriber.clone());
+
+        let events = receive_events(&mut events, 1).await;
+
+        assert!(matches!(&events[0], ChatEvent::Notice(ChatNotice { event: ChatNoticeEvent::Follow, .. })));
+        assert_eq!(subscriber.session_ids(), vec![WELCOME_SESSION_ID, "second_session"]);
+    }
+
+    #[tokio::test]
+    async fn event_sub_recovers_when_the_server_is_killed_mid_stream() {
+        let (listener, url) = bind_mock_server().await;
+        let address = listener.local_addr().unwrap();
+        let server = serve_mock_server(listener, vec![vec![WELCOME.to_string(), FOLLOW.to_string()]]);
+        let subscriber = Arc::new(FakeSubscriber::new());
+        let (mut events, mut states, _token) = start_event_sub(url, subscriber.clone());
+        let before_kill = receive_events(&mut events, 1).await;
+
+        server.kill();
+        // Connections are refused while the server is down
+        wait_for_state(&mut states, |state| matches!(state, ConnectionState::Reconnecting { attempt: 2, .. })).await;
+        let second_welcome = WELCOME.replace(WELCOME_SESSION_ID, "second_session");
+        serve_mock_server(rebind_mock_server(address).await, vec![vec![second_welcome, CHEER.to_string()]]);
+        let after_restart = receive_events(&mut events, 1).await;
+
+        assert!(matches!(&before_kill[0], ChatEvent::Notice(ChatNotice { event: ChatNoticeEvent::Follow, .. })));
+        assert!(matches!(&after_restart[0], ChatEvent::Action(ChatAction { action_name, .. }) if action_name == "bits"));
+        assert_eq!(subscriber.session_ids(), vec![WELCOME_SESSION_ID, "second_session"]);
+    }
+
+    #[tokio::test]
+    async fn event_sub_token_refresh_starts_a_new_session_with_new_subscriptions() {
+        let (listener, url) = bind_mock_server().await;
+        let second_welcome = WELCOME.replace(WELCOME_SESSION_ID, "second_session");
+        serve_mock_server(listener, vec![vec![WELCOME.to_string(), FOLLOW.to_string()], vec![second_welcome, CHEER.to_string()]]);
+        let subscriber = Arc::new(FakeSubscriber::new());
+        let (mut events, _states, token) = start_event_sub(url, subscriber.clone());
+        let before_refresh = receive_events(&mut events, 1).await;
+
+        token.send(String::from("refreshed_access_token")).unwrap();
+        let after_refresh = receive_events(&mut events, 1).await;
+
+        assert!(matches!(&before_refresh[0], ChatEvent::Notice(ChatNotice { event: ChatNoticeEvent::Follow, .. })));
+        assert!(matches!(&after_refresh[0], ChatEvent::Action(ChatAction { action_name, .. }) if action_name == "bits"));
+        assert_eq!(subscriber.session_ids(), vec![WELCOME_SESSION_ID, "second_session"]);
+    }
+
+    fn start_event_sub(url: String, subscriber: Arc<FakeSubscriber>) -> (Receiver<ChatEvent>, UnboundedReceiver<ConnectionStateEvent>, watch::Sender<String>) {
+        let (events_sender, events) = channel::<ChatEvent>(100);
+        let (states_sender, states) = unbounded::<ConnectionStateEvent>();
+        let (token_sender, token) = watch::channel(String::from("access_token"));
+        let backoff = BackoffOptions { initial_delay: Duration::from_millis(10), max_delay: Duration::from_millis(50), multiplier: 2, jitter: 0.0 };
+        tokio::spawn(supervise(EventSubConnection::new(url, subscriber, token), backoff, events_sender, states_sender));
+
+        (events, states, token_sender)
+    }
+
+    async fn receive_events(events: &mut Receiver<ChatEvent>, count: usize) -> Vec<ChatEvent> {
+        time::timeout(Duration::from_secs(10), async {
+            let mut received = vec![];
+            while received.len() < count {
+                received.push(events.recv().await.expect("Events stream ended"));
+            }
+            received
+        }).await.expect("Events not received in time")
+    }
+
+    async fn wait_for_state(states: &mut UnboundedReceiver<ConnectionStateEvent>, predicate: impl Fn(&ConnectionState) -> bool) {
+        time::timeout(Duration::from_secs(10), async {
+            while let Some(event) = states.next().await {
+                if predicate(&event.state) {
+                    return;
+                }
+            }
+        }).await.expect("Connection state not reached in time")
+    }
+
+    async fn bind_mock_server() -> (TcpListener, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        (listener, url)
+    }
+
+    // The killed server releases its address asynchronously
+    async fn rebind_mock_server(address: SocketAddr) -> TcpListener {
+        loop {
+            match TcpListener::bind(address).await {
+                Ok(listener) => return listener,
+                Err(_) => time::sleep(Duration::from_millis(5)).await,
+            }
+        }
+    }
+
+    struct MockServer { tasks: Arc<Mutex<Vec<JoinHandle<()>>>> }
+    impl MockServer {
+        fn kill(&self) {
+            self.tasks.lock().unwrap().drain(..).for_each(|task| task.abort());
+        }
+    }
+
+    // Every accepted connection replays its messages then stays silent until the client leaves.
+    fn serve_mock_server(listener: TcpListener, connections: Vec<Vec<String>>) -> MockServer {
+        let tasks = Arc::new(Mutex::new(vec![]));
+        let connection_tasks = tasks.clone();
+        let accept_task = tokio::spawn(async move {
+            for messages in connections {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut websocket = accept_async(stream).await.unwrap();
+                connection_tasks.lock().unwrap().push(tokio::spawn(async move {
+                    for message in messages {
+                        websocket.send(Message::Text(message)).await.unwrap();
+                    }
+                    while let Some(Ok(_)) = websocket.next().await {}
+                }));
+            }
+        });
+        tasks.lock().unwrap().push(accept_task);
+
+        MockServer { tasks }
+    }
+
+    struct FakeSubscriber { session_ids: Mutex<Vec<String>> }
+    impl FakeSubscriber {
+        fn new() -> Self { Self { session_ids: Mutex::new(vec![]) } }
+        fn session_ids(&self) -> Vec<String> { self.session_ids.lock().unwrap().clone() }
+    }
+    impl EventSubSubscriber for FakeSubscriber {
+        fn subscribe<'a>(&'a self, session_id: &'a str) -> BoxFuture<'a, Result<(), String>> {
+            self.session_ids.lock().unwrap().push(session_id.to_string());
+            Box::pin(async { Ok(()) })
+        }
+    }
 }
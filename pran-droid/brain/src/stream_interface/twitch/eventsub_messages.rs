@@ -0,0 +1,325 @@
+use std::time::Duration;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use crate::stream_interface::events::{ChatAction, ChatEvent, ChatNotice, ChatNoticeEvent};
+
+pub const CHANNEL_POINTS_REDEMPTION: &str = "channel.channel_points_custom_reward_redemption.add";
+pub const CHANNEL_CHEER: &str = "channel.cheer";
+pub const CHANNEL_FOLLOW: &str = "channel.follow";
+pub const HYPE_TRAIN_BEGIN: &str = "channel.hype_train.begin";
+pub const HYPE_TRAIN_PROGRESS: &str = "channel.hype_train.progress";
+pub const HYPE_TRAIN_END: &str = "channel.hype_train.end";
+
+#[derive(Debug, PartialEq)]
+pub enum EventSubMessage {
+    Welcome { session_id: String, keepalive_timeout: Duration },
+    Keepalive,
+    Notification { message_id: String, event: EventSubEvent },
+    Reconnect { reconnect_url: String },
+    Revocation { subscription_type: String, status: String },
+    Unhandled { message_type: String },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EventSubEvent {
+    RewardRedemption(RewardRedemptionEvent),
+    Cheer(CheerEvent),
+    Follow(FollowEvent),
+    HypeTrainBegin(HypeTrainEvent),
+    HypeTrainProgress(HypeTrainEvent),
+    HypeTrainEnd(HypeTrainEvent),
+    Unhandled { subscription_type: String },
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct RewardRedemptionEvent {
+    pub user_name: String,
+    #[serde(default)]
+    pub user_input: String,
+    pub reward: RewardEvent,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct RewardEvent {
+    pub title: String,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct CheerEvent {
+    pub is_anonymous: bool,
+    pub user_name: Option<String>,
+    pub message: String,
+    pub bits: u32,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct FollowEvent {
+    pub user_name: String,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct HypeTrainEvent {
+    pub broadcaster_user_name: String,
+    pub level: u32,
+}
+
+#[derive(Deserialize)]
+struct EventSubMessageStorage {
+    metadata: EventSubMetadataStorage,
+    payload: Value,
+}
+
+#[derive(Deserialize)]
+struct EventSubMetadataStorage {
+    message_id: String,
+    message_type: String,
+    subscription_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EventSubSessionPayloadStorage {
+    session: EventSubSessionStorage,
+}
+
+#[derive(Deserialize)]
+struct EventSubSessionStorage {
+    id: String,
+    keepalive_timeout_seconds: Option<u64>,
+    reconnect_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EventSubSubscriptionPayloadStorage {
+    subscription: EventSubSubscriptionStorage,
+}
+
+#[derive(Deserialize)]
+struct EventSubSubscriptionStorage {
+    #[serde(rename = "type")]
+    subscription_type: String,
+    status: String,
+}
+
+#[derive(Deserialize)]
+struct EventSubNotificationPayloadStorage {
+    event: Value,
+}
+
+impl EventSubMessage {
+    pub fn parse(text: &str) -> Result<Self, serde_json::Error> {
+        let EventSubMessageStorage { metadata, payload } = serde_json::from_str(text)?;
+
+        Ok(match metadata.message_type.as_str() {
+            "session_welcome" => {
+                let EventSubSessionPayloadStorage { session } = serde_json::from_value(payload)?;
+                EventSubMessage::Welcome {
+                    session_id: session.id,
+                    keepalive_timeout: Duration::from_secs(session.keepalive_timeout_seconds.unwrap_or(10)),
+                }
+            },
+            "session_keepalive" => EventSubMessage::Keepalive,
+            "session_reconnect" => {
+                let EventSubSessionPayloadStorage { session } = serde_json::from_value(payload)?;
+                EventSubMessage::Reconnect { reconnect_url: session.reconnect_url.unwrap_or_default() }
+            },
+            "revocation" => {
+                let EventSubSubscriptionPayloadStorage { subscription } = serde_json::from_value(payload)?;
+                EventSubMessage::Revocation { subscription_type: subscription.subscription_type, status: subscription.status }
+            },
+            "notification" => {
+                let EventSubNotificationPayloadStorage { event } = serde_json::from_value(payload)?;
+                EventSubMessage::Notification {
+                    message_id: metadata.message_id,
+                    event: EventSubEvent::parse(metadata.subscription_type.unwrap_or_default(), event)?,
+                }
+            },
+            _ => EventSubMessage::Unhandled { message_type: metadata.message_type },
+        })
+    }
+}
+
+impl EventSubEvent {
+    fn parse(subscription_type: String, event: Value) -> Result<Self, serde_json::Error> {
+        fn parse_event<T: DeserializeOwned>(event: Value, into_event: fn(T) -> EventSubEvent) -> Result<EventSubEvent, serde_json::Error> {
+            serde_json::from_value(event).map(into_event)
+        }
+
+        match subscription_type.as_str() {
+            CHANNEL_POINTS_REDEMPTION => parse_event(event, EventSubEvent::RewardRedemption),
+            CHANNEL_CHEER => parse_event(event, EventSubEvent::Cheer),
+            CHANNEL_FOLLOW => parse_event(event, EventSubEvent::Follow),
+            HYPE_TRAIN_BEGIN => parse_event(event, EventSubEvent::HypeTrainBegin),
+            HYPE_TRAIN_PROGRESS => parse_event(event, EventSubEvent::HypeTrainProgress),
+            HYPE_TRAIN_END => parse_event(event, EventSubEvent::HypeTrainEnd),
+            _ => Ok(EventSubEvent::Unhandled { subscription_type }),
+        }
+    }
+}
+
+// Hype train progress is sent for every contribution, the mapper keeps the level to only report level ups.
+#[derive(Default)]
+pub struct EventSubEventMapper {
+    hype_train_level: Option<u32>,
+}
+
+impl EventSubEventMapper {
+    pub fn into_chat_event(&mut self, event: EventSubEvent) -> Option<ChatEvent> {
+        match event {
+            EventSubEvent::RewardRedemption(redemption) => Some(ChatEvent::Action(ChatAction {
+                name: redemption.user_name,
+                action_name: "reward_redeem".to_string(),
+                action_id: redemption.reward.title,
+                input: Some(redemption.user_input).filter(|input| !input.is_empty()),
+                is_mod: false,
+                is_broadcaster: false,
+                is_vip: false,
+                is_subscriber: false,
+            })),
+            EventSubEvent::Cheer(cheer) => Some(ChatEvent::Action(ChatAction {
+                name: cheer.user_name.filter(|_| !cheer.is_anonymous).unwrap_or_else(|| "Anonymous".to_string()),
+                action_name: "bits".to_string(),
+                action_id: cheer.bits.to_string(),
+                input: Some(cheer.message).filter(|message| !message.is_empty()),
+                is_mod: false,
+                is_broadcaster: false,
+                is_vip: false,
+                is_subscriber: false,
+            })),
+            EventSubEvent::Follow(follow) => Some(into_notice(follow.user_name, ChatNoticeEvent::Follow)),
+            EventSubEvent::HypeTrainBegin(hype_train) => {
+                self.hype_train_level = Some(hype_train.level);
+                Some(into_notice(hype_train.broadcaster_user_name, ChatNoticeEvent::HypeTrainStart { level: hype_train.level }))
+            },
+            EventSubEvent::HypeTrainProgress(hype_train) => {
+                let previous_level = self.hype_train_level.replace(hype_train.level).unwrap_or(1);
+                if hype_train.level > previous_level {
+                    Some(into_notice(hype_train.broadcaster_user_name, ChatNoticeEvent::HypeTrainLevelUp { level: hype_train.level }))
+                } else {
+                    None
+                }
+            },
+            EventSubEvent::HypeTrainEnd(hype_train) => {
+                self.hype_train_level = None;
+                Some(into_notice(hype_train.broadcaster_user_name, ChatNoticeEvent::HypeTrainEnd { level: hype_train.level }))
+            },
+            EventSubEvent::Unhandled { subscription_type } => {
+                debug!("EventSub notification not handled {}", subscription_type);
+                None
+            }
+        }
+    }
+}
+
+fn into_notice(name: String, event: ChatNoticeEvent) -> ChatEvent {
+    ChatEvent::Notice(ChatNotice {
+        name,
+        is_mod: false,
+        is_broadcaster: false,
+        is_vip: false,
+        is_subscriber: false,
+        event,
+        message: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_session_messages() {
+        assert_eq!(
+            EventSubMessage::parse(include_str!("../../../fixtures/eventsub/session_welcome.json")).unwrap(),
+            EventSubMessage::Welcome { session_id: String::from("AQoQILE98gtqShGmLD7AM6yJThAB"), keepalive_timeout: Duration::from_secs(10) }
+        );
+        assert_eq!(EventSubMessage::parse(include_str!("../../../fixtures/eventsub/session_keepalive.json")).unwrap(), EventSubMessage::Keepalive);
+        assert_eq!(
+            EventSubMessage::parse(include_str!("../../../fixtures/eventsub/session_reconnect.json")).unwrap(),
+            EventSubMessage::Reconnect { reconnect_url: String::from("wss://eventsub.wss.twitch.tv?id=AQoQILE98gtqShGmLD7AM6yJThAB") }
+        );
+        assert_eq!(
+            EventSubMessage::parse(include_str!("../../../fixtures/eventsub/revocation.json")).unwrap(),
+            EventSubMessage::Revocation { subscription_type: String::from("channel.follow"), status: String::from("authorization_revoked") }
+        );
+    }
+
+    #[test]
+    fn parse_notification_messages() {
+        assert_eq!(
+            EventSubMessage::parse(include_str!("../../../fixtures/eventsub/channel_points_redemption.json")).unwrap(),
+            EventSubMessage::Notification {
+                message_id: String::from("befa7b53-d79d-478f-86b9-120f112b044e"),
+                event: EventSubEvent::RewardRedemption(RewardRedemptionEvent {
+                    user_name: String::from("Cooler_User"),
+                    user_input: String::from("pogchamp"),
+                    reward: RewardEvent { title: String::from("Hydrate") },
+                }),
+            }
+        );
+        assert!(matches!(
+            EventSubMessage::parse(include_str!("../../../fixtures/eventsub/channel_cheer_anonymous.json")).unwrap(),
+            EventSubMessage::Notification { event: EventSubEvent::Cheer(CheerEvent { is_anonymous: true, user_name: None, bits: 100, .. }), .. }
+        ));
+        assert!(matches!(
+            EventSubMessage::parse(include_str!("../../../fixtures/eventsub/hype_train_end.json")).unwrap(),
+            EventSubMessage::Notification { event: EventSubEvent::HypeTrainEnd(HypeTrainEvent { level: 2, .. }), .. }
+        ));
+    }
+
+    #[test]
+    fn parse_notification_of_unknown_subscription_is_unhandled() {
+        let text = include_str!("../../../fixtures/eventsub/channel_follow.json").replace("\"channel.follow\"", "\"channel.raid\"");
+
+        assert!(matches!(
+            EventSubMessage::parse(&text).unwrap(),
+            EventSubMessage::Notification { event: EventSubEvent::Unhandled { subscription_type }, .. } if subscription_type == "channel.raid"
+        ));
+    }
+
+    #[test]
+    fn mapper_turns_redemptions_and_cheers_into_actions() {
+        let mut mapper = EventSubEventMapper::default();
+
+        assert!(matches!(
+            mapper.into_chat_event(parse_event(include_str!("../../../fixtures/eventsub/channel_points_redemption.json"))),
+            Some(ChatEvent::Action(ChatAction { ref name, ref action_name, ref action_id, input: Some(ref input), .. }))
+                if name == "Cooler_User" && action_name == "reward_redeem" && action_id == "Hydrate" && input == "pogchamp"
+        ));
+        assert!(matches!(
+            mapper.into_chat_event(parse_event(include_str!("../../../fixtures/eventsub/channel_cheer.json"))),
+            Some(ChatEvent::Action(ChatAction { ref name, ref action_name, ref action_id, input: Some(ref input), .. }))
+                if name == "Cheering_User" && action_name == "bits" && action_id == "250" && input == "Cheer250 keep it up"
+        ));
+        assert!(matches!(
+            mapper.into_chat_event(parse_event(include_str!("../../../fixtures/eventsub/channel_cheer_anonymous.json"))),
+            Some(ChatEvent::Action(ChatAction { ref name, input: None, .. })) if name == "Anonymous"
+        ));
+    }
+
+    #[test]
+    fn mapper_reports_hype_train_level_ups_only_once() {
+        let mut mapper = EventSubEventMapper::default();
+        let progress = |level: u32| parse_event(&include_str!("../../../fixtures/eventsub/hype_train_progress.json").replace("\"level\": 2", &format!("\"level\": {}", level)));
+
+        assert_eq!(notice_event(mapper.into_chat_event(parse_event(include_str!("../../../fixtures/eventsub/hype_train_begin.json")))), Some(ChatNoticeEvent::HypeTrainStart { level: 1 }));
+        assert_eq!(notice_event(mapper.into_chat_event(progress(1))), None);
+        assert_eq!(notice_event(mapper.into_chat_event(progress(2))), Some(ChatNoticeEvent::HypeTrainLevelUp { level: 2 }));
+        assert_eq!(notice_event(mapper.into_chat_event(progress(2))), None);
+        assert_eq!(notice_event(mapper.into_chat_event(parse_event(include_str!("../../../fixtures/eventsub/hype_train_end.json")))), Some(ChatNoticeEvent::HypeTrainEnd { level: 2 }));
+    }
+
+    fn parse_event(text: &str) -> EventSubEvent {
+        match EventSubMessage::parse(text).unwrap() {
+            EventSubMessage::Notification { event, .. } => event,
+            message => panic!("Expected a notification, got {:?}", message)
+        }
+    }
+
+    fn notice_event(event: Option<ChatEvent>) -> Option<ChatNoticeEvent> {
+        match event {
+            Some(ChatEvent::Notice(notice)) => Some(notice.event),
+            _ => None
+        }
+    }
+}
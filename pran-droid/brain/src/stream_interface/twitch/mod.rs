@@ -1,3 +1,7 @@
 pub mod twitch_interface;
+pub mod twitch_chat_output;
+pub mod twitch_stream_interface;
 pub mod user_id_from_login_name;
-pub mod channel_events_stream;
\ No newline at end of file
+pub mod channel_events_stream;
+pub mod eventsub_messages;
+pub mod token_refresh;
\ No newline at end of file
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+use tokio::time;
+use pran_droid_core::domain::credentials::twitch_credentials::TwitchCredentials;
+use pran_droid_core::domain::credentials::twitch_credentials_repository::TwitchCredentialsRepository;
+use crate::authentication::twitch_oauth_client::TwitchOAuthClient;
+use crate::stream_interface::supervisor::{Backoff, BackoffOptions};
+
+const REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+// Stored credentials win over the configured refresh token, both are refreshed straight away when close to expiry
+pub async fn load_twitch_credentials(oauth: &TwitchOAuthClient, repository: &dyn TwitchCredentialsRepository, configured_refresh_token: &str) -> Result<Option<TwitchCredentials>, String> {
+    match repository.get().await {
+        Some(credentials) if credentials.refresh_in(SystemTime::now(), REFRESH_MARGIN).is_zero() => refresh_twitch_credentials(oauth, repository, &credentials.refresh_token).await.map(Some),
+        Some(credentials) => Ok(Some(credentials)),
+        None if !configured_refresh_token.is_empty() => refresh_twitch_credentials(oauth, repository, configured_refresh_token).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+async fn refresh_twitch_credentials(oauth: &TwitchOAuthClient, repository: &dyn TwitchCredentialsRepository, refresh_token: &str) -> Result<TwitchCredentials, String> {
+    let credentials = oauth.refresh(refresh_token, SystemTime::now()).await?;
+    repository.save(&credentials).await.map_err(|error| error.to_string())?;
+
+    Ok(credentials)
+}
+
+// Refreshes the access token before it expires and publishes every new token, until nobody listens anymore.
+pub async fn run_token_refresh(oauth: TwitchOAuthClient, repository: Arc<dyn TwitchCredentialsRepository>, mut credentials: TwitchCredentials, token: watch::Sender<String>, options: BackoffOptions) {
+    let mut backoff = Backoff::new(options);
+
+    while !token.is_closed() {
+        time::sleep(credentials.refresh_in(SystemTime::now(), REFRESH_MARGIN)).await;
+
+        match refresh_twitch_credentials(&oauth, repository.as_ref(), &credentials.refresh_token).await {
+            Ok(refreshed) => {
+                info!("Twitch access token refreshed");
+                backoff.reset();
+                credentials = refreshed;
+                token.send(credentials.access_token.clone()).ok();
+            },
+            Err(error) => {
+                let delay = backoff.next_delay();
+                error!("Twitch access token refresh failed, retrying in {:?}: {}", delay, error);
+                time::sleep(delay).await;
+            },
+        }
+    }
+}
+
+// Resolves when a new access token is published, never when the token can't change anymore
+pub async fn token_changed(token: &mut watch::Receiver<String>) {
+    if token.changed().await.is_err() {
+        futures::future::pending::<()>().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pran_droid_core::persistence::credentials::in_memory_twitch_credentials_repository::InMemoryTwitchCredentialsRepository;
+    use crate::authentication::mock_oauth_server::serve_mock_oauth;
+
+    const TOKEN_RESPONSE: &str = r#"{"access_token":"new_access","expires_in":14124,"refresh_token":"new_refresh","scope":[],"token_type":"bearer"}"#;
+
+    #[tokio::test]
+    async fn load_twitch_credentials_returns_stored_credentials_far_from_expiry() {
+        let server = serve_mock_oauth(vec![]).await;
+        let repository = InMemoryTwitchCredentialsRepository::new();
+        let stored = credentials("stored_access", "stored_refresh", SystemTime::now() + Duration::from_secs(3600));
+        repository.save(&stored).await.unwrap();
+
+        let loaded = load_twitch_credentials(&oauth(&server.url), &repository, "").await;
+
+        assert_eq!(loaded, Ok(Some(stored)));
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn load_twitch_credentials_refreshes_and_stores_expiring_credentials() {
+        let server = serve_mock_oauth(vec![(200, TOKEN_RESPONSE)]).await;
+        let repository = InMemoryTwitchCredentialsRepository::new();
+        repository.save(&credentials("stored_access", "stored_refresh", SystemTime::now() + Duration::from_secs(60))).await.unwrap();
+
+        let loaded = load_twitch_credentials(&oauth(&server.url), &repository, "configured_refresh").await.unwrap().unwrap();
+
+        assert_eq!(loaded.access_token, "new_access");
+        assert_eq!(repository.get().await, Some(loaded));
+        assert!(server.requests()[0].ends_with("refresh_token=stored_refresh"));
+    }
+
+    #[tokio::test]
+    async fn load_twitch_credentials_without_stored_ones_uses_the_configured_refresh_token() {
+        let server = serve_mock_oauth(vec![(200, TOKEN_RESPONSE)]).await;
+        let repository = InMemoryTwitchCredentialsRepository::new();
+
+        let loaded = load_twitch_credentials(&oauth(&server.url), &repository, "configured_refresh").await.unwrap();
+
+        assert_eq!(loaded.map(|credentials| credentials.refresh_token), Some(String::from("new_refresh")));
+        assert!(server.requests()[0].ends_with("refresh_token=configured_refresh"));
+    }
+
+    #[tokio::test]
+    async fn load_twitch_credentials_without_any_refresh_token_returns_none() {
+        let server = serve_mock_oauth(vec![]).await;
+
+        let loaded = load_twitch_credentials(&oauth(&server.url), &InMemoryTwitchCredentialsRepository::new(), "").await;
+
+        assert_eq!(loaded, Ok(None));
+    }
+
+    #[tokio::test]
+    async fn run_token_refresh_retries_failed_refreshes_then_publishes_and_stores_the_new_token() {
+        let server = serve_mock_oauth(vec![(500, r#"{"status":500,"message":"Oops"}"#), (200, TOKEN_RESPONSE)]).await;
+        let repository = Arc::new(InMemoryTwitchCredentialsRepository::new());
+        let (token_sender, mut token) = watch::channel(String::from("expired_access"));
+        let options = BackoffOptions { initial_delay: Duration::from_millis(1), jitter: 0.0, ..Default::default() };
+        tokio::spawn(run_token_refresh(oauth(&server.url), repository.clone(), credentials("expired_access", "old_refresh", SystemTime::now()), token_sender, options));
+
+        time::timeout(Duration::from_secs(10), token_changed(&mut token)).await.expect("Token not refreshed in time");
+
+        assert_eq!(*token.borrow(), "new_access");
+        assert_eq!(repository.get().await.map(|credentials| credentials.refresh_token), Some(String::from("new_refresh")));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    fn oauth(url: &str) -> TwitchOAuthClient {
+        TwitchOAuthClient::with_base_url(url.to_string(), String::from("client"), String::from("secret"))
+    }
+
+    fn credentials(access_token: &str, refresh_token: &str, expires_at: SystemTime) -> TwitchCredentials {
+        TwitchCredentials::new(access_token.to_string(), refresh_token.to_string(), expires_at).unwrap()
+    }
+}
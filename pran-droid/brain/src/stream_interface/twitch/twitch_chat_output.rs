@@ -0,0 +1,131 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::StreamExt;
+use tokio::time;
+use crate::brain_output::chat_output::{ChatOutput, ChatOutputMessage};
+
+// Twitch allows 20 messages every 30 seconds to a channel, 100 when the bot is a moderator or the broadcaster
+pub(crate) const CHAT_RATE_LIMIT_WINDOW: Duration = Duration::from_secs(30);
+pub(crate) const CHAT_RATE_LIMIT: usize = 20;
+pub(crate) const MODERATOR_CHAT_RATE_LIMIT: usize = 100;
+
+pub struct TwitchChatOutput {
+    messages: UnboundedSender<ChatOutputMessage>,
+}
+
+impl ChatOutput for TwitchChatOutput {
+    fn send_chat_message(&self, message: ChatOutputMessage) {
+        if self.messages.unbounded_send(message).is_err() {
+            warn!("Chat connection stopped, message not sent");
+        }
+    }
+}
+
+// Messages are queued until the chat connection is able to send them
+pub fn create_twitch_chat_output() -> (TwitchChatOutput, UnboundedReceiver<ChatOutputMessage>) {
+    let (messages, receiver) = unbounded::<ChatOutputMessage>();
+
+    (TwitchChatOutput { messages }, receiver)
+}
+
+// Waits until the rate limit allows another message before taking it from the queue, so that nothing is lost if cancelled
+pub(crate) async fn next_chat_message(messages: &mut UnboundedReceiver<ChatOutputMessage>, rate_limiter: &mut ChatRateLimiter, is_moderator: bool) -> Option<ChatOutputMessage> {
+    let limit = if is_moderator { MODERATOR_CHAT_RATE_LIMIT } else { CHAT_RATE_LIMIT };
+    time::sleep(rate_limiter.delay_before_next(Instant::now(), limit)).await;
+
+    let message = messages.next().await?;
+    rate_limiter.record(Instant::now());
+    Some(message)
+}
+
+pub(crate) struct ChatRateLimiter {
+    window: Duration,
+    sent_at: VecDeque<Instant>,
+}
+
+impl ChatRateLimiter {
+    pub(crate) fn new(window: Duration) -> Self {
+        ChatRateLimiter { window, sent_at: VecDeque::new() }
+    }
+
+    pub(crate) fn delay_before_next(&mut self, now: Instant, limit: usize) -> Duration {
+        while matches!(self.sent_at.front(), Some(sent_at) if now.saturating_duration_since(*sent_at) >= self.window) {
+            self.sent_at.pop_front();
+        }
+
+        if self.sent_at.len() < limit {
+            return Duration::ZERO;
+        }
+
+        let oldest_in_limit = self.sent_at[self.sent_at.len() - limit];
+        (oldest_in_limit + self.window).saturating_duration_since(now)
+    }
+
+    pub(crate) fn record(&mut self, now: Instant) {
+        self.sent_at.push_back(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chat_rate_limiter_allows_messages_up_to_the_limit() {
+        let mut limiter = ChatRateLimiter::new(Duration::from_secs(30));
+        let start = Instant::now();
+
+        for second in 0..3 {
+            let now = start + Duration::from_secs(second);
+            assert_eq!(limiter.delay_before_next(now, 3), Duration::ZERO);
+            limiter.record(now);
+        }
+
+        assert_eq!(limiter.delay_before_next(start + Duration::from_secs(10), 3), Duration::from_secs(20));
+    }
+
+    #[test]
+    fn chat_rate_limiter_frees_up_messages_once_they_leave_the_window() {
+        let mut limiter = ChatRateLimiter::new(Duration::from_secs(30));
+        let start = Instant::now();
+        limiter.record(start);
+        limiter.record(start + Duration::from_secs(5));
+
+        assert_eq!(limiter.delay_before_next(start + Duration::from_secs(30), 2), Duration::ZERO);
+        limiter.record(start + Duration::from_secs(30));
+        assert_eq!(limiter.delay_before_next(start + Duration::from_secs(31), 2), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn chat_rate_limiter_higher_limit_for_moderators() {
+        let mut limiter = ChatRateLimiter::new(CHAT_RATE_LIMIT_WINDOW);
+        let now = Instant::now();
+        (0..CHAT_RATE_LIMIT).for_each(|_| limiter.record(now));
+
+        assert_eq!(limiter.delay_before_next(now, CHAT_RATE_LIMIT), CHAT_RATE_LIMIT_WINDOW);
+        assert_eq!(limiter.delay_before_next(now, MODERATOR_CHAT_RATE_LIMIT), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn twitch_chat_output_queues_messages_for_the_connection() {
+        let (chat_output, mut messages) = create_twitch_chat_output();
+        let message = ChatOutputMessage { text: String::from("hello"), reply_to_message_id: Some(String::from("an id")) };
+
+        chat_output.send_chat_message(message.clone());
+
+        assert_eq!(messages.next().await, Some(message));
+    }
+
+    #[tokio::test]
+    async fn next_chat_message_records_the_sent_messages() {
+        let (chat_output, mut messages) = create_twitch_chat_output();
+        let mut limiter = ChatRateLimiter::new(Duration::from_secs(30));
+        chat_output.send_chat_message(ChatOutputMessage { text: String::from("hello"), reply_to_message_id: None });
+
+        let message = next_chat_message(&mut messages, &mut limiter, false).await;
+
+        assert!(matches!(message, Some(ChatOutputMessage { text, .. }) if text == "hello"));
+        assert!(limiter.delay_before_next(Instant::now(), 1) > Duration::ZERO);
+    }
+}
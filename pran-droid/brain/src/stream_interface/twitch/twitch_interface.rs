@@ -1,72 +1,247 @@
 use std::fmt::{Display, Error, Formatter};
+use futures::future::BoxFuture;
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
 use futures::stream::Stream;
 use tokio::sync::mpsc::channel;
-use tokio_stream::StreamExt;
+use tokio::sync::watch;
 use tokio_stream::wrappers::ReceiverStream;
 use twitch_irc::{ClientConfig, PlainTCPTransport, TwitchIRCClient};
 use twitch_irc::login::StaticLoginCredentials;
-use twitch_irc::message::ServerMessage;
-use crate::stream_interface::events::{ChatEvent, ChatMessage};
-use crate::stream_interface::twitch::channel_events_stream::create_channel_events_stream;
+use twitch_irc::message::{Badge, IRCMessage, ServerMessage, UserNoticeEvent, UserNoticeMessage};
+use crate::brain_output::chat_output::ChatOutputMessage;
+use crate::stream_interface::events::{ChatEvent, ChatMessage, ChatNotice, ChatNoticeEvent};
+use crate::stream_interface::supervisor::{BackoffOptions, ConnectionSession, ConnectionStateEvent, SupervisedConnection, supervise};
+use crate::stream_interface::twitch::channel_events_stream::create_channel_events_connection;
+use crate::stream_interface::twitch::token_refresh::token_changed;
+use crate::stream_interface::twitch::twitch_chat_output::{CHAT_RATE_LIMIT_WINDOW, ChatRateLimiter, next_chat_message};
 
-pub async fn connect_to_twitch(options: TwitchConnectOptions) -> impl Stream<Item = ChatEvent> {
+pub fn connect_to_twitch(options: TwitchConnectOptions, chat_messages: UnboundedReceiver<ChatOutputMessage>, connection_states: UnboundedSender<ConnectionStateEvent>) -> impl Stream<Item = ChatEvent> {
     info!("Connecting to twitch stream: {}", options);
-    let chat_stream = create_messages_stream(options.clone()).await;
-    info!("Chat connection initiated");
-    let channel_rewards_stream = create_channel_events_stream(options).await;
-    info!("PubSub connection initiated");
-    chat_stream.merge(channel_rewards_stream)
+    let (tx, rx) = channel::<ChatEvent>(100);
+
+    let chat_connection = ChatConnection {
+        options: options.clone(),
+        chat_messages,
+        rate_limiter: ChatRateLimiter::new(CHAT_RATE_LIMIT_WINDOW),
+    };
+    tokio::spawn(supervise(chat_connection, BackoffOptions::default(), tx.clone(), connection_states.clone()));
+    tokio::spawn(supervise(create_channel_events_connection(options), BackoffOptions::default(), tx, connection_states));
+
+    ReceiverStream::new(rx)
 }
 
-async fn create_messages_stream(options: TwitchConnectOptions) -> impl Stream<Item = ChatEvent> {
-    let TwitchConnectOptions { user, token, channel: channel_to_log_into, .. } = options;
-    let config = ClientConfig::new_simple(StaticLoginCredentials::new(user, Some(token)));
-    let (mut incoming_messages, client) =
-        TwitchIRCClient::<PlainTCPTransport, StaticLoginCredentials>::new(config);
-    
-    let (tx, rx) = channel::<ChatEvent>(100);
+// Chat messages and the rate limiter outlive a single session, messages sent while disconnected wait for the next one
+struct ChatConnection {
+    options: TwitchConnectOptions,
+    chat_messages: UnboundedReceiver<ChatOutputMessage>,
+    rate_limiter: ChatRateLimiter,
+}
+
+impl SupervisedConnection for ChatConnection {
+    fn name(&self) -> &str {
+        "chat"
+    }
+
+    fn run<'a>(&'a mut self, session: &'a ConnectionSession) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let TwitchConnectOptions { user, mut token, channel: channel_to_log_into, .. } = self.options.clone();
+            let access_token = token.borrow_and_update().clone();
+            let config = ClientConfig::new_simple(StaticLoginCredentials::new(user, Some(access_token)));
+            let (mut incoming_messages, client) =
+                TwitchIRCClient::<PlainTCPTransport, StaticLoginCredentials>::new(config);
 
-    tokio::spawn(async move {
-        let join_handle = tokio::spawn(async move {
-            while let Some(message) = incoming_messages.recv().await {
-                if let ServerMessage::Privmsg(msg) = message {
-                    debug!("Irc Private Message received {:?}", msg);
-                    let has_mod_tag;
-                    match msg.source.tags.0.get("mod") {
-                        Some(Some(value)) => has_mod_tag = value == "1",
-                        _ => has_mod_tag = false
-                    };
-
-                    let has_broadcaster_badge = msg.badges.into_iter().any(|badge| badge.name == "broadcaster");
-                    tx.send(ChatEvent::Message(ChatMessage {
-                        name: msg.sender.name.to_string(),
-                        content: msg.message_text.to_string(),
-                        is_mod: has_mod_tag || has_broadcaster_badge
-                    })).await.unwrap();
-                } else {
-                    debug!("Irc message that is not a Private Message {:?}", message);
+            client.join(channel_to_log_into.clone()).map_err(|error| error.to_string())?;
+
+            let mut is_moderator = false;
+            // The client reconnects on its own, the session ends only if the client stops or the token is refreshed
+            loop {
+                tokio::select! {
+                    message = incoming_messages.recv() => match message {
+                        Some(message) => {
+                            match &message {
+                                ServerMessage::Join(_) => session.connected(),
+                                ServerMessage::UserState(user_state) => is_moderator = has_badge(&user_state.badges, "moderator") || has_badge(&user_state.badges, "broadcaster"),
+                                _ => {}
+                            }
+                            if let Some(event) = server_message_to_chat_event(message) {
+                                session.send(event).await?;
+                            }
+                        },
+                        None => return Ok(()),
+                    },
+                    Some(chat_message) = next_chat_message(&mut self.chat_messages, &mut self.rate_limiter, is_moderator) => {
+                        send_chat_message(&client, &channel_to_log_into, chat_message).await;
+                    },
+                    _ = token_changed(&mut token) => return Err(String::from("Access token refreshed")),
                 }
             }
-        });
+        })
+    }
+}
 
-        client.join(channel_to_log_into).unwrap();
+async fn send_chat_message(client: &TwitchIRCClient<PlainTCPTransport, StaticLoginCredentials>, channel: &str, message: ChatOutputMessage) {
+    let result = match message.reply_to_message_id {
+        Some(message_id) => client.say_in_reply_to(&(channel, message_id.as_str()), message.text).await,
+        None => client.say(channel.to_string(), message.text).await,
+    };
 
-        join_handle.await.unwrap();
-    });
-    
-    ReceiverStream::new(rx)
+    if let Err(error) = result {
+        warn!("Could not send chat message: {}", error);
+    }
+}
+
+fn server_message_to_chat_event(message: ServerMessage) -> Option<ChatEvent> {
+    match message {
+        ServerMessage::Privmsg(msg) => {
+            debug!("Irc Private Message received {:?}", msg);
+            let has_mod_tag = has_enabled_tag(&msg.source, "mod");
+            let has_broadcaster_badge = has_badge(&msg.badges, "broadcaster");
+
+            Some(ChatEvent::Message(ChatMessage {
+                id: msg.message_id.clone(),
+                name: msg.sender.name.to_string(),
+                content: msg.message_text.to_string(),
+                is_mod: has_mod_tag || has_broadcaster_badge,
+                is_broadcaster: has_broadcaster_badge,
+                is_vip: has_badge(&msg.badges, "vip"),
+                is_subscriber: has_badge(&msg.badges, "subscriber") || has_badge(&msg.badges, "founder"),
+            }))
+        },
+        ServerMessage::UserNotice(notice) => {
+            debug!("Irc User Notice received {:?}", notice);
+            user_notice_to_chat_event(notice)
+        },
+        message => {
+            debug!("Irc message that is not handled {:?}", message);
+            None
+        }
+    }
+}
+
+fn user_notice_to_chat_event(notice: UserNoticeMessage) -> Option<ChatEvent> {
+    let event = match &notice.event {
+        UserNoticeEvent::SubOrResub { is_resub: false, cumulative_months, .. } => ChatNoticeEvent::Subscription { months: into_u32(*cumulative_months) },
+        UserNoticeEvent::SubOrResub { is_resub: true, cumulative_months, .. } => ChatNoticeEvent::Resubscription { months: into_u32(*cumulative_months) },
+        // Gifts that are part of a mystery gift are already covered by the mystery gift notice itself.
+        UserNoticeEvent::SubGift { .. } if notice.source.tags.0.contains_key("msg-param-community-gift-id") => return None,
+        UserNoticeEvent::SubGift { recipient, cumulative_months, .. } => ChatNoticeEvent::GiftSubscription { recipient: recipient.name.clone(), months: into_u32(*cumulative_months) },
+        UserNoticeEvent::SubMysteryGift { mass_gift_count, .. } | UserNoticeEvent::AnonSubMysteryGift { mass_gift_count, .. } => ChatNoticeEvent::MysteryGift { count: into_u32(*mass_gift_count) },
+        UserNoticeEvent::Raid { viewer_count, .. } => ChatNoticeEvent::Raid { viewers: into_u32(*viewer_count) },
+        _ => return None
+    };
+    let has_mod_tag = has_enabled_tag(&notice.source, "mod");
+    let has_broadcaster_badge = has_badge(&notice.badges, "broadcaster");
+
+    Some(ChatEvent::Notice(ChatNotice {
+        name: notice.sender.name,
+        is_mod: has_mod_tag || has_broadcaster_badge,
+        is_broadcaster: has_broadcaster_badge,
+        is_vip: has_badge(&notice.badges, "vip"),
+        is_subscriber: has_badge(&notice.badges, "subscriber") || has_badge(&notice.badges, "founder"),
+        event,
+        message: notice.message_text,
+    }))
+}
+
+fn has_enabled_tag(source: &IRCMessage, tag: &str) -> bool {
+    matches!(source.tags.0.get(tag), Some(Some(value)) if value == "1")
+}
+
+fn has_badge(badges: &[Badge], name: &str) -> bool {
+    badges.iter().any(|badge| badge.name == name)
+}
+
+fn into_u32(value: u64) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
 }
 
 #[derive(Clone)]
 pub struct TwitchConnectOptions {
     pub user: String,
-    pub token: String,
+    pub token: watch::Receiver<String>,
     pub channel: String,
     pub client_id: String
 }
 
+impl TwitchConnectOptions {
+    pub fn access_token(&self) -> String {
+        self.token.borrow().clone()
+    }
+}
+
 impl Display for TwitchConnectOptions {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         write!(f, "User: {}, Channel: {}", &self.user, &self.channel)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_message_to_chat_event_parses_subscription() {
+        let event = parse_chat_event("@badge-info=subscriber/1;badges=subscriber/0;color=#008000;display-name=Pmyl;emotes=;flags=;id=db25007f-7a18-43eb-9379-80131e44d633;login=pmyl;mod=0;msg-id=sub;msg-param-cumulative-months=1;msg-param-months=0;msg-param-should-share-streak=0;msg-param-sub-plan-name=Channel\\sSubscription;msg-param-sub-plan=1000;room-id=12345678;subscriber=1;system-msg=Pmyl\\ssubscribed\\sat\\sTier\\s1.;tmi-sent-ts=1507246572675;user-id=87654321;user-type= :tmi.twitch.tv USERNOTICE #pran");
+
+        assert_eq!(event, Some(ChatNotice {
+            name: String::from("Pmyl"),
+            is_mod: false,
+            is_broadcaster: false,
+            is_vip: false,
+            is_subscriber: true,
+            event: ChatNoticeEvent::Subscription { months: 1 },
+            message: None,
+        }));
+    }
+
+    #[test]
+    fn server_message_to_chat_event_parses_resubscription_with_months_and_message() {
+        let event = parse_chat_event("@badge-info=subscriber/6;badges=vip/1,subscriber/6;color=#008000;display-name=ronni;emotes=;flags=;id=db25007f-7a18-43eb-9379-80131e44d633;login=ronni;mod=0;msg-id=resub;msg-param-cumulative-months=6;msg-param-months=0;msg-param-should-share-streak=1;msg-param-streak-months=2;msg-param-sub-plan-name=Prime;msg-param-sub-plan=Prime;room-id=12345678;subscriber=1;system-msg=ronni\\shas\\ssubscribed\\sfor\\s6\\smonths!;tmi-sent-ts=1507246572675;user-id=87654321;user-type= :tmi.twitch.tv USERNOTICE #pran :Great stream -- keep it up!");
+
+        assert!(matches!(event, Some(ChatNotice { event: ChatNoticeEvent::Resubscription { months: 6 }, is_vip: true, is_subscriber: true, ref message, .. }) if message.as_deref() == Some("Great stream -- keep it up!")));
+    }
+
+    #[test]
+    fn server_message_to_chat_event_parses_gift_subscription_with_recipient() {
+        let event = parse_chat_event("@badge-info=;badges=;color=;display-name=TWW2;emotes=;flags=;id=e21409b1-d25d-4a1a-b5cf-ef27d8b7030e;login=tww2;mod=1;msg-id=subgift;msg-param-gift-months=1;msg-param-months=2;msg-param-origin-id=da\\s39\\sa3;msg-param-recipient-display-name=Mr_Woodchuck;msg-param-recipient-id=55554444;msg-param-recipient-user-name=mr_woodchuck;msg-param-sender-count=0;msg-param-sub-plan-name=Channel\\sSubscription;msg-param-sub-plan=1000;room-id=12345678;subscriber=0;system-msg=TWW2\\sgifted\\sa\\sTier\\s1\\ssub\\sto\\sMr_Woodchuck!;tmi-sent-ts=1594583782376;user-id=87654321;user-type= :tmi.twitch.tv USERNOTICE #pran");
+
+        assert!(matches!(event, Some(ChatNotice { event: ChatNoticeEvent::GiftSubscription { ref recipient, months: 2 }, is_mod: true, .. }) if recipient == "Mr_Woodchuck"));
+    }
+
+    #[test]
+    fn server_message_to_chat_event_ignores_gift_subscriptions_from_mystery_gift() {
+        let event = parse_chat_event("@badge-info=;badges=;color=;display-name=TWW2;emotes=;flags=;id=e21409b1-d25d-4a1a-b5cf-ef27d8b7030e;login=tww2;mod=0;msg-id=subgift;msg-param-community-gift-id=1234;msg-param-gift-months=1;msg-param-months=1;msg-param-origin-id=da\\s39\\sa3;msg-param-recipient-display-name=Mr_Woodchuck;msg-param-recipient-id=55554444;msg-param-recipient-user-name=mr_woodchuck;msg-param-sender-count=0;msg-param-sub-plan-name=Channel\\sSubscription;msg-param-sub-plan=1000;room-id=12345678;subscriber=0;system-msg=TWW2\\sgifted\\sa\\sTier\\s1\\ssub\\sto\\sMr_Woodchuck!;tmi-sent-ts=1594583782376;user-id=87654321;user-type= :tmi.twitch.tv USERNOTICE #pran");
+
+        assert_eq!(event, None);
+    }
+
+    #[test]
+    fn server_message_to_chat_event_parses_mystery_gift_count() {
+        let event = parse_chat_event("@badge-info=;badges=broadcaster/1;color=;display-name=Pmyl;emotes=;flags=;id=e21409b1-d25d-4a1a-b5cf-ef27d8b7030e;login=pmyl;mod=0;msg-id=submysterygift;msg-param-mass-gift-count=15;msg-param-origin-id=da\\s39\\sa3;msg-param-sender-count=40;msg-param-sub-plan=1000;room-id=12345678;subscriber=0;system-msg=Pmyl\\sis\\sgifting\\s15\\sTier\\s1\\sSubs!;tmi-sent-ts=1594583778756;user-id=87654321;user-type= :tmi.twitch.tv USERNOTICE #pran");
+
+        assert!(matches!(event, Some(ChatNotice { event: ChatNoticeEvent::MysteryGift { count: 15 }, is_broadcaster: true, is_mod: true, .. })));
+    }
+
+    #[test]
+    fn server_message_to_chat_event_parses_raid_viewers() {
+        let event = parse_chat_event("@badge-info=;badges=turbo/1;color=#9ACD32;display-name=TestChannel;emotes=;flags=;id=3d830f12-795c-447d-af3c-ea05e40fbddb;login=testchannel;mod=0;msg-id=raid;msg-param-displayName=TestChannel;msg-param-login=testchannel;msg-param-profileImageURL=https://static-cdn.jtvnw.net/jtv_user_pictures/testchannel-profile_image-300x300.png;msg-param-viewerCount=15;room-id=12345678;subscriber=0;system-msg=15\\sraiders\\sfrom\\sTestChannel\\shave\\sjoined!;tmi-sent-ts=1507246572675;user-id=123456;user-type= :tmi.twitch.tv USERNOTICE #pran");
+
+        assert!(matches!(event, Some(ChatNotice { event: ChatNoticeEvent::Raid { viewers: 15 }, ref name, .. }) if name == "TestChannel"));
+    }
+
+    #[test]
+    fn server_message_to_chat_event_ignores_other_user_notices() {
+        let event = parse_chat_event("@badge-info=;badges=;color=;display-name=Pmyl;emotes=;flags=;id=3d830f12-795c-447d-af3c-ea05e40fbddb;login=pmyl;mod=0;msg-id=ritual;msg-param-ritual-name=new_chatter;room-id=12345678;subscriber=0;system-msg=@Pmyl\\sis\\snew\\shere!;tmi-sent-ts=1507246572675;user-id=123456;user-type= :tmi.twitch.tv USERNOTICE #pran :HeyGuys");
+
+        assert_eq!(event, None);
+    }
+
+    fn parse_chat_event(raw: &str) -> Option<ChatNotice> {
+        let message = ServerMessage::try_from(IRCMessage::parse(raw).unwrap()).unwrap();
+
+        match server_message_to_chat_event(message) {
+            Some(ChatEvent::Notice(notice)) => Some(notice),
+            _ => None
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+use futures::channel::mpsc::UnboundedSender;
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use reqwest::Client;
+use tokio::sync::watch;
+use pran_droid_core::domain::credentials::twitch_credentials_repository::TwitchCredentialsRepository;
+use crate::authentication::twitch_oauth_client::TwitchOAuthClient;
+use crate::brain_output::chat_output::ChatOutput;
+use crate::stream_interface::interface::{StreamConnection, StreamInterface};
+use crate::stream_interface::supervisor::{BackoffOptions, ConnectionStateEvent};
+use crate::stream_interface::twitch::token_refresh::{load_twitch_credentials, run_token_refresh};
+use crate::stream_interface::twitch::twitch_chat_output::create_twitch_chat_output;
+use crate::stream_interface::twitch::twitch_interface::{connect_to_twitch, TwitchConnectOptions};
+
+pub struct TwitchStreamConfig {
+    pub client_secret: String,
+    pub client_id: String,
+    pub token: String,
+    pub refresh_token: String,
+    pub channel: String,
+    pub user: String,
+}
+
+pub struct TwitchStreamInterface {
+    config: TwitchStreamConfig,
+    credentials_repository: Arc<dyn TwitchCredentialsRepository>,
+}
+
+impl TwitchStreamInterface {
+    pub fn new(config: TwitchStreamConfig, credentials_repository: Arc<dyn TwitchCredentialsRepository>) -> Self {
+        TwitchStreamInterface { config, credentials_repository }
+    }
+}
+
+impl StreamInterface for TwitchStreamInterface {
+    fn connect(self: Box<Self>, connection_states: UnboundedSender<ConnectionStateEvent>) -> BoxFuture<'static, Result<StreamConnection, String>> {
+        let TwitchStreamInterface { config, credentials_repository } = *self;
+
+        Box::pin(async move {
+            let oauth = TwitchOAuthClient::new(config.client_id.clone(), config.client_secret.clone());
+            let credentials = load_twitch_credentials(&oauth, credentials_repository.as_ref(), &config.refresh_token).await?;
+            let access_token = match &credentials {
+                Some(credentials) => credentials.access_token.clone(),
+                None => {
+                    warn!("No stored Twitch credentials nor refresh token, the static token will not be refreshed");
+                    authenticate(config.client_secret, config.token).await?
+                },
+            };
+            let (token_sender, token) = watch::channel(access_token);
+            if let Some(credentials) = credentials {
+                tokio::spawn(run_token_refresh(oauth, credentials_repository, credentials, token_sender, BackoffOptions::default()));
+            }
+
+            let (twitch_chat_output, chat_messages) = create_twitch_chat_output();
+            let chat_output: Arc<dyn ChatOutput> = Arc::new(twitch_chat_output);
+            let events = connect_to_twitch(TwitchConnectOptions {
+                token,
+                channel: config.channel,
+                client_id: config.client_id,
+                user: config.user
+            }, chat_messages, connection_states);
+
+            Ok(StreamConnection { events: events.boxed(), chat_output })
+        })
+    }
+}
+
+async fn authenticate(client_secret: String, old_token: String) -> Result<String, String> {
+    if old_token.is_empty() {
+        return Err(String::from("No Twitch token, authorise the droid through the API or configure a token"));
+    }
+    let client_secret = twitch_oauth2::ClientSecret::new(client_secret);
+
+    let token = twitch_oauth2::UserToken::from_existing(
+        &Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build().unwrap(),
+        twitch_oauth2::AccessToken::new(old_token),
+        None,
+        client_secret
+    ).await.map_err(|error| format!("Could not authenticate: {}", error))?;
+
+    Ok(token.access_token.secret().to_string())
+}
@@ -13,28 +13,29 @@ struct TwitchUsersResponseData {
     id: String
 }
 
-pub async fn user_id_from_login_name(options: TwitchConnectOptions) -> u32 {
+pub async fn user_id_from_login_name(options: TwitchConnectOptions) -> Result<u32, String> {
     info!("Fetching channel id for user {:?}", options.channel);
 
+    let token = options.access_token();
     let client = reqwest::Client::new();
     let response = client.get(format!("https://api.twitch.tv/helix/users?login={}", options.channel))
-        .header("Authorization", format!("Bearer {}", options.token))
+        .header("Authorization", format!("Bearer {}", token))
         .header("Client-Id", options.client_id)
         .send()
-        .await.unwrap()
+        .await.map_err(|error| error.to_string())?
         .json::<TwitchUsersResponse>()
-        .await.unwrap();
+        .await.map_err(|error| error.to_string())?;
 
     match response {
         TwitchUsersResponse::Success { data: users } => {
-            let TwitchUsersResponseData { id } = users.first().unwrap();
-            let id = id.parse::<u32>().unwrap();
+            let TwitchUsersResponseData { id } = users.first().ok_or_else(|| format!("User {} not found", options.channel))?;
+            let id = id.parse::<u32>().map_err(|error| error.to_string())?;
 
             info!("Channel id fetched {}", id);
-            id
+            Ok(id)
         },
         TwitchUsersResponse::Error { error, status, message} => {
-            panic!("Error: {}\nStatus: {}\nMessage: {}", error, status, message);
+            Err(format!("Error: {}\nStatus: {}\nMessage: {}", error, status, message))
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use pran_droid_core::domain::emotions::emotion::MouthPositionName;
+use pran_droid_core::domain::visemes::viseme_mapping::VisemeMapping;
+
+// The file maps phonemes to mouth position names, e.g. { "HH": ["idle"], "AA": ["ah"] }
+pub fn read_viseme_mapping_file(path: &Path) -> Result<VisemeMapping, String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("Could not read viseme mapping {}: {}", path.display(), error))?;
+    parse_viseme_mapping(&text)
+}
+
+// Phonemes missing from the file keep the default mouth positions
+pub fn parse_viseme_mapping(text: &str) -> Result<VisemeMapping, String> {
+    let phonemes: HashMap<String, Vec<String>> = serde_json::from_str(text).map_err(|error| format!("Invalid viseme mapping: {}", error))?;
+    let overrides = phonemes.into_iter()
+        .map(|(phoneme, positions)| positions.iter()
+            .map(|position| MouthPositionName::try_from(position).map_err(|_| format!("Invalid viseme mapping: unknown mouth position {} for {}", position, phoneme)))
+            .collect::<Result<Vec<MouthPositionName>, String>>()
+            .map(|positions| (phoneme, positions)))
+        .collect::<Result<HashMap<String, Vec<MouthPositionName>>, String>>()?;
+    Ok(VisemeMapping::default().with_overrides(overrides))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_viseme_mapping_overrides_the_default_mapping() {
+        let mapping = parse_viseme_mapping(r#"{ "HH": ["idle"], "p": ["b"] }"#).unwrap();
+
+        assert_eq!(mapping.mouth_positions("HH"), &[MouthPositionName::Idle]);
+        assert_eq!(mapping.mouth_positions("P"), &[MouthPositionName::B]);
+        assert_eq!(mapping.mouth_positions("AA"), &[MouthPositionName::Oh]);
+    }
+
+    #[test]
+    fn parse_viseme_mapping_rejects_invalid_mappings() {
+        assert!(parse_viseme_mapping(r#"{ "HH": ["wide"] }"#).is_err());
+        assert!(parse_viseme_mapping(r#"{ "HH": "idle" }"#).is_err());
+    }
+}
@@ -1,47 +1,135 @@
 use dotenv::dotenv;
 use std::env;
 use std::future::{Future};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 use log::{debug, LevelFilter};
 use simplelog::SimpleLogger;
+use pran_droid_brain::overlay_server::{OverlayServerOptions, OverlayTlsOptions};
 use pran_droid_brain::run::{PranDroidBrainConfig, start_droid_brain};
+use pran_droid_brain::stream_interface::interface::StreamInterface;
+use pran_droid_brain::stream_interface::local::local_chat_output::LogChatOutput;
+use pran_droid_brain::stream_interface::local::local_stream_interface::{LocalChatSource, LocalStreamInterface};
+use pran_droid_brain::stream_interface::twitch::twitch_stream_interface::{TwitchStreamConfig, TwitchStreamInterface};
+use pran_droid_brain::viseme_mapping_file::read_viseme_mapping_file;
+use pran_droid_core::domain::credentials::twitch_credentials_repository::TwitchCredentialsRepository;
+use pran_droid_core::domain::emotions::emotion_repository::EmotionRepository;
+use pran_droid_core::domain::images::image_repository::ImageRepository;
+use pran_droid_core::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+use pran_droid_core::domain::visemes::mouth_track::MouthTrackModel;
+use pran_droid_persistence_deta::emotions::deta_emotion_repository::DetaEmotionRepository;
+use pran_droid_persistence_deta::images::deta_image_repository::DetaImageRepository;
 use pran_droid_persistence_deta::reactions::deta_reaction_repository::DetaReactionRepository;
+use pran_droid_persistence_local::credentials::file_twitch_credentials_repository::FileTwitchCredentialsRepository;
+use pran_droid_persistence_local::database::Database;
+use pran_droid_persistence_local::emotions::sqlite_emotion_repository::SqliteEmotionRepository;
+use pran_droid_persistence_local::images::sqlite_image_repository::SqliteImageRepository;
+use pran_droid_persistence_local::reactions::sqlite_reaction_repository::SqliteReactionRepository;
 use crate::asciifier::asciify_gif;
 
 mod asciifier;
 
 #[derive(Debug)]
 struct Config {
+    chat_source: ChatSource,
     twitch_channel: String,
     twitch_client_id: String,
     twitch_client_secret: String,
     twitch_user: String,
     twitch_token: String,
+    twitch_refresh_token: String,
+    twitch_credentials_path: String,
+    websocket_bind_address: IpAddr,
     websocket_port: u16,
+    websocket_token: Option<String>,
+    websocket_tls_certificate: Option<String>,
+    websocket_tls_private_key: Option<String>,
+    viseme_mapping_path: Option<String>,
+    reaction_queue_size: usize,
+    reaction_reload_interval: Duration,
     log_level: LevelFilter,
     show_intro: bool,
+    persistence: Persistence,
     deta_project_key: String,
     deta_project_id: String,
+    sqlite_path: String,
     api_secret_key: String,
     api_base_path: String,
 }
 
+#[derive(Debug)]
+enum Persistence {
+    Deta,
+    Local
+}
+
+impl FromStr for Persistence {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Persistence, Self::Err> {
+        match input {
+            "Deta" => Ok(Persistence::Deta),
+            "Local" => Ok(Persistence::Local),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ChatSource {
+    Twitch,
+    Local(LocalChatSource),
+}
+
+// Anything other than Twitch is a local source: "stdin", "tcp:<address>" or "file:<path>"
+impl FromStr for ChatSource {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<ChatSource, Self::Err> {
+        match input {
+            "Twitch" => Ok(ChatSource::Twitch),
+            _ => input.parse::<LocalChatSource>().map(ChatSource::Local),
+        }
+    }
+}
+
 impl Config {
     pub fn new() -> Config {
+        let persistence = env::var("PERSISTENCE").or_else(|_| Ok("Deta".to_string())).and_then(|persistence| persistence.parse::<Persistence>()).expect("PERSISTENCE not a persistence, can be Deta or Local");
+        let is_deta = matches!(persistence, Persistence::Deta);
+        let chat_source = env::var("CHAT_SOURCE").or_else(|_| Ok("Twitch".to_string())).and_then(|chat_source| chat_source.parse::<ChatSource>()).expect("CHAT_SOURCE not a chat source, can be Twitch, stdin, tcp:<address> or file:<path>");
+        let is_twitch = matches!(chat_source, ChatSource::Twitch);
+
         Config {
-            twitch_channel: env::var("TWITCH_CHANNEL").expect("TWITCH_CHANNEL missing in env variables"),
-            twitch_client_id: env::var("TWITCH_CLIENT_ID").expect("TWITCH_CLIENT_ID missing in env variables"),
-            twitch_client_secret: env::var("TWITCH_CLIENT_SECRET").expect("TWITCH_CLIENT_SECRET missing in env variables"),
-            twitch_user: env::var("TWITCH_USER").expect("TWITCH_USER missing in env variables"),
-            twitch_token: env::var("TWITCH_TOKEN").expect("TWITCH_TOKEN missing in env variables"),
+            chat_source,
+            twitch_channel: env::var("TWITCH_CHANNEL").or_else(|error| if is_twitch { Err(error) } else { Ok(String::new()) }).expect("TWITCH_CHANNEL missing in env variables"),
+            twitch_client_id: env::var("TWITCH_CLIENT_ID").or_else(|error| if is_twitch { Err(error) } else { Ok(String::new()) }).expect("TWITCH_CLIENT_ID missing in env variables"),
+            twitch_client_secret: env::var("TWITCH_CLIENT_SECRET").or_else(|error| if is_twitch { Err(error) } else { Ok(String::new()) }).expect("TWITCH_CLIENT_SECRET missing in env variables"),
+            twitch_user: env::var("TWITCH_USER").or_else(|error| if is_twitch { Err(error) } else { Ok(String::new()) }).expect("TWITCH_USER missing in env variables"),
+            twitch_token: env::var("TWITCH_TOKEN").unwrap_or_default(),
+            twitch_refresh_token: env::var("TWITCH_REFRESH_TOKEN").unwrap_or_default(),
+            twitch_credentials_path: env::var("TWITCH_CREDENTIALS_PATH").unwrap_or_else(|_| "twitch_credentials.json".to_string()),
+            websocket_bind_address: env::var("WEBSOCKET_BIND_ADDRESS").unwrap_or_else(|_| "127.0.0.1".to_string()).parse::<IpAddr>().expect("WEBSOCKET_BIND_ADDRESS is not an ip address"),
             websocket_port: env::var("WEBSOCKET_PORT").or(Ok("8080".to_string())).and_then(|port| port.parse::<u16>()).expect("WEBSOCKET_PORT is not a number"),
+            websocket_token: env::var("WEBSOCKET_TOKEN").ok().filter(|token| !token.is_empty()),
+            websocket_tls_certificate: env::var("WEBSOCKET_TLS_CERTIFICATE").ok().filter(|path| !path.is_empty()),
+            websocket_tls_private_key: env::var("WEBSOCKET_TLS_PRIVATE_KEY").ok().filter(|path| !path.is_empty()),
+            viseme_mapping_path: env::var("VISEME_MAPPING_PATH").ok().filter(|path| !path.is_empty()),
+            reaction_queue_size: env::var("REACTION_QUEUE_SIZE").or(Ok("10".to_string())).and_then(|size| size.parse::<usize>()).expect("REACTION_QUEUE_SIZE is not a number"),
+            reaction_reload_interval: env::var("REACTION_RELOAD_INTERVAL_SECONDS").or(Ok("10".to_string())).and_then(|seconds| seconds.parse::<u64>()).map(Duration::from_secs).expect("REACTION_RELOAD_INTERVAL_SECONDS is not a number"),
             log_level: env::var("LOG_LEVEL").or::<String>(Ok("INFO".to_string())).map(|log_level| match log_level.as_str() {
                 "INFO" => LevelFilter::Info,
                 "DEBUG" => LevelFilter::Debug,
                 _ => LevelFilter::Off
             }).expect("Unexpected error when parsing log_level env"),
             show_intro: !env::var("SKIP_INTRO").or(Ok("false".to_string())).and_then(|skip| skip.parse::<bool>()).expect("SKIP_INTRO is not a bool"),
-            deta_project_key: env::var("DETA_PROJECT_KEY").expect("DETA_PROJECT_KEY missing in env variables"),
-            deta_project_id: env::var("DETA_PROJECT_ID").expect("DETA_PROJECT_ID missing in env variables"),
+            persistence,
+            deta_project_key: env::var("DETA_PROJECT_KEY").or_else(|error| if is_deta { Err(error) } else { Ok(String::new()) }).expect("DETA_PROJECT_KEY missing in env variables"),
+            deta_project_id: env::var("DETA_PROJECT_ID").or_else(|error| if is_deta { Err(error) } else { Ok(String::new()) }).expect("DETA_PROJECT_ID missing in env variables"),
+            sqlite_path: env::var("SQLITE_PATH").unwrap_or_else(|_| "pran_droid.sqlite".to_string()),
             api_secret_key: env::var("API_SECRET_KEY").expect("API_SECRET_KEY missing in env variables"),
             api_base_path: env::var("API_BASE_PATH").expect("API_BASE_PATH missing in env variables"),
         }
@@ -62,28 +150,70 @@ async fn main() {
 }
 
 fn start_brain(config: &Config) -> impl Future<Output=()> {
-    let reaction_repo = DetaReactionRepository::new(config.deta_project_key.clone(), config.deta_project_id.clone());
-
-    let twitch_client_secret = config.twitch_client_secret.clone();
-    let twitch_client_id = config.twitch_client_id.clone();
-    let twitch_token = config.twitch_token.clone();
-    let twitch_channel = config.twitch_channel.clone();
-    let twitch_user = config.twitch_user.clone();
-    let websocket_port = config.websocket_port.clone();
+    let reaction_repo: Arc<dyn ReactionDefinitionRepository>;
+    let emotion_repo: Arc<dyn EmotionRepository>;
+    let images_repo: Arc<dyn ImageRepository>;
+    match config.persistence {
+        Persistence::Deta => {
+            reaction_repo = Arc::new(DetaReactionRepository::new(config.deta_project_key.clone(), config.deta_project_id.clone()));
+            emotion_repo = Arc::new(DetaEmotionRepository::new(config.deta_project_key.clone(), config.deta_project_id.clone()));
+            images_repo = Arc::new(DetaImageRepository::new(config.deta_project_key.clone(), config.deta_project_id.clone()));
+        },
+        Persistence::Local => {
+            let database = Arc::new(Database::open(&config.sqlite_path).expect("Failed opening the SQLite database"));
+            reaction_repo = Arc::new(SqliteReactionRepository::new(database.clone()));
+            emotion_repo = Arc::new(SqliteEmotionRepository::new(database.clone()));
+            images_repo = Arc::new(SqliteImageRepository::new(database));
+        },
+    }
+    let stream_interface: Box<dyn StreamInterface> = match &config.chat_source {
+        ChatSource::Twitch => {
+            let credentials_repo: Arc<dyn TwitchCredentialsRepository> = Arc::new(FileTwitchCredentialsRepository::new(config.twitch_credentials_path.clone()));
+            Box::new(TwitchStreamInterface::new(TwitchStreamConfig {
+                client_secret: config.twitch_client_secret.clone(),
+                client_id: config.twitch_client_id.clone(),
+                token: config.twitch_token.clone(),
+                refresh_token: config.twitch_refresh_token.clone(),
+                channel: config.twitch_channel.clone(),
+                user: config.twitch_user.clone(),
+            }, credentials_repo))
+        },
+        ChatSource::Local(source) => Box::new(LocalStreamInterface::new(source.clone(), Arc::new(LogChatOutput))),
+    };
+
+    let overlay = OverlayServerOptions {
+        address: SocketAddr::new(config.websocket_bind_address, config.websocket_port),
+        token: config.websocket_token.clone(),
+        tls: match (&config.websocket_tls_certificate, &config.websocket_tls_private_key) {
+            (Some(certificate_path), Some(private_key_path)) => Some(OverlayTlsOptions {
+                certificate_path: PathBuf::from(certificate_path),
+                private_key_path: PathBuf::from(private_key_path),
+            }),
+            (None, None) => None,
+            _ => panic!("WEBSOCKET_TLS_CERTIFICATE and WEBSOCKET_TLS_PRIVATE_KEY have to be set together"),
+        },
+    };
+    let mouth_track_model = MouthTrackModel {
+        mapping: config.viseme_mapping_path.as_ref()
+            .map(|path| read_viseme_mapping_file(Path::new(path)).expect("VISEME_MAPPING_PATH is not a valid viseme mapping"))
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+    let reaction_queue_size = config.reaction_queue_size;
+    let reaction_reload_interval = config.reaction_reload_interval;
     let api_base_path = config.api_base_path.clone();
     let api_secret_key = config.api_secret_key.clone();
 
     async move {
         start_droid_brain(PranDroidBrainConfig {
-            twitch_client_secret,
-            twitch_client_id,
-            twitch_token,
-            twitch_channel,
-            twitch_user,
-            websocket_port,
+            overlay,
+            mouth_track_model,
+            reaction_queue_size,
+            reaction_reload_interval,
             api_base_path,
             api_secret_key,
-        }, &reaction_repo).await
+            connection_state_listener: None,
+        }, reaction_repo, emotion_repo, images_repo, stream_interface).await
     }
 }
 
@@ -0,0 +1,28 @@
+use crate::application::emotions::dtos::emotion_dto::EmotionDto;
+use crate::application::reactions::dtos::reaction_dto::ReactionDto;
+
+pub struct BackupDto {
+    pub reactions: Vec<ReactionDto>,
+    pub emotions: Vec<EmotionDto>,
+    pub images: Vec<BackupImageDto>,
+}
+
+#[derive(Debug)]
+pub struct BackupImageDto {
+    pub id: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImportBackupModeDto {
+    Merge,
+    Replace,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportBackupResultDto {
+    pub imported_reactions: usize,
+    pub imported_emotions: usize,
+    pub imported_images: usize,
+    pub conflicts: Vec<String>,
+}
@@ -0,0 +1 @@
+pub mod backup_dto;
\ No newline at end of file
@@ -0,0 +1,72 @@
+use std::fmt::Debug;
+use thiserror::Error;
+use crate::application::backup::dtos::backup_dto::{BackupDto, BackupImageDto};
+use crate::domain::emotions::emotion_repository::EmotionRepository;
+use crate::domain::images::image_repository::ImageRepository;
+use crate::domain::images::image_storage::ImageStorage;
+use crate::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+
+#[derive(Debug, Error)]
+pub enum ExportBackupError {
+    #[error("Image {0} is missing from the storage")]
+    MissingImageData(String),
+}
+
+pub async fn export_backup(reaction_repository: &dyn ReactionDefinitionRepository, emotion_repository: &dyn EmotionRepository, image_repository: &dyn ImageRepository, image_storage: &dyn ImageStorage) -> Result<BackupDto, ExportBackupError> {
+    let mut images = vec![];
+    for image in image_repository.get_all().await {
+        let data = image_storage.get(&image.url).await.ok_or_else(|| ExportBackupError::MissingImageData(image.id.0.clone()))?;
+        images.push(BackupImageDto { id: image.id.0, data: data.0 });
+    }
+
+    Ok(BackupDto {
+        reactions: reaction_repository.get_all().await.into_iter().map(From::from).collect(),
+        emotions: emotion_repository.get_all().await.into_iter().map(From::from).collect(),
+        images,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::images::create::{create_image, CreateImageRequest};
+    use crate::domain::emotions::emotion_repository::tests::setup_dummy_emotions;
+    use crate::domain::images::image::{Image, ImageId, ImageUrl};
+    use crate::domain::reactions::reaction_definition_repository::tests::setup_dummy_chat_command_reaction_definitions;
+    use crate::persistence::emotions::in_memory_emotion_repository::InMemoryEmotionRepository;
+    use crate::persistence::images::in_memory_image_repository::InMemoryImageRepository;
+    use crate::persistence::images::in_memory_image_storage::InMemoryImageStorage;
+    use crate::persistence::reactions::in_memory_reaction_repository::InMemoryReactionRepository;
+
+    #[tokio::test]
+    async fn export_backup_contains_every_reaction_emotion_and_image_data() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let emotion_repository = InMemoryEmotionRepository::new();
+        let image_repository = InMemoryImageRepository::new();
+        let image_storage = InMemoryImageStorage::new();
+        setup_dummy_chat_command_reaction_definitions(vec!["!hello", "!bye"], &reaction_repository).await;
+        setup_dummy_emotions(vec!["happy"], &emotion_repository).await;
+        create_image(CreateImageRequest { id: String::from("image"), image: vec![1, 2, 3] }, &image_repository, &image_storage).await.unwrap();
+
+        let backup = export_backup(&reaction_repository, &emotion_repository, &image_repository, &image_storage).await.unwrap();
+
+        assert_eq!(backup.reactions.len(), 2);
+        assert_eq!(backup.emotions.len(), 1);
+        assert_eq!(backup.images.len(), 1);
+        assert_eq!(backup.images[0].id, "image");
+        assert_eq!(backup.images[0].data, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn export_backup_with_image_missing_from_storage_errors() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let emotion_repository = InMemoryEmotionRepository::new();
+        let image_repository = InMemoryImageRepository::new();
+        let image_storage = InMemoryImageStorage::new();
+        image_repository.insert(&Image { id: ImageId(String::from("image")), url: ImageUrl(String::from("api/images/image")) }).await.unwrap();
+
+        let result = export_backup(&reaction_repository, &emotion_repository, &image_repository, &image_storage).await;
+
+        assert!(matches!(result, Err(ExportBackupError::MissingImageData(id)) if id == "image"));
+    }
+}
@@ -0,0 +1,448 @@
+use std::collections::HashSet;
+use std::fmt::Debug;
+use thiserror::Error;
+use crate::application::backup::dtos::backup_dto::{BackupDto, BackupImageDto, ImportBackupModeDto, ImportBackupResultDto};
+use crate::application::emotions::dtos::emotion_dto::EmotionDto;
+use crate::application::reactions::dtos::reaction_dto::ReactionDto;
+use crate::domain::emotions::emotion::Emotion;
+use crate::domain::emotions::emotion_repository::EmotionRepository;
+use crate::domain::images::image::{Image, ImageId};
+use crate::domain::images::image_repository::ImageRepository;
+use crate::domain::images::image_storage::{ImageData, ImageStorage, StorageDeleteError};
+use crate::domain::reactions::reaction_definition::{ReactionDefinition, ReactionTrigger};
+use crate::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+
+#[derive(Debug, Error)]
+pub enum ImportBackupError {
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+    #[error("{0} references the not existing image {1}")]
+    MissingImage(String, String),
+    #[error("{0} references the not existing emotion {1}")]
+    MissingEmotion(String, String),
+    #[error("Storage failure")]
+    StorageFail,
+    #[error("Unexpected error")]
+    Unexpected,
+}
+
+pub struct ImportBackupRequest {
+    pub backup: BackupDto,
+    pub mode: ImportBackupModeDto,
+}
+
+pub async fn import_backup(request: ImportBackupRequest, reaction_repository: &dyn ReactionDefinitionRepository, emotion_repository: &dyn EmotionRepository, image_repository: &dyn ImageRepository, image_storage: &dyn ImageStorage) -> Result<ImportBackupResultDto, ImportBackupError> {
+    let mut images = build_domain_images(request.backup.images)?;
+    let mut emotions = build_domain_emotions(request.backup.emotions)?;
+    let mut reactions = build_domain_reactions(request.backup.reactions)?;
+    let mut result = ImportBackupResultDto::default();
+
+    let mut known_image_ids: HashSet<String> = HashSet::new();
+    let mut known_emotion_ids: HashSet<String> = HashSet::new();
+    let mut skipped_emotion_ids: HashSet<String> = HashSet::new();
+
+    if request.mode == ImportBackupModeDto::Merge {
+        for (id, _) in images.iter() {
+            if image_repository.has(id).await { result.conflicts.push(format!("Image: {}", id.0)); }
+        }
+        images.retain(|(id, _)| !result.conflicts.contains(&format!("Image: {}", id.0)));
+
+        for emotion in emotions.iter() {
+            if emotion_repository.exists(&emotion.id).await {
+                result.conflicts.push(format!("Emotion: {}", emotion.id.0));
+            } else if emotion_repository.exists_with_name(&emotion.name).await {
+                result.conflicts.push(format!("Emotion: {}", emotion.id.0));
+                skipped_emotion_ids.insert(emotion.id.0.clone());
+            }
+        }
+        emotions.retain(|emotion| !result.conflicts.contains(&format!("Emotion: {}", emotion.id.0)));
+
+        known_image_ids.extend(image_repository.get_all().await.into_iter().map(|image| image.id.0));
+        known_emotion_ids.extend(emotion_repository.get_all().await.into_iter().map(|emotion| emotion.id.0));
+    }
+
+    known_image_ids.extend(images.iter().map(|(id, _)| id.0.clone()));
+    known_emotion_ids.extend(emotions.iter().map(|emotion| emotion.id.0.clone()));
+
+    let mut imported_triggers: Vec<ReactionTrigger> = vec![];
+    for reaction in reactions.iter() {
+        if is_conflicting_reaction(reaction, &request.mode, &imported_triggers, &skipped_emotion_ids, reaction_repository).await {
+            result.conflicts.push(format!("Reaction: {}", reaction.id.0));
+        } else {
+            imported_triggers.extend(reaction.triggers.iter().cloned());
+        }
+    }
+    reactions.retain(|reaction| !result.conflicts.contains(&format!("Reaction: {}", reaction.id.0)));
+
+    validate_references(&emotions, &reactions, &known_image_ids, &known_emotion_ids)?;
+
+    if request.mode == ImportBackupModeDto::Replace {
+        clear_all(reaction_repository, emotion_repository, image_repository, image_storage).await?;
+    }
+
+    for (id, data) in images.iter() {
+        let url = image_storage.save(id, data).await.map_err(|_| ImportBackupError::StorageFail)?;
+        image_repository.insert(&Image::new(id, &url)).await.map_err(|_| ImportBackupError::Unexpected)?;
+    }
+
+    for emotion in emotions.iter() {
+        emotion_repository.insert(emotion).await.map_err(|_| ImportBackupError::Unexpected)?;
+    }
+
+    for reaction in reactions.iter() {
+        reaction_repository.insert(reaction).await.map_err(|_| ImportBackupError::Unexpected)?;
+    }
+
+    result.imported_images = images.len();
+    result.imported_emotions = emotions.len();
+    result.imported_reactions = reactions.len();
+
+    Ok(result)
+}
+
+fn build_domain_images(images: Vec<BackupImageDto>) -> Result<Vec<(ImageId, ImageData)>, ImportBackupError> {
+    let mut ids = HashSet::new();
+    images.into_iter()
+        .map(|image| {
+            if !ids.insert(image.id.clone()) {
+                return Err(ImportBackupError::BadRequest(format!("Image: {} is duplicated", image.id)));
+            }
+
+            let malformed = || ImportBackupError::BadRequest(format!("Image: {} is malformed", image.id));
+            Ok((ImageId::try_from(image.id.clone()).map_err(|_| malformed())?, ImageData::try_from(image.data.clone()).map_err(|_| malformed())?))
+        })
+        .collect()
+}
+
+fn build_domain_emotions(emotions: Vec<EmotionDto>) -> Result<Vec<Emotion>, ImportBackupError> {
+    let mut ids = HashSet::new();
+    let mut names = HashSet::new();
+    emotions.into_iter()
+        .map(|emotion| {
+            let id = emotion.id.clone();
+            if !ids.insert(id.clone()) || !names.insert(emotion.name.clone()) {
+                return Err(ImportBackupError::BadRequest(format!("Emotion: {} is duplicated", id)));
+            }
+
+            emotion.try_into().map_err(|_| ImportBackupError::BadRequest(format!("Emotion: {} is malformed", id)))
+        })
+        .collect()
+}
+
+fn build_domain_reactions(reactions: Vec<ReactionDto>) -> Result<Vec<ReactionDefinition>, ImportBackupError> {
+    let mut ids = HashSet::new();
+    reactions.into_iter()
+        .map(|reaction| {
+            let id = reaction.id.clone();
+            if !ids.insert(id.clone()) {
+                return Err(ImportBackupError::BadRequest(format!("Reaction: {} is duplicated", id)));
+            }
+
+            reaction.try_into().map_err(|_| ImportBackupError::BadRequest(format!("Reaction: {} is malformed", id)))
+        })
+        .collect()
+}
+
+// A reaction is skipped when it clashes with a stored reaction or one imported before it, or uses a skipped emotion
+async fn is_conflicting_reaction(reaction: &ReactionDefinition, mode: &ImportBackupModeDto, imported_triggers: &[ReactionTrigger], skipped_emotion_ids: &HashSet<String>, reaction_repository: &dyn ReactionDefinitionRepository) -> bool {
+    if reaction.all_emotion_ids().iter().any(|emotion_id| skipped_emotion_ids.contains(&emotion_id.0)) {
+        return true;
+    }
+    if reaction.triggers.iter().any(|trigger| imported_triggers.iter().any(|imported_trigger| imported_trigger.conflicts_with(trigger))) {
+        return true;
+    }
+    if *mode == ImportBackupModeDto::Replace {
+        return false;
+    }
+    if reaction_repository.get(&reaction.id).await.is_some() {
+        return true;
+    }
+    for trigger in reaction.triggers.iter() {
+        if reaction_repository.exists_with_trigger(trigger).await {
+            return true;
+        }
+    }
+
+    false
+}
+
+fn validate_references(emotions: &[Emotion], reactions: &[ReactionDefinition], known_image_ids: &HashSet<String>, known_emotion_ids: &HashSet<String>) -> Result<(), ImportBackupError> {
+    for emotion in emotions {
+        if let Some(image_id) = emotion.all_image_ids().into_iter().find(|image_id| !known_image_ids.contains(&image_id.0)) {
+            return Err(ImportBackupError::MissingImage(format!("Emotion: {}", emotion.id.0), image_id.0.clone()));
+        }
+    }
+
+    for reaction in reactions {
+        if let Some(image_id) = reaction.all_image_ids().into_iter().find(|image_id| !known_image_ids.contains(&image_id.0)) {
+            return Err(ImportBackupError::MissingImage(format!("Reaction: {}", reaction.id.0), image_id.0.clone()));
+        }
+        if let Some(emotion_id) = reaction.all_emotion_ids().into_iter().find(|emotion_id| !known_emotion_ids.contains(&emotion_id.0)) {
+            return Err(ImportBackupError::MissingEmotion(format!("Reaction: {}", reaction.id.0), emotion_id.0.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+async fn clear_all(reaction_repository: &dyn ReactionDefinitionRepository, emotion_repository: &dyn EmotionRepository, image_repository: &dyn ImageRepository, image_storage: &dyn ImageStorage) -> Result<(), ImportBackupError> {
+    for reaction in reaction_repository.get_all().await {
+        reaction_repository.delete(&reaction.id).await.map_err(|_| ImportBackupError::Unexpected)?;
+    }
+
+    for emotion in emotion_repository.get_all().await {
+        emotion_repository.delete(&emotion.id).await.map_err(|_| ImportBackupError::Unexpected)?;
+    }
+
+    for image in image_repository.get_all().await {
+        match image_storage.delete(&image.url).await {
+            Ok(_) | Err(StorageDeleteError::Missing) => {},
+            Err(StorageDeleteError::Unexpected) => return Err(ImportBackupError::StorageFail),
+        }
+        image_repository.delete(&image.id).await.map_err(|_| ImportBackupError::Unexpected)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+    use crate::application::backup::export::export_backup;
+    use crate::application::emotions::dtos::emotion_dto::EmotionLayerDto;
+    use crate::application::images::create::{create_image, CreateImageRequest};
+    use crate::application::reactions::dtos::reaction_dto::{ReactionCooldownDto, ReactionPermissionLevelDto, ReactionTriggerDto, ChatTriggerMatchOptionsDto};
+    use crate::application::reactions::dtos::reaction_step_dto::{AnimationFrameDto, MovingReactionStepDto, ReactionStepDto, ReactionStepSkipDto, ReactionStepTextAlternativeDto, ReactionStepTextDto, TalkingReactionStepDto};
+    use crate::domain::emotions::emotion::EmotionId;
+    use crate::domain::emotions::emotion_repository::tests::setup_dummy_emotions;
+    use crate::domain::reactions::reaction_definition::ReactionDefinitionId;
+    use crate::domain::reactions::reaction_definition_repository::tests::setup_dummy_chat_command_reaction_definitions;
+    use crate::persistence::emotions::in_memory_emotion_repository::InMemoryEmotionRepository;
+    use crate::persistence::images::in_memory_image_repository::InMemoryImageRepository;
+    use crate::persistence::images::in_memory_image_storage::InMemoryImageStorage;
+    use crate::persistence::reactions::in_memory_reaction_repository::InMemoryReactionRepository;
+
+    struct Droid {
+        reactions: InMemoryReactionRepository,
+        emotions: InMemoryEmotionRepository,
+        images: InMemoryImageRepository,
+        storage: InMemoryImageStorage,
+    }
+
+    impl Droid {
+        fn new() -> Self {
+            Self { reactions: InMemoryReactionRepository::new(), emotions: InMemoryEmotionRepository::new(), images: InMemoryImageRepository::new(), storage: InMemoryImageStorage::new() }
+        }
+
+        async fn import(&self, backup: BackupDto, mode: ImportBackupModeDto) -> Result<ImportBackupResultDto, ImportBackupError> {
+            import_backup(ImportBackupRequest { backup, mode }, &self.reactions, &self.emotions, &self.images, &self.storage).await
+        }
+
+        async fn export(&self) -> BackupDto {
+            export_backup(&self.reactions, &self.emotions, &self.images, &self.storage).await.unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn import_exported_backup_into_empty_droid_recreates_everything() {
+        let source = Droid::new();
+        let target = Droid::new();
+        create_image(CreateImageRequest { id: String::from("image"), image: vec![1, 2, 3] }, &source.images, &source.storage).await.unwrap();
+        source.emotions.insert(&create_emotion_dto("happy", "image").try_into().unwrap()).await.unwrap();
+        source.reactions.insert(&create_reaction_dto("reaction", "happy", "image").try_into().unwrap()).await.unwrap();
+
+        let result = target.import(source.export().await, ImportBackupModeDto::Merge).await.unwrap();
+
+        assert_eq!((result.imported_images, result.imported_emotions, result.imported_reactions), (1, 1, 1));
+        assert!(result.conflicts.is_empty());
+        let image = target.images.get(&ImageId(String::from("image"))).await.unwrap();
+        assert_eq!(target.storage.get(&image.url).await.unwrap().0, vec![1, 2, 3]);
+        assert!(target.emotions.exists(&EmotionId(String::from("happy"))).await);
+        assert!(target.reactions.has(&ReactionDefinitionId(String::from("reaction"))));
+    }
+
+    #[tokio::test]
+    async fn import_backup_in_merge_mode_skips_and_reports_existing_ids() {
+        let target = Droid::new();
+        setup_dummy_emotions(vec!["happy"], &target.emotions).await;
+        let existing = setup_dummy_chat_command_reaction_definitions(vec!["!hello"], &target.reactions).await;
+        let backup = BackupDto {
+            reactions: vec![create_reaction_dto(&existing[0].id.0, "happy", "image"), create_reaction_dto("new", "happy", "image")],
+            emotions: vec![create_emotion_dto("happy", "image")],
+            images: vec![BackupImageDto { id: String::from("image"), data: vec![1] }],
+        };
+
+        let result = target.import(backup, ImportBackupModeDto::Merge).await.unwrap();
+
+        assert_eq!(result.conflicts, vec![String::from("Emotion: happy"), format!("Reaction: {}", existing[0].id.0)]);
+        assert_eq!((result.imported_images, result.imported_emotions, result.imported_reactions), (1, 0, 1));
+        assert_eq!(target.reactions.get_all().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn import_backup_in_merge_mode_skips_and_reports_existing_triggers() {
+        let target = Droid::new();
+        setup_dummy_emotions(vec!["happy"], &target.emotions).await;
+        setup_dummy_chat_command_reaction_definitions(vec!["!hello"], &target.reactions).await;
+        let backup = BackupDto {
+            reactions: vec![create_reaction_dto("hello", "happy", "image"), create_reaction_dto("new", "happy", "image")],
+            emotions: vec![],
+            images: vec![BackupImageDto { id: String::from("image"), data: vec![1] }],
+        };
+
+        let result = target.import(backup, ImportBackupModeDto::Merge).await.unwrap();
+
+        assert_eq!(result.conflicts, vec![String::from("Reaction: hello")]);
+        assert_eq!(result.imported_reactions, 1);
+        assert!(!target.reactions.has(&ReactionDefinitionId(String::from("hello"))));
+    }
+
+    #[tokio::test]
+    async fn import_backup_skips_and_reports_triggers_duplicated_in_the_backup() {
+        let target = Droid::new();
+        let mut duplicated = create_reaction_dto("duplicated", "happy", "image");
+        duplicated.triggers = vec![ReactionTriggerDto::ChatCommand(String::from("!first"), ChatTriggerMatchOptionsDto::default())];
+        let backup = BackupDto {
+            reactions: vec![create_reaction_dto("first", "happy", "image"), duplicated],
+            emotions: vec![create_emotion_dto("happy", "image")],
+            images: vec![BackupImageDto { id: String::from("image"), data: vec![1] }],
+        };
+
+        let result = target.import(backup, ImportBackupModeDto::Replace).await.unwrap();
+
+        assert_eq!(result.conflicts, vec![String::from("Reaction: duplicated")]);
+        assert_eq!(target.reactions.get_all().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn import_backup_in_merge_mode_reports_reactions_using_an_emotion_skipped_for_its_name() {
+        let target = Droid::new();
+        setup_dummy_emotions(vec!["happy"], &target.emotions).await;
+        let mut same_name = create_emotion_dto("other", "image");
+        same_name.name = String::from("happy_name");
+        let backup = BackupDto {
+            reactions: vec![create_reaction_dto("reaction", "other", "image"), create_reaction_dto("new", "happy", "image")],
+            emotions: vec![same_name],
+            images: vec![BackupImageDto { id: String::from("image"), data: vec![1] }],
+        };
+
+        let result = target.import(backup, ImportBackupModeDto::Merge).await.unwrap();
+
+        assert_eq!(result.conflicts, vec![String::from("Emotion: other"), String::from("Reaction: reaction")]);
+        assert_eq!((result.imported_emotions, result.imported_reactions), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn import_backup_in_replace_mode_removes_existing_configuration() {
+        let target = Droid::new();
+        setup_dummy_emotions(vec!["sad"], &target.emotions).await;
+        setup_dummy_chat_command_reaction_definitions(vec!["!hello"], &target.reactions).await;
+        create_image(CreateImageRequest { id: String::from("old"), image: vec![1] }, &target.images, &target.storage).await.unwrap();
+        let backup = BackupDto {
+            reactions: vec![create_reaction_dto("reaction", "happy", "image")],
+            emotions: vec![create_emotion_dto("happy", "image")],
+            images: vec![BackupImageDto { id: String::from("image"), data: vec![1] }],
+        };
+
+        let result = target.import(backup, ImportBackupModeDto::Replace).await.unwrap();
+
+        assert!(result.conflicts.is_empty());
+        assert_eq!(target.reactions.get_all().await.len(), 1);
+        assert!(!target.emotions.exists(&EmotionId(String::from("sad"))).await);
+        assert!(!target.images.has(&ImageId(String::from("old"))).await);
+        assert_eq!(target.storage.files_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn import_backup_referencing_missing_image_errors_without_changes() {
+        let target = Droid::new();
+        setup_dummy_emotions(vec!["sad"], &target.emotions).await;
+        let backup = BackupDto {
+            reactions: vec![],
+            emotions: vec![create_emotion_dto("happy", "not existing")],
+            images: vec![],
+        };
+
+        let result = target.import(backup, ImportBackupModeDto::Replace).await;
+
+        assert!(matches!(result, Err(ImportBackupError::MissingImage(source, image)) if source == "Emotion: happy" && image == "not existing"));
+        assert!(target.emotions.exists(&EmotionId(String::from("sad"))).await);
+    }
+
+    #[tokio::test]
+    async fn import_backup_referencing_missing_emotion_errors() {
+        let target = Droid::new();
+        let backup = BackupDto {
+            reactions: vec![create_reaction_dto("reaction", "happy", "image")],
+            emotions: vec![],
+            images: vec![BackupImageDto { id: String::from("image"), data: vec![1] }],
+        };
+
+        let result = target.import(backup, ImportBackupModeDto::Merge).await;
+
+        assert!(matches!(result, Err(ImportBackupError::MissingEmotion(source, emotion)) if source == "Reaction: reaction" && emotion == "happy"));
+        assert!(!target.images.has(&ImageId(String::from("image"))).await);
+    }
+
+    #[tokio::test]
+    async fn import_backup_in_merge_mode_can_reference_existing_emotions_and_images() {
+        let target = Droid::new();
+        setup_dummy_emotions(vec!["happy"], &target.emotions).await;
+        create_image(CreateImageRequest { id: String::from("image"), image: vec![1] }, &target.images, &target.storage).await.unwrap();
+        let backup = BackupDto {
+            reactions: vec![create_reaction_dto("reaction", "happy", "image")],
+            emotions: vec![],
+            images: vec![],
+        };
+
+        let result = target.import(backup, ImportBackupModeDto::Merge).await.unwrap();
+
+        assert_eq!(result.imported_reactions, 1);
+    }
+
+    #[tokio::test]
+    async fn import_backup_with_duplicated_ids_errors() {
+        let target = Droid::new();
+        let backup = BackupDto {
+            reactions: vec![],
+            emotions: vec![],
+            images: vec![BackupImageDto { id: String::from("image"), data: vec![1] }, BackupImageDto { id: String::from("image"), data: vec![2] }],
+        };
+
+        let result = target.import(backup, ImportBackupModeDto::Merge).await;
+
+        assert!(matches!(result, Err(ImportBackupError::BadRequest(_))));
+    }
+
+    fn create_emotion_dto(id: &str, image_id: &str) -> EmotionDto {
+        EmotionDto {
+            id: String::from(id),
+            name: format!("{}_name", id),
+            animation: vec![EmotionLayerDto::Mouth { mouth_mapping: HashMap::from([(String::from("ah"), String::from(image_id))]) }],
+        }
+    }
+
+    fn create_reaction_dto(id: &str, emotion_id: &str, image_id: &str) -> ReactionDto {
+        ReactionDto {
+            id: String::from(id),
+            is_disabled: false,
+            count: 3,
+            cooldown: ReactionCooldownDto::default(),
+            permission_level: ReactionPermissionLevelDto::Everyone,
+            triggers: vec![ReactionTriggerDto::ChatCommand(format!("!{}", id), ChatTriggerMatchOptionsDto::default())],
+            overlay_targets: vec![],
+            steps: vec![
+                ReactionStepDto::Moving(MovingReactionStepDto {
+                    animation: vec![AnimationFrameDto { frame_start: 0, frame_end: 1, image_id: String::from(image_id) }],
+                    skip: ReactionStepSkipDto::ImmediatelyAfter,
+                }),
+                ReactionStepDto::Talking(TalkingReactionStepDto {
+                    text: vec![ReactionStepTextAlternativeDto { probability: None, text: ReactionStepTextDto::Instant(String::from("hello")), conditions: vec![] }],
+                    emotion_id: String::from(emotion_id),
+                    skip: ReactionStepSkipDto::ImmediatelyAfter,
+                }),
+            ],
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod export;
+pub mod import;
+pub mod dtos;
\ No newline at end of file
@@ -1,15 +1,17 @@
 use std::sync::Arc;
 use crate::domain::brain::builder::PranDroidBrainBuilder;
+use crate::domain::brain::clock::Clock;
 use crate::domain::brain::pran_droid_brain::{PranDroidBrain, ReactionNotifier};
 use crate::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+use crate::domain::visemes::mouth_track::MouthTrackModel;
 
 pub trait TextPhonemiser: Send + Sync {
     fn phonemise_text(&self, text: &str) -> Vec<String>;
 }
 
-pub async fn create_droid_brain(reaction_repository: &dyn ReactionDefinitionRepository, text_phonemiser: &Arc<dyn TextPhonemiser>, reaction_notifier: &Arc<dyn ReactionNotifier>) -> PranDroidBrain {
+pub async fn create_droid_brain(reaction_repository: &dyn ReactionDefinitionRepository, text_phonemiser: &Arc<dyn TextPhonemiser>, reaction_notifier: &Arc<dyn ReactionNotifier>, clock: &Arc<dyn Clock>, mouth_track_model: &Arc<MouthTrackModel>) -> PranDroidBrain {
     let reactions = reaction_repository.get_all().await;
-    let mut brain_builder = PranDroidBrainBuilder::new(text_phonemiser.clone(), reaction_notifier.clone());
+    let mut brain_builder = PranDroidBrainBuilder::new(text_phonemiser.clone(), reaction_notifier.clone(), clock.clone(), mouth_track_model.clone());
 
     for reaction in reactions {
         brain_builder.with_reaction(reaction)
@@ -18,18 +20,26 @@ pub async fn create_droid_brain(reaction_repository: &dyn ReactionDefinitionRepo
     brain_builder.build()
 }
 
+pub async fn reload_droid_brain(brain: &mut PranDroidBrain, reaction_repository: &dyn ReactionDefinitionRepository) {
+    brain.reload(reaction_repository.get_all().await);
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::sync::Mutex;
+    use std::time::{Duration, Instant};
     use crate::application::reactions::update::{update_reaction, UpdateReactionRequest};
     use crate::domain::animations::animation::{Animation, AnimationFrame, AnimationFrames};
     use crate::domain::brain::pran_droid_brain::ReactionNotifier;
-    use crate::domain::brain::stimuli::{Action, ActionStimulus, ChatMessageStimulus, Source, Stimulus};
-    use crate::domain::emotions::emotion::EmotionId;
+    use crate::domain::brain::stimuli::{Action, ActionStimulus, ChatMessageStimulus, Source, Stimulus, StreamEvent, StreamEventStimulus};
+    use crate::domain::emotions::emotion::{EmotionId, MouthPositionName};
     use crate::domain::images::image::ImageId;
-    use crate::domain::reactions::reaction::{Milliseconds, TalkingReactionStep, Reaction, ReactionStepSkip, ReactionStep, ReactionStepText};
-    use crate::domain::reactions::reaction_definition::{MovingReactionStepDefinition, ReactionDefinition, ReactionDefinitionId, ReactionStepDefinition, ReactionStepMessageAlternativeDefinition, ReactionStepMessageAlternativesDefinition, TalkingReactionStepDefinition};
-    use crate::domain::reactions::reaction_definition_repository::tests::{setup_dummy_action_reaction_definitions, setup_dummy_chat_command_reaction_definitions, setup_dummy_chat_keyword_reaction_definitions};
+    use crate::domain::reactions::reaction::{ChatMessageReactionStep, Milliseconds, TalkingReactionStep, Reaction, ReactionStepSkip, ReactionStep, ReactionStepText};
+    use crate::domain::reactions::reaction_definition::{ChatMessageReactionStepDefinition, MovingReactionStepDefinition, ReactionCondition, ReactionCooldown, ChatTriggerMatchOptions, ReactionDefinition, ReactionDefinitionId, ReactionPermissionLevel, ReactionStepDefinition, ReactionStepMessageAlternativeDefinition, ReactionStepMessageAlternativesDefinition, ReactionTrigger, StreamEventKind, TalkingReactionStepDefinition};
+    use crate::domain::reactions::reaction_definition_repository::tests::{setup_dummy_action_reaction_definitions, setup_dummy_chat_command_reaction_definitions, setup_dummy_chat_keyword_reaction_definitions, setup_dummy_chat_pattern_reaction_definitions};
+    use crate::domain::visemes::mouth_track::{PhonemeDurations, TimedMouthPosition};
+    use crate::domain::visemes::viseme_mapping::VisemeMapping;
     use crate::persistence::reactions::in_memory_reaction_repository::InMemoryReactionRepository;
     use super::*;
 
@@ -39,7 +49,7 @@ mod tests {
         let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
         setup_dummy_chat_command_reaction_definitions(vec!["!hello", "!hug"], &reaction_repository).await;
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction_hello = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
         let reaction_hug = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hug"));
@@ -61,7 +71,7 @@ mod tests {
             ..Default::default()
         }, &reaction_repository).await.expect("update should have worked");
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction_hello = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
         let reaction_hug = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hug"));
@@ -78,7 +88,7 @@ mod tests {
         let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
         setup_dummy_chat_command_reaction_definitions(vec!["!hello"], &reaction_repository).await;
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction_start = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
         let reaction_start_connected = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!helloSome"));
@@ -95,7 +105,7 @@ mod tests {
         let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
         setup_dummy_chat_keyword_reaction_definitions(vec!["hello message"], &reaction_repository).await;
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction_not_contain = stimulate_with_chat_message(&mut brain, |stimulus|
             stimulus.text = String::from("message hello"));
@@ -109,13 +119,151 @@ mod tests {
         assert!(reaction_contains.is_some());
     }
 
+    #[tokio::test]
+    async fn create_droid_brain_chat_keyword_case_insensitive_react_regardless_of_case() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        setup_keyword_reaction_definition_with_options("hello", ChatTriggerMatchOptions { case_sensitive: false, ..Default::default() }, &reaction_repository).await;
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction_upper = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("HELLO everyone"));
+        let reaction_mixed = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("well HeLLo"));
+        let reaction_punctuation = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("Hello!"));
+
+        assert!(reaction_upper.is_some());
+        assert!(reaction_mixed.is_some());
+        assert!(reaction_punctuation.is_none());
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_chat_keyword_case_sensitive_by_default() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        setup_dummy_chat_keyword_reaction_definitions(vec!["hello"], &reaction_repository).await;
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction_upper = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("HELLO"));
+        let reaction_same_case = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("hello"));
+
+        assert!(reaction_upper.is_none());
+        assert!(reaction_same_case.is_some());
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_chat_keyword_punctuation_as_boundary_react_next_to_punctuation() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        setup_keyword_reaction_definition_with_options("hello", ChatTriggerMatchOptions { punctuation_as_boundary: true, ..Default::default() }, &reaction_repository).await;
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction_exclamation = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("hello!"));
+        let reaction_comma = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("well,hello, everyone"));
+        let reaction_parenthesis = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("(hello)"));
+        let reaction_touch_other = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("hellooo!"));
+
+        assert!(reaction_exclamation.is_some());
+        assert!(reaction_comma.is_some());
+        assert!(reaction_parenthesis.is_some());
+        assert!(reaction_touch_other.is_none());
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_chat_keyword_normalise_unicode_react_ignoring_accents_and_compatibility_forms() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        setup_keyword_reaction_definition_with_options("café", ChatTriggerMatchOptions { normalise_unicode: true, ..Default::default() }, &reaction_repository).await;
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction_without_accent = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("a cafe please"));
+        let reaction_decomposed = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("a cafe\u{301} please"));
+        let reaction_full_width = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("ｃａｆｅ"));
+
+        assert!(reaction_without_accent.is_some());
+        assert!(reaction_decomposed.is_some());
+        assert!(reaction_full_width.is_some());
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_chat_command_with_options_react_to_differently_written_command() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = ReactionDefinition::new_empty(
+            ReactionDefinitionId(String::from("0")),
+            ReactionTrigger::new_chat_command_with_options(String::from("!hello"), ChatTriggerMatchOptions {
+                case_sensitive: false,
+                punctuation_as_boundary: true,
+                normalise_unicode: false,
+            }).unwrap(),
+        );
+        reaction_definition.steps.push(create_talking_step_definition(None));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction_upper = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!HELLO"));
+        let reaction_punctuation = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!Hello, friend"));
+        let reaction_connected = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!helloSome"));
+        let reaction_not_start = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("some words !hello"));
+
+        assert!(reaction_upper.is_some());
+        assert!(reaction_punctuation.is_some());
+        assert!(reaction_connected.is_none());
+        assert!(reaction_not_start.is_none());
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_chat_pattern_react_if_message_matches_it() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        setup_dummy_chat_pattern_reaction_definitions(vec!["(?i)good (morning|night)"], &reaction_repository).await;
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction_not_matching = stimulate_with_chat_message(&mut brain, |stimulus|
+            stimulus.text = String::from("good evening"));
+        let reaction_matching = stimulate_with_chat_message(&mut brain, |stimulus|
+            stimulus.text = String::from("well, GOOD MORNING everyone"));
+
+        assert!(reaction_not_matching.is_none());
+        assert!(reaction_matching.is_some());
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_chat_pattern_lower_priority_than_keyword() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+
+        let mut pattern_reaction_definition = ReactionDefinition::new_empty(
+            ReactionDefinitionId(String::from("pattern")),
+            ReactionTrigger::new_chat_pattern(String::from("keyword")).unwrap(),
+        );
+        pattern_reaction_definition.steps.push(create_talking_step_definition(None));
+        reaction_repository.insert(&pattern_reaction_definition).await.unwrap();
+
+        let mut keyword_reaction_definition = create_keyword_reaction_definition("some keyword");
+        keyword_reaction_definition.steps.push(create_talking_step_definition(None));
+        reaction_repository.insert(&keyword_reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("some keyword"));
+        assert!(matches!(reaction, Some(reaction) if reaction.source_definition_id == keyword_reaction_definition.id));
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("other keyword"));
+        assert!(matches!(reaction, Some(reaction) if reaction.source_definition_id == pattern_reaction_definition.id));
+    }
+
     #[tokio::test]
     async fn create_droid_brain_action_react_if_name_and_id_matches() {
         let reaction_repository = InMemoryReactionRepository::new();
         let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
         setup_dummy_action_reaction_definitions(vec![("action id", "action name")], &reaction_repository).await;
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction_different_id = stimulate_with_action(&mut brain, |stimulus|
             stimulus.action.name = String::from("action name"));
@@ -157,7 +305,7 @@ mod tests {
         }));
         reaction_repository.insert(&reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
 
@@ -201,7 +349,7 @@ mod tests {
         }));
         reaction_repository.insert(&reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
 
@@ -223,6 +371,103 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn create_droid_brain_reacts_to_stimulus_with_defined_composite_talking_steps() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = ReactionDefinition::new_empty(
+            ReactionDefinitionId(String::from("0")),
+            ReactionTrigger::new_chat_command(String::from("!hello")).unwrap(),
+        );
+        reaction_definition.steps.push(ReactionStepDefinition::CompositeTalking(vec![
+            TalkingReactionStepDefinition {
+                skip: ReactionStepSkip::ImmediatelyAfter,
+                alternatives: ReactionStepMessageAlternativesDefinition::new_single(ReactionStepText::LetterByLetter(String::from("hi ${user}"))),
+                emotion_id: EmotionId(String::from("an emotion id"))
+            },
+            TalkingReactionStepDefinition {
+                skip: ReactionStepSkip::AfterMilliseconds(Milliseconds(15)),
+                alternatives: ReactionStepMessageAlternativesDefinition::new_single(ReactionStepText::Instant(String::from("bye"))),
+                emotion_id: EmotionId(String::from("an emotion id2"))
+            },
+        ]));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| {
+            stimulus.text = String::from("!hello");
+            stimulus.source.user_name = String::from("pran");
+        }).expect("Expected a reaction");
+
+        assert_eq!(reaction.steps.len(), 1);
+        if let ReactionStep::CompositeTalking(talking_steps) = &reaction.steps[0] {
+            assert_eq!(talking_steps.len(), 2);
+            assert!(matches!(talking_steps[0].text, ReactionStepText::LetterByLetter(ref text) if text == "hi pran"));
+            assert_eq!(talking_steps[0].phonemes, vec!["h", "i", " ", "p", "r", "a", "n"]);
+            assert!(matches!(talking_steps[1].skip, ReactionStepSkip::AfterMilliseconds(Milliseconds(15))));
+            assert!(matches!(talking_steps[1].emotion_id, EmotionId(ref emotion_id) if emotion_id == "an emotion id2"));
+        } else {
+            unreachable!("should have reacted with a composite talking step");
+        }
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_reacts_to_stimulus_with_chat_message_steps() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = ReactionDefinition::new_empty(
+            ReactionDefinitionId(String::from("0")),
+            ReactionTrigger::new_chat_command(String::from("!hello")).unwrap(),
+        );
+        reaction_definition.steps.push(ReactionStepDefinition::ChatMessage(ChatMessageReactionStepDefinition {
+            alternatives: ReactionStepMessageAlternativesDefinition::new_single(ReactionStepText::Instant(String::from("hi ${user}"))),
+            reply: true
+        }));
+        reaction_definition.steps.push(ReactionStepDefinition::ChatMessage(ChatMessageReactionStepDefinition {
+            alternatives: ReactionStepMessageAlternativesDefinition::new_single(ReactionStepText::LetterByLetter(String::from("welcome"))),
+            reply: false
+        }));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| {
+            stimulus.text = String::from("!hello");
+            stimulus.source.user_name = String::from("pran");
+            stimulus.message_id = Some(String::from("a message id"));
+        }).expect("Expected a reaction");
+
+        assert_eq!(reaction.steps.len(), 2);
+        assert!(matches!(&reaction.steps[0], ReactionStep::ChatMessage(ChatMessageReactionStep { text, reply_to_message_id: Some(message_id) }) if text == "hi pran" && message_id == "a message id"));
+        assert!(matches!(&reaction.steps[1], ReactionStep::ChatMessage(ChatMessageReactionStep { text, reply_to_message_id: None }) if text == "welcome"));
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_chat_message_step_does_not_reply_to_actions() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = ReactionDefinition::new_empty(
+            ReactionDefinitionId(String::from("0")),
+            ReactionTrigger::new_action(String::from("an action id"), String::from("an action")).unwrap(),
+        );
+        reaction_definition.steps.push(ReactionStepDefinition::ChatMessage(ChatMessageReactionStepDefinition {
+            alternatives: ReactionStepMessageAlternativesDefinition::new_single(ReactionStepText::Instant(String::from("thanks ${user}"))),
+            reply: true
+        }));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_action(&mut brain, |stimulus| {
+            stimulus.action.id = String::from("an action id");
+            stimulus.action.name = String::from("an action");
+            stimulus.source.user_name = String::from("pran");
+        }).expect("Expected a reaction");
+
+        assert!(matches!(&reaction.steps[0], ReactionStep::ChatMessage(ChatMessageReactionStep { text, reply_to_message_id: None }) if text == "thanks pran"));
+    }
+
     #[tokio::test]
     async fn create_droid_brain_talking_reaction_phonemise_text() {
         let reaction_repository = InMemoryReactionRepository::new();
@@ -238,7 +483,7 @@ mod tests {
         }));
         reaction_repository.insert(&reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
 
@@ -253,6 +498,36 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn create_droid_brain_talking_reaction_with_mouth_track_from_the_brain_model() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = ReactionDefinition::new_empty(
+            ReactionDefinitionId(String::from("0")),
+            ReactionTrigger::new_chat_command(String::from("!hello")).unwrap(),
+        );
+        reaction_definition.steps.push(ReactionStepDefinition::Talking(TalkingReactionStepDefinition {
+            skip: ReactionStepSkip::ImmediatelyAfter,
+            alternatives: ReactionStepMessageAlternativesDefinition::new_single(ReactionStepText::Instant(String::from("bob"))),
+            emotion_id: EmotionId(String::from("an emotion id"))
+        }));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mouth_track_model = Arc::new(MouthTrackModel {
+            mapping: VisemeMapping::new(HashMap::from([(String::from("b"), vec![MouthPositionName::B]), (String::from("o"), vec![MouthPositionName::Oh])])),
+            durations: PhonemeDurations { vowel_ms: 100, consonant_ms: 50, pause_ms: 200 },
+        });
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &mouth_track_model).await;
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
+
+        assert!(matches!(&reaction.unwrap().steps[..], [ReactionStep::Talking(TalkingReactionStep { mouth_track, .. })] if mouth_track == &vec![
+            TimedMouthPosition { position: MouthPositionName::B, start_ms: 0, duration_ms: 50 },
+            TimedMouthPosition { position: MouthPositionName::Oh, start_ms: 50, duration_ms: 50 },
+            TimedMouthPosition { position: MouthPositionName::B, start_ms: 100, duration_ms: 50 },
+        ]));
+    }
+
     #[tokio::test]
     async fn create_droid_brain_talking_reaction_interpolate_chat_message_with_user() {
         let reaction_repository = InMemoryReactionRepository::new();
@@ -268,7 +543,7 @@ mod tests {
         }));
         reaction_repository.insert(&reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| {
             stimulus.text = String::from("!hello");
@@ -300,7 +575,7 @@ mod tests {
         }));
         reaction_repository.insert(&reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello Pmyl"));
 
@@ -330,7 +605,7 @@ mod tests {
         }));
         reaction_repository.insert(&reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!count"));
 
@@ -370,7 +645,7 @@ mod tests {
         }));
         reaction_repository.insert(&reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| {
             stimulus.text = String::from("!hello");
@@ -402,7 +677,7 @@ mod tests {
         }));
         reaction_repository.insert(&reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| {
             stimulus.text = String::from("!hello PranDroid");
@@ -419,6 +694,110 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn create_droid_brain_talking_reaction_interpolate_chat_message_with_arguments() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = create_command_reaction_definition("!hello");
+        reaction_definition.steps.push(create_talking_step_definition(Some("${arg2} then ${args}")));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello first second  third"));
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "second then first second third"));
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello first"));
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == " then first"));
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_talking_reaction_interpolate_chat_message_with_default_if_target_missing() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = create_command_reaction_definition("!hello");
+        reaction_definition.steps.push(create_talking_step_definition(Some("Hello ${target|someone}!")));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "Hello someone!"));
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_talking_reaction_interpolate_chat_message_with_filters() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = create_command_reaction_definition("!hello");
+        reaction_definition.steps.push(create_talking_step_definition(Some("${user:upper}, ${count:ordinal} time")));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| {
+            stimulus.text = String::from("!hello");
+            stimulus.source.user_name = String::from("Pmyl");
+        });
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "PMYL, 1st time"));
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_talking_reaction_with_milestone_alternative_uses_it_only_on_milestone_count() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = create_command_reaction_definition("!hug");
+        reaction_definition.count = 998;
+        reaction_definition.steps.push(ReactionStepDefinition::Talking(TalkingReactionStepDefinition {
+            skip: ReactionStepSkip::ImmediatelyAfter,
+            alternatives: ReactionStepMessageAlternativesDefinition::try_new(vec![
+                ReactionStepMessageAlternativeDefinition {
+                    message: ReactionStepText::LetterByLetter(String::from("hug")),
+                    probability: None,
+                    conditions: vec![]
+                },
+                ReactionStepMessageAlternativeDefinition {
+                    message: ReactionStepText::LetterByLetter(String::from("hug number ${count}!")),
+                    probability: None,
+                    conditions: vec![ReactionCondition::CountEquals(1000)]
+                },
+            ]).unwrap(),
+            emotion_id: EmotionId(String::from("an emotion id"))
+        }));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hug"));
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "hug"));
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hug"));
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "hug number 1000!"));
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_talking_reaction_interpolate_chat_message_with_pattern_named_captures() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = ReactionDefinition::new_empty(
+            ReactionDefinitionId(String::from("0")),
+            ReactionTrigger::new_chat_pattern(String::from("^!so @?(?P<streamer>\\w+)(?: (?P<game>.+))?")).unwrap(),
+        );
+        reaction_definition.steps.push(create_talking_step_definition(Some("Go follow ${streamer}, playing ${game}! ${user} approves")));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| {
+            stimulus.text = String::from("!so @pran some game");
+            stimulus.source.user_name = String::from("Pyromyl");
+        });
+        assert!(matches!(reaction.unwrap().steps.get(0).unwrap(),
+            ReactionStep::Talking(talking) if talking.text.get_text() == "Go follow pran, playing some game! Pyromyl approves"));
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!so pran"));
+        assert!(matches!(reaction.unwrap().steps.get(0).unwrap(),
+            ReactionStep::Talking(talking) if talking.text.get_text() == "Go follow pran, playing ${game}! _a name_ approves"));
+    }
+
     #[tokio::test]
     async fn create_droid_brain_talking_reaction_interpolate_chat_message_before_phonemising_text() {
         let reaction_repository = InMemoryReactionRepository::new();
@@ -434,7 +813,7 @@ mod tests {
         }));
         reaction_repository.insert(&reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| {
             stimulus.text = String::from("!hello");
@@ -455,7 +834,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn create_droid_brain_talking_reaction_with_target_not_react_if_target_is_not_specified() {
+    async fn create_droid_brain_talking_reaction_with_target_renders_nothing_if_target_is_not_specified() {
         let reaction_repository = InMemoryReactionRepository::new();
         let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
         let mut reaction_definition = ReactionDefinition::new_empty(
@@ -469,11 +848,11 @@ mod tests {
         }));
         reaction_repository.insert(&reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
 
-        assert!(reaction.is_none());
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "Hello !"));
     }
 
     #[tokio::test]
@@ -493,7 +872,7 @@ mod tests {
         reaction_repository.insert(&reaction_definition).await.unwrap();
         let fake_notifier = Arc::new(FakeNotifier::new());
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &(fake_notifier.clone() as Arc<dyn ReactionNotifier>)).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &(fake_notifier.clone() as Arc<dyn ReactionNotifier>), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!acommand"));
         stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!acommand"));
@@ -512,7 +891,7 @@ mod tests {
         command_reaction_definition.steps.push(create_talking_step_definition(Some("Hi ${user}")));
         reaction_repository.insert(&command_reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello ${touser}")).expect("reaction expected");
         assert!(matches!(reaction.steps.get(0).unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "Hi ${touser}"));
@@ -554,7 +933,7 @@ mod tests {
         command_reaction_definition.steps.push(create_talking_step_definition(Some("${not} keyword ${existing} $")));
         reaction_repository.insert(&command_reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("keyword")).expect("reaction expected");
         assert!(matches!(reaction.steps.get(0).unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "${not} keyword ${existing} $"));
@@ -573,7 +952,7 @@ mod tests {
         keyword_reaction_definition.steps.push(create_talking_step_definition(None));
         reaction_repository.insert(&keyword_reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
         assert!(matches!(reaction, Some(reaction) if reaction.source_definition_id == command_reaction_definition.id));
@@ -597,11 +976,13 @@ mod tests {
                 alternatives: ReactionStepMessageAlternativesDefinition::try_new(vec![
                     ReactionStepMessageAlternativeDefinition {
                         message: ReactionStepText::LetterByLetter("first message".to_string()),
-                        probability: Some(0.0)
+                        probability: Some(0.0),
+                        conditions: vec![]
                     },
                     ReactionStepMessageAlternativeDefinition {
                         message: ReactionStepText::LetterByLetter("second message".to_string()),
-                        probability: Some(100.0)
+                        probability: Some(100.0),
+                        conditions: vec![]
                     },
                 ]).unwrap(),
                 emotion_id: EmotionId(String::from("an emotion id"))
@@ -609,7 +990,7 @@ mod tests {
         );
         reaction_repository.insert(&reaction_definition).await.unwrap();
 
-        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier()).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
 
         let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
         let reaction = reaction.expect("should get a reaction");
@@ -620,6 +1001,373 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn create_droid_brain_reaction_in_global_cooldown_not_react() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = create_command_reaction_definition("!hello");
+        reaction_definition.cooldown = ReactionCooldown { global: Duration::from_secs(10), per_user: Duration::ZERO };
+        reaction_definition.steps.push(create_talking_step_definition(None));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+        let fake_clock = Arc::new(FakeClock::new());
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &(fake_clock.clone() as Arc<dyn Clock>), &create_dummy_mouth_track_model()).await;
+
+        let reaction_first = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
+        fake_clock.advance(Duration::from_secs(9));
+        let reaction_other_user = stimulate_with_chat_message(&mut brain, |stimulus| {
+            stimulus.text = String::from("!hello");
+            stimulus.source.user_name = String::from("another user");
+        });
+        fake_clock.advance(Duration::from_secs(1));
+        let reaction_after_cooldown = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
+
+        assert!(reaction_first.is_some());
+        assert!(reaction_other_user.is_none());
+        assert!(reaction_after_cooldown.is_some());
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_reaction_in_user_cooldown_react_only_to_other_users() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = create_command_reaction_definition("!hello");
+        reaction_definition.cooldown = ReactionCooldown { global: Duration::ZERO, per_user: Duration::from_secs(30) };
+        reaction_definition.steps.push(create_talking_step_definition(None));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+        let fake_clock = Arc::new(FakeClock::new());
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &(fake_clock.clone() as Arc<dyn Clock>), &create_dummy_mouth_track_model()).await;
+
+        let reaction_first = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
+        let reaction_same_user = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
+        let reaction_other_user = stimulate_with_chat_message(&mut brain, |stimulus| {
+            stimulus.text = String::from("!hello");
+            stimulus.source.user_name = String::from("another user");
+        });
+        fake_clock.advance(Duration::from_secs(30));
+        let reaction_same_user_after_cooldown = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
+
+        assert!(reaction_first.is_some());
+        assert!(reaction_same_user.is_none());
+        assert!(reaction_other_user.is_some());
+        assert!(reaction_same_user_after_cooldown.is_some());
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_reaction_in_cooldown_not_count_nor_notify_usage() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = create_command_reaction_definition("!acommand");
+        reaction_definition.cooldown = ReactionCooldown { global: Duration::from_secs(10), per_user: Duration::ZERO };
+        reaction_definition.steps.push(create_talking_step_definition(Some("${count}")));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+        let fake_notifier = Arc::new(FakeNotifier::new());
+        let fake_clock = Arc::new(FakeClock::new());
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &(fake_notifier.clone() as Arc<dyn ReactionNotifier>), &(fake_clock.clone() as Arc<dyn Clock>), &create_dummy_mouth_track_model()).await;
+
+        stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!acommand"));
+        stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!acommand"));
+        fake_clock.advance(Duration::from_secs(10));
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!acommand"));
+
+        assert_eq!(fake_notifier.count_notifications.lock().unwrap().to_vec(), vec![1, 2]);
+        assert!(matches!(reaction.unwrap().steps.get(0).unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "2"));
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_reaction_with_permission_level_react_only_to_allowed_sources() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = create_command_reaction_definition("!hello");
+        reaction_definition.permission_level = ReactionPermissionLevel::Vips;
+        reaction_definition.steps.push(create_talking_step_definition(None));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction_everyone = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
+        let reaction_subscriber = stimulate_with_chat_message(&mut brain, |stimulus| {
+            stimulus.text = String::from("!hello");
+            stimulus.source.is_subscriber = true;
+        });
+        let reaction_vip = stimulate_with_chat_message(&mut brain, |stimulus| {
+            stimulus.text = String::from("!hello");
+            stimulus.source.is_vip = true;
+        });
+        let reaction_mod = stimulate_with_chat_message(&mut brain, |stimulus| {
+            stimulus.text = String::from("!hello");
+            stimulus.source.is_mod = true;
+        });
+        let reaction_broadcaster = stimulate_with_chat_message(&mut brain, |stimulus| {
+            stimulus.text = String::from("!hello");
+            stimulus.source.is_broadcaster = true;
+        });
+
+        assert!(reaction_everyone.is_none());
+        assert!(reaction_subscriber.is_none());
+        assert!(reaction_vip.is_some());
+        assert!(reaction_mod.is_some());
+        assert!(reaction_broadcaster.is_some());
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_reaction_not_allowed_for_source_fallback_to_next_matching_reaction() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+
+        let mut mods_reaction_definition = create_command_reaction_definition("!hello");
+        mods_reaction_definition.permission_level = ReactionPermissionLevel::Mods;
+        mods_reaction_definition.steps.push(create_talking_step_definition(None));
+        reaction_repository.insert(&mods_reaction_definition).await.unwrap();
+
+        let mut keyword_reaction_definition = create_keyword_reaction_definition("hello");
+        keyword_reaction_definition.steps.push(create_talking_step_definition(None));
+        reaction_repository.insert(&keyword_reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello hello"));
+        assert!(matches!(reaction, Some(reaction) if reaction.source_definition_id == keyword_reaction_definition.id));
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| {
+            stimulus.text = String::from("!hello hello");
+            stimulus.source.is_mod = true;
+        });
+        assert!(matches!(reaction, Some(reaction) if reaction.source_definition_id == mods_reaction_definition.id));
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_action_with_permission_level_not_react_to_not_allowed_source() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = ReactionDefinition::new_empty(
+            ReactionDefinitionId(String::from("0")),
+            ReactionTrigger::new_action(String::from("action id"), String::from("action name")).unwrap(),
+        );
+        reaction_definition.permission_level = ReactionPermissionLevel::Subscribers;
+        reaction_definition.steps.push(create_talking_step_definition(None));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction_everyone = stimulate_with_action(&mut brain, |stimulus| {
+            stimulus.action.id = String::from("action id");
+            stimulus.action.name = String::from("action name");
+        });
+        let reaction_subscriber = stimulate_with_action(&mut brain, |stimulus| {
+            stimulus.action.id = String::from("action id");
+            stimulus.action.name = String::from("action name");
+            stimulus.source.is_subscriber = true;
+        });
+
+        assert!(reaction_everyone.is_none());
+        assert!(reaction_subscriber.is_some());
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_action_range_reacts_to_best_matching_tier() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let small_tier = create_action_range_reaction_definition("bits", 1, Some(99));
+        let medium_tier = create_action_range_reaction_definition("bits", 100, None);
+        let big_tier = create_action_range_reaction_definition("bits", 500, None);
+        for reaction_definition in [&small_tier, &medium_tier, &big_tier] {
+            reaction_repository.insert(reaction_definition).await.unwrap();
+        }
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let stimulate_with_bits = |brain: &mut PranDroidBrain, bits: &str| stimulate_with_action(brain, |stimulus| {
+            stimulus.action.id = bits.to_string();
+            stimulus.action.name = String::from("bits");
+        });
+        assert!(matches!(stimulate_with_bits(&mut brain, "50"), Some(reaction) if reaction.source_definition_id == small_tier.id));
+        assert!(matches!(stimulate_with_bits(&mut brain, "100"), Some(reaction) if reaction.source_definition_id == medium_tier.id));
+        assert!(matches!(stimulate_with_bits(&mut brain, "499"), Some(reaction) if reaction.source_definition_id == medium_tier.id));
+        assert!(matches!(stimulate_with_bits(&mut brain, "10000"), Some(reaction) if reaction.source_definition_id == big_tier.id));
+        assert!(stimulate_with_bits(&mut brain, "0").is_none());
+        assert!(stimulate_with_bits(&mut brain, "a lot").is_none());
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_action_exact_trigger_wins_over_range() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let range_reaction_definition = create_action_range_reaction_definition("bits", 100, None);
+        let mut exact_reaction_definition = ReactionDefinition::new_empty(
+            ReactionDefinitionId(String::from("exact")),
+            ReactionTrigger::new_action(String::from("1000"), String::from("bits")).unwrap(),
+        );
+        exact_reaction_definition.steps.push(create_talking_step_definition(None));
+        reaction_repository.insert(&range_reaction_definition).await.unwrap();
+        reaction_repository.insert(&exact_reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_action(&mut brain, |stimulus| {
+            stimulus.action.id = String::from("1000");
+            stimulus.action.name = String::from("bits");
+        });
+        assert!(matches!(reaction, Some(reaction) if reaction.source_definition_id == exact_reaction_definition.id));
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_talking_reaction_interpolate_action_input_and_amount() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = create_action_range_reaction_definition("bits", 1, None);
+        reaction_definition.steps = vec![create_talking_step_definition(Some("${amount} bits: ${input|no message}"))];
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_action(&mut brain, |stimulus| {
+            stimulus.action.id = String::from("250");
+            stimulus.action.name = String::from("bits");
+            stimulus.input = Some(String::from("Cheer250 hello"));
+        });
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "250 bits: Cheer250 hello"));
+        let reaction = stimulate_with_action(&mut brain, |stimulus| {
+            stimulus.action.id = String::from("250");
+            stimulus.action.name = String::from("bits");
+        });
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "250 bits: no message"));
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_stream_event_reacts_to_matching_kind() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let subscription_reaction_definition = create_stream_event_reaction_definition(StreamEventKind::Subscription, "Thanks ${user}!");
+        let raid_reaction_definition = create_stream_event_reaction_definition(StreamEventKind::Raid, "${viewers} raiders from ${user}!");
+        reaction_repository.insert(&subscription_reaction_definition).await.unwrap();
+        reaction_repository.insert(&raid_reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_stream_event(&mut brain, StreamEvent::Raid { viewers: 42 });
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "42 raiders from _a name_!"));
+        let reaction = stimulate_with_stream_event(&mut brain, StreamEvent::Subscription { months: 1 });
+        assert!(matches!(reaction, Some(reaction) if reaction.source_definition_id == subscription_reaction_definition.id));
+        assert!(stimulate_with_stream_event(&mut brain, StreamEvent::MysteryGift { count: 5 }).is_none());
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_stream_event_resubscription_falls_back_to_subscription_reaction() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let subscription_reaction_definition = create_stream_event_reaction_definition(StreamEventKind::Subscription, "${months:ordinal} month");
+        reaction_repository.insert(&subscription_reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+        let reaction = stimulate_with_stream_event(&mut brain, StreamEvent::Resubscription { months: 3 });
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "3rd month"));
+
+        let resubscription_reaction_definition = create_stream_event_reaction_definition(StreamEventKind::Resubscription, "Welcome back");
+        reaction_repository.insert(&resubscription_reaction_definition).await.unwrap();
+        reload_droid_brain(&mut brain, &reaction_repository).await;
+        let reaction = stimulate_with_stream_event(&mut brain, StreamEvent::Resubscription { months: 3 });
+        assert!(matches!(reaction, Some(reaction) if reaction.source_definition_id == resubscription_reaction_definition.id));
+    }
+
+    #[tokio::test]
+    async fn create_droid_brain_talking_reaction_interpolate_gift_recipient_as_target() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let reaction_definition = create_stream_event_reaction_definition(StreamEventKind::GiftSubscription, "${user} gifted ${touser}");
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+
+        let reaction = stimulate_with_stream_event(&mut brain, StreamEvent::GiftSubscription { recipient: String::from("Pmyl"), months: 1 });
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "_a name_ gifted Pmyl"));
+    }
+
+    #[tokio::test]
+    async fn reload_droid_brain_reacts_to_added_reactions_and_not_to_removed_ones() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let reactions = setup_dummy_chat_command_reaction_definitions(vec!["!hello"], &reaction_repository).await;
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+        reaction_repository.delete(&reactions.first().unwrap().id).await.unwrap();
+        setup_dummy_chat_command_reaction_definitions(vec!["!hug"], &reaction_repository).await;
+
+        reload_droid_brain(&mut brain, &reaction_repository).await;
+
+        let reaction_hello = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hello"));
+        let reaction_hug = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!hug"));
+        assert!(reaction_hello.is_none());
+        assert!(reaction_hug.is_some());
+    }
+
+    #[tokio::test]
+    async fn reload_droid_brain_keeps_usage_counts_not_yet_stored() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = create_command_reaction_definition("!acommand");
+        reaction_definition.count = 3;
+        reaction_definition.steps.push(create_talking_step_definition(Some("${count}")));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+        stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!acommand"));
+        stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!acommand"));
+
+        reload_droid_brain(&mut brain, &reaction_repository).await;
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!acommand"));
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "6"));
+    }
+
+    #[tokio::test]
+    async fn reload_droid_brain_uses_stored_count_when_edited_elsewhere() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = create_command_reaction_definition("!acommand");
+        reaction_definition.count = 3;
+        reaction_definition.steps.push(create_talking_step_definition(Some("${count}")));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+        stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!acommand"));
+        update_reaction(UpdateReactionRequest {
+            id: reaction_definition.id.0.clone(),
+            count: Some(0),
+            ..Default::default()
+        }, &reaction_repository).await.expect("update should have worked");
+
+        reload_droid_brain(&mut brain, &reaction_repository).await;
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!acommand"));
+        assert!(matches!(reaction.unwrap().steps.first().unwrap(), ReactionStep::Talking(talking) if talking.text.get_text() == "1"));
+    }
+
+    #[tokio::test]
+    async fn reload_droid_brain_keeps_reactions_in_cooldown() {
+        let reaction_repository = InMemoryReactionRepository::new();
+        let text_phonemiser: Arc<dyn TextPhonemiser> = Arc::new(SplitLettersTextPhonemiser {});
+        let mut reaction_definition = create_command_reaction_definition("!acommand");
+        reaction_definition.cooldown = ReactionCooldown { global: Duration::from_secs(10), per_user: Duration::ZERO };
+        reaction_definition.steps.push(create_talking_step_definition(None));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+        let mut brain = create_droid_brain(&reaction_repository, &text_phonemiser, &create_dummy_notifier(), &create_dummy_clock(), &create_dummy_mouth_track_model()).await;
+        stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!acommand"));
+
+        reload_droid_brain(&mut brain, &reaction_repository).await;
+
+        let reaction = stimulate_with_chat_message(&mut brain, |stimulus| stimulus.text = String::from("!acommand"));
+        assert!(reaction.is_none());
+    }
+
+    async fn setup_keyword_reaction_definition_with_options(keyword: &str, options: ChatTriggerMatchOptions, reaction_repository: &InMemoryReactionRepository) {
+        let mut reaction_definition = ReactionDefinition::new_empty(
+            ReactionDefinitionId(uuid::Uuid::new_v4().to_string()),
+            ReactionTrigger::new_chat_keyword_with_options(keyword.to_string(), options).unwrap(),
+        );
+        reaction_definition.steps.push(create_talking_step_definition(None));
+        reaction_repository.insert(&reaction_definition).await.unwrap();
+    }
+
     fn stimulate_with_chat_message<F>(brain: &mut PranDroidBrain, func: F) -> Option<Reaction> where F: Fn(&mut ChatMessageStimulus) -> () {
         brain.stimulate(create_chat_stimulus(func))
     }
@@ -627,7 +1375,8 @@ mod tests {
     fn create_chat_stimulus<F>(func: F) -> Stimulus where F: Fn(&mut ChatMessageStimulus) -> () {
         let mut chat_message_stimulus = ChatMessageStimulus {
             text: String::from("_a trigger_"),
-            source: Source { user_name: String::from("_a name_"), is_mod: false }
+            source: Source { user_name: String::from("_a name_"), is_mod: false, is_broadcaster: false, is_vip: false, is_subscriber: false },
+            message_id: None,
         };
         func(&mut chat_message_stimulus);
 
@@ -644,13 +1393,22 @@ mod tests {
                 id: String::from("_an id_"),
                 name: String::from("_a name_")
             },
-            source: Source { user_name: String::from("_a name_"), is_mod: false }
+            input: None,
+            source: Source { user_name: String::from("_a name_"), is_mod: false, is_broadcaster: false, is_vip: false, is_subscriber: false }
         };
         func(&mut action_stimulus);
 
         Stimulus::Action(action_stimulus)
     }
 
+    fn stimulate_with_stream_event(brain: &mut PranDroidBrain, event: StreamEvent) -> Option<Reaction> {
+        brain.stimulate(Stimulus::StreamEvent(StreamEventStimulus {
+            event,
+            message: None,
+            source: Source { user_name: String::from("_a name_"), is_mod: false, is_broadcaster: false, is_vip: false, is_subscriber: false }
+        }))
+    }
+
     struct SplitLettersTextPhonemiser {}
     impl TextPhonemiser for SplitLettersTextPhonemiser {
         fn phonemise_text(&self, text: &str) -> Vec<String> {
@@ -673,6 +1431,25 @@ mod tests {
         Arc::new(FakeNotifier::new())
     }
 
+    struct FakeClock { start: Instant, elapsed: Mutex<Duration> }
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            self.start + *self.elapsed.lock().unwrap()
+        }
+    }
+    impl FakeClock {
+        fn new() -> Self { Self { start: Instant::now(), elapsed: Mutex::new(Duration::ZERO) } }
+        fn advance(&self, duration: Duration) { *self.elapsed.lock().unwrap() += duration; }
+    }
+
+    fn create_dummy_clock() -> Arc<dyn Clock> {
+        Arc::new(FakeClock::new())
+    }
+
+    fn create_dummy_mouth_track_model() -> Arc<MouthTrackModel> {
+        Arc::new(MouthTrackModel::default())
+    }
+
     fn create_talking_step_definition(text: Option<&str>) -> ReactionStepDefinition {
         ReactionStepDefinition::Talking(TalkingReactionStepDefinition {
             skip: ReactionStepSkip::ImmediatelyAfter,
@@ -690,6 +1467,24 @@ mod tests {
         )
     }
 
+    fn create_action_range_reaction_definition(name: &str, min: u32, max: Option<u32>) -> ReactionDefinition {
+        let mut reaction_definition = ReactionDefinition::new_empty(
+            ReactionDefinitionId(uuid::Uuid::new_v4().to_string()),
+            ReactionTrigger::new_action_range(name.to_string(), min, max).unwrap(),
+        );
+        reaction_definition.steps.push(create_talking_step_definition(None));
+        reaction_definition
+    }
+
+    fn create_stream_event_reaction_definition(kind: StreamEventKind, text: &str) -> ReactionDefinition {
+        let mut reaction_definition = ReactionDefinition::new_empty(
+            ReactionDefinitionId(uuid::Uuid::new_v4().to_string()),
+            ReactionTrigger::new_stream_event(kind),
+        );
+        reaction_definition.steps.push(create_talking_step_definition(Some(text)));
+        reaction_definition
+    }
+
     fn create_keyword_reaction_definition(keyword: &str) -> ReactionDefinition {
         ReactionDefinition::new_empty(
             ReactionDefinitionId(uuid::Uuid::new_v4().to_string()),
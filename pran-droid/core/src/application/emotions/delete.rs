@@ -0,0 +1,97 @@
+use std::fmt::Debug;
+use thiserror::Error;
+use crate::domain::emotions::emotion::EmotionId;
+use crate::domain::emotions::emotion_repository::{EmotionDeleteError, EmotionRepository};
+use crate::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+
+#[derive(Debug, Error)]
+pub enum DeleteEmotionError {
+    #[error("Requested emotion not existing")]
+    NotFound,
+    #[error("Emotion still used by {0:?}")]
+    InUse(Vec<String>),
+    #[error("Unexpected error")]
+    Unexpected,
+}
+
+pub struct DeleteEmotionRequest {
+    pub id: String
+}
+
+pub async fn delete_emotion(request: DeleteEmotionRequest, repository: &dyn EmotionRepository, reaction_repository: &dyn ReactionDefinitionRepository) -> Result<(), DeleteEmotionError> {
+    let id = EmotionId(request.id);
+    if !repository.exists(&id).await {
+        return Err(DeleteEmotionError::NotFound);
+    }
+
+    let referencing_reactions: Vec<String> = reaction_repository.get_all().await
+        .into_iter()
+        .filter(|reaction| reaction.uses_emotion(&id))
+        .map(|reaction| format!("Reaction: {}", reaction.id.0))
+        .collect();
+
+    if !referencing_reactions.is_empty() {
+        return Err(DeleteEmotionError::InUse(referencing_reactions));
+    }
+
+    repository.delete(&id).await
+        .map_err(|error| match error {
+            EmotionDeleteError::Missing => DeleteEmotionError::NotFound,
+            EmotionDeleteError::Unexpected => DeleteEmotionError::Unexpected,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::reactions::insert_talking_step::{insert_talking_step_to_reaction, InsertTalkingStepToReactionRequest};
+    use crate::application::reactions::dtos::reaction_step_dto::{ReactionStepSkipDto, ReactionStepTextAlternativeDto, ReactionStepTextDto};
+    use crate::domain::emotions::emotion_repository::tests::setup_dummy_emotions;
+    use crate::domain::reactions::reaction_definition_repository::tests::setup_dummy_chat_command_reaction_definitions;
+    use crate::persistence::emotions::in_memory_emotion_repository::InMemoryEmotionRepository;
+    use crate::persistence::reactions::in_memory_reaction_repository::InMemoryReactionRepository;
+
+    #[tokio::test]
+    async fn delete_emotion_not_referenced_remove_it() {
+        let repository = InMemoryEmotionRepository::new();
+        let reaction_repository = InMemoryReactionRepository::new();
+        setup_dummy_emotions(vec!["happy", "sad"], &repository).await;
+
+        delete_emotion(DeleteEmotionRequest { id: String::from("happy") }, &repository, &reaction_repository).await.expect("Expected delete not to fail");
+
+        assert!(!repository.exists(&EmotionId(String::from("happy"))).await);
+        assert!(repository.exists(&EmotionId(String::from("sad"))).await);
+    }
+
+    #[tokio::test]
+    async fn delete_emotion_not_existing_id_errors() {
+        let repository = InMemoryEmotionRepository::new();
+        let reaction_repository = InMemoryReactionRepository::new();
+
+        let result = delete_emotion(DeleteEmotionRequest { id: String::from("happy") }, &repository, &reaction_repository).await;
+
+        assert!(matches!(result, Err(DeleteEmotionError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn delete_emotion_used_by_talking_steps_errors_listing_reactions() {
+        let repository = InMemoryEmotionRepository::new();
+        let reaction_repository = InMemoryReactionRepository::new();
+        setup_dummy_emotions(vec!["happy"], &repository).await;
+        let reactions = setup_dummy_chat_command_reaction_definitions(vec!["!hello", "!bye", "!other"], &reaction_repository).await;
+        for reaction in &reactions[0..2] {
+            insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
+                reaction_id: reaction.id.0.clone(),
+                step_index: 0,
+                emotion_id: String::from("happy"),
+                skip: ReactionStepSkipDto::ImmediatelyAfter,
+                alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("text")), probability: None, conditions: vec![] }],
+            }, &reaction_repository, &repository).await.unwrap();
+        }
+
+        let result = delete_emotion(DeleteEmotionRequest { id: String::from("happy") }, &repository, &reaction_repository).await;
+
+        assert!(matches!(result, Err(DeleteEmotionError::InUse(ref ids)) if *ids == vec![format!("Reaction: {}", reactions[0].id.0), format!("Reaction: {}", reactions[1].id.0)]));
+        assert!(repository.exists(&EmotionId(String::from("happy"))).await);
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
-use crate::application::reactions::dtos::reaction_step_dto::AnimationFrameDto;
-use crate::domain::emotions::emotion::{Emotion, EmotionLayer};
+use crate::application::reactions::dtos::reaction_step_dto::{AnimationFrameDto, frames_dtos_to_animation};
+use crate::domain::emotions::emotion::{Emotion, EmotionId, EmotionLayer, EmotionName, MouthPositionName};
+use crate::domain::images::image::ImageId;
 
 pub struct EmotionDto {
     pub id: String,
@@ -33,3 +34,36 @@ impl From<EmotionLayer> for EmotionLayerDto {
         }
     }
 }
+
+impl TryInto<Emotion> for EmotionDto {
+    type Error = ();
+
+    fn try_into(self) -> Result<Emotion, Self::Error> {
+        let animation = self.animation.into_iter().map(TryInto::try_into).collect::<Result<Vec<EmotionLayer>, ()>>()?;
+        if animation.iter().filter(|layer| matches!(layer, EmotionLayer::Mouth { .. })).count() != 1 {
+            return Err(());
+        }
+
+        Ok(Emotion {
+            id: EmotionId(self.id),
+            name: EmotionName::new(self.name)?,
+            animation,
+        })
+    }
+}
+
+impl TryInto<EmotionLayer> for EmotionLayerDto {
+    type Error = ();
+
+    fn try_into(self) -> Result<EmotionLayer, Self::Error> {
+        match self {
+            EmotionLayerDto::Animation(frames) => frames_dtos_to_animation(frames)
+                .map(EmotionLayer::Animation)
+                .map_err(|_| ()),
+            EmotionLayerDto::Mouth { mouth_mapping } => mouth_mapping.into_iter()
+                .map(|(pos, id)| Ok((MouthPositionName::try_from(pos)?, ImageId(id))))
+                .collect::<Result<HashMap<MouthPositionName, ImageId>, ()>>()
+                .map(|mouth_mapping| EmotionLayer::Mouth { mouth_mapping })
+        }
+    }
+}
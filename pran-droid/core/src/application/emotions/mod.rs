@@ -5,3 +5,6 @@ pub mod get_all;
 pub mod create;
 pub mod update_mouth_mapping;
 pub mod update_layer;
+pub mod remove_layer;
+pub mod reorder_layers;
+pub mod delete;
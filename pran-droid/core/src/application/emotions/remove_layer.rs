@@ -0,0 +1,97 @@
+use std::fmt::Debug;
+use thiserror::Error;
+use crate::domain::emotions::emotion::EmotionId;
+use crate::domain::emotions::emotion_repository::EmotionRepository;
+
+#[derive(Debug, Error)]
+pub enum RemoveEmotionLayerError {
+    #[error("Bad request")]
+    BadRequest(String),
+    #[error("Requested main aggregate not existing")]
+    NotExistingMainAggregate,
+}
+
+pub struct RemoveEmotionLayerRequest {
+    pub emotion_id: String,
+    pub index: usize
+}
+
+pub async fn remove_emotion_layer(request: RemoveEmotionLayerRequest, repository: &dyn EmotionRepository) -> Result<(), RemoveEmotionLayerError> {
+    let mut emotion = repository.get(&EmotionId(request.emotion_id)).await
+        .ok_or(RemoveEmotionLayerError::NotExistingMainAggregate)?;
+
+    emotion.remove_layer(request.index)
+        .map_err(|_| RemoveEmotionLayerError::BadRequest(String::from("The requested layer does not exist or is the mouth layer")))?;
+
+    repository.update(&emotion).await.unwrap();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::application::emotions::dtos::emotion_dto::EmotionLayerDto;
+    use crate::application::emotions::get::{get_emotion, GetEmotionRequest};
+    use crate::application::emotions::update_layer::{AddEmotionAnimationLayerRequest, update_emotion_animation_layer};
+    use crate::application::reactions::dtos::reaction_step_dto::AnimationFrameDto;
+    use crate::domain::emotions::emotion_repository::tests::setup_dummy_emotion;
+    use crate::domain::images::image_repository::tests::setup_dummy_images;
+    use crate::persistence::emotions::in_memory_emotion_repository::InMemoryEmotionRepository;
+    use crate::persistence::images::in_memory_image_repository::InMemoryImageRepository;
+    use super::*;
+
+    #[tokio::test]
+    async fn remove_emotion_layer_wrong_id_return_error() {
+        let repository = InMemoryEmotionRepository::new();
+        setup_dummy_emotion(&repository).await;
+
+        let result = remove_emotion_layer(RemoveEmotionLayerRequest { emotion_id: String::from("not existing id"), index: 1 }, &repository).await;
+
+        assert!(matches!(result, Err(RemoveEmotionLayerError::NotExistingMainAggregate)));
+    }
+
+    #[tokio::test]
+    async fn remove_emotion_layer_animation_layer_removes_it() {
+        let repository = InMemoryEmotionRepository::new();
+        let image_repository = InMemoryImageRepository::new();
+        let emotion = setup_dummy_emotion(&repository).await;
+        setup_dummy_images(vec!["id1", "id2"], &image_repository).await;
+        add_layer(&emotion.id.0, 1, "id1", &repository, &image_repository).await;
+        add_layer(&emotion.id.0, 2, "id2", &repository, &image_repository).await;
+
+        remove_emotion_layer(RemoveEmotionLayerRequest { emotion_id: emotion.id.0.clone(), index: 1 }, &repository).await
+            .expect("Expected remove layer not to fail");
+
+        let emotion = get_emotion(GetEmotionRequest { id: emotion.id.0 }, &repository).await.unwrap();
+        assert_eq!(emotion.animation.len(), 2);
+        assert!(matches!(&emotion.animation[1], EmotionLayerDto::Animation(frames) if frames[0].image_id == "id2"));
+    }
+
+    #[tokio::test]
+    async fn remove_emotion_layer_mouth_layer_errors() {
+        let repository = InMemoryEmotionRepository::new();
+        let emotion = setup_dummy_emotion(&repository).await;
+
+        let result = remove_emotion_layer(RemoveEmotionLayerRequest { emotion_id: emotion.id.0, index: 0 }, &repository).await;
+
+        assert!(matches!(result, Err(RemoveEmotionLayerError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn remove_emotion_layer_out_of_bounds_index_errors() {
+        let repository = InMemoryEmotionRepository::new();
+        let emotion = setup_dummy_emotion(&repository).await;
+
+        let result = remove_emotion_layer(RemoveEmotionLayerRequest { emotion_id: emotion.id.0, index: 1 }, &repository).await;
+
+        assert!(matches!(result, Err(RemoveEmotionLayerError::BadRequest(_))));
+    }
+
+    async fn add_layer(emotion_id: &str, index: usize, image_id: &str, repository: &dyn EmotionRepository, image_repository: &InMemoryImageRepository) {
+        update_emotion_animation_layer(AddEmotionAnimationLayerRequest {
+            index,
+            emotion_id: String::from(emotion_id),
+            animation: vec![AnimationFrameDto { frame_start: 0, frame_end: 10, image_id: String::from(image_id) }]
+        }, repository, image_repository).await.unwrap();
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt::Debug;
+use thiserror::Error;
+use crate::domain::emotions::emotion::EmotionId;
+use crate::domain::emotions::emotion_repository::EmotionRepository;
+
+#[derive(Debug, Error)]
+pub enum ReorderEmotionLayersError {
+    #[error("Bad request")]
+    BadRequest(String),
+    #[error("Requested main aggregate not existing")]
+    NotExistingMainAggregate,
+}
+
+pub struct ReorderEmotionLayersRequest {
+    pub emotion_id: String,
+    pub order: Vec<usize>
+}
+
+pub async fn reorder_emotion_layers(request: ReorderEmotionLayersRequest, repository: &dyn EmotionRepository) -> Result<(), ReorderEmotionLayersError> {
+    let mut emotion = repository.get(&EmotionId(request.emotion_id)).await
+        .ok_or(ReorderEmotionLayersError::NotExistingMainAggregate)?;
+
+    emotion.reorder_layers(&request.order)
+        .map_err(|_| ReorderEmotionLayersError::BadRequest(String::from("The requested order must contain every layer index exactly once")))?;
+
+    repository.update(&emotion).await.unwrap();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::application::emotions::dtos::emotion_dto::EmotionLayerDto;
+    use crate::application::emotions::get::{get_emotion, GetEmotionRequest};
+    use crate::application::emotions::update_layer::{AddEmotionAnimationLayerRequest, update_emotion_animation_layer};
+    use crate::application::reactions::dtos::reaction_step_dto::AnimationFrameDto;
+    use crate::domain::emotions::emotion_repository::tests::setup_dummy_emotion;
+    use crate::domain::images::image_repository::tests::setup_dummy_images;
+    use crate::persistence::emotions::in_memory_emotion_repository::InMemoryEmotionRepository;
+    use crate::persistence::images::in_memory_image_repository::InMemoryImageRepository;
+    use super::*;
+
+    #[tokio::test]
+    async fn reorder_emotion_layers_wrong_id_return_error() {
+        let repository = InMemoryEmotionRepository::new();
+        setup_dummy_emotion(&repository).await;
+
+        let result = reorder_emotion_layers(ReorderEmotionLayersRequest { emotion_id: String::from("not existing id"), order: vec![0] }, &repository).await;
+
+        assert!(matches!(result, Err(ReorderEmotionLayersError::NotExistingMainAggregate)));
+    }
+
+    #[tokio::test]
+    async fn reorder_emotion_layers_valid_order_moves_layers() {
+        let repository = InMemoryEmotionRepository::new();
+        let image_repository = InMemoryImageRepository::new();
+        let emotion = setup_dummy_emotion(&repository).await;
+        setup_dummy_images(vec!["id1", "id2"], &image_repository).await;
+        add_layer(&emotion.id.0, 1, "id1", &repository, &image_repository).await;
+        add_layer(&emotion.id.0, 2, "id2", &repository, &image_repository).await;
+
+        reorder_emotion_layers(ReorderEmotionLayersRequest { emotion_id: emotion.id.0.clone(), order: vec![2, 0, 1] }, &repository).await
+            .expect("Expected reorder not to fail");
+
+        let emotion = get_emotion(GetEmotionRequest { id: emotion.id.0 }, &repository).await.unwrap();
+        assert!(matches!(&emotion.animation[0], EmotionLayerDto::Animation(frames) if frames[0].image_id == "id2"));
+        assert!(matches!(&emotion.animation[1], EmotionLayerDto::Mouth { .. }));
+        assert!(matches!(&emotion.animation[2], EmotionLayerDto::Animation(frames) if frames[0].image_id == "id1"));
+    }
+
+    #[tokio::test]
+    async fn reorder_emotion_layers_not_a_permutation_errors() {
+        let repository = InMemoryEmotionRepository::new();
+        let image_repository = InMemoryImageRepository::new();
+        let emotion = setup_dummy_emotion(&repository).await;
+        setup_dummy_images(vec!["id1"], &image_repository).await;
+        add_layer(&emotion.id.0, 1, "id1", &repository, &image_repository).await;
+
+        let result_duplicate = reorder_emotion_layers(ReorderEmotionLayersRequest { emotion_id: emotion.id.0.clone(), order: vec![1, 1] }, &repository).await;
+        let result_missing = reorder_emotion_layers(ReorderEmotionLayersRequest { emotion_id: emotion.id.0.clone(), order: vec![1] }, &repository).await;
+        let result_out_of_bounds = reorder_emotion_layers(ReorderEmotionLayersRequest { emotion_id: emotion.id.0.clone(), order: vec![0, 2] }, &repository).await;
+
+        assert!(matches!(result_duplicate, Err(ReorderEmotionLayersError::BadRequest(_))));
+        assert!(matches!(result_missing, Err(ReorderEmotionLayersError::BadRequest(_))));
+        assert!(matches!(result_out_of_bounds, Err(ReorderEmotionLayersError::BadRequest(_))));
+    }
+
+    async fn add_layer(emotion_id: &str, index: usize, image_id: &str, repository: &dyn EmotionRepository, image_repository: &InMemoryImageRepository) {
+        update_emotion_animation_layer(AddEmotionAnimationLayerRequest {
+            index,
+            emotion_id: String::from(emotion_id),
+            animation: vec![AnimationFrameDto { frame_start: 0, frame_end: 10, image_id: String::from(image_id) }]
+        }, repository, image_repository).await.unwrap();
+    }
+}
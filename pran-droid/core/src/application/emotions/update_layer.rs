@@ -9,6 +9,8 @@ use crate::domain::images::image_repository::ImageRepository;
 
 #[derive(Debug, Error)]
 pub enum AddEmotionAnimationLayerError {
+    #[error("Requested main aggregate not existing")]
+    NotExistingMainAggregate,
     #[error("Bad request {0}")]
     BadRequest(String),
     #[error("Wrong animation details {0}")]
@@ -22,9 +24,8 @@ pub struct AddEmotionAnimationLayerRequest {
 }
 
 pub async fn update_emotion_animation_layer(request: AddEmotionAnimationLayerRequest, repository: &dyn EmotionRepository, image_repository: &dyn ImageRepository) -> Result<(), AddEmotionAnimationLayerError> {
-    let mut emotion = repository.get(&EmotionId(request.emotion_id.clone()))
-        .await
-        .ok_or_else(|| AddEmotionAnimationLayerError::BadRequest(format!("Emotion with id {:?} does not exists", request.emotion_id)))?;
+    let mut emotion = repository.get(&EmotionId(request.emotion_id)).await
+        .ok_or(AddEmotionAnimationLayerError::NotExistingMainAggregate)?;
 
     update_layer_in_emotion(request.index, &mut emotion, frames_dtos_to_animation(request.animation)?, image_repository)
         .await
@@ -56,7 +57,7 @@ mod tests {
             animation: vec![]
         }, &repository, &image_repository).await;
 
-        assert!(matches!(result, Err(AddEmotionAnimationLayerError::BadRequest(_))), "Expected to fail with bad request but was {:?}", result);
+        assert!(matches!(result, Err(AddEmotionAnimationLayerError::NotExistingMainAggregate)), "Expected to fail with not existing main aggregate but was {:?}", result);
     }
 
     #[tokio::test]
@@ -8,6 +8,8 @@ use crate::domain::images::image_repository::ImageRepository;
 
 #[derive(Debug, Error)]
 pub enum UpdateEmotionMouthMappingError {
+    #[error("Requested main aggregate not existing")]
+    NotExistingMainAggregate,
     #[error("Bad request")]
     BadRequest(String),
 }
@@ -23,9 +25,8 @@ pub struct UpdateEmotionMouthMappingRequest {
 }
 
 pub async fn update_emotion_mouth_mapping(request: UpdateEmotionMouthMappingRequest, repository: &dyn EmotionRepository, image_repository: &dyn ImageRepository) -> Result<(), UpdateEmotionMouthMappingError> {
-    let mut emotion = repository.get(&EmotionId(request.emotion_id.clone()))
-        .await
-        .ok_or_else(|| UpdateEmotionMouthMappingError::BadRequest(format!("Emotion with id {:?} does not exists", request.emotion_id)))?;
+    let mut emotion = repository.get(&EmotionId(request.emotion_id)).await
+        .ok_or(UpdateEmotionMouthMappingError::NotExistingMainAggregate)?;
 
     for element in request.mapping.into_iter() {
         match (ImageId::try_from(element.image_id), MouthPositionName::try_from(element.name)) {
@@ -72,7 +73,8 @@ mod tests {
         match update_emotion_mouth_mapping(request, &repository, &image_repository).await {
             Ok(_) => unreachable!("expected update emotion mouth mapping to fail"),
             Err(error) => match error {
-                UpdateEmotionMouthMappingError::BadRequest(_) => {}
+                UpdateEmotionMouthMappingError::NotExistingMainAggregate => {}
+                other => unreachable!("expected not existing main aggregate but was {:?}", other)
             }
         }
     }
@@ -151,6 +153,7 @@ mod tests {
             Ok(_) => unreachable!("expected update emotion mouth mapping to fail"),
             Err(error) => match error {
                 UpdateEmotionMouthMappingError::BadRequest(_) => {}
+                other => unreachable!("expected bad request but was {:?}", other)
             }
         }
     }
@@ -174,6 +177,7 @@ mod tests {
             Ok(_) => unreachable!("expected update emotion mouth mapping to fail"),
             Err(error) => match error {
                 UpdateEmotionMouthMappingError::BadRequest(_) => {}
+                other => unreachable!("expected bad request but was {:?}", other)
             }
         }
     }
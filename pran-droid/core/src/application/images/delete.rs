@@ -0,0 +1,138 @@
+use std::fmt::Debug;
+use thiserror::Error;
+use crate::domain::emotions::emotion_repository::EmotionRepository;
+use crate::domain::images::image::ImageId;
+use crate::domain::images::image_repository::{DeleteError, ImageRepository};
+use crate::domain::images::image_storage::{ImageStorage, StorageDeleteError};
+use crate::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+
+#[derive(Debug, Error)]
+pub enum DeleteImageError {
+    #[error("Requested image not existing")]
+    NotFound,
+    #[error("Image still used by {0:?}")]
+    InUse(Vec<String>),
+    #[error("Storage failure")]
+    StorageFail,
+    #[error("Unexpected error")]
+    Unexpected,
+}
+
+pub struct DeleteImageRequest {
+    pub id: String
+}
+
+pub async fn delete_image(request: DeleteImageRequest, repository: &dyn ImageRepository, storage: &dyn ImageStorage, emotion_repository: &dyn EmotionRepository, reaction_repository: &dyn ReactionDefinitionRepository) -> Result<(), DeleteImageError> {
+    let id = ImageId(request.id);
+    let image = repository.get(&id).await.ok_or(DeleteImageError::NotFound)?;
+
+    let mut references: Vec<String> = emotion_repository.get_all().await
+        .into_iter()
+        .filter(|emotion| emotion.uses_image(&id))
+        .map(|emotion| format!("Emotion: {}", emotion.name.0))
+        .collect();
+    references.extend(reaction_repository.get_all().await
+        .into_iter()
+        .filter(|reaction| reaction.uses_image(&id))
+        .map(|reaction| format!("Reaction: {}", reaction.id.0)));
+
+    if !references.is_empty() {
+        return Err(DeleteImageError::InUse(references));
+    }
+
+    match storage.delete(&image.url).await {
+        Ok(_) | Err(StorageDeleteError::Missing) => {},
+        Err(StorageDeleteError::Unexpected) => return Err(DeleteImageError::StorageFail),
+    }
+
+    repository.delete(&id).await
+        .map_err(|error| match error {
+            DeleteError::Missing => DeleteImageError::NotFound,
+            DeleteError::Unexpected => DeleteImageError::Unexpected,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+    use crate::application::images::create::{create_image, CreateImageRequest};
+    use crate::domain::animations::animation::{Animation, AnimationFrame, AnimationFrames};
+    use crate::domain::emotions::emotion::{Emotion, EmotionId, EmotionLayer, EmotionName, MouthPositionName};
+    use crate::domain::images::image::ImageUrl;
+    use crate::domain::reactions::reaction::ReactionStepSkip;
+    use crate::domain::reactions::reaction_definition::{MovingReactionStepDefinition, ReactionStepDefinition};
+    use crate::domain::reactions::reaction_definition_repository::tests::setup_dummy_chat_command_reaction_definition;
+    use crate::persistence::emotions::in_memory_emotion_repository::InMemoryEmotionRepository;
+    use crate::persistence::images::in_memory_image_repository::InMemoryImageRepository;
+    use crate::persistence::images::in_memory_image_storage::InMemoryImageStorage;
+    use crate::persistence::reactions::in_memory_reaction_repository::InMemoryReactionRepository;
+
+    #[tokio::test]
+    async fn delete_image_not_referenced_remove_it_from_repository_and_storage() {
+        let repository = InMemoryImageRepository::new();
+        let storage = InMemoryImageStorage::new();
+        let emotion_repository = InMemoryEmotionRepository::new();
+        let reaction_repository = InMemoryReactionRepository::new();
+        create_image(CreateImageRequest { id: String::from("image"), image: vec![1] }, &repository, &storage).await.unwrap();
+
+        delete_image(DeleteImageRequest { id: String::from("image") }, &repository, &storage, &emotion_repository, &reaction_repository).await
+            .expect("Expected delete not to fail");
+
+        assert!(!repository.has(&ImageId(String::from("image"))).await);
+        assert_eq!(storage.files_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn delete_image_not_existing_id_errors() {
+        let repository = InMemoryImageRepository::new();
+        let storage = InMemoryImageStorage::new();
+        let emotion_repository = InMemoryEmotionRepository::new();
+        let reaction_repository = InMemoryReactionRepository::new();
+
+        let result = delete_image(DeleteImageRequest { id: String::from("image") }, &repository, &storage, &emotion_repository, &reaction_repository).await;
+
+        assert!(matches!(result, Err(DeleteImageError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn delete_image_used_by_emotions_and_reactions_errors_listing_them() {
+        let repository = InMemoryImageRepository::new();
+        let storage = InMemoryImageStorage::new();
+        let emotion_repository = InMemoryEmotionRepository::new();
+        let reaction_repository = InMemoryReactionRepository::new();
+        create_image(CreateImageRequest { id: String::from("image"), image: vec![1] }, &repository, &storage).await.unwrap();
+        emotion_repository.insert(&Emotion {
+            id: EmotionId(String::from("happy")),
+            name: EmotionName(String::from("happy_name")),
+            animation: vec![EmotionLayer::Mouth { mouth_mapping: HashMap::from([(MouthPositionName::Ah, ImageId(String::from("image")))]) }],
+        }).await.unwrap();
+        emotion_repository.insert(&Emotion {
+            id: EmotionId(String::from("sad")),
+            name: EmotionName(String::from("sad_name")),
+            animation: vec![EmotionLayer::Animation(create_animation("image"))],
+        }).await.unwrap();
+        let mut reaction = setup_dummy_chat_command_reaction_definition(&reaction_repository).await;
+        reaction.steps.push(ReactionStepDefinition::Moving(MovingReactionStepDefinition {
+            animation: create_animation("image"),
+            skip: ReactionStepSkip::ImmediatelyAfter,
+        }));
+        reaction_repository.update(&reaction).await.unwrap();
+
+        let result = delete_image(DeleteImageRequest { id: String::from("image") }, &repository, &storage, &emotion_repository, &reaction_repository).await;
+
+        assert!(matches!(result, Err(DeleteImageError::InUse(ref references)) if *references == vec![
+            String::from("Emotion: happy_name"),
+            String::from("Emotion: sad_name"),
+            format!("Reaction: {}", reaction.id.0),
+        ]));
+        assert!(repository.has(&ImageId(String::from("image"))).await);
+        assert!(storage.has(&ImageUrl(String::from("api/images/image"))));
+    }
+
+    fn create_animation(image_id: &str) -> Animation {
+        Animation {
+            frames: AnimationFrames(vec![AnimationFrame { frame_start: 0, frame_end: 1, image_id: ImageId(String::from(image_id)) }])
+        }
+    }
+}
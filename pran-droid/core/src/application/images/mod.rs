@@ -1,3 +1,4 @@
 pub mod get_all;
 pub mod create;
+pub mod delete;
 pub mod dtos;
\ No newline at end of file
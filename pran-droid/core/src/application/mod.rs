@@ -1,4 +1,5 @@
 pub mod reactions;
 pub mod emotions;
 pub mod images;
+pub mod backup;
 pub mod brain;
\ No newline at end of file
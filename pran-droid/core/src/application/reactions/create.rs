@@ -34,7 +34,7 @@ pub async fn create_reaction(request: CreateReactionRequest, repository: &dyn Re
 
 #[cfg(test)]
 mod tests {
-    use crate::application::reactions::dtos::reaction_dto::ReactionTriggerDto;
+    use crate::application::reactions::dtos::reaction_dto::{ChatTriggerMatchOptionsDto, ReactionTriggerDto};
     use crate::domain::reactions::reaction_definition::{ReactionDefinitionId};
     use crate::persistence::reactions::in_memory_reaction_repository::InMemoryReactionRepository;
     use super::*;
@@ -42,12 +42,12 @@ mod tests {
     #[tokio::test]
     async fn create_reaction_return_new_reaction_from_chat_command() {
         let trigger = String::from("!fire");
-        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(trigger.clone()) };
+        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(trigger.clone(), Default::default()) };
         let repository: InMemoryReactionRepository = InMemoryReactionRepository::new();
 
         match create_reaction(request, &repository).await {
             Ok(reaction) => match &reaction.triggers[..] {
-                [ReactionTriggerDto::ChatCommand(text)] => assert_eq!(text, &trigger),
+                [ReactionTriggerDto::ChatCommand(text, _)] => assert_eq!(text, &trigger),
                 _ => unreachable!("expected reaction to trigger through chat")
             },
             _ => unreachable!("expected create reaction to not fail")
@@ -57,21 +57,58 @@ mod tests {
     #[tokio::test]
     async fn create_reaction_return_new_reaction_from_chat_keyword() {
         let trigger = String::from("!fire");
-        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatKeyword(trigger.clone()) };
+        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatKeyword(trigger.clone(), Default::default()) };
         let repository: InMemoryReactionRepository = InMemoryReactionRepository::new();
 
         match create_reaction(request, &repository).await {
             Ok(reaction) => match &reaction.triggers[..] {
-                [ReactionTriggerDto::ChatKeyword(text)] => assert_eq!(text, &trigger),
+                [ReactionTriggerDto::ChatKeyword(text, _)] => assert_eq!(text, &trigger),
                 _ => unreachable!("expected reaction to trigger through chat")
             },
             _ => unreachable!("expected create reaction to not fail")
         }
     }
 
+    #[tokio::test]
+    async fn create_reaction_return_new_reaction_from_chat_pattern() {
+        let trigger = String::from("^!so @?(?P<streamer>\\w+)");
+        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatPattern(trigger.clone()) };
+        let repository: InMemoryReactionRepository = InMemoryReactionRepository::new();
+
+        match create_reaction(request, &repository).await {
+            Ok(reaction) => match &reaction.triggers[..] {
+                [ReactionTriggerDto::ChatPattern(pattern)] => assert_eq!(pattern, &trigger),
+                _ => unreachable!("expected reaction to trigger through chat pattern")
+            },
+            _ => unreachable!("expected create reaction to not fail")
+        }
+    }
+
+    #[tokio::test]
+    async fn create_reaction_with_invalid_chat_pattern_bad_request_error() {
+        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatPattern(String::from("^!so (?P<streamer>\\w+")) };
+        let repository: InMemoryReactionRepository = InMemoryReactionRepository::new();
+
+        let result = create_reaction(request, &repository).await;
+
+        assert!(matches!(result, Err(CreateReactionError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn create_reaction_with_existing_command_and_different_options_conflict_error() {
+        let repository: InMemoryReactionRepository = InMemoryReactionRepository::new();
+        create_reaction(CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("!fire"), Default::default()) }, &repository).await.unwrap();
+
+        let result = create_reaction(CreateReactionRequest {
+            trigger: ReactionTriggerDto::ChatCommand(String::from("!fire"), ChatTriggerMatchOptionsDto { case_sensitive: false, ..Default::default() })
+        }, &repository).await;
+
+        assert!(matches!(result, Err(CreateReactionError::Conflict(_))));
+    }
+
     #[tokio::test]
     async fn create_reaction_return_new_reaction_with_no_steps() {
-        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("!fire")) };
+        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("!fire"), Default::default()) };
         let repository: InMemoryReactionRepository = InMemoryReactionRepository::new();
 
         match create_reaction(request, &repository).await {
@@ -82,7 +119,7 @@ mod tests {
 
     #[tokio::test]
     async fn create_reaction_return_new_reaction_enabled() {
-        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("!fire")) };
+        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("!fire"), Default::default()) };
         let repository: InMemoryReactionRepository = InMemoryReactionRepository::new();
 
         match create_reaction(request, &repository).await {
@@ -93,7 +130,7 @@ mod tests {
 
     #[tokio::test]
     async fn create_reaction_return_new_reaction_with_zero_usages() {
-        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("!fire")) };
+        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("!fire"), Default::default()) };
         let repository: InMemoryReactionRepository = InMemoryReactionRepository::new();
 
         match create_reaction(request, &repository).await {
@@ -104,7 +141,7 @@ mod tests {
 
     #[tokio::test]
     async fn create_reaction_save_reaction_in_repository() {
-        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("!fire")) };
+        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("!fire"), Default::default()) };
         let repository = InMemoryReactionRepository::new();
 
         match create_reaction(request, &repository).await {
@@ -115,7 +152,7 @@ mod tests {
 
     #[tokio::test]
     async fn create_reaction_empty_trigger_error() {
-        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("")) };
+        let request = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from(""), Default::default()) };
         let repository = InMemoryReactionRepository::new();
 
         match create_reaction(request, &repository).await {
@@ -129,7 +166,7 @@ mod tests {
 
     #[tokio::test]
     async fn create_reaction_twice_same_command_trigger_conflict_error() {
-        let trigger = ReactionTriggerDto::ChatCommand(String::from("trigger1"));
+        let trigger = ReactionTriggerDto::ChatCommand(String::from("trigger1"), Default::default());
         let request1 = CreateReactionRequest { trigger: trigger.clone() };
         let request2 = CreateReactionRequest { trigger: trigger.clone() };
         let repository = InMemoryReactionRepository::new();
@@ -146,8 +183,8 @@ mod tests {
 
     #[tokio::test]
     async fn create_reaction_twice_different_command_trigger_not_fail() {
-        let request1 = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("trigger1")) };
-        let request2 = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("trigger2")) };
+        let request1 = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("trigger1"), Default::default()) };
+        let request2 = CreateReactionRequest { trigger: ReactionTriggerDto::ChatCommand(String::from("trigger2"), Default::default()) };
         let repository = InMemoryReactionRepository::new();
         create_reaction(request1, &repository).await.unwrap();
 
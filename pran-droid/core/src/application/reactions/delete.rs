@@ -0,0 +1,52 @@
+use std::fmt::Debug;
+use thiserror::Error;
+use crate::domain::reactions::reaction_definition::ReactionDefinitionId;
+use crate::domain::reactions::reaction_definition_repository::{ReactionDefinitionRepository, ReactionDeleteError};
+
+#[derive(Debug, Error)]
+pub enum DeleteReactionError {
+    #[error("Requested reaction not existing")]
+    NotFound,
+    #[error("Unexpected error")]
+    Unexpected,
+}
+
+pub struct DeleteReactionRequest {
+    pub id: String
+}
+
+pub async fn delete_reaction(request: DeleteReactionRequest, repository: &dyn ReactionDefinitionRepository) -> Result<(), DeleteReactionError> {
+    repository.delete(&ReactionDefinitionId(request.id)).await
+        .map_err(|error| match error {
+            ReactionDeleteError::Missing => DeleteReactionError::NotFound,
+            ReactionDeleteError::Unexpected => DeleteReactionError::Unexpected,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::reactions::reaction_definition_repository::tests::setup_dummy_chat_command_reaction_definitions;
+    use crate::persistence::reactions::in_memory_reaction_repository::InMemoryReactionRepository;
+
+    #[tokio::test]
+    async fn delete_reaction_existing_id_remove_only_that_reaction() {
+        let repository = InMemoryReactionRepository::new();
+        let reactions = setup_dummy_chat_command_reaction_definitions(vec!["!hello", "!bye"], &repository).await;
+
+        delete_reaction(DeleteReactionRequest { id: reactions[0].id.0.clone() }, &repository).await.expect("Expected delete not to fail");
+
+        assert!(!repository.has(&reactions[0].id));
+        assert!(repository.has(&reactions[1].id));
+    }
+
+    #[tokio::test]
+    async fn delete_reaction_not_existing_id_errors() {
+        let repository = InMemoryReactionRepository::new();
+        setup_dummy_chat_command_reaction_definitions(vec!["!hello"], &repository).await;
+
+        let result = delete_reaction(DeleteReactionRequest { id: String::from("not existing") }, &repository).await;
+
+        assert!(matches!(result, Err(DeleteReactionError::NotFound)));
+    }
+}
@@ -1,5 +1,6 @@
-use crate::domain::reactions::reaction_definition::{ReactionDefinition, ReactionTrigger};
+use crate::domain::reactions::reaction_definition::{ChatTriggerMatchOptions, OverlayTarget, ReactionCooldown, ReactionDefinition, ReactionDefinitionId, ReactionPermissionLevel, ReactionStepDefinition, ReactionTrigger, StreamEventKind};
 use std::fmt::Debug;
+use std::time::Duration;
 use crate::application::reactions::dtos::reaction_step_dto::{ReactionStepDto};
 
 #[derive(Debug)]
@@ -7,8 +8,11 @@ pub struct ReactionDto {
     pub id: String,
     pub is_disabled: bool,
     pub count: u32,
+    pub cooldown: ReactionCooldownDto,
+    pub permission_level: ReactionPermissionLevelDto,
     pub triggers: Vec<ReactionTriggerDto>,
     pub steps: Vec<ReactionStepDto>,
+    pub overlay_targets: Vec<String>,
 }
 
 impl From<ReactionDefinition> for ReactionDto {
@@ -17,25 +21,159 @@ impl From<ReactionDefinition> for ReactionDto {
             id: value.id.0,
             is_disabled: value.is_disabled,
             count: value.count,
+            cooldown: value.cooldown.into(),
+            permission_level: value.permission_level.into(),
             triggers: value.triggers.into_iter().map(From::from).collect(),
             steps: value.steps.into_iter().map(From::from).collect(),
+            overlay_targets: value.overlay_targets.into_iter().map(|target| target.0).collect(),
+        }
+    }
+}
+
+impl TryInto<ReactionDefinition> for ReactionDto {
+    type Error = ();
+
+    fn try_into(self) -> Result<ReactionDefinition, Self::Error> {
+        let triggers = self.triggers.into_iter().map(TryInto::try_into).collect::<Result<Vec<ReactionTrigger>, ()>>()?;
+        if triggers.is_empty() {
+            return Err(());
+        }
+
+        Ok(ReactionDefinition {
+            id: ReactionDefinitionId(self.id),
+            is_disabled: self.is_disabled,
+            triggers,
+            steps: self.steps.into_iter().map(TryInto::try_into).collect::<Result<Vec<ReactionStepDefinition>, ()>>()?,
+            count: self.count,
+            cooldown: self.cooldown.into(),
+            permission_level: self.permission_level.into(),
+            overlay_targets: self.overlay_targets.into_iter().map(OverlayTarget::new).collect::<Result<Vec<OverlayTarget>, ()>>()?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ReactionCooldownDto {
+    pub global_ms: u64,
+    pub per_user_ms: u64,
+}
+
+impl From<ReactionCooldown> for ReactionCooldownDto {
+    fn from(value: ReactionCooldown) -> Self {
+        Self {
+            global_ms: value.global.as_millis() as u64,
+            per_user_ms: value.per_user.as_millis() as u64,
+        }
+    }
+}
+
+impl From<ReactionCooldownDto> for ReactionCooldown {
+    fn from(value: ReactionCooldownDto) -> Self {
+        Self {
+            global: Duration::from_millis(value.global_ms),
+            per_user: Duration::from_millis(value.per_user_ms),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReactionPermissionLevelDto {
+    Everyone,
+    Subscribers,
+    Vips,
+    Mods,
+    Broadcaster,
+}
+
+impl From<ReactionPermissionLevel> for ReactionPermissionLevelDto {
+    fn from(value: ReactionPermissionLevel) -> Self {
+        match value {
+            ReactionPermissionLevel::Everyone => ReactionPermissionLevelDto::Everyone,
+            ReactionPermissionLevel::Subscribers => ReactionPermissionLevelDto::Subscribers,
+            ReactionPermissionLevel::Vips => ReactionPermissionLevelDto::Vips,
+            ReactionPermissionLevel::Mods => ReactionPermissionLevelDto::Mods,
+            ReactionPermissionLevel::Broadcaster => ReactionPermissionLevelDto::Broadcaster,
+        }
+    }
+}
+
+impl From<ReactionPermissionLevelDto> for ReactionPermissionLevel {
+    fn from(value: ReactionPermissionLevelDto) -> Self {
+        match value {
+            ReactionPermissionLevelDto::Everyone => ReactionPermissionLevel::Everyone,
+            ReactionPermissionLevelDto::Subscribers => ReactionPermissionLevel::Subscribers,
+            ReactionPermissionLevelDto::Vips => ReactionPermissionLevel::Vips,
+            ReactionPermissionLevelDto::Mods => ReactionPermissionLevel::Mods,
+            ReactionPermissionLevelDto::Broadcaster => ReactionPermissionLevel::Broadcaster,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatTriggerMatchOptionsDto {
+    pub case_sensitive: bool,
+    pub punctuation_as_boundary: bool,
+    pub normalise_unicode: bool,
+}
+
+impl Default for ChatTriggerMatchOptionsDto {
+    fn default() -> Self {
+        ChatTriggerMatchOptions::default().into()
+    }
+}
+
+impl From<ChatTriggerMatchOptions> for ChatTriggerMatchOptionsDto {
+    fn from(value: ChatTriggerMatchOptions) -> Self {
+        Self {
+            case_sensitive: value.case_sensitive,
+            punctuation_as_boundary: value.punctuation_as_boundary,
+            normalise_unicode: value.normalise_unicode,
+        }
+    }
+}
+
+impl From<ChatTriggerMatchOptionsDto> for ChatTriggerMatchOptions {
+    fn from(value: ChatTriggerMatchOptionsDto) -> Self {
+        Self {
+            case_sensitive: value.case_sensitive,
+            punctuation_as_boundary: value.punctuation_as_boundary,
+            normalise_unicode: value.normalise_unicode,
         }
     }
 }
 
 #[derive(Clone, Debug)]
 pub enum ReactionTriggerDto {
-    ChatCommand(String),
-    ChatKeyword(String),
-    Action(String, String)
+    ChatCommand(String, ChatTriggerMatchOptionsDto),
+    ChatKeyword(String, ChatTriggerMatchOptionsDto),
+    ChatPattern(String),
+    Action(String, String),
+    ActionRange(String, u32, Option<u32>),
+    StreamEvent(StreamEventKindDto),
+}
+
+#[derive(Clone, Debug)]
+pub enum StreamEventKindDto {
+    Subscription,
+    Resubscription,
+    GiftSubscription,
+    MysteryGift,
+    Raid,
+    Follow,
+    HypeTrainStart,
+    HypeTrainLevelUp,
+    HypeTrainEnd,
 }
 
 impl From<ReactionTrigger> for ReactionTriggerDto {
     fn from(value: ReactionTrigger) -> Self {
         match value {
-            ReactionTrigger::ChatCommand(chat) => ReactionTriggerDto::ChatCommand(chat.text),
-            ReactionTrigger::ChatKeyword(chat) => ReactionTriggerDto::ChatKeyword(chat.text),
+            ReactionTrigger::ChatCommand(chat) => ReactionTriggerDto::ChatCommand(chat.text, chat.options.into()),
+            ReactionTrigger::ChatKeyword(chat) => ReactionTriggerDto::ChatKeyword(chat.text, chat.options.into()),
+            ReactionTrigger::ChatPattern(chat) => ReactionTriggerDto::ChatPattern(chat.pattern),
             ReactionTrigger::Action(action) => ReactionTriggerDto::Action(action.id, action.name),
+            ReactionTrigger::ActionRange(action) => ReactionTriggerDto::ActionRange(action.name, action.min, action.max),
+            ReactionTrigger::StreamEvent(stream_event) => ReactionTriggerDto::StreamEvent(stream_event.kind.into()),
         }
     }
 }
@@ -44,9 +182,44 @@ impl TryInto<ReactionTrigger> for ReactionTriggerDto {
     type Error = ();
     fn try_into(self) -> Result<ReactionTrigger, Self::Error> {
         match self {
-            ReactionTriggerDto::ChatCommand(text) => ReactionTrigger::new_chat_command(text),
-            ReactionTriggerDto::ChatKeyword(text) => ReactionTrigger::new_chat_keyword(text),
+            ReactionTriggerDto::ChatCommand(text, options) => ReactionTrigger::new_chat_command_with_options(text, options.into()),
+            ReactionTriggerDto::ChatKeyword(text, options) => ReactionTrigger::new_chat_keyword_with_options(text, options.into()),
+            ReactionTriggerDto::ChatPattern(pattern) => ReactionTrigger::new_chat_pattern(pattern),
             ReactionTriggerDto::Action(id, name) => ReactionTrigger::new_action(id, name),
+            ReactionTriggerDto::ActionRange(name, min, max) => ReactionTrigger::new_action_range(name, min, max),
+            ReactionTriggerDto::StreamEvent(kind) => Ok(ReactionTrigger::new_stream_event(kind.into())),
+        }
+    }
+}
+
+impl From<StreamEventKind> for StreamEventKindDto {
+    fn from(value: StreamEventKind) -> Self {
+        match value {
+            StreamEventKind::Subscription => StreamEventKindDto::Subscription,
+            StreamEventKind::Resubscription => StreamEventKindDto::Resubscription,
+            StreamEventKind::GiftSubscription => StreamEventKindDto::GiftSubscription,
+            StreamEventKind::MysteryGift => StreamEventKindDto::MysteryGift,
+            StreamEventKind::Raid => StreamEventKindDto::Raid,
+            StreamEventKind::Follow => StreamEventKindDto::Follow,
+            StreamEventKind::HypeTrainStart => StreamEventKindDto::HypeTrainStart,
+            StreamEventKind::HypeTrainLevelUp => StreamEventKindDto::HypeTrainLevelUp,
+            StreamEventKind::HypeTrainEnd => StreamEventKindDto::HypeTrainEnd,
+        }
+    }
+}
+
+impl From<StreamEventKindDto> for StreamEventKind {
+    fn from(value: StreamEventKindDto) -> Self {
+        match value {
+            StreamEventKindDto::Subscription => StreamEventKind::Subscription,
+            StreamEventKindDto::Resubscription => StreamEventKind::Resubscription,
+            StreamEventKindDto::GiftSubscription => StreamEventKind::GiftSubscription,
+            StreamEventKindDto::MysteryGift => StreamEventKind::MysteryGift,
+            StreamEventKindDto::Raid => StreamEventKind::Raid,
+            StreamEventKindDto::Follow => StreamEventKind::Follow,
+            StreamEventKindDto::HypeTrainStart => StreamEventKind::HypeTrainStart,
+            StreamEventKindDto::HypeTrainLevelUp => StreamEventKind::HypeTrainLevelUp,
+            StreamEventKindDto::HypeTrainEnd => StreamEventKind::HypeTrainEnd,
         }
     }
 }
\ No newline at end of file
@@ -1,14 +1,18 @@
 use std::fmt::Debug;
 use std::clone::Clone;
 use crate::domain::reactions::reaction::{Milliseconds};
-use crate::domain::reactions::reaction_definition::{MovingReactionStepDefinition, ReactionStepDefinition, ReactionStepSkipDefinition, ReactionStepMessageAlternativesDefinition, ReactionStepMessageDefinition, TalkingReactionStepDefinition};
+use crate::application::reactions::dtos::reaction_dto::ReactionPermissionLevelDto;
+use crate::domain::reactions::reaction_definition::{ChatMessageReactionStepDefinition, MovingReactionStepDefinition, ReactionCondition, ReactionStepDefinition, ReactionStepSkipDefinition, ReactionStepMessageAlternativeDefinition, ReactionStepMessageAlternativesDefinition, ReactionStepMessageDefinition, TalkingReactionStepDefinition};
 use crate::domain::animations::animation::{Animation, AnimationFrame, AnimationFrames, CreateAnimationError};
+use crate::domain::emotions::emotion::EmotionId;
 use crate::domain::images::image::ImageId;
 
 #[derive(Clone, Debug)]
 pub enum ReactionStepDto {
     Moving(MovingReactionStepDto),
-    Talking(TalkingReactionStepDto)
+    Talking(TalkingReactionStepDto),
+    CompositeTalking(CompositeTalkingReactionStepDto),
+    ChatMessage(ChatMessageReactionStepDto)
 }
 
 #[derive(Clone, Debug)]
@@ -24,10 +28,30 @@ pub struct TalkingReactionStepDto {
     pub skip: ReactionStepSkipDto
 }
 
+#[derive(Clone, Debug)]
+pub struct CompositeTalkingReactionStepDto {
+    pub parts: Vec<TalkingReactionStepDto>
+}
+
+#[derive(Clone, Debug)]
+pub struct ChatMessageReactionStepDto {
+    pub text: Vec<ReactionStepTextAlternativeDto>,
+    pub reply: bool
+}
+
 #[derive(Clone, Debug)]
 pub struct ReactionStepTextAlternativeDto {
     pub probability: Option<f32>,
-    pub text: ReactionStepTextDto
+    pub text: ReactionStepTextDto,
+    pub conditions: Vec<ReactionConditionDto>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReactionConditionDto {
+    CountMultipleOf(u32),
+    CountEquals(u32),
+    MinimumPermissionLevel(ReactionPermissionLevelDto),
+    HasTarget,
 }
 
 #[derive(Clone, Debug)]
@@ -55,7 +79,33 @@ impl From<ReactionStepDefinition> for ReactionStepDto {
         match step {
             ReactionStepDefinition::Moving(step) => step.into(),
             ReactionStepDefinition::Talking(step) => step.into(),
-            ReactionStepDefinition::CompositeTalking(_) => todo!("This should never happen, reaction step composite is not implemented")
+            ReactionStepDefinition::CompositeTalking(steps) => ReactionStepDto::CompositeTalking(CompositeTalkingReactionStepDto {
+                parts: steps.into_iter().map(From::from).collect()
+            }),
+            ReactionStepDefinition::ChatMessage(step) => step.into(),
+        }
+    }
+}
+
+impl TryInto<ReactionStepDefinition> for ReactionStepDto {
+    type Error = ();
+
+    fn try_into(self) -> Result<ReactionStepDefinition, Self::Error> {
+        match self {
+            ReactionStepDto::Moving(moving_step) => Ok(ReactionStepDefinition::Moving(MovingReactionStepDefinition {
+                animation: frames_dtos_to_animation(moving_step.animation).map_err(|_| ())?,
+                skip: moving_step.skip.into()
+            })),
+            ReactionStepDto::Talking(talking_step) => Ok(ReactionStepDefinition::Talking(talking_step.try_into()?)),
+            ReactionStepDto::CompositeTalking(composite_step) if composite_step.parts.is_empty() => Err(()),
+            ReactionStepDto::CompositeTalking(composite_step) => Ok(ReactionStepDefinition::CompositeTalking(composite_step.parts
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<Vec<TalkingReactionStepDefinition>, ()>>()?)),
+            ReactionStepDto::ChatMessage(chat_message_step) => Ok(ReactionStepDefinition::ChatMessage(ChatMessageReactionStepDefinition {
+                alternatives: text_alternatives_dtos_to_definition(&chat_message_step.text).map_err(|_| ())?,
+                reply: chat_message_step.reply
+            })),
         }
     }
 }
@@ -91,10 +141,43 @@ impl Into<ReactionStepSkipDefinition> for ReactionStepSkipDto {
 
 impl From<TalkingReactionStepDefinition> for ReactionStepDto {
     fn from(talking_step: TalkingReactionStepDefinition) -> Self {
-        ReactionStepDto::Talking(TalkingReactionStepDto {
+        ReactionStepDto::Talking(talking_step.into())
+    }
+}
+
+impl From<TalkingReactionStepDefinition> for TalkingReactionStepDto {
+    fn from(talking_step: TalkingReactionStepDefinition) -> Self {
+        TalkingReactionStepDto {
             skip: talking_step.skip.into(),
             emotion_id: talking_step.emotion_id.0,
             text: from_text_alternatives_domain(talking_step.alternatives)
+        }
+    }
+}
+
+impl TryInto<TalkingReactionStepDefinition> for TalkingReactionStepDto {
+    type Error = ();
+
+    fn try_into(self) -> Result<TalkingReactionStepDefinition, Self::Error> {
+        Ok(TalkingReactionStepDefinition {
+            skip: self.skip.into(),
+            emotion_id: EmotionId(self.emotion_id),
+            alternatives: text_alternatives_dtos_to_definition(&self.text).map_err(|_| ())?
+        })
+    }
+}
+
+impl From<Vec<TalkingReactionStepDefinition>> for ReactionStepDto {
+    fn from(talking_steps: Vec<TalkingReactionStepDefinition>) -> Self {
+        ReactionStepDefinition::CompositeTalking(talking_steps).into()
+    }
+}
+
+impl From<ChatMessageReactionStepDefinition> for ReactionStepDto {
+    fn from(chat_message_step: ChatMessageReactionStepDefinition) -> Self {
+        ReactionStepDto::ChatMessage(ChatMessageReactionStepDto {
+            text: from_text_alternatives_domain(chat_message_step.alternatives),
+            reply: chat_message_step.reply
         })
     }
 }
@@ -102,10 +185,53 @@ impl From<TalkingReactionStepDefinition> for ReactionStepDto {
 fn from_text_alternatives_domain(text_definition_alternatives: ReactionStepMessageAlternativesDefinition) -> Vec<ReactionStepTextAlternativeDto> {
     text_definition_alternatives.0.iter().map(|alternative| ReactionStepTextAlternativeDto {
         text: alternative.message.clone().into(),
-        probability: alternative.probability
+        probability: alternative.probability,
+        conditions: alternative.conditions.iter().cloned().map(From::from).collect()
     }).collect()
 }
 
+pub(crate) fn text_alternatives_dtos_to_definition(alternatives: &[ReactionStepTextAlternativeDto]) -> Result<ReactionStepMessageAlternativesDefinition, String> {
+    let alternatives = alternatives.iter().map(|alternative| Ok(ReactionStepMessageAlternativeDefinition {
+        message: match &alternative.text {
+            ReactionStepTextDto::Instant(text) => ReactionStepMessageDefinition::Instant(text.clone()),
+            ReactionStepTextDto::LetterByLetter(text) => ReactionStepMessageDefinition::LetterByLetter(text.clone()),
+        },
+        probability: alternative.probability,
+        conditions: alternative.conditions.iter()
+            .cloned()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<ReactionCondition>, ()>>()
+            .map_err(|_| String::from("The request text has invalid conditions"))?
+    })).collect::<Result<Vec<ReactionStepMessageAlternativeDefinition>, String>>()?;
+
+    ReactionStepMessageAlternativesDefinition::try_new(alternatives)
+        .map_err(|_| String::from("The request text does not have 100 probability total"))
+}
+
+impl From<ReactionCondition> for ReactionConditionDto {
+    fn from(condition: ReactionCondition) -> Self {
+        match condition {
+            ReactionCondition::CountMultipleOf(count) => ReactionConditionDto::CountMultipleOf(count),
+            ReactionCondition::CountEquals(count) => ReactionConditionDto::CountEquals(count),
+            ReactionCondition::MinimumPermissionLevel(permission_level) => ReactionConditionDto::MinimumPermissionLevel(permission_level.into()),
+            ReactionCondition::HasTarget => ReactionConditionDto::HasTarget,
+        }
+    }
+}
+
+impl TryInto<ReactionCondition> for ReactionConditionDto {
+    type Error = ();
+
+    fn try_into(self) -> Result<ReactionCondition, Self::Error> {
+        match self {
+            ReactionConditionDto::CountMultipleOf(count) => ReactionCondition::new_count_multiple_of(count),
+            ReactionConditionDto::CountEquals(count) => Ok(ReactionCondition::CountEquals(count)),
+            ReactionConditionDto::MinimumPermissionLevel(permission_level) => Ok(ReactionCondition::MinimumPermissionLevel(permission_level.into())),
+            ReactionConditionDto::HasTarget => Ok(ReactionCondition::HasTarget),
+        }
+    }
+}
+
 impl From<ReactionStepMessageDefinition> for ReactionStepTextDto {
     fn from(text: ReactionStepMessageDefinition) -> Self {
         match text {
@@ -0,0 +1,157 @@
+use std::fmt::Debug;
+use thiserror::Error;
+use crate::application::reactions::dtos::reaction_step_dto::{ReactionStepDto, ReactionStepTextAlternativeDto, text_alternatives_dtos_to_definition};
+use crate::domain::reactions::reaction_definition::{ChatMessageReactionStepDefinition, ReactionDefinition, ReactionDefinitionId};
+use crate::domain::reactions::reaction_domain_service::{add_chat_message_step_to_reaction, replace_chat_message_step_in_reaction};
+use crate::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+
+#[derive(Debug, Error)]
+pub enum AddChatMessageStepToReactionError {
+    #[error("Bad request")]
+    BadRequest(String),
+}
+
+pub struct InsertChatMessageStepToReactionRequest {
+    pub reaction_id: String,
+    pub step_index: usize,
+    pub reply: bool,
+    pub alternatives: Vec<ReactionStepTextAlternativeDto>,
+}
+
+pub async fn insert_chat_message_step_to_reaction(request: InsertChatMessageStepToReactionRequest, repository: &dyn ReactionDefinitionRepository) -> Result<ReactionStepDto, AddChatMessageStepToReactionError> {
+    let mut reaction = repository.get(&ReactionDefinitionId(request.reaction_id.clone())).await
+        .ok_or_else(|| AddChatMessageStepToReactionError::BadRequest(String::from("The requested reaction id does not exist")))?;
+
+    let reaction_step = ChatMessageReactionStepDefinition {
+        reply: request.reply,
+        alternatives: text_alternatives_dtos_to_definition(&request.alternatives)
+            .map_err(AddChatMessageStepToReactionError::BadRequest)?
+    };
+    reaction_step.alternatives.validate_templates(&reaction.template_variables())
+        .map_err(|error| AddChatMessageStepToReactionError::BadRequest(error.to_string()))?;
+    insert_step_in_correct_index(&mut reaction, reaction_step.clone(), request.step_index)?;
+    repository.update(&reaction).await.unwrap();
+
+    Ok(reaction_step.into())
+}
+
+fn insert_step_in_correct_index(reaction: &mut ReactionDefinition, reaction_step: ChatMessageReactionStepDefinition, step_index: usize) -> Result<(), AddChatMessageStepToReactionError> {
+    if step_index > reaction.steps.len() {
+        return Err(AddChatMessageStepToReactionError::BadRequest(String::from("Index out of bounds")));
+    } else if step_index == reaction.steps.len() {
+        add_chat_message_step_to_reaction(reaction, reaction_step);
+    } else {
+        replace_chat_message_step_in_reaction(reaction, reaction_step, step_index);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::reactions::get::{get_reaction, GetReactionRequest};
+    use crate::application::reactions::dtos::reaction_step_dto::{ChatMessageReactionStepDto, ReactionStepTextDto};
+    use crate::domain::reactions::reaction_definition_repository::tests::setup_dummy_chat_command_reaction_definition;
+    use crate::persistence::reactions::in_memory_reaction_repository::InMemoryReactionRepository;
+
+    #[tokio::test]
+    async fn insert_chat_message_step_to_reaction_wrong_id_return_error() {
+        let repository = InMemoryReactionRepository::new();
+        setup_dummy_chat_command_reaction_definition(&repository).await;
+
+        let result = insert_chat_message_step_to_reaction(InsertChatMessageStepToReactionRequest {
+            reaction_id: String::from("new id"),
+            ..base_request()
+        }, &repository).await;
+
+        assert!(matches!(result, Err(AddChatMessageStepToReactionError::BadRequest(_))), "Expected insert step to fail with bad request");
+    }
+
+    #[tokio::test]
+    async fn insert_chat_message_step_to_reaction_correctly_save_text_and_reply() {
+        let repository = InMemoryReactionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+
+        insert_chat_message_step_to_reaction(InsertChatMessageStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            reply: true,
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Hi ${user}")), probability: None, conditions: vec![] }],
+            ..base_request()
+        }, &repository).await.expect("Expected insert step not to fail");
+
+        let chat_message_step = get_chat_message_step_at(&repository, &reaction.id.0, 0).await;
+        assert!(chat_message_step.reply);
+        assert!(matches!(chat_message_step.text[..], [ReactionStepTextAlternativeDto {
+            text: ReactionStepTextDto::Instant(ref text), probability: None, ..
+        }] if text == "Hi ${user}"));
+    }
+
+    #[tokio::test]
+    async fn insert_chat_message_step_to_reaction_replaces_existing_step() {
+        let repository = InMemoryReactionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+
+        insert_chat_message_step_to_reaction(InsertChatMessageStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            ..base_request()
+        }, &repository).await.expect("Expected first insert step not to fail");
+        insert_chat_message_step_to_reaction(InsertChatMessageStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            reply: true,
+            ..base_request()
+        }, &repository).await.expect("Expected second insert step not to fail");
+
+        let reaction = get_reaction(GetReactionRequest { id: reaction.id.0 }, &repository).await.unwrap();
+        assert_eq!(reaction.steps.len(), 1);
+        assert!(matches!(&reaction.steps[0], ReactionStepDto::ChatMessage(ChatMessageReactionStepDto { reply: true, .. })));
+    }
+
+    #[tokio::test]
+    async fn insert_chat_message_step_to_reaction_index_out_of_bounds_errors() {
+        let repository = InMemoryReactionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+
+        let result = insert_chat_message_step_to_reaction(InsertChatMessageStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            step_index: 1,
+            ..base_request()
+        }, &repository).await;
+
+        assert!(matches!(result, Err(AddChatMessageStepToReactionError::BadRequest(message)) if message == "Index out of bounds"));
+    }
+
+    #[tokio::test]
+    async fn insert_chat_message_step_to_reaction_text_with_invalid_template_errors() {
+        let repository = InMemoryReactionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+
+        let result = insert_chat_message_step_to_reaction(InsertChatMessageStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Hi ${user:shout}")), probability: None, conditions: vec![] }],
+            ..base_request()
+        }, &repository).await;
+
+        assert!(matches!(result, Err(AddChatMessageStepToReactionError::BadRequest(message)) if message == "Filter shout does not exist"));
+        let reaction = get_reaction(GetReactionRequest { id: reaction.id.0 }, &repository).await.unwrap();
+        assert_eq!(reaction.steps.len(), 0);
+    }
+
+    async fn get_chat_message_step_at(repository: &dyn ReactionDefinitionRepository, reaction_id: &str, index: usize) -> ChatMessageReactionStepDto {
+        let reaction = get_reaction(GetReactionRequest { id: reaction_id.to_string() }, repository)
+            .await.unwrap_or_else(|| panic!("should have a reaction with id {}", reaction_id));
+        match reaction.steps.get(index) {
+            Some(ReactionStepDto::ChatMessage(chat_message_step)) => chat_message_step.clone(),
+            _ => unreachable!("should have saved a chat message step at index {}", index),
+        }
+    }
+
+    fn base_request() -> InsertChatMessageStepToReactionRequest {
+        InsertChatMessageStepToReactionRequest {
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("some text")), probability: Some(100.0), conditions: vec![] }],
+            reply: false,
+            step_index: 0,
+            reaction_id: String::from("an id")
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::fmt::Debug;
+use thiserror::Error;
+use crate::application::reactions::dtos::reaction_step_dto::{ReactionStepDto, TalkingReactionStepDto, text_alternatives_dtos_to_definition};
+use crate::domain::emotions::emotion::EmotionId;
+use crate::domain::emotions::emotion_repository::EmotionRepository;
+use crate::domain::reactions::reaction_definition::{ReactionDefinition, ReactionDefinitionId, TalkingReactionStepDefinition};
+use crate::domain::reactions::reaction_domain_service::{add_composite_talking_step_to_reaction, AddStepToReactionError, replace_composite_talking_step_in_reaction};
+use crate::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
+
+#[derive(Debug, Error)]
+pub enum AddCompositeTalkingStepToReactionError {
+    #[error("Bad request")]
+    BadRequest(String),
+    #[error("Wrong emotion details")]
+    BadEmotionRequest(#[from] AddStepToReactionError),
+}
+
+pub struct InsertCompositeTalkingStepToReactionRequest {
+    pub reaction_id: String,
+    pub step_index: usize,
+    pub parts: Vec<TalkingReactionStepDto>,
+}
+
+pub async fn insert_composite_talking_step_to_reaction(request: InsertCompositeTalkingStepToReactionRequest, repository: &dyn ReactionDefinitionRepository, emotion_repository: &dyn EmotionRepository) -> Result<ReactionStepDto, AddCompositeTalkingStepToReactionError> {
+    let mut reaction = repository.get(&ReactionDefinitionId(request.reaction_id.clone())).await
+        .ok_or_else(|| AddCompositeTalkingStepToReactionError::BadRequest(String::from("The requested reaction id does not exist")))?;
+
+    if request.parts.is_empty() {
+        return Err(AddCompositeTalkingStepToReactionError::BadRequest(String::from("A composite talking step needs at least one part")));
+    }
+
+    let reaction_steps = request.parts.iter()
+        .map(|part| Ok(TalkingReactionStepDefinition {
+            skip: part.skip.clone().into(),
+            emotion_id: EmotionId(part.emotion_id.clone()),
+            alternatives: text_alternatives_dtos_to_definition(&part.text)
+                .map_err(AddCompositeTalkingStepToReactionError::BadRequest)?
+        }))
+        .collect::<Result<Vec<TalkingReactionStepDefinition>, AddCompositeTalkingStepToReactionError>>()?;
+    let variables = reaction.template_variables();
+    reaction_steps.iter().try_for_each(|step| step.alternatives.validate_templates(&variables))
+        .map_err(|error| AddCompositeTalkingStepToReactionError::BadRequest(error.to_string()))?;
+
+    insert_step_in_correct_index(&mut reaction, reaction_steps.clone(), request.step_index, emotion_repository).await?;
+    repository.update(&reaction).await.unwrap();
+
+    Ok(reaction_steps.into())
+}
+
+async fn insert_step_in_correct_index(reaction: &mut ReactionDefinition, reaction_steps: Vec<TalkingReactionStepDefinition>, step_index: usize, emotion_repository: &dyn EmotionRepository) -> Result<(), AddCompositeTalkingStepToReactionError> {
+    if step_index > reaction.steps.len() {
+        return Err(AddCompositeTalkingStepToReactionError::BadRequest(String::from("Index out of bounds")));
+    } else if step_index == reaction.steps.len() {
+        add_composite_talking_step_to_reaction(reaction, reaction_steps, emotion_repository).await?;
+    } else {
+        replace_composite_talking_step_in_reaction(reaction, reaction_steps, step_index, emotion_repository).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::reactions::get::{get_reaction, GetReactionRequest};
+    use crate::application::reactions::dtos::reaction_step_dto::{CompositeTalkingReactionStepDto, ReactionStepSkipDto, ReactionStepTextAlternativeDto, ReactionStepTextDto};
+    use crate::domain::emotions::emotion_repository::tests::setup_dummy_emotions;
+    use crate::domain::reactions::reaction_definition_repository::tests::setup_dummy_chat_command_reaction_definition;
+    use crate::persistence::emotions::in_memory_emotion_repository::InMemoryEmotionRepository;
+    use crate::persistence::reactions::in_memory_reaction_repository::InMemoryReactionRepository;
+
+    #[tokio::test]
+    async fn insert_composite_talking_step_to_reaction_wrong_id_return_error() {
+        let repository = InMemoryReactionRepository::new();
+        let emotion_repo = InMemoryEmotionRepository::new();
+        setup_dummy_chat_command_reaction_definition(&repository).await;
+        setup_dummy_emotions(vec!["happy"], &emotion_repo).await;
+
+        let result = insert_composite_talking_step_to_reaction(InsertCompositeTalkingStepToReactionRequest {
+            reaction_id: String::from("new id"),
+            ..base_request()
+        }, &repository, &emotion_repo).await;
+
+        assert!(matches!(result, Err(AddCompositeTalkingStepToReactionError::BadRequest(_))), "Expected insert step to fail with bad request");
+    }
+
+    #[tokio::test]
+    async fn insert_composite_talking_step_to_reaction_valid_input_store_parts_in_order() {
+        let repository = InMemoryReactionRepository::new();
+        let emotion_repo = InMemoryEmotionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+        setup_dummy_emotions(vec!["happy", "sad"], &emotion_repo).await;
+
+        insert_composite_talking_step_to_reaction(InsertCompositeTalkingStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            parts: vec![create_part("happy", "first"), create_part("sad", "second")],
+            ..base_request()
+        }, &repository, &emotion_repo).await.expect("Expected insert step not to fail");
+
+        let composite_step = get_composite_talking_step_at(&repository, &reaction.id.0, 0).await;
+        assert_eq!(composite_step.parts.len(), 2);
+        assert_eq!(composite_step.parts[0].emotion_id, "happy");
+        assert_eq!(composite_step.parts[1].emotion_id, "sad");
+        assert!(matches!(composite_step.parts[1].text[..], [ReactionStepTextAlternativeDto {
+            text: ReactionStepTextDto::Instant(ref text), ..
+        }] if text == "second"));
+    }
+
+    #[tokio::test]
+    async fn insert_composite_talking_step_to_reaction_existing_index_replaces_step() {
+        let repository = InMemoryReactionRepository::new();
+        let emotion_repo = InMemoryEmotionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+        setup_dummy_emotions(vec!["happy", "sad"], &emotion_repo).await;
+
+        insert_composite_talking_step_to_reaction(InsertCompositeTalkingStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            parts: vec![create_part("happy", "first")],
+            ..base_request()
+        }, &repository, &emotion_repo).await.expect("Expected first insert step not to fail");
+        insert_composite_talking_step_to_reaction(InsertCompositeTalkingStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            parts: vec![create_part("sad", "replaced")],
+            ..base_request()
+        }, &repository, &emotion_repo).await.expect("Expected replace step not to fail");
+
+        let updated_reaction = get_reaction(GetReactionRequest { id: reaction.id.0.clone() }, &repository).await.unwrap();
+        let composite_step = get_composite_talking_step_at(&repository, &reaction.id.0, 0).await;
+        assert_eq!(updated_reaction.steps.len(), 1);
+        assert_eq!(composite_step.parts[0].emotion_id, "sad");
+    }
+
+    #[tokio::test]
+    async fn insert_composite_talking_step_to_reaction_index_out_of_bounds_errors() {
+        let repository = InMemoryReactionRepository::new();
+        let emotion_repo = InMemoryEmotionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+        setup_dummy_emotions(vec!["happy"], &emotion_repo).await;
+
+        let result = insert_composite_talking_step_to_reaction(InsertCompositeTalkingStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            step_index: 1,
+            ..base_request()
+        }, &repository, &emotion_repo).await;
+
+        assert!(matches!(result, Err(AddCompositeTalkingStepToReactionError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn insert_composite_talking_step_to_reaction_no_parts_errors() {
+        let repository = InMemoryReactionRepository::new();
+        let emotion_repo = InMemoryEmotionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+        setup_dummy_emotions(vec!["happy"], &emotion_repo).await;
+
+        let result = insert_composite_talking_step_to_reaction(InsertCompositeTalkingStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            parts: vec![],
+            ..base_request()
+        }, &repository, &emotion_repo).await;
+
+        assert!(matches!(result, Err(AddCompositeTalkingStepToReactionError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn insert_composite_talking_step_to_reaction_part_with_wrong_probability_errors() {
+        let repository = InMemoryReactionRepository::new();
+        let emotion_repo = InMemoryEmotionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+        setup_dummy_emotions(vec!["happy"], &emotion_repo).await;
+
+        let mut wrong_part = create_part("happy", "text");
+        wrong_part.text[0].probability = Some(50.0);
+        let result = insert_composite_talking_step_to_reaction(InsertCompositeTalkingStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            parts: vec![create_part("happy", "text"), wrong_part],
+            ..base_request()
+        }, &repository, &emotion_repo).await;
+
+        assert!(matches!(result, Err(AddCompositeTalkingStepToReactionError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn insert_composite_talking_step_to_reaction_part_with_invalid_template_errors() {
+        let repository = InMemoryReactionRepository::new();
+        let emotion_repo = InMemoryEmotionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+        setup_dummy_emotions(vec!["happy"], &emotion_repo).await;
+
+        let result = insert_composite_talking_step_to_reaction(InsertCompositeTalkingStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            parts: vec![create_part("happy", "text"), create_part("happy", "Hi ${user")],
+            ..base_request()
+        }, &repository, &emotion_repo).await;
+
+        assert!(matches!(result, Err(AddCompositeTalkingStepToReactionError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn insert_composite_talking_step_to_reaction_with_non_existing_emotion_id_errors() {
+        let repository = InMemoryReactionRepository::new();
+        let emotion_repo = InMemoryEmotionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+        setup_dummy_emotions(vec!["happy"], &emotion_repo).await;
+
+        let result = insert_composite_talking_step_to_reaction(InsertCompositeTalkingStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            parts: vec![create_part("happy", "text"), create_part("not happy", "text")],
+            ..base_request()
+        }, &repository, &emotion_repo).await;
+
+        assert!(matches!(result, Err(AddCompositeTalkingStepToReactionError::BadEmotionRequest(_))), "Expected insert step to fail with bad emotion request");
+        let reaction = get_reaction(GetReactionRequest { id: reaction.id.0 }, &repository).await.unwrap();
+        assert_eq!(reaction.steps.len(), 0);
+    }
+
+    async fn get_composite_talking_step_at(repository: &dyn ReactionDefinitionRepository, reaction_id: &String, index: usize) -> CompositeTalkingReactionStepDto {
+        let updated_reaction = get_reaction(GetReactionRequest { id: reaction_id.clone() }, repository)
+            .await.expect(format!("should have a reaction with id {}", reaction_id).as_str());
+        match updated_reaction.steps.get(index) {
+            Some(ReactionStepDto::CompositeTalking(composite_step)) => composite_step.clone(),
+            _ => unreachable!("should have saved a composite talking step"),
+        }
+    }
+
+    fn create_part(emotion_id: &str, text: &str) -> TalkingReactionStepDto {
+        TalkingReactionStepDto {
+            text: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from(text)), probability: Some(100.0), conditions: vec![] }],
+            emotion_id: String::from(emotion_id),
+            skip: ReactionStepSkipDto::ImmediatelyAfter
+        }
+    }
+
+    fn base_request() -> InsertCompositeTalkingStepToReactionRequest {
+        InsertCompositeTalkingStepToReactionRequest {
+            parts: vec![create_part("happy", "some text")],
+            step_index: 0,
+            reaction_id: String::from("an id")
+        }
+    }
+}
@@ -1,9 +1,9 @@
 use std::fmt::Debug;
 use thiserror::Error;
-use crate::application::reactions::dtos::reaction_step_dto::{ReactionStepDto, ReactionStepSkipDto, ReactionStepTextAlternativeDto, ReactionStepTextDto};
+use crate::application::reactions::dtos::reaction_step_dto::{ReactionStepDto, ReactionStepSkipDto, ReactionStepTextAlternativeDto, text_alternatives_dtos_to_definition};
 use crate::domain::emotions::emotion::EmotionId;
 use crate::domain::emotions::emotion_repository::EmotionRepository;
-use crate::domain::reactions::reaction_definition::{ReactionDefinition, ReactionDefinitionId, ReactionStepMessageAlternativeDefinition, ReactionStepMessageAlternativesDefinition, ReactionStepMessageDefinition, TalkingReactionStepDefinition};
+use crate::domain::reactions::reaction_definition::{ReactionDefinition, ReactionDefinitionId, TalkingReactionStepDefinition};
 use crate::domain::reactions::reaction_domain_service::{add_talking_step_to_reaction, AddStepToReactionError, replace_talking_step_in_reaction};
 use crate::domain::reactions::reaction_definition_repository::ReactionDefinitionRepository;
 
@@ -30,14 +30,11 @@ pub async fn insert_talking_step_to_reaction(request: InsertTalkingStepToReactio
     let reaction_step = TalkingReactionStepDefinition {
         skip: request.skip.into(),
         emotion_id: EmotionId(request.emotion_id),
-        alternatives: ReactionStepMessageAlternativesDefinition::try_new(request.alternatives.iter().map(|alternative| ReactionStepMessageAlternativeDefinition {
-            message: match &alternative.text {
-                ReactionStepTextDto::Instant(text) => ReactionStepMessageDefinition::Instant(text.clone()),
-                ReactionStepTextDto::LetterByLetter(text) => ReactionStepMessageDefinition::LetterByLetter(text.clone()),
-            },
-            probability: alternative.probability
-        }).collect()).map_err(|_| AddTalkingStepToReactionError::BadRequest(String::from("The request text does not have 100 probability total")))?
+        alternatives: text_alternatives_dtos_to_definition(&request.alternatives)
+            .map_err(AddTalkingStepToReactionError::BadRequest)?
     };
+    reaction_step.alternatives.validate_templates(&reaction.template_variables())
+        .map_err(|error| AddTalkingStepToReactionError::BadRequest(error.to_string()))?;
     insert_step_in_correct_index(&mut reaction, reaction_step.clone(), request.step_index, emotion_repository).await?;
     repository.update(&reaction).await.unwrap();
 
@@ -61,9 +58,10 @@ mod tests {
     use super::*;
     use crate::application::reactions::get::{get_reaction, GetReactionRequest};
     use crate::persistence::reactions::in_memory_reaction_repository::InMemoryReactionRepository;
-    use crate::application::reactions::dtos::reaction_step_dto::{ReactionStepSkipDto, ReactionStepTextDto, TalkingReactionStepDto};
+    use crate::application::reactions::dtos::reaction_dto::ReactionPermissionLevelDto;
+    use crate::application::reactions::dtos::reaction_step_dto::{ReactionConditionDto, ReactionStepSkipDto, ReactionStepTextDto, TalkingReactionStepDto};
     use crate::domain::emotions::emotion_repository::tests::setup_dummy_emotions;
-    use crate::domain::reactions::reaction_definition_repository::tests::setup_dummy_chat_command_reaction_definition;
+    use crate::domain::reactions::reaction_definition_repository::tests::{setup_dummy_chat_command_reaction_definition, setup_dummy_chat_pattern_reaction_definitions};
     use crate::persistence::emotions::in_memory_emotion_repository::InMemoryEmotionRepository;
 
     #[tokio::test]
@@ -110,8 +108,8 @@ mod tests {
             reaction_id: reaction.id.0.clone(),
             emotion_id: String::from("happy"),
             alternatives: vec![
-                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("some text2")), probability: Some(75.0) },
-                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::LetterByLetter(String::from("some text3")), probability: Some(26.0) },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("some text2")), probability: Some(75.0), conditions: vec![] },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::LetterByLetter(String::from("some text3")), probability: Some(26.0), conditions: vec![] },
             ],
             ..base_request()
         }, &repository, &emotion_repo).await;
@@ -120,7 +118,7 @@ mod tests {
             reaction_id: reaction.id.0.clone(),
             emotion_id: String::from("happy"),
             alternatives: vec![
-                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("some text2")), probability: Some(99.0) },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("some text2")), probability: Some(99.0), conditions: vec![] },
             ],
             ..base_request()
         }, &repository, &emotion_repo).await;
@@ -140,17 +138,17 @@ mod tests {
             reaction_id: reaction.id.0.clone(),
             emotion_id: String::from("happy"),
             alternatives: vec![
-                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("some text2")), probability: None },
-                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::LetterByLetter(String::from("some text3")), probability: None },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("some text2")), probability: None, conditions: vec![] },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::LetterByLetter(String::from("some text3")), probability: None, conditions: vec![] },
             ],
             ..base_request()
         }, &repository, &emotion_repo).await.expect("Inserting step with no probability alternatives should not fail");
 
         let talking_step = get_talking_animation_step_at(&repository, &reaction.id.0, 0).await;
         assert!(matches!(talking_step.text[..], [ReactionStepTextAlternativeDto {
-            text: ReactionStepTextDto::Instant(_), probability: None
+            text: ReactionStepTextDto::Instant(_), probability: None, ..
         }, ReactionStepTextAlternativeDto {
-            text: ReactionStepTextDto::LetterByLetter(_), probability: None
+            text: ReactionStepTextDto::LetterByLetter(_), probability: None, ..
         }]));
     }
 
@@ -171,6 +169,106 @@ mod tests {
         assert!(matches!(result, Err(AddTalkingStepToReactionError::BadRequest(_))));
     }
 
+    #[tokio::test]
+    async fn insert_talking_step_to_reaction_text_with_invalid_template_errors() {
+        let repository = InMemoryReactionRepository::new();
+        let emotion_repo = InMemoryEmotionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+        setup_dummy_emotions(vec!["happy"], &emotion_repo).await;
+
+        let result = insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            emotion_id: String::from("happy"),
+            alternatives: vec![
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Hi ${user}")), probability: None, conditions: vec![] },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::LetterByLetter(String::from("Hi ${user:shout}")), probability: None, conditions: vec![] },
+            ],
+            ..base_request()
+        }, &repository, &emotion_repo).await;
+
+        assert!(matches!(result, Err(AddTalkingStepToReactionError::BadRequest(message)) if message == "Filter shout does not exist"));
+        let reaction = get_reaction(GetReactionRequest { id: reaction.id.0 }, &repository).await.unwrap();
+        assert_eq!(reaction.steps.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn insert_talking_step_to_reaction_text_with_unknown_placeholder_errors() {
+        let repository = InMemoryReactionRepository::new();
+        let emotion_repo = InMemoryEmotionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+        setup_dummy_emotions(vec!["happy"], &emotion_repo).await;
+
+        let result = insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            emotion_id: String::from("happy"),
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("Hi ${usr}, you are ${arg1}")), probability: None, conditions: vec![] }],
+            ..base_request()
+        }, &repository, &emotion_repo).await;
+
+        assert!(matches!(result, Err(AddTalkingStepToReactionError::BadRequest(message)) if message == "Placeholder usr does not exist"));
+        let reaction = get_reaction(GetReactionRequest { id: reaction.id.0 }, &repository).await.unwrap();
+        assert_eq!(reaction.steps.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn insert_talking_step_to_reaction_text_with_pattern_capture_placeholder_is_valid() {
+        let repository = InMemoryReactionRepository::new();
+        let emotion_repo = InMemoryEmotionRepository::new();
+        let reaction = setup_dummy_chat_pattern_reaction_definitions(vec![r"^I like (?P<food>\w+)"], &repository).await.remove(0);
+        setup_dummy_emotions(vec!["happy"], &emotion_repo).await;
+
+        let result = insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            emotion_id: String::from("happy"),
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("${user} likes ${food}")), probability: None, conditions: vec![] }],
+            ..base_request()
+        }, &repository, &emotion_repo).await;
+
+        assert!(result.is_ok(), "Expected placeholders captured by the pattern to be valid");
+    }
+
+    #[tokio::test]
+    async fn insert_talking_step_to_reaction_text_with_invalid_condition_errors() {
+        let repository = InMemoryReactionRepository::new();
+        let emotion_repo = InMemoryEmotionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+        setup_dummy_emotions(vec!["happy"], &emotion_repo).await;
+
+        let result = insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            emotion_id: String::from("happy"),
+            alternatives: vec![
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("some text")), probability: None, conditions: vec![] },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("never")), probability: None, conditions: vec![ReactionConditionDto::CountMultipleOf(0)] },
+            ],
+            ..base_request()
+        }, &repository, &emotion_repo).await;
+
+        assert!(matches!(result, Err(AddTalkingStepToReactionError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn insert_talking_step_to_reaction_saves_text_conditions() {
+        let repository = InMemoryReactionRepository::new();
+        let emotion_repo = InMemoryEmotionRepository::new();
+        let reaction = setup_dummy_chat_command_reaction_definition(&repository).await;
+        setup_dummy_emotions(vec!["happy"], &emotion_repo).await;
+
+        insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
+            reaction_id: reaction.id.0.clone(),
+            emotion_id: String::from("happy"),
+            alternatives: vec![
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("some text")), probability: None, conditions: vec![] },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("mods only")), probability: Some(50.0), conditions: vec![ReactionConditionDto::MinimumPermissionLevel(ReactionPermissionLevelDto::Mods), ReactionConditionDto::HasTarget] },
+            ],
+            ..base_request()
+        }, &repository, &emotion_repo).await.expect("Expected insert step not to fail");
+
+        let talking_step = get_talking_animation_step_at(&repository, &reaction.id.0, 0).await;
+        assert_eq!(talking_step.text[0].conditions, vec![]);
+        assert_eq!(talking_step.text[1].conditions, vec![ReactionConditionDto::MinimumPermissionLevel(ReactionPermissionLevelDto::Mods), ReactionConditionDto::HasTarget]);
+    }
+
     #[tokio::test]
     async fn insert_talking_step_to_reaction_correctly_save_text() {
         let repository = InMemoryReactionRepository::new();
@@ -181,7 +279,7 @@ mod tests {
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
             reaction_id: reaction.id.0.clone(),
             emotion_id: String::from("happy"),
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::LetterByLetter(String::from("some text1")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::LetterByLetter(String::from("some text1")), probability: Some(100.0), conditions: vec![] }],
             ..base_request()
         }, &repository, &emotion_repo).await.expect("Expected insert step not to fail");
         insert_talking_step_to_reaction(InsertTalkingStepToReactionRequest {
@@ -189,8 +287,8 @@ mod tests {
             reaction_id: reaction.id.0.clone(),
             emotion_id: String::from("happy"),
             alternatives: vec![
-                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("some text2")), probability: Some(75.0) },
-                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::LetterByLetter(String::from("some text3")), probability: Some(25.0) },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("some text2")), probability: Some(75.0), conditions: vec![] },
+                ReactionStepTextAlternativeDto { text: ReactionStepTextDto::LetterByLetter(String::from("some text3")), probability: Some(25.0), conditions: vec![] },
             ],
             ..base_request()
         }, &repository, &emotion_repo).await.expect("Expected insert step not to fail");
@@ -200,11 +298,11 @@ mod tests {
 
         assert!(matches!(talking_step_1.text[..], [ReactionStepTextAlternativeDto {
             text: ReactionStepTextDto::LetterByLetter(ref text),
-            probability: Some(probability)
+            probability: Some(probability), ..
         }] if text == "some text1" && probability == 100.0));
         assert!(matches!(talking_step_2.text[..], [
-            ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(ref text_1), probability: Some(probability_1) },
-            ReactionStepTextAlternativeDto { text: ReactionStepTextDto::LetterByLetter(ref text_2), probability: Some(probability_2) },
+            ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(ref text_1), probability: Some(probability_1), .. },
+            ReactionStepTextAlternativeDto { text: ReactionStepTextDto::LetterByLetter(ref text_2), probability: Some(probability_2), .. },
         ] if text_1 == "some text2" && probability_1 == 75.0 && text_2 == "some text3" && probability_2 == 25.0));
     }
 
@@ -283,7 +381,7 @@ mod tests {
 
     fn base_request() -> InsertTalkingStepToReactionRequest {
         InsertTalkingStepToReactionRequest {
-            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("some text")), probability: Some(100.0) }],
+            alternatives: vec![ReactionStepTextAlternativeDto { text: ReactionStepTextDto::Instant(String::from("some text")), probability: Some(100.0), conditions: vec![] }],
             skip: ReactionStepSkipDto::ImmediatelyAfter,
             emotion_id: String::from("an emotion"),
             step_index: 0,
@@ -2,7 +2,10 @@ pub mod dtos;
 pub mod create;
 pub mod insert_movement_step;
 pub mod insert_talking_step;
+pub mod insert_composite_talking_step;
+pub mod insert_chat_message_step;
 pub mod get;
 pub mod get_all;
 pub mod update;
-pub mod remove_step;
\ No newline at end of file
+pub mod remove_step;
+pub mod delete;
\ No newline at end of file
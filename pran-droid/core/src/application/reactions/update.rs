@@ -1,8 +1,8 @@
 use std::collections::HashSet;
 use std::fmt::Debug;
 use thiserror::Error;
-use crate::application::reactions::dtos::reaction_dto::{ReactionDto, ReactionTriggerDto};
-use crate::domain::reactions::reaction_definition::{ReactionDefinitionId, ReactionTrigger};
+use crate::application::reactions::dtos::reaction_dto::{ReactionCooldownDto, ReactionDto, ReactionPermissionLevelDto, ReactionTriggerDto};
+use crate::domain::reactions::reaction_definition::{OverlayTarget, ReactionDefinitionId, ReactionTrigger};
 use crate::domain::reactions::reaction_definition_repository::{ReactionDefinitionRepository};
 
 #[derive(Debug, Error)]
@@ -18,6 +18,7 @@ pub enum UpdateReactionError {
 impl UpdateReactionError {
     fn missing_reaction() -> Self { UpdateReactionError::BadRequest(String::from("The requested reaction id does not exist")) }
     fn malformed_triggers() -> Self { UpdateReactionError::BadRequest(String::from("The triggers to update are malformed")) }
+    fn malformed_overlay_targets() -> Self { UpdateReactionError::BadRequest(String::from("Overlay targets can only contain letters, numbers, dashes and underscores")) }
 }
 
 #[derive(Default)]
@@ -25,7 +26,10 @@ pub struct UpdateReactionRequest {
     pub id: String,
     pub triggers: Option<Vec<ReactionTriggerDto>>,
     pub is_disabled: Option<bool>,
-    pub count: Option<u32>
+    pub count: Option<u32>,
+    pub cooldown: Option<ReactionCooldownDto>,
+    pub permission_level: Option<ReactionPermissionLevelDto>,
+    pub overlay_targets: Option<Vec<String>>,
 }
 
 pub async fn update_reaction(request: UpdateReactionRequest, repository: &dyn ReactionDefinitionRepository) -> Result<ReactionDto, UpdateReactionError> {
@@ -49,6 +53,22 @@ pub async fn update_reaction(request: UpdateReactionRequest, repository: &dyn Re
         definition.update_count(request_count);
     }
 
+    if let Some(request_cooldown) = request.cooldown {
+        definition.update_cooldown(request_cooldown.into());
+    }
+
+    if let Some(request_permission_level) = request.permission_level {
+        definition.update_permission_level(request_permission_level.into());
+    }
+
+    if let Some(request_overlay_targets) = request.overlay_targets {
+        let overlay_targets = request_overlay_targets.into_iter()
+            .map(OverlayTarget::new)
+            .collect::<Result<Vec<OverlayTarget>, ()>>()
+            .map_err(|_| UpdateReactionError::malformed_overlay_targets())?;
+        definition.update_overlay_targets(overlay_targets);
+    }
+
     repository.update(&definition).await.map_err(|_| UpdateReactionError::Unexpected)?;
 
     Ok(definition.into())
@@ -68,7 +88,7 @@ fn build_domain_triggers(request_triggers: Vec<ReactionTriggerDto>) -> Result<Ve
 fn assert_no_duplicate_triggers(triggers: &Vec<ReactionTriggerDto>) -> Result<(), UpdateReactionError> {
     let mut uniq = HashSet::new();
     if !triggers.iter().all(move |trigger| {
-        if let ReactionTriggerDto::ChatCommand(command_trigger) = trigger { uniq.insert(command_trigger) } else { true }
+        if let ReactionTriggerDto::ChatCommand(command_trigger, _) = trigger { uniq.insert(command_trigger) } else { true }
     }) {
         Err(UpdateReactionError::BadRequest(String::from("Multiple of same trigger provided, remove duplicates")))
     } else {
@@ -89,7 +109,7 @@ async fn assert_no_trigger_already_exists(id: &ReactionDefinitionId, triggers: &
 #[cfg(test)]
 mod tests {
     use crate::application::reactions::create::{create_reaction, CreateReactionRequest};
-    use crate::application::reactions::dtos::reaction_dto::ReactionTriggerDto;
+    use crate::application::reactions::dtos::reaction_dto::{ChatTriggerMatchOptionsDto, ReactionTriggerDto};
     use crate::application::reactions::get::{get_reaction, GetReactionRequest};
     use crate::persistence::reactions::in_memory_reaction_repository::InMemoryReactionRepository;
     use super::*;
@@ -117,8 +137,8 @@ mod tests {
 
         let fetched_reaction = get_reaction(GetReactionRequest { id: reaction.id }, &repository).await.unwrap();
         assert!(matches!(result, Ok(_)));
-        assert!(matches!(&result.unwrap().triggers[..], [ReactionTriggerDto::ChatCommand(command)] if command == "!fire"));
-        assert!(matches!(&fetched_reaction.triggers[..], [ReactionTriggerDto::ChatCommand(command)] if command == "!fire"));
+        assert!(matches!(&result.unwrap().triggers[..], [ReactionTriggerDto::ChatCommand(command, _)] if command == "!fire"));
+        assert!(matches!(&fetched_reaction.triggers[..], [ReactionTriggerDto::ChatCommand(command, _)] if command == "!fire"));
     }
 
     #[tokio::test]
@@ -131,8 +151,8 @@ mod tests {
 
         let fetched_reaction = get_reaction(GetReactionRequest { id: reaction.id }, &repository).await.unwrap();
         assert!(matches!(result, Ok(_)));
-        assert!(matches!(&result.unwrap().triggers[..], [ReactionTriggerDto::ChatCommand(command)] if command == "!water"));
-        assert!(matches!(&fetched_reaction.triggers[..], [ReactionTriggerDto::ChatCommand(command)] if command == "!water"));
+        assert!(matches!(&result.unwrap().triggers[..], [ReactionTriggerDto::ChatCommand(command, _)] if command == "!water"));
+        assert!(matches!(&fetched_reaction.triggers[..], [ReactionTriggerDto::ChatCommand(command, _)] if command == "!water"));
     }
 
     #[tokio::test]
@@ -197,13 +217,86 @@ mod tests {
         assert!(matches!(result, Ok(dto) if dto.count == 14));
     }
 
+    #[tokio::test]
+    async fn update_reaction_set_cooldown_updates_reaction() {
+        let repository = InMemoryReactionRepository::new();
+        let reaction = create_reaction(CreateReactionRequest { trigger: command_dto("!fire") }, &repository).await.unwrap();
+
+        let request = create_request(&reaction, |req| req.cooldown = Some(ReactionCooldownDto { global_ms: 5000, per_user_ms: 30000 }));
+        let result = update_reaction(request, &repository).await;
+        assert!(matches!(result, Ok(dto) if dto.cooldown.global_ms == 5000 && dto.cooldown.per_user_ms == 30000));
+
+        let fetched_reaction = get_reaction(GetReactionRequest { id: reaction.id }, &repository).await.unwrap();
+        assert_eq!(fetched_reaction.cooldown.global_ms, 5000);
+        assert_eq!(fetched_reaction.cooldown.per_user_ms, 30000);
+    }
+
+    #[tokio::test]
+    async fn update_reaction_set_permission_level_updates_reaction() {
+        let repository = InMemoryReactionRepository::new();
+        let reaction = create_reaction(CreateReactionRequest { trigger: command_dto("!fire") }, &repository).await.unwrap();
+        assert_eq!(reaction.permission_level, ReactionPermissionLevelDto::Everyone);
+
+        let request = create_request(&reaction, |req| req.permission_level = Some(ReactionPermissionLevelDto::Mods));
+        let result = update_reaction(request, &repository).await;
+        assert!(matches!(result, Ok(dto) if dto.permission_level == ReactionPermissionLevelDto::Mods));
+
+        let fetched_reaction = get_reaction(GetReactionRequest { id: reaction.id }, &repository).await.unwrap();
+        assert_eq!(fetched_reaction.permission_level, ReactionPermissionLevelDto::Mods);
+    }
+
+    #[tokio::test]
+    async fn update_reaction_set_overlay_targets_updates_reaction() {
+        let repository = InMemoryReactionRepository::new();
+        let reaction = create_reaction(CreateReactionRequest { trigger: command_dto("!fire") }, &repository).await.unwrap();
+        assert!(reaction.overlay_targets.is_empty());
+
+        let request = create_request(&reaction, |req| req.overlay_targets = Some(vec![String::from("main-scene"), String::from("be_right_back")]));
+        let result = update_reaction(request, &repository).await;
+        assert!(matches!(result, Ok(dto) if dto.overlay_targets == vec!["main-scene", "be_right_back"]));
+
+        let fetched_reaction = get_reaction(GetReactionRequest { id: reaction.id }, &repository).await.unwrap();
+        assert_eq!(fetched_reaction.overlay_targets, vec!["main-scene", "be_right_back"]);
+    }
+
+    #[tokio::test]
+    async fn update_reaction_set_invalid_overlay_targets_bad_request() {
+        let repository = InMemoryReactionRepository::new();
+        let reaction = create_reaction(CreateReactionRequest { trigger: command_dto("!fire") }, &repository).await.unwrap();
+
+        for target in ["", "main scene", "scenes/main"] {
+            let request = create_request(&reaction, |req| req.overlay_targets = Some(vec![String::from(target)]));
+            let result = update_reaction(request, &repository).await;
+            assert!(matches!(result, Err(UpdateReactionError::BadRequest(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn update_reaction_set_trigger_match_options_updates_reaction() {
+        let repository = InMemoryReactionRepository::new();
+        let reaction = create_reaction(CreateReactionRequest { trigger: command_dto("!fire") }, &repository).await.unwrap();
+        let options = ChatTriggerMatchOptionsDto { case_sensitive: false, punctuation_as_boundary: true, normalise_unicode: true };
+
+        let request = create_request(&reaction, |req| req.triggers = Some(vec![
+            ReactionTriggerDto::ChatCommand(String::from("!fire"), options.clone()),
+            ReactionTriggerDto::ChatKeyword(String::from("fire"), options.clone()),
+        ]));
+        update_reaction(request, &repository).await.unwrap();
+
+        let fetched_reaction = get_reaction(GetReactionRequest { id: reaction.id }, &repository).await.unwrap();
+        assert!(matches!(&fetched_reaction.triggers[..], [
+            ReactionTriggerDto::ChatCommand(command, command_options),
+            ReactionTriggerDto::ChatKeyword(keyword, keyword_options)
+        ] if command == "!fire" && command_options == &options && keyword == "fire" && keyword_options == &options));
+    }
+
     fn create_request<F>(reaction: &ReactionDto, configure: F) -> UpdateReactionRequest where F: FnOnce(&mut UpdateReactionRequest) -> () {
-        let mut req = UpdateReactionRequest { id: reaction.id.clone(), triggers: None, is_disabled: None, count: None };
+        let mut req = UpdateReactionRequest { id: reaction.id.clone(), triggers: None, is_disabled: None, count: None, cooldown: None, permission_level: None, overlay_targets: None };
         configure(&mut req);
         req
     }
 
     fn command_dto(text: &str) -> ReactionTriggerDto {
-        ReactionTriggerDto::ChatCommand(String::from(text))
+        ReactionTriggerDto::ChatCommand(String::from(text), Default::default())
     }
 }
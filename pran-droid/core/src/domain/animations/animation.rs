@@ -29,7 +29,7 @@ pub enum CreateAnimationError {
 }
 
 impl AnimationFrames {
-    pub(super) fn all_image_ids(&self) -> Vec<&ImageId> {
+    pub(crate) fn all_image_ids(&self) -> Vec<&ImageId> {
         self.0.iter().map(|frame| &frame.image_id).collect()
     }
 }
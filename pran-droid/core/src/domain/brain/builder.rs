@@ -1,25 +1,27 @@
 use std::sync::Arc;
 use crate::application::brain::pran_droid_brain::TextPhonemiser;
-use crate::domain::brain::pran_droid_brain::{PranDroidBrain, ReactionNotifier};
-use crate::domain::reactions::reaction_definition::{ActionTrigger, ChatCommandTrigger, ChatKeywordTrigger, ReactionDefinition, ReactionDefinitionId, ReactionTrigger};
+use crate::domain::brain::clock::Clock;
+use crate::domain::brain::pran_droid_brain::{PranDroidBrain, PranDroidBrainTriggers, ReactionNotifier};
+use crate::domain::reactions::reaction_definition::{ReactionDefinition, ReactionTrigger};
+use crate::domain::visemes::mouth_track::MouthTrackModel;
 
 pub struct PranDroidBrainBuilder {
-    chat_command_triggers: Vec<(ChatCommandTrigger, ReactionDefinitionId)>,
-    chat_keyword_triggers: Vec<(ChatKeywordTrigger, ReactionDefinitionId)>,
-    action_triggers: Vec<(ActionTrigger, ReactionDefinitionId)>,
+    triggers: PranDroidBrainTriggers,
     reaction_definitions: Vec<ReactionDefinition>,
     text_phonemiser: Arc<dyn TextPhonemiser>,
     reaction_notifier: Arc<dyn ReactionNotifier>,
+    clock: Arc<dyn Clock>,
+    mouth_track_model: Arc<MouthTrackModel>,
 }
 
 impl PranDroidBrainBuilder {
-    pub fn new(text_phonemiser: Arc<dyn TextPhonemiser>, reaction_notifier: Arc<dyn ReactionNotifier>) -> Self {
+    pub fn new(text_phonemiser: Arc<dyn TextPhonemiser>, reaction_notifier: Arc<dyn ReactionNotifier>, clock: Arc<dyn Clock>, mouth_track_model: Arc<MouthTrackModel>) -> Self {
         PranDroidBrainBuilder {
             text_phonemiser,
             reaction_notifier,
-            chat_command_triggers: vec![],
-            chat_keyword_triggers: vec![],
-            action_triggers: vec![],
+            clock,
+            mouth_track_model,
+            triggers: PranDroidBrainTriggers::default(),
             reaction_definitions: vec![],
         }
     }
@@ -31,9 +33,12 @@ impl PranDroidBrainBuilder {
 
         for trigger in &reaction.triggers {
             match trigger {
-                ReactionTrigger::ChatCommand(command_trigger) => self.chat_command_triggers.push((command_trigger.clone(), reaction.id.clone())),
-                ReactionTrigger::ChatKeyword(keyword_trigger) => self.chat_keyword_triggers.push((keyword_trigger.clone(), reaction.id.clone())),
-                ReactionTrigger::Action(action_trigger) => self.action_triggers.push((action_trigger.clone(), reaction.id.clone())),
+                ReactionTrigger::ChatCommand(command_trigger) => self.triggers.chat_command.push((command_trigger.clone(), reaction.id.clone())),
+                ReactionTrigger::ChatKeyword(keyword_trigger) => self.triggers.chat_keyword.push((keyword_trigger.clone(), reaction.id.clone())),
+                ReactionTrigger::ChatPattern(pattern_trigger) => self.triggers.chat_pattern.push((pattern_trigger.clone(), reaction.id.clone())),
+                ReactionTrigger::Action(action_trigger) => self.triggers.action.push((action_trigger.clone(), reaction.id.clone())),
+                ReactionTrigger::ActionRange(action_range_trigger) => self.triggers.action_range.push((action_range_trigger.clone(), reaction.id.clone())),
+                ReactionTrigger::StreamEvent(stream_event_trigger) => self.triggers.stream_event.push((stream_event_trigger.clone(), reaction.id.clone())),
             }
         }
         self.reaction_definitions.push(reaction);
@@ -43,9 +48,9 @@ impl PranDroidBrainBuilder {
         PranDroidBrain::new(
             self.text_phonemiser,
             self.reaction_notifier,
-            self.chat_command_triggers,
-            self.chat_keyword_triggers,
-            self.action_triggers,
+            self.clock,
+            self.mouth_track_model,
+            self.triggers,
             self.reaction_definitions,
         )
     }
@@ -1,3 +1,4 @@
 pub mod builder;
+pub mod clock;
 pub mod pran_droid_brain;
 pub mod stimuli;
\ No newline at end of file
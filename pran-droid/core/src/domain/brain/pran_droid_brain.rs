@@ -1,42 +1,60 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use crate::application::brain::pran_droid_brain::TextPhonemiser;
-use crate::domain::brain::stimuli::{Action, ActionStimulus, ChatMessageStimulus, Stimulus};
+use crate::domain::brain::builder::PranDroidBrainBuilder;
+use crate::domain::brain::clock::Clock;
+use crate::domain::brain::stimuli::{Action, ActionStimulus, ChatMessageStimulus, Source, Stimulus, StreamEvent, StreamEventStimulus};
 use crate::domain::reactions::reaction::{Reaction, ReactionContext};
-use crate::domain::reactions::reaction_definition::{ActionTrigger, ChatCommandTrigger, ChatKeywordTrigger, ReactionDefinition, ReactionDefinitionId};
+use crate::domain::reactions::reaction_definition::{ActionRangeTrigger, ActionTrigger, ChatCommandTrigger, ChatKeywordTrigger, ChatPatternTrigger, ReactionDefinition, ReactionDefinitionId, StreamEventTrigger};
+use crate::domain::visemes::mouth_track::MouthTrackModel;
 
 pub trait ReactionNotifier: Send + Sync {
     fn notify_reaction_usage(&self, reaction_definition_id: &ReactionDefinitionId, new_count: u32);
 }
 
+#[derive(Default)]
+pub struct PranDroidBrainTriggers {
+    pub chat_command: Vec<(ChatCommandTrigger, ReactionDefinitionId)>,
+    pub chat_keyword: Vec<(ChatKeywordTrigger, ReactionDefinitionId)>,
+    pub chat_pattern: Vec<(ChatPatternTrigger, ReactionDefinitionId)>,
+    pub action: Vec<(ActionTrigger, ReactionDefinitionId)>,
+    pub action_range: Vec<(ActionRangeTrigger, ReactionDefinitionId)>,
+    pub stream_event: Vec<(StreamEventTrigger, ReactionDefinitionId)>,
+}
+
 pub struct PranDroidBrain {
-    chat_command_triggers: Vec<(ChatCommandTrigger, ReactionDefinitionId)>,
-    chat_keyword_triggers: Vec<(ChatKeywordTrigger, ReactionDefinitionId)>,
-    action_triggers: Vec<(ActionTrigger, ReactionDefinitionId)>,
+    triggers: PranDroidBrainTriggers,
     reaction_definitions: HashMap<ReactionDefinitionId, ReactionDefinition>,
     reaction_counters: HashMap<ReactionDefinitionId, u32>,
+    reaction_last_usages: HashMap<ReactionDefinitionId, Instant>,
+    reaction_last_user_usages: HashMap<(ReactionDefinitionId, String), Instant>,
     text_phonemiser: Arc<dyn TextPhonemiser>,
     reaction_notifier: Arc<dyn ReactionNotifier>,
+    clock: Arc<dyn Clock>,
+    mouth_track_model: Arc<MouthTrackModel>,
 }
 
 impl PranDroidBrain {
     pub fn new(
         text_phonemiser: Arc<dyn TextPhonemiser>,
         reaction_notifier: Arc<dyn ReactionNotifier>,
-        chat_command_triggers: Vec<(ChatCommandTrigger, ReactionDefinitionId)>,
-        chat_keyword_triggers: Vec<(ChatKeywordTrigger, ReactionDefinitionId)>,
-        action_triggers: Vec<(ActionTrigger, ReactionDefinitionId)>,
+        clock: Arc<dyn Clock>,
+        mouth_track_model: Arc<MouthTrackModel>,
+        triggers: PranDroidBrainTriggers,
         reaction_definitions: Vec<ReactionDefinition>,
     ) -> Self {
-        let mut chat_keyword_triggers = chat_keyword_triggers.clone();
-        chat_keyword_triggers.sort_by_key(|(keyword_trigger, _)| keyword_trigger.text.len());
+        let mut triggers = triggers;
+        triggers.chat_keyword.sort_by_key(|(keyword_trigger, _)| keyword_trigger.text.len());
         PranDroidBrain {
             text_phonemiser,
             reaction_notifier,
-            chat_command_triggers,
-            chat_keyword_triggers,
-            action_triggers,
+            clock,
+            mouth_track_model,
+            triggers,
             reaction_counters: HashMap::new(),
+            reaction_last_usages: HashMap::new(),
+            reaction_last_user_usages: HashMap::new(),
             reaction_definitions: reaction_definitions.into_iter().map(|definition| (definition.id.clone(), definition)).collect()
         }
     }
@@ -52,47 +70,134 @@ impl PranDroidBrain {
                 let id = { id.clone() };
                 let name = { name.clone() };
                 self.try_react_to_action(stimulus, id.as_str(), name.as_str())
+            },
+            Stimulus::StreamEvent(StreamEventStimulus { ref event, .. }) => {
+                let event = { event.clone() };
+                self.try_react_to_stream_event(stimulus, &event)
             }
         }
     }
 
+    pub fn reload(&mut self, reaction_definitions: Vec<ReactionDefinition>) {
+        let mut brain_builder = PranDroidBrainBuilder::new(self.text_phonemiser.clone(), self.reaction_notifier.clone(), self.clock.clone(), self.mouth_track_model.clone());
+        for reaction in reaction_definitions {
+            brain_builder.with_reaction(reaction);
+        }
+        let mut reloaded = brain_builder.build();
+
+        // Counters are kept unless the stored count moved outside of what this brain counted, meaning it was edited elsewhere.
+        reloaded.reaction_counters = self.reaction_counters.drain()
+            .filter(|(id, counter)| match (self.reaction_definitions.get(id), reloaded.reaction_definitions.get(id)) {
+                (Some(previous), Some(current)) => (previous.count..=*counter).contains(&current.count),
+                _ => false
+            })
+            .collect();
+        reloaded.reaction_last_usages = self.reaction_last_usages.drain()
+            .filter(|(id, _)| reloaded.reaction_definitions.contains_key(id))
+            .collect();
+        reloaded.reaction_last_user_usages = self.reaction_last_user_usages.drain()
+            .filter(|((id, _), _)| reloaded.reaction_definitions.contains_key(id))
+            .collect();
+
+        *self = reloaded;
+    }
+
     fn try_react_to_chat_message(&mut self, stimulus: Stimulus, text: &String) -> Option<Reaction> {
-        let definition_id = self.chat_command_triggers
+        let source = stimulus.get_source();
+        let definition_id = self.triggers.chat_command
             .iter()
-            .find(|(trigger, _)| trigger.matches(text))
+            .find(|(trigger, definition_id)| trigger.matches(text) && self.can_be_used_by(definition_id, source))
             .map(|(_, definition_id)| definition_id)
-            .or_else(|| self.chat_keyword_triggers
+            .or_else(|| self.triggers.chat_keyword
                 .iter()
-                .find(|(trigger, _)| trigger.matches(text))
+                .find(|(trigger, definition_id)| trigger.matches(text) && self.can_be_used_by(definition_id, source))
                 .map(|(_, definition_id)| definition_id));
 
-        self.try_react(stimulus, definition_id.cloned())
+        if definition_id.is_some() {
+            return self.try_react(stimulus, definition_id.cloned(), HashMap::new());
+        }
+
+        let pattern_match = self.triggers.chat_pattern
+            .iter()
+            .filter(|(_, definition_id)| self.can_be_used_by(definition_id, source))
+            .find_map(|(trigger, definition_id)| trigger.captures(text).map(|variables| (definition_id.clone(), variables)));
+
+        match pattern_match {
+            Some((definition_id, variables)) => self.try_react(stimulus, Some(definition_id), variables),
+            None => None
+        }
     }
 
     fn try_react_to_action(&mut self, stimulus: Stimulus, id: &str, name: &str) -> Option<Reaction> {
-        let definition_id = self.action_triggers
+        let source = stimulus.get_source();
+        let definition_id = self.triggers.action
             .iter()
-            .find(|(trigger, _)| trigger.id == id && trigger.name == name)
-            .map(|(_, definition_id)| definition_id);
+            .find(|(trigger, definition_id)| trigger.id == id && trigger.name == name && self.can_be_used_by(definition_id, source))
+            .map(|(_, definition_id)| definition_id)
+            // Among the matching ranges the best tier is the one with the highest minimum, then the narrowest one.
+            .or_else(|| self.triggers.action_range
+                .iter()
+                .filter(|(trigger, definition_id)| trigger.matches(id, name) && self.can_be_used_by(definition_id, source))
+                .max_by_key(|(trigger, _)| (trigger.min, std::cmp::Reverse(trigger.max.unwrap_or(u32::MAX))))
+                .map(|(_, definition_id)| definition_id));
+
+        self.try_react(stimulus, definition_id.cloned(), HashMap::new())
+    }
+
+    fn try_react_to_stream_event(&mut self, stimulus: Stimulus, event: &StreamEvent) -> Option<Reaction> {
+        let source = stimulus.get_source();
+        let definition_id = event.get_trigger_kinds()
+            .into_iter()
+            .find_map(|kind| self.triggers.stream_event
+                .iter()
+                .find(|(trigger, definition_id)| trigger.kind == kind && self.can_be_used_by(definition_id, source))
+                .map(|(_, definition_id)| definition_id));
+
+        self.try_react(stimulus, definition_id.cloned(), HashMap::new())
+    }
 
-        self.try_react(stimulus, definition_id.cloned())
+    fn can_be_used_by(&self, definition_id: &ReactionDefinitionId, source: &Source) -> bool {
+        self.reaction_definitions.get(definition_id)
+            .is_some_and(|definition| definition.can_be_used_by(source))
     }
 
-    fn try_react(&mut self, stimulus: Stimulus, definition_id: Option<ReactionDefinitionId>) -> Option<Reaction> {
+    fn try_react(&mut self, stimulus: Stimulus, definition_id: Option<ReactionDefinitionId>, variables: HashMap<String, String>) -> Option<Reaction> {
         if let Some(definition_id) = definition_id {
             let reaction_definition = self.reaction_definitions.get(&definition_id).unwrap();
             debug!("Matching reaction found {:?}", definition_id);
 
+            let now = self.clock.now();
+            let user_usage_key = (definition_id.clone(), stimulus.get_source_name());
+            if self.is_in_cooldown(reaction_definition, &user_usage_key, now) {
+                debug!("Reaction {:?} suppressed by cooldown", definition_id);
+                return None;
+            }
+            self.reaction_last_usages.insert(definition_id.clone(), now);
+            if !reaction_definition.cooldown.per_user.is_zero() {
+                self.reaction_last_user_usages.insert(user_usage_key, now);
+            }
+
             *self.reaction_counters.entry(definition_id.clone()).or_insert(reaction_definition.count) += 1;
             let new_count = *self.reaction_counters.get(&definition_id).unwrap();
             self.reaction_notifier.notify_reaction_usage(&definition_id, new_count);
 
-            Reaction::try_create(self.text_phonemiser.as_ref(), reaction_definition, &ReactionContext {
+            Some(Reaction::create(self.text_phonemiser.as_ref(), self.mouth_track_model.as_ref(), reaction_definition, &ReactionContext {
                 count: new_count,
-                stimulus
-            })
+                stimulus,
+                variables
+            }))
         } else {
             None
         }
     }
+
+    fn is_in_cooldown(&self, reaction_definition: &ReactionDefinition, user_usage_key: &(ReactionDefinitionId, String), now: Instant) -> bool {
+        let is_within_cooldown = |last_usage: Option<&Instant>, cooldown: Duration| matches!(
+            last_usage,
+            Some(last_usage) if now.saturating_duration_since(*last_usage) < cooldown
+        );
+
+        is_within_cooldown(self.reaction_last_usages.get(&user_usage_key.0), reaction_definition.cooldown.global)
+            || is_within_cooldown(self.reaction_last_user_usages.get(user_usage_key), reaction_definition.cooldown.per_user)
+    }
 }
\ No newline at end of file
@@ -1,19 +1,24 @@
+use crate::domain::reactions::reaction_definition::{ReactionPermissionLevel, StreamEventKind};
+
 #[derive(Debug)]
 pub enum Stimulus {
     ChatMessage(ChatMessageStimulus),
-    Action(ActionStimulus)
+    Action(ActionStimulus),
+    StreamEvent(StreamEventStimulus),
 }
 
 #[derive(Debug)]
 pub struct ChatMessageStimulus {
     pub source: Source,
     pub text: String,
+    pub message_id: Option<String>,
 }
 
 #[derive(Debug)]
 pub struct ActionStimulus {
     pub source: Source,
-    pub action: Action
+    pub action: Action,
+    pub input: Option<String>,
 }
 
 #[derive(Debug)]
@@ -22,23 +27,107 @@ pub struct Action {
     pub name: String
 }
 
+#[derive(Debug)]
+pub struct StreamEventStimulus {
+    pub source: Source,
+    pub event: StreamEvent,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    Subscription { months: u32 },
+    Resubscription { months: u32 },
+    GiftSubscription { recipient: String, months: u32 },
+    MysteryGift { count: u32 },
+    Raid { viewers: u32 },
+    Follow,
+    HypeTrainStart { level: u32 },
+    HypeTrainLevelUp { level: u32 },
+    HypeTrainEnd { level: u32 },
+}
+
 #[derive(Debug)]
 pub struct Source {
     pub user_name: String,
     pub is_mod: bool,
+    pub is_broadcaster: bool,
+    pub is_vip: bool,
+    pub is_subscriber: bool,
 }
 
 impl Stimulus {
+    pub(crate) fn get_source(&self) -> &Source {
+        match self {
+            Stimulus::ChatMessage(ChatMessageStimulus { source, .. }) => source,
+            Stimulus::Action(ActionStimulus { source, .. }) => source,
+            Stimulus::StreamEvent(StreamEventStimulus { source, .. }) => source,
+        }
+    }
+
     pub(crate) fn get_source_name(&self) -> String {
         match self {
             Stimulus::ChatMessage(ChatMessageStimulus { source: Source { user_name, .. }, .. }) => user_name.clone(),
-            Stimulus::Action(ActionStimulus { source: Source { user_name, .. }, .. }) => user_name.clone()
+            Stimulus::Action(ActionStimulus { source: Source { user_name, .. }, .. }) => user_name.clone(),
+            Stimulus::StreamEvent(StreamEventStimulus { source: Source { user_name, .. }, .. }) => user_name.clone(),
+        }
+    }
+}
+
+impl Source {
+    pub(crate) fn get_permission_level(&self) -> ReactionPermissionLevel {
+        if self.is_broadcaster {
+            ReactionPermissionLevel::Broadcaster
+        } else if self.is_mod {
+            ReactionPermissionLevel::Mods
+        } else if self.is_vip {
+            ReactionPermissionLevel::Vips
+        } else if self.is_subscriber {
+            ReactionPermissionLevel::Subscribers
+        } else {
+            ReactionPermissionLevel::Everyone
+        }
+    }
+}
+
+impl StreamEventStimulus {
+    pub(crate) fn get_target(&self) -> Option<String> {
+        match &self.event {
+            StreamEvent::GiftSubscription { recipient, .. } => Some(recipient.clone()),
+            _ => None
+        }
+    }
+}
+
+impl StreamEvent {
+    // Kinds that can react to this event, best match first: a resubscription falls back to subscription reactions.
+    pub(crate) fn get_trigger_kinds(&self) -> Vec<StreamEventKind> {
+        match self {
+            StreamEvent::Subscription { .. } => vec![StreamEventKind::Subscription],
+            StreamEvent::Resubscription { .. } => vec![StreamEventKind::Resubscription, StreamEventKind::Subscription],
+            StreamEvent::GiftSubscription { .. } => vec![StreamEventKind::GiftSubscription],
+            StreamEvent::MysteryGift { .. } => vec![StreamEventKind::MysteryGift],
+            StreamEvent::Raid { .. } => vec![StreamEventKind::Raid],
+            StreamEvent::Follow => vec![StreamEventKind::Follow],
+            StreamEvent::HypeTrainStart { .. } => vec![StreamEventKind::HypeTrainStart],
+            StreamEvent::HypeTrainLevelUp { .. } => vec![StreamEventKind::HypeTrainLevelUp],
+            StreamEvent::HypeTrainEnd { .. } => vec![StreamEventKind::HypeTrainEnd],
         }
     }
 }
 
 impl ChatMessageStimulus {
     pub(crate) fn get_target(&self) -> Option<String> {
-        self.text.split_whitespace().nth(1).map(|s| s.to_string())
+        self.get_argument(1)
+    }
+
+    pub(crate) fn get_argument(&self, index: usize) -> Option<String> {
+        self.text.split_whitespace().nth(index).map(|s| s.to_string())
+    }
+
+    pub(crate) fn get_arguments(&self) -> Option<String> {
+        let arguments = self.text.split_whitespace().skip(1).collect::<Vec<&str>>();
+
+        if arguments.is_empty() { None } else { Some(arguments.join(" ")) }
     }
 }
\ No newline at end of file
@@ -0,0 +1,2 @@
+pub mod twitch_credentials;
+pub mod twitch_credentials_repository;
\ No newline at end of file
@@ -0,0 +1,52 @@
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TwitchCredentials {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: SystemTime,
+}
+
+impl TwitchCredentials {
+    pub fn new(access_token: String, refresh_token: String, expires_at: SystemTime) -> Result<Self, ()> {
+        if access_token.is_empty() || refresh_token.is_empty() {
+            return Err(());
+        }
+
+        Ok(Self { access_token, refresh_token, expires_at })
+    }
+
+    // Time left before the access token should be refreshed, zero when it is already due
+    pub fn refresh_in(&self, now: SystemTime, margin: Duration) -> Duration {
+        self.expires_at.duration_since(now).unwrap_or(Duration::ZERO).saturating_sub(margin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_credentials_without_tokens_errors() {
+        assert!(TwitchCredentials::new(String::new(), String::from("refresh"), SystemTime::UNIX_EPOCH).is_err());
+        assert!(TwitchCredentials::new(String::from("access"), String::new(), SystemTime::UNIX_EPOCH).is_err());
+    }
+
+    #[test]
+    fn refresh_in_leaves_the_margin_before_expiry() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let credentials = TwitchCredentials::new(String::from("access"), String::from("refresh"), now + Duration::from_secs(3600)).unwrap();
+
+        assert_eq!(credentials.refresh_in(now, Duration::from_secs(300)), Duration::from_secs(3300));
+    }
+
+    #[test]
+    fn refresh_in_is_zero_for_credentials_expiring_within_the_margin_or_expired() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let expiring = TwitchCredentials::new(String::from("access"), String::from("refresh"), now + Duration::from_secs(60)).unwrap();
+        let expired = TwitchCredentials::new(String::from("access"), String::from("refresh"), now - Duration::from_secs(60)).unwrap();
+
+        assert_eq!(expiring.refresh_in(now, Duration::from_secs(300)), Duration::ZERO);
+        assert_eq!(expired.refresh_in(now, Duration::from_secs(300)), Duration::ZERO);
+    }
+}
@@ -0,0 +1,16 @@
+use async_trait::async_trait;
+use std::marker::{Send, Sync};
+use thiserror::Error;
+use crate::domain::credentials::twitch_credentials::TwitchCredentials;
+
+#[derive(Error, Debug)]
+pub enum CredentialsSaveError {
+    #[error("Unexpected error while saving the credentials")]
+    Unexpected
+}
+
+#[async_trait]
+pub trait TwitchCredentialsRepository: Send + Sync {
+    async fn get(&self) -> Option<TwitchCredentials>;
+    async fn save(&self, credentials: &TwitchCredentials) -> Result<(), CredentialsSaveError>;
+}
@@ -59,6 +59,40 @@ impl Emotion {
         }
     }
 
+    pub(crate) fn remove_layer(&mut self, index: usize) -> Result<(), ()> {
+        if self.animation.len() <= index || matches!(self.animation[index], EmotionLayer::Mouth { .. }) {
+            Err(())
+        } else {
+            self.animation.remove(index);
+            Ok(())
+        }
+    }
+
+    pub(crate) fn reorder_layers(&mut self, order: &[usize]) -> Result<(), ()> {
+        let mut sorted_order = order.to_vec();
+        sorted_order.sort_unstable();
+        if !sorted_order.into_iter().eq(0..self.animation.len()) {
+            return Err(());
+        }
+
+        self.animation = order.iter().map(|index| self.animation[*index].clone()).collect();
+        Ok(())
+    }
+
+    pub(crate) fn uses_image(&self, image_id: &ImageId) -> bool {
+        self.animation.iter().any(|layer| match layer {
+            EmotionLayer::Animation(animation) => animation.frames.all_image_ids().contains(&image_id),
+            EmotionLayer::Mouth { mouth_mapping } => mouth_mapping.values().any(|mouth_image_id| mouth_image_id == image_id),
+        })
+    }
+
+    pub(crate) fn all_image_ids(&self) -> Vec<&ImageId> {
+        self.animation.iter().flat_map(|layer| match layer {
+            EmotionLayer::Animation(animation) => animation.frames.all_image_ids(),
+            EmotionLayer::Mouth { mouth_mapping } => mouth_mapping.values().collect(),
+        }).collect()
+    }
+
     pub(super) fn set_mouth_position(&mut self, position_name: MouthPositionName, image_id: ImageId) {
         let mouth_index = self.animation.iter().position(|layer| matches!(layer, EmotionLayer::Mouth { .. })).unwrap();
         let mouth_layer = self.animation.get_mut(mouth_index).unwrap();
@@ -16,6 +16,20 @@ pub enum EmotionUpdateError {
     Missing
 }
 
+#[derive(Debug, Error)]
+pub enum EmotionGetAllError {
+    #[error("Unexpected error while getting the emotions: {0}")]
+    Unexpected(String)
+}
+
+#[derive(Debug, Error)]
+pub enum EmotionDeleteError {
+    #[error("Trying to delete a not existing emotion")]
+    Missing,
+    #[error("Unexpected error while deleting the emotion")]
+    Unexpected
+}
+
 #[async_trait]
 pub trait EmotionRepository: Send + Sync {
     fn next_id(&self) -> EmotionId;
@@ -23,9 +37,11 @@ pub trait EmotionRepository: Send + Sync {
     async fn update(&self, emotion: &Emotion) -> Result<(), EmotionUpdateError>;
     async fn get(&self, id: &EmotionId) -> Option<Emotion>;
     async fn get_all(&self) -> Vec<Emotion>;
+    async fn try_get_all(&self) -> Result<Vec<Emotion>, EmotionGetAllError>;
     async fn exists(&self, id: &EmotionId) -> bool;
     async fn get_by_name(&self, name: &EmotionName) -> Option<Emotion>;
     async fn exists_with_name(&self, name: &EmotionName) -> bool;
+    async fn delete(&self, id: &EmotionId) -> Result<(), EmotionDeleteError>;
 }
 
 #[cfg(test)]
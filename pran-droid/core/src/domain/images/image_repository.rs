@@ -11,12 +11,28 @@ pub enum InsertError {
     Unexpected
 }
 
+#[derive(Debug, Error)]
+pub enum GetAllError {
+    #[error("Unexpected error: {0}")]
+    Unexpected(String)
+}
+
+#[derive(Debug, Error)]
+pub enum DeleteError {
+    #[error("Trying to delete a not existing image")]
+    Missing,
+    #[error("Unexpected error")]
+    Unexpected
+}
+
 #[async_trait]
 pub trait ImageRepository: Send + Sync {
     async fn get(&self, id: &ImageId) -> Option<Image>;
     async fn get_all(&self) -> Vec<Image>;
+    async fn try_get_all(&self) -> Result<Vec<Image>, GetAllError>;
     async fn has(&self, id: &ImageId) -> bool;
     async fn insert(&self, image: &Image) -> Result<(), InsertError>;
+    async fn delete(&self, id: &ImageId) -> Result<(), DeleteError>;
 }
 
 #[cfg(test)]
@@ -2,4 +2,6 @@ pub mod emotions;
 pub mod images;
 pub mod animations;
 pub mod reactions;
-pub mod brain;
\ No newline at end of file
+pub mod brain;
+pub mod credentials;
+pub mod visemes;
\ No newline at end of file
@@ -2,3 +2,4 @@ pub mod reaction_definition;
 pub mod reaction_definition_repository;
 pub mod reaction_domain_service;
 pub mod reaction;
+pub mod text_template;
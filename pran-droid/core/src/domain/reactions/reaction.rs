@@ -1,21 +1,25 @@
 
+use std::collections::HashMap;
 use crate::application::brain::pran_droid_brain::TextPhonemiser;
 use crate::domain::animations::animation::Animation;
 use crate::domain::brain::stimuli::Stimulus;
 use crate::domain::emotions::emotion::EmotionId;
-use crate::domain::reactions::reaction_definition::{ReactionDefinition, ReactionDefinitionId, ReactionStepDefinition, TalkingReactionStepDefinition};
+use crate::domain::reactions::reaction_definition::{ChatMessageReactionStepDefinition, OverlayTarget, ReactionDefinition, ReactionDefinitionId, ReactionStepDefinition, TalkingReactionStepDefinition};
+use crate::domain::visemes::mouth_track::{MouthTrackModel, TimedMouthPosition};
 
 #[derive(Clone, Debug)]
 pub struct Reaction {
     pub source_definition_id: ReactionDefinitionId,
     pub steps: Vec<ReactionStep>,
+    pub overlay_targets: Vec<OverlayTarget>,
 }
 
 #[derive(Clone, Debug)]
 pub enum ReactionStep {
     Moving(MovingReactionStep),
     Talking(TalkingReactionStep),
-    CompositeTalking(Vec<TalkingReactionStep>)
+    CompositeTalking(Vec<TalkingReactionStep>),
+    ChatMessage(ChatMessageReactionStep)
 }
 
 #[derive(Clone, Debug)]
@@ -29,7 +33,14 @@ pub struct TalkingReactionStep {
     pub emotion_id: EmotionId,
     pub skip: ReactionStepSkip,
     pub phonemes: Vec<String>,
-    pub text: ReactionStepText
+    pub text: ReactionStepText,
+    pub mouth_track: Vec<TimedMouthPosition>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ChatMessageReactionStep {
+    pub text: String,
+    pub reply_to_message_id: Option<String>
 }
 
 #[derive(Clone, Debug)]
@@ -50,43 +61,65 @@ pub struct Milliseconds(pub u16);
 
 pub struct ReactionContext {
     pub stimulus: Stimulus,
-    pub count: u32
+    pub count: u32,
+    pub variables: HashMap<String, String>,
 }
 
 impl Reaction {
-    pub(crate) fn try_create(text_phonemiser: &dyn TextPhonemiser, definition: &ReactionDefinition, context: &ReactionContext) -> Option<Self> {
-        let mut steps = vec![];
-        for step in &definition.steps {
-            steps.push(ReactionStep::try_create(text_phonemiser, step, context)?);
-        }
+    pub(crate) fn create(text_phonemiser: &dyn TextPhonemiser, mouth_track_model: &MouthTrackModel, definition: &ReactionDefinition, context: &ReactionContext) -> Self {
+        let steps = definition.steps.iter()
+            .map(|step| ReactionStep::create(text_phonemiser, mouth_track_model, step, context))
+            .collect();
 
-        Some(Reaction { source_definition_id: definition.id.clone(), steps })
+        Reaction { source_definition_id: definition.id.clone(), steps, overlay_targets: definition.overlay_targets.clone() }
     }
 }
 
 impl ReactionStep {
-    pub(crate) fn try_create(text_phonemiser: &dyn TextPhonemiser, step_definition: &ReactionStepDefinition, context: &ReactionContext) -> Option<Self> {
-        Some(match step_definition {
+    pub(crate) fn create(text_phonemiser: &dyn TextPhonemiser, mouth_track_model: &MouthTrackModel, step_definition: &ReactionStepDefinition, context: &ReactionContext) -> Self {
+        match step_definition {
             ReactionStepDefinition::Moving(moving_step_definition) =>
                 ReactionStep::Moving(moving_step_definition.clone()),
             ReactionStepDefinition::Talking(talking_step_definition) =>
-                ReactionStep::Talking(TalkingReactionStep::try_create(text_phonemiser, talking_step_definition, context)?),
-            ReactionStepDefinition::CompositeTalking(_) =>
-                todo!("should never get here because not implemented")
-        })
+                ReactionStep::Talking(TalkingReactionStep::create(text_phonemiser, mouth_track_model, talking_step_definition, context)),
+            ReactionStepDefinition::CompositeTalking(talking_step_definitions) =>
+                ReactionStep::CompositeTalking(talking_step_definitions
+                    .iter()
+                    .map(|talking_step_definition| TalkingReactionStep::create(text_phonemiser, mouth_track_model, talking_step_definition, context))
+                    .collect()),
+            ReactionStepDefinition::ChatMessage(chat_message_step_definition) =>
+                ReactionStep::ChatMessage(ChatMessageReactionStep::create(chat_message_step_definition, context))
+        }
     }
 }
 
 impl TalkingReactionStep {
-    fn try_create(text_phonemiser: &dyn TextPhonemiser, step_definition: &TalkingReactionStepDefinition, context: &ReactionContext) -> Option<Self> {
-        let text = step_definition.alternatives.get_random_text().try_contextualise_text_reaction(context)?;
+    fn create(text_phonemiser: &dyn TextPhonemiser, mouth_track_model: &MouthTrackModel, step_definition: &TalkingReactionStepDefinition, context: &ReactionContext) -> Self {
+        let text = step_definition.alternatives.get_random_text(context).contextualise_text_reaction(context);
+        let phonemes = text_phonemiser.phonemise_text(&text.get_text());
 
-        Some(TalkingReactionStep {
+        TalkingReactionStep {
             skip: step_definition.skip.clone(),
-            phonemes: text_phonemiser.phonemise_text(&text.get_text()),
+            mouth_track: mouth_track_model.mouth_track(&phonemes),
+            phonemes,
             text,
             emotion_id: step_definition.emotion_id.clone(),
-        })
+        }
+    }
+}
+
+impl ChatMessageReactionStep {
+    fn create(step_definition: &ChatMessageReactionStepDefinition, context: &ReactionContext) -> Self {
+        let text = step_definition.alternatives.get_random_text(context).contextualise_text_reaction(context);
+        let reply_to_message_id = match &context.stimulus {
+            Stimulus::ChatMessage(message) if step_definition.reply => message.message_id.clone(),
+            _ => None
+        };
+
+        ChatMessageReactionStep {
+            text: text.get_text(),
+            reply_to_message_id,
+        }
     }
 }
 
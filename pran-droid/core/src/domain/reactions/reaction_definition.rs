@@ -1,10 +1,17 @@
-use std::fmt::{Debug, Write};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Debug;
 use std::clone::Clone;
 use std::cmp::PartialEq;
+use std::time::Duration;
 use rand::random;
-use crate::domain::brain::stimuli::Stimulus;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use crate::domain::brain::stimuli::{Source, Stimulus, StreamEvent};
 use crate::domain::emotions::emotion::EmotionId;
+use crate::domain::images::image::ImageId;
 use crate::domain::reactions::reaction::{MovingReactionStep, ReactionContext, ReactionStepSkip, ReactionStepText};
+use crate::domain::reactions::text_template::{TextTemplate, TextTemplateError, TextTemplateValue};
 
 #[derive(Clone, Debug)]
 pub struct ReactionDefinition {
@@ -13,26 +20,68 @@ pub struct ReactionDefinition {
     pub triggers: Vec<ReactionTrigger>,
     pub steps: Vec<ReactionStepDefinition>,
     pub count: u32,
+    pub cooldown: ReactionCooldown,
+    pub permission_level: ReactionPermissionLevel,
+    pub overlay_targets: Vec<OverlayTarget>,
 }
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub struct ReactionDefinitionId(pub String);
 
+// Named overlay channel, reactions without targets are sent to every overlay
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct OverlayTarget(pub String);
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReactionCooldown {
+    pub global: Duration,
+    pub per_user: Duration,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub enum ReactionPermissionLevel {
+    #[default]
+    Everyone,
+    Subscribers,
+    Vips,
+    Mods,
+    Broadcaster,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ReactionTrigger {
     ChatCommand(ChatCommandTrigger),
     ChatKeyword(ChatKeywordTrigger),
-    Action(ActionTrigger)
+    ChatPattern(ChatPatternTrigger),
+    Action(ActionTrigger),
+    ActionRange(ActionRangeTrigger),
+    StreamEvent(StreamEventTrigger),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct ChatCommandTrigger {
     pub text: String,
+    pub options: ChatTriggerMatchOptions,
+    match_regex: regex::Regex,
 }
 
 #[derive(Debug, Clone)]
 pub struct ChatKeywordTrigger {
     pub text: String,
+    pub options: ChatTriggerMatchOptions,
+    match_regex: regex::Regex,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatTriggerMatchOptions {
+    pub case_sensitive: bool,
+    pub punctuation_as_boundary: bool,
+    pub normalise_unicode: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatPatternTrigger {
+    pub pattern: String,
     match_regex: regex::Regex,
 }
 
@@ -42,26 +91,138 @@ pub struct ActionTrigger {
     pub name: String,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionRangeTrigger {
+    pub name: String,
+    pub min: u32,
+    pub max: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamEventTrigger {
+    pub kind: StreamEventKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamEventKind {
+    Subscription,
+    Resubscription,
+    GiftSubscription,
+    MysteryGift,
+    Raid,
+    Follow,
+    HypeTrainStart,
+    HypeTrainLevelUp,
+    HypeTrainEnd,
+}
+
+// The regex is built from the text and the options, comparing those compares every field
+impl PartialEq for ChatCommandTrigger {
+    fn eq(&self, other: &Self) -> bool {
+        self.text == other.text && self.options == other.options
+    }
+}
+
 impl PartialEq for ChatKeywordTrigger {
     fn eq(&self, other: &Self) -> bool {
-        self.text == other.text
+        self.text == other.text && self.options == other.options
+    }
+}
+
+impl PartialEq for ChatPatternTrigger {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl Default for ChatTriggerMatchOptions {
+    fn default() -> Self {
+        Self { case_sensitive: true, punctuation_as_boundary: false, normalise_unicode: false }
+    }
+}
+
+impl ChatTriggerMatchOptions {
+    fn build_regex(&self, start: &str, text: &str, end: &str) -> regex::Regex {
+        let case_flag = if self.case_sensitive { "" } else { "(?i)" };
+        regex::Regex::new(format!("{}{}{}{}", case_flag, start, regex::escape(&self.normalise(text)), end).as_str()).unwrap()
+    }
+
+    fn boundary(&self, default_boundary: &'static str) -> &'static str {
+        if self.punctuation_as_boundary { r"[\s\p{P}]" } else { default_boundary }
+    }
+
+    fn normalise<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.normalise_unicode {
+            Cow::Owned(text.nfkd().filter(|c| !is_combining_mark(*c)).collect())
+        } else {
+            Cow::Borrowed(text)
+        }
     }
 }
 
 impl ChatCommandTrigger {
+    pub fn new(text: String, options: ChatTriggerMatchOptions) -> Self {
+        let end = format!("({}|$)", options.boundary(r"\s"));
+        Self { match_regex: options.build_regex(r"^\s*", &text, &end), text, options }
+    }
+
     pub fn matches(&self, message_text: &str) -> bool {
-        message_text.split_whitespace().next() == Some(&self.text)
+        self.match_regex.is_match(&self.options.normalise(message_text))
     }
 }
 
 impl ChatKeywordTrigger {
-    pub fn new(text: String) -> Self {
-        Self { match_regex: regex::Regex::new(format!("(^| ){}($| )", regex::escape(&text)).as_str()).unwrap(), text }
+    pub fn new(text: String, options: ChatTriggerMatchOptions) -> Self {
+        let boundary = options.boundary(" ");
+        let start = format!("(^|{})", boundary);
+        let end = format!("({}|$)", boundary);
+        Self { match_regex: options.build_regex(&start, &text, &end), text, options }
+    }
+
+    pub fn matches(&self, message_text: &str) -> bool {
+        self.match_regex.is_match(&self.options.normalise(message_text))
+    }
+}
+
+impl ChatPatternTrigger {
+    pub fn try_new(pattern: String) -> Result<Self, ()> {
+        let match_regex = regex::Regex::new(&pattern).map_err(|_| ())?;
+        Ok(Self { match_regex, pattern })
     }
 
     pub fn matches(&self, message_text: &str) -> bool {
         self.match_regex.is_match(message_text)
     }
+
+    pub fn capture_names(&self) -> Vec<String> {
+        self.match_regex.capture_names().flatten().map(String::from).collect()
+    }
+
+    pub fn captures(&self, message_text: &str) -> Option<HashMap<String, String>> {
+        let captures = self.match_regex.captures(message_text)?;
+
+        Some(self.match_regex.capture_names()
+            .flatten()
+            .filter_map(|name| captures.name(name).map(|capture| (name.to_string(), capture.as_str().to_string())))
+            .collect())
+    }
+}
+
+impl ActionRangeTrigger {
+    // Action ids that are not numbers, like reward titles, never match a range.
+    pub fn matches(&self, id: &str, name: &str) -> bool {
+        self.name == name && id.trim().parse::<u32>().is_ok_and(|amount| amount >= self.min && self.max.is_none_or(|max| amount <= max))
+    }
+}
+
+impl OverlayTarget {
+    // Targets are matched against the websocket path of the overlays
+    pub fn new(name: String) -> Result<Self, ()> {
+        if name.is_empty() || !name.chars().all(|character| character.is_ascii_alphanumeric() || character == '-' || character == '_') {
+            return Err(());
+        }
+        Ok(OverlayTarget(name))
+    }
 }
 
 impl ReactionDefinition {
@@ -72,9 +233,22 @@ impl ReactionDefinition {
             triggers: vec![trigger],
             steps: vec![],
             count: 0,
+            cooldown: ReactionCooldown::default(),
+            permission_level: ReactionPermissionLevel::default(),
+            overlay_targets: vec![],
         }
     }
 
+    pub(crate) fn template_variables(&self) -> Vec<String> {
+        self.triggers.iter()
+            .filter_map(|trigger| match trigger {
+                ReactionTrigger::ChatPattern(pattern_trigger) => Some(pattern_trigger.capture_names()),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
     pub(crate) fn update_triggers(&mut self, triggers: Vec<ReactionTrigger>) -> Result<(), ()> {
         if triggers.is_empty() {
             Err(())
@@ -88,6 +262,22 @@ impl ReactionDefinition {
         self.count = new_count;
     }
 
+    pub(crate) fn update_cooldown(&mut self, cooldown: ReactionCooldown) {
+        self.cooldown = cooldown;
+    }
+
+    pub(crate) fn update_permission_level(&mut self, permission_level: ReactionPermissionLevel) {
+        self.permission_level = permission_level;
+    }
+
+    pub(crate) fn update_overlay_targets(&mut self, overlay_targets: Vec<OverlayTarget>) {
+        self.overlay_targets = overlay_targets;
+    }
+
+    pub(crate) fn can_be_used_by(&self, source: &Source) -> bool {
+        source.get_permission_level() >= self.permission_level
+    }
+
     pub(crate) fn disable(&mut self) {
         self.is_disabled = true;
     }
@@ -105,6 +295,38 @@ impl ReactionDefinition {
         }
     }
 
+    pub(crate) fn uses_emotion(&self, emotion_id: &EmotionId) -> bool {
+        self.steps.iter().any(|step| match step {
+            ReactionStepDefinition::Moving(_) => false,
+            ReactionStepDefinition::Talking(talking_step) => talking_step.emotion_id == *emotion_id,
+            ReactionStepDefinition::CompositeTalking(talking_steps) => talking_steps.iter().any(|talking_step| talking_step.emotion_id == *emotion_id),
+            ReactionStepDefinition::ChatMessage(_) => false,
+        })
+    }
+
+    pub(crate) fn uses_image(&self, image_id: &ImageId) -> bool {
+        self.steps.iter().any(|step| match step {
+            ReactionStepDefinition::Moving(moving_step) => moving_step.animation.frames.all_image_ids().contains(&image_id),
+            ReactionStepDefinition::Talking(_) | ReactionStepDefinition::CompositeTalking(_) | ReactionStepDefinition::ChatMessage(_) => false,
+        })
+    }
+
+    pub(crate) fn all_emotion_ids(&self) -> Vec<&EmotionId> {
+        self.steps.iter().flat_map(|step| match step {
+            ReactionStepDefinition::Moving(_) => vec![],
+            ReactionStepDefinition::Talking(talking_step) => vec![&talking_step.emotion_id],
+            ReactionStepDefinition::CompositeTalking(talking_steps) => talking_steps.iter().map(|talking_step| &talking_step.emotion_id).collect(),
+            ReactionStepDefinition::ChatMessage(_) => vec![],
+        }).collect()
+    }
+
+    pub(crate) fn all_image_ids(&self) -> Vec<&ImageId> {
+        self.steps.iter().flat_map(|step| match step {
+            ReactionStepDefinition::Moving(moving_step) => moving_step.animation.frames.all_image_ids(),
+            ReactionStepDefinition::Talking(_) | ReactionStepDefinition::CompositeTalking(_) | ReactionStepDefinition::ChatMessage(_) => vec![],
+        }).collect()
+    }
+
     pub(super) fn add_step(&mut self, step: ReactionStepDefinition) {
         self.steps.push(step);
     }
@@ -116,20 +338,45 @@ impl ReactionDefinition {
 }
 
 impl ReactionTrigger {
+    // Two reactions cannot share a chat command or keyword, whatever their match options
+    pub fn conflicts_with(&self, other: &ReactionTrigger) -> bool {
+        match (self, other) {
+            (ReactionTrigger::ChatCommand(trigger), ReactionTrigger::ChatCommand(other)) => trigger.text == other.text,
+            (ReactionTrigger::ChatKeyword(trigger), ReactionTrigger::ChatKeyword(other)) => trigger.text == other.text,
+            _ => self == other,
+        }
+    }
+
     pub fn new_chat_command(trigger: String) -> Result<Self, ()> {
+        Self::new_chat_command_with_options(trigger, ChatTriggerMatchOptions::default())
+    }
+
+    pub fn new_chat_command_with_options(trigger: String, options: ChatTriggerMatchOptions) -> Result<Self, ()> {
         if trigger.is_empty() {
             return Err(());
         }
 
-        Ok(ReactionTrigger::ChatCommand(ChatCommandTrigger { text: trigger }))
+        Ok(ReactionTrigger::ChatCommand(ChatCommandTrigger::new(trigger, options)))
     }
 
     pub fn new_chat_keyword(trigger: String) -> Result<Self, ()> {
+        Self::new_chat_keyword_with_options(trigger, ChatTriggerMatchOptions::default())
+    }
+
+    pub fn new_chat_keyword_with_options(trigger: String, options: ChatTriggerMatchOptions) -> Result<Self, ()> {
         if trigger.is_empty() {
             return Err(());
         }
 
-        Ok(ReactionTrigger::ChatKeyword(ChatKeywordTrigger::new(trigger)))
+        Ok(ReactionTrigger::ChatKeyword(ChatKeywordTrigger::new(trigger, options)))
+    }
+
+    pub fn new_chat_pattern(pattern: String) -> Result<Self, ()> {
+        if pattern.is_empty() {
+            return Err(());
+        }
+
+        Ok(ReactionTrigger::ChatPattern(ChatPatternTrigger::try_new(pattern)?))
     }
 
     pub fn new_action(action_id: String, action_name: String) -> Result<Self, ()> {
@@ -139,6 +386,18 @@ impl ReactionTrigger {
 
         Ok(ReactionTrigger::Action(ActionTrigger { id: action_id, name: action_name }))
     }
+
+    pub fn new_action_range(action_name: String, min: u32, max: Option<u32>) -> Result<Self, ()> {
+        if action_name.is_empty() || max.is_some_and(|max| max < min) {
+            return Err(());
+        }
+
+        Ok(ReactionTrigger::ActionRange(ActionRangeTrigger { name: action_name, min, max }))
+    }
+
+    pub fn new_stream_event(kind: StreamEventKind) -> Self {
+        ReactionTrigger::StreamEvent(StreamEventTrigger { kind })
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -146,6 +405,7 @@ pub enum ReactionStepDefinition {
     Moving(MovingReactionStep),
     Talking(TalkingReactionStepDefinition),
     CompositeTalking(Vec<TalkingReactionStepDefinition>),
+    ChatMessage(ChatMessageReactionStepDefinition),
 }
 
 pub type MovingReactionStepDefinition = MovingReactionStep;
@@ -157,6 +417,13 @@ pub struct TalkingReactionStepDefinition {
     pub alternatives: ReactionStepMessageAlternativesDefinition,
 }
 
+#[derive(Clone, Debug)]
+pub struct ChatMessageReactionStepDefinition {
+    pub alternatives: ReactionStepMessageAlternativesDefinition,
+    // Sends the message as a reply to the chat message that triggered the reaction, when there is one
+    pub reply: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct ReactionStepMessageAlternativesDefinition(pub Vec<ReactionStepMessageAlternativeDefinition>);
 
@@ -164,20 +431,68 @@ pub struct ReactionStepMessageAlternativesDefinition(pub Vec<ReactionStepMessage
 pub struct ReactionStepMessageAlternativeDefinition {
     pub message: ReactionStepMessageDefinition,
     pub probability: Option<f32>,
+    pub conditions: Vec<ReactionCondition>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReactionCondition {
+    CountMultipleOf(u32),
+    CountEquals(u32),
+    MinimumPermissionLevel(ReactionPermissionLevel),
+    HasTarget,
 }
 
 pub type ReactionStepSkipDefinition = ReactionStepSkip;
 pub type ReactionStepMessageDefinition = ReactionStepText;
 
+impl ReactionCondition {
+    pub fn new_count_multiple_of(count: u32) -> Result<Self, ()> {
+        if count == 0 {
+            return Err(());
+        }
+
+        Ok(ReactionCondition::CountMultipleOf(count))
+    }
+
+    pub(crate) fn is_met(&self, context: &ReactionContext) -> bool {
+        match self {
+            ReactionCondition::CountMultipleOf(count) => context.count.is_multiple_of(*count),
+            ReactionCondition::CountEquals(count) => context.count == *count,
+            ReactionCondition::MinimumPermissionLevel(permission_level) => context.stimulus.get_source().get_permission_level() >= *permission_level,
+            ReactionCondition::HasTarget => match &context.stimulus {
+                Stimulus::ChatMessage(message) => message.get_target().is_some(),
+                Stimulus::StreamEvent(stream_event) => stream_event.get_target().is_some(),
+                Stimulus::Action(_) => false,
+            },
+        }
+    }
+}
+
+impl ReactionStepMessageAlternativeDefinition {
+    fn is_conditional(&self) -> bool {
+        !self.conditions.is_empty()
+    }
+
+    fn are_conditions_met(&self, context: &ReactionContext) -> bool {
+        self.conditions.iter().all(|condition| condition.is_met(context))
+    }
+}
+
 impl ReactionStepMessageAlternativesDefinition {
+    // Alternatives without conditions need to add up to 100, conditional ones take up to their probability when met.
     pub fn try_new(alternatives: Vec<ReactionStepMessageAlternativeDefinition>) -> Result<ReactionStepMessageAlternativesDefinition, ()> {
-        let total_probability: f32 = alternatives.iter()
-            .filter(|alternative| alternative.probability.is_some())
-            .map(|alternative| alternative.probability.unwrap())
-            .sum();
-        let has_alternatives_without_probability = alternatives.iter().any(|alternative| alternative.probability.is_none());
-
-        if total_probability > 100.0 || total_probability < 100.0 && !has_alternatives_without_probability {
+        let total_probability = |conditional: bool| -> f32 {
+            alternatives.iter()
+                .filter(|alternative| alternative.is_conditional() == conditional)
+                .filter_map(|alternative| alternative.probability)
+                .sum()
+        };
+        let unconditional_total_probability = total_probability(false);
+        let has_alternatives_without_probability = alternatives.iter().any(|alternative| !alternative.is_conditional() && alternative.probability.is_none());
+
+        if unconditional_total_probability > 100.0
+            || unconditional_total_probability < 100.0 && !has_alternatives_without_probability
+            || total_probability(true) > 100.0 {
             Err(())
         } else {
             Ok(Self(alternatives))
@@ -187,102 +502,142 @@ impl ReactionStepMessageAlternativesDefinition {
     pub fn new_single(text: ReactionStepMessageDefinition) -> ReactionStepMessageAlternativesDefinition {
         Self(vec![ReactionStepMessageAlternativeDefinition {
             probability: Some(100.0),
-            message: text
+            message: text,
+            conditions: vec![],
         }])
     }
 
-    pub(super) fn get_random_text_pure(alternatives: &Vec<ReactionStepMessageAlternativeDefinition>, mut random_hit: f32) -> ReactionStepMessageDefinition {
-        let alternatives_with_none_probability = alternatives.iter()
-            .filter(|alternative| alternative.probability.is_none())
-            .count() as f32;
-
-        let total_set_probability: f32 = alternatives.iter()
-            .map(|alternative| alternative.probability.unwrap_or(0.0))
-            .sum();
-
-        let probability_to_share_among_none: f32 = 100.0 - total_set_probability;
-
-        for alternative in alternatives {
-            let probability = match alternative.probability {
-                None => probability_to_share_among_none / alternatives_with_none_probability,
-                Some(probability) => probability
-            };
+    pub(super) fn get_random_text_pure(alternatives: &[ReactionStepMessageAlternativeDefinition], context: &ReactionContext, mut random_hit: f32) -> ReactionStepMessageDefinition {
+        let (conditional, unconditional): (Vec<&ReactionStepMessageAlternativeDefinition>, Vec<&ReactionStepMessageAlternativeDefinition>) = alternatives.iter()
+            .partition(|alternative| alternative.is_conditional());
+        let met_conditional: Vec<&ReactionStepMessageAlternativeDefinition> = conditional.into_iter()
+            .filter(|alternative| alternative.are_conditions_met(context))
+            .collect();
+
+        let mut weighted_alternatives = share_probability(&met_conditional);
+        let probability_left: f32 = 100.0 - weighted_alternatives.iter().map(|(_, probability)| probability).sum::<f32>();
+        if probability_left > 0.0 {
+            weighted_alternatives.extend(share_probability(&unconditional)
+                .into_iter()
+                .map(|(alternative, probability)| (alternative, probability * probability_left / 100.0)));
+        }
 
-            if probability > random_hit {
+        for (alternative, probability) in &weighted_alternatives {
+            if *probability > random_hit {
                 return alternative.message.clone()
             } else {
                 random_hit -= probability;
             }
         }
 
-        unreachable!();
+        weighted_alternatives.last().map(|(alternative, _)| alternative.message.clone()).unwrap()
     }
 
-    pub(super) fn get_random_text(&self) -> ReactionStepMessageDefinition {
+    // Variables are the names captured by the pattern triggers of the reaction, on top of the built-in placeholders.
+    pub(crate) fn validate_templates(&self, variables: &[String]) -> Result<(), TextTemplateError> {
+        self.0.iter().try_for_each(|alternative| {
+            let template = TextTemplate::parse(&alternative.message.get_text())?;
+            let unknown_placeholder = template.placeholder_names().find(|name| !is_template_placeholder(name, variables)).map(String::from);
+            unknown_placeholder.map_or(Ok(()), |name| Err(TextTemplateError::UnknownPlaceholder(name)))
+        })
+    }
+
+    pub(super) fn get_random_text(&self, context: &ReactionContext) -> ReactionStepMessageDefinition {
         let random_hit = random::<f32>() * 100.0;
-        ReactionStepMessageAlternativesDefinition::get_random_text_pure(&self.0, random_hit)
+        ReactionStepMessageAlternativesDefinition::get_random_text_pure(&self.0, context, random_hit)
     }
 }
 
+fn share_probability<'a>(alternatives: &[&'a ReactionStepMessageAlternativeDefinition]) -> Vec<(&'a ReactionStepMessageAlternativeDefinition, f32)> {
+    let alternatives_with_none_probability = alternatives.iter()
+        .filter(|alternative| alternative.probability.is_none())
+        .count() as f32;
+
+    let total_set_probability: f32 = alternatives.iter()
+        .map(|alternative| alternative.probability.unwrap_or(0.0))
+        .sum();
+
+    let probability_to_share_among_none: f32 = 100.0 - total_set_probability;
+
+    alternatives.iter()
+        .map(|alternative| (*alternative, match alternative.probability {
+            None => probability_to_share_among_none / alternatives_with_none_probability,
+            Some(probability) => probability
+        }))
+        .collect()
+}
+
 impl ReactionStepMessageDefinition {
-    pub fn try_contextualise_text_reaction(&self, context: &ReactionContext) -> Option<ReactionStepText> {
-        Some(match self {
-            ReactionStepMessageDefinition::Instant(_) => ReactionStepText::Instant(self.try_apply_context(context)?),
-            ReactionStepMessageDefinition::LetterByLetter(_) => ReactionStepText::LetterByLetter(self.try_apply_context(context)?),
-        })
+    pub fn contextualise_text_reaction(&self, context: &ReactionContext) -> ReactionStepText {
+        match self {
+            ReactionStepMessageDefinition::Instant(_) => ReactionStepText::Instant(self.apply_context(context)),
+            ReactionStepMessageDefinition::LetterByLetter(_) => ReactionStepText::LetterByLetter(self.apply_context(context)),
+        }
     }
 
-    fn try_apply_context(&self, context: &ReactionContext) -> Option<String> {
+    fn apply_context(&self, context: &ReactionContext) -> String {
         let text = self.get_text();
-        let template_chunks = text.split("$");
-        let mut output_message = String::new();
 
-        for template_chunk in template_chunks {
-            if template_chunk.starts_with("{user}") {
-                write!(output_message, "{}", template_chunk.replacen("{user}", &context.stimulus.get_source_name(), 1)).unwrap();
-                continue;
-            }
+        match TextTemplate::parse(&text) {
+            Ok(template) => template.render(|name| resolve_template_value(name, context)),
+            Err(_) => text,
+        }
+    }
+}
 
-            if template_chunk.starts_with("{count}") {
-                write!(output_message, "{}", template_chunk.replacen("{count}", &context.count.to_string(), 1)).unwrap();
-                continue;
-            }
+fn resolve_template_value(name: &str, context: &ReactionContext) -> TextTemplateValue {
+    let present_or_missing = |value: Option<String>| value.map(TextTemplateValue::Present).unwrap_or(TextTemplateValue::Missing);
+
+    match (name, &context.stimulus) {
+        ("user", _) => TextTemplateValue::Present(context.stimulus.get_source_name()),
+        ("count", _) => TextTemplateValue::Present(context.count.to_string()),
+        ("target", Stimulus::ChatMessage(message)) => present_or_missing(message.get_target()),
+        ("touser", Stimulus::ChatMessage(message)) => TextTemplateValue::Present(message.get_target().unwrap_or_else(|| context.stimulus.get_source_name())),
+        ("touser", Stimulus::Action(_)) => TextTemplateValue::Present(context.stimulus.get_source_name()),
+        ("target", Stimulus::StreamEvent(stream_event)) => present_or_missing(stream_event.get_target()),
+        ("touser", Stimulus::StreamEvent(stream_event)) => TextTemplateValue::Present(stream_event.get_target().unwrap_or_else(|| context.stimulus.get_source_name())),
+        ("input", Stimulus::StreamEvent(stream_event)) => present_or_missing(stream_event.message.clone().filter(|message| !message.trim().is_empty())),
+        ("months", Stimulus::StreamEvent(stream_event)) => match &stream_event.event {
+            StreamEvent::Subscription { months } | StreamEvent::Resubscription { months } | StreamEvent::GiftSubscription { months, .. } => TextTemplateValue::Present(months.to_string()),
+            _ => TextTemplateValue::Missing,
+        },
+        ("viewers", Stimulus::StreamEvent(stream_event)) => match &stream_event.event {
+            StreamEvent::Raid { viewers } => TextTemplateValue::Present(viewers.to_string()),
+            _ => TextTemplateValue::Missing,
+        },
+        ("level", Stimulus::StreamEvent(stream_event)) => match &stream_event.event {
+            StreamEvent::HypeTrainStart { level } | StreamEvent::HypeTrainLevelUp { level } | StreamEvent::HypeTrainEnd { level } => TextTemplateValue::Present(level.to_string()),
+            _ => TextTemplateValue::Missing,
+        },
+        ("gifts", Stimulus::StreamEvent(stream_event)) => match &stream_event.event {
+            StreamEvent::MysteryGift { count } => TextTemplateValue::Present(count.to_string()),
+            _ => TextTemplateValue::Missing,
+        },
+        ("args", Stimulus::ChatMessage(message)) => present_or_missing(message.get_arguments()),
+        ("input", Stimulus::Action(action)) => present_or_missing(action.input.clone().filter(|input| !input.trim().is_empty())),
+        ("amount", Stimulus::Action(action)) => present_or_missing(action.action.id.trim().parse::<u32>().ok().map(|amount| amount.to_string())),
+        (name, Stimulus::ChatMessage(message)) if argument_index(name).is_some() => present_or_missing(message.get_argument(argument_index(name).unwrap())),
+        (name, _) => context.variables.get(name).cloned().map(TextTemplateValue::Present).unwrap_or(TextTemplateValue::Unknown),
+    }
+}
 
-            match &context.stimulus {
-                Stimulus::ChatMessage(message) => {
-                    if template_chunk.starts_with("{target}") {
-                        write!(output_message, "{}", template_chunk.replacen("{target}", &message.get_target()?, 1)).unwrap();
-                        continue;
-                    }
-
-                    if template_chunk.starts_with("{touser}") {
-                        write!(output_message,
-                               "{}",
-                               template_chunk.replacen("{touser}", &message
-                                   .get_target()
-                                   .unwrap_or_else(|| context.stimulus.get_source_name()),
-                               1)).unwrap();
-                        continue;
-                    }
-                },
-                Stimulus::Action(_) => {
-                    if template_chunk.starts_with("{touser}") {
-                        write!(output_message, "{}", template_chunk.replacen("{touser}", &context.stimulus.get_source_name(), 1)).unwrap();
-                        continue;
-                    }
-                },
-            }
+// Keep in sync with resolve_template_value
+const TEMPLATE_PLACEHOLDERS: [&str; 11] = ["user", "count", "target", "touser", "input", "months", "viewers", "level", "gifts", "args", "amount"];
 
-            write!(output_message, "${}", template_chunk.to_string()).unwrap();
-        }
+fn is_template_placeholder(name: &str, variables: &[String]) -> bool {
+    TEMPLATE_PLACEHOLDERS.contains(&name) || argument_index(name).is_some() || variables.iter().any(|variable| variable == name)
+}
 
-        Some(output_message[1..].to_string())
-    }
+fn argument_index(name: &str) -> Option<usize> {
+    name.strip_prefix("arg")
+        .and_then(|index| index.parse::<usize>().ok())
+        .filter(|index| *index > 0)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::brain::stimuli::{ChatMessageStimulus, StreamEvent, StreamEventStimulus};
 
     #[test]
     fn reaction_text_alternatives_get_random_text_max_random_hit_get_last() {
@@ -362,13 +717,162 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn reaction_text_alternatives_get_random_text_met_conditional_alternative_without_probability_always_hits() {
+        let alternatives = [
+            ("some text", None, vec![]),
+            ("milestone", None, vec![ReactionCondition::new_count_multiple_of(100).unwrap()]),
+        ];
+
+        assert!(matches!(
+            get_random_text_in_context(&alternatives, &create_context(200, false), 0.0),
+            ReactionStepText::Instant(text) if text == "milestone"
+        ));
+        assert!(matches!(
+            get_random_text_in_context(&alternatives, &create_context(200, false), 99.9999),
+            ReactionStepText::Instant(text) if text == "milestone"
+        ));
+        assert!(matches!(
+            get_random_text_in_context(&alternatives, &create_context(201, false), 0.0),
+            ReactionStepText::Instant(text) if text == "some text"
+        ));
+    }
+
+    #[test]
+    fn reaction_text_alternatives_get_random_text_met_conditional_alternative_with_probability_leaves_the_rest_to_others() {
+        let alternatives = [
+            ("some text1", Some(50.0), vec![]),
+            ("some text2", None, vec![]),
+            ("mods only", Some(30.0), vec![ReactionCondition::MinimumPermissionLevel(ReactionPermissionLevel::Mods)]),
+        ];
+
+        assert!(matches!(
+            get_random_text_in_context(&alternatives, &create_context(1, true), 29.9),
+            ReactionStepText::Instant(text) if text == "mods only"
+        ));
+        assert!(matches!(
+            get_random_text_in_context(&alternatives, &create_context(1, true), 64.9),
+            ReactionStepText::Instant(text) if text == "some text1"
+        ));
+        assert!(matches!(
+            get_random_text_in_context(&alternatives, &create_context(1, true), 65.1),
+            ReactionStepText::Instant(text) if text == "some text2"
+        ));
+        assert!(matches!(
+            get_random_text_in_context(&alternatives, &create_context(1, false), 29.9),
+            ReactionStepText::Instant(text) if text == "some text1"
+        ));
+    }
+
+    #[test]
+    fn reaction_text_alternatives_try_new_validates_unconditional_and_conditional_probabilities_separately() {
+        let alternative = |probability: Option<f32>, conditions: Vec<ReactionCondition>| ReactionStepMessageAlternativeDefinition {
+            message: ReactionStepText::Instant(String::from("text")),
+            probability,
+            conditions,
+        };
+
+        assert!(ReactionStepMessageAlternativesDefinition::try_new(vec![alternative(Some(100.0), vec![]), alternative(Some(100.0), vec![ReactionCondition::HasTarget])]).is_ok());
+        assert!(ReactionStepMessageAlternativesDefinition::try_new(vec![alternative(None, vec![ReactionCondition::HasTarget])]).is_err());
+        assert!(ReactionStepMessageAlternativesDefinition::try_new(vec![alternative(None, vec![]), alternative(Some(60.0), vec![ReactionCondition::HasTarget]), alternative(Some(60.0), vec![ReactionCondition::CountEquals(1)])]).is_err());
+    }
+
+    #[test]
+    fn reaction_condition_is_met_checks_context() {
+        assert!(ReactionCondition::CountEquals(1000).is_met(&create_context(1000, false)));
+        assert!(!ReactionCondition::CountEquals(1000).is_met(&create_context(999, false)));
+        assert!(ReactionCondition::CountMultipleOf(100).is_met(&create_context(300, false)));
+        assert!(!ReactionCondition::CountMultipleOf(100).is_met(&create_context(301, false)));
+        assert!(ReactionCondition::MinimumPermissionLevel(ReactionPermissionLevel::Mods).is_met(&create_context(1, true)));
+        assert!(!ReactionCondition::MinimumPermissionLevel(ReactionPermissionLevel::Mods).is_met(&create_context(1, false)));
+        assert!(ReactionCondition::new_count_multiple_of(0).is_err());
+    }
+
+    #[test]
+    fn reaction_condition_has_target_is_met_for_chat_messages_with_a_target_and_gift_subscriptions() {
+        let mut context = create_context(1, false);
+        assert!(!ReactionCondition::HasTarget.is_met(&context));
+
+        if let Stimulus::ChatMessage(message) = &mut context.stimulus {
+            message.text = String::from("!hug Pmyl");
+        }
+        assert!(ReactionCondition::HasTarget.is_met(&context));
+
+        context.stimulus = create_stream_event_stimulus(StreamEvent::GiftSubscription { recipient: String::from("Pmyl"), months: 1 });
+        assert!(ReactionCondition::HasTarget.is_met(&context));
+
+        context.stimulus = create_stream_event_stimulus(StreamEvent::Subscription { months: 1 });
+        assert!(!ReactionCondition::HasTarget.is_met(&context));
+    }
+
+    #[test]
+    fn chat_triggers_differing_only_in_options_are_not_equal_but_conflict() {
+        let case_insensitive = ChatTriggerMatchOptions { case_sensitive: false, ..Default::default() };
+        let command = ReactionTrigger::new_chat_command(String::from("!hello")).unwrap();
+        let insensitive_command = ReactionTrigger::new_chat_command_with_options(String::from("!hello"), case_insensitive.clone()).unwrap();
+        let keyword = ReactionTrigger::new_chat_keyword(String::from("hello")).unwrap();
+        let insensitive_keyword = ReactionTrigger::new_chat_keyword_with_options(String::from("hello"), case_insensitive).unwrap();
+
+        assert_ne!(command, insensitive_command);
+        assert_ne!(keyword, insensitive_keyword);
+        assert_eq!(command, ReactionTrigger::new_chat_command(String::from("!hello")).unwrap());
+        assert!(command.conflicts_with(&insensitive_command));
+        assert!(keyword.conflicts_with(&insensitive_keyword));
+        assert!(!command.conflicts_with(&ReactionTrigger::new_chat_keyword(String::from("!hello")).unwrap()));
+        assert!(!command.conflicts_with(&ReactionTrigger::new_chat_command(String::from("!bye")).unwrap()));
+    }
+
+    #[test]
+    fn action_range_trigger_matches_numeric_ids_within_bounds() {
+        let ReactionTrigger::ActionRange(trigger) = ReactionTrigger::new_action_range(String::from("bits"), 100, Some(499)).unwrap() else { panic!() };
+
+        assert!(trigger.matches("100", "bits"));
+        assert!(trigger.matches("499", "bits"));
+        assert!(!trigger.matches("99", "bits"));
+        assert!(!trigger.matches("500", "bits"));
+        assert!(!trigger.matches("200", "reward_redeem"));
+        assert!(!trigger.matches("Hydrate", "bits"));
+        assert!(ReactionTrigger::new_action_range(String::from("bits"), 100, Some(99)).is_err());
+        assert!(ReactionTrigger::new_action_range(String::new(), 100, None).is_err());
+    }
+
     fn get_random_text(alternatives: &[(&str, Option<f32>)], random_hit: f32) -> ReactionStepText {
+        get_random_text_in_context(
+            &alternatives.iter().map(|(text, probability)| (*text, *probability, vec![])).collect::<Vec<(&str, Option<f32>, Vec<ReactionCondition>)>>(),
+            &create_context(1, false),
+            random_hit
+        )
+    }
+
+    fn get_random_text_in_context(alternatives: &[(&str, Option<f32>, Vec<ReactionCondition>)], context: &ReactionContext, random_hit: f32) -> ReactionStepText {
         ReactionStepMessageAlternativesDefinition::get_random_text_pure(
             &alternatives.iter().map(|alternative| ReactionStepMessageAlternativeDefinition {
                 message: ReactionStepText::Instant(alternative.0.to_string()),
-                probability: alternative.1
-            }).collect(),
+                probability: alternative.1,
+                conditions: alternative.2.clone()
+            }).collect::<Vec<ReactionStepMessageAlternativeDefinition>>(),
+            context,
             random_hit
         )
     }
-}
\ No newline at end of file
+
+    fn create_context(count: u32, is_mod: bool) -> ReactionContext {
+        ReactionContext {
+            stimulus: Stimulus::ChatMessage(ChatMessageStimulus {
+                text: String::from("!hug"),
+                source: Source { user_name: String::from("_a name_"), is_mod, is_broadcaster: false, is_vip: false, is_subscriber: false },
+                message_id: None,
+            }),
+            count,
+            variables: HashMap::new(),
+        }
+    }
+
+    fn create_stream_event_stimulus(event: StreamEvent) -> Stimulus {
+        Stimulus::StreamEvent(StreamEventStimulus {
+            source: Source { user_name: String::from("_a name_"), is_mod: false, is_broadcaster: false, is_vip: false, is_subscriber: false },
+            event,
+            message: None,
+        })
+    }
+}
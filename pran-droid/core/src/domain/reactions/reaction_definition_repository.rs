@@ -17,6 +17,20 @@ pub enum ReactionUpdateError {
     Missing
 }
 
+#[derive(Debug, Error)]
+pub enum ReactionGetAllError {
+    #[error("Unexpected error while getting the reactions: {0}")]
+    Unexpected(String)
+}
+
+#[derive(Debug, Error)]
+pub enum ReactionDeleteError {
+    #[error("Trying to delete a not existing reaction")]
+    Missing,
+    #[error("Unexpected error while deleting the reaction")]
+    Unexpected
+}
+
 #[async_trait]
 pub trait ReactionDefinitionRepository: Send + Sync {
     fn next_id(&self) -> ReactionDefinitionId;
@@ -25,7 +39,9 @@ pub trait ReactionDefinitionRepository: Send + Sync {
     async fn other_exists_with_trigger(&self, trigger: &ReactionTrigger, excluded_reaction_definition_id: &ReactionDefinitionId) -> bool;
     async fn get(&self, id: &ReactionDefinitionId) -> Option<ReactionDefinition>;
     async fn get_all(&self) -> Vec<ReactionDefinition>;
+    async fn try_get_all(&self) -> Result<Vec<ReactionDefinition>, ReactionGetAllError>;
     async fn update(&self, reaction: &ReactionDefinition) -> Result<(), ReactionUpdateError>;
+    async fn delete(&self, id: &ReactionDefinitionId) -> Result<(), ReactionDeleteError>;
 }
 
 #[cfg(test)]
@@ -50,6 +66,13 @@ pub mod tests {
         ).await
     }
 
+    pub async fn setup_dummy_chat_pattern_reaction_definitions(chat_pattern_triggers: Vec<&str>, repository: &dyn ReactionDefinitionRepository) -> Vec<ReactionDefinition> {
+        setup_dummy_reaction_definitions_with_triggers(
+            chat_pattern_triggers.iter().map(|trigger| ReactionTrigger::new_chat_pattern(trigger.to_string()).unwrap()).collect(),
+            repository
+        ).await
+    }
+
     pub async fn setup_dummy_action_reaction_definitions(action_triggers: Vec<(&str, &str)>, repository: &dyn ReactionDefinitionRepository) -> Vec<ReactionDefinition> {
         setup_dummy_reaction_definitions_with_triggers(
             action_triggers.iter().map(|trigger| ReactionTrigger::new_action(trigger.0.to_string(), trigger.1.to_string()).unwrap()).collect(),
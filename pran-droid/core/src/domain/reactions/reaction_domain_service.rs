@@ -2,7 +2,7 @@ use std::fmt::Debug;
 use thiserror::Error;
 use crate::domain::animations::animation_domain_service::validate_images;
 use crate::domain::emotions::emotion_repository::EmotionRepository;
-use crate::domain::reactions::reaction_definition::{MovingReactionStepDefinition, ReactionDefinition, ReactionStepDefinition, TalkingReactionStepDefinition};
+use crate::domain::reactions::reaction_definition::{ChatMessageReactionStepDefinition, MovingReactionStepDefinition, ReactionDefinition, ReactionStepDefinition, TalkingReactionStepDefinition};
 use crate::domain::images::image_repository::ImageRepository;
 
 pub(crate) async fn add_moving_step_to_reaction(reaction: &mut ReactionDefinition, reaction_step: MovingReactionStepDefinition, image_repository: &dyn ImageRepository) -> Result<(), AddStepToReactionError> {
@@ -29,6 +29,30 @@ pub(crate) async fn replace_talking_step_in_reaction(reaction: &mut ReactionDefi
     Ok(())
 }
 
+pub(crate) async fn add_composite_talking_step_to_reaction(reaction: &mut ReactionDefinition, reaction_steps: Vec<TalkingReactionStepDefinition>, emotion_repository: &dyn EmotionRepository) -> Result<(), AddStepToReactionError> {
+    for reaction_step in &reaction_steps {
+        validate_talking_step(reaction_step, emotion_repository).await?;
+    }
+    reaction.add_step(ReactionStepDefinition::CompositeTalking(reaction_steps));
+    Ok(())
+}
+
+pub(crate) async fn replace_composite_talking_step_in_reaction(reaction: &mut ReactionDefinition, reaction_steps: Vec<TalkingReactionStepDefinition>, step_index: usize, emotion_repository: &dyn EmotionRepository) -> Result<(), AddStepToReactionError> {
+    for reaction_step in &reaction_steps {
+        validate_talking_step(reaction_step, emotion_repository).await?;
+    }
+    reaction.replace_step_at(ReactionStepDefinition::CompositeTalking(reaction_steps), step_index);
+    Ok(())
+}
+
+pub(crate) fn add_chat_message_step_to_reaction(reaction: &mut ReactionDefinition, reaction_step: ChatMessageReactionStepDefinition) {
+    reaction.add_step(ReactionStepDefinition::ChatMessage(reaction_step));
+}
+
+pub(crate) fn replace_chat_message_step_in_reaction(reaction: &mut ReactionDefinition, reaction_step: ChatMessageReactionStepDefinition, step_index: usize) {
+    reaction.replace_step_at(ReactionStepDefinition::ChatMessage(reaction_step), step_index);
+}
+
 #[derive(Debug, Error)]
 pub enum AddStepToReactionError {
     #[error("Entity not found [{0}]")]
@@ -0,0 +1,263 @@
+use std::fmt::Debug;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TextTemplateError {
+    #[error("Placeholder at position {0} is not closed")]
+    UnclosedPlaceholder(usize),
+    #[error("Placeholder at position {0} has no name")]
+    MissingName(usize),
+    #[error("Filter {0} does not exist")]
+    InvalidFilter(String),
+    #[error("Placeholder at position {0} contains a brace")]
+    NestedBrace(usize),
+    #[error("Placeholder {0} does not exist")]
+    UnknownPlaceholder(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextTemplate(Vec<TextTemplatePart>);
+
+#[derive(Clone, Debug, PartialEq)]
+enum TextTemplatePart {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Placeholder {
+    source: String,
+    name: String,
+    filters: Vec<TextTemplateFilter>,
+    default: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TextTemplateFilter {
+    Ordinal,
+    Upper,
+    Lower,
+    Truncate(usize),
+}
+
+pub enum TextTemplateValue {
+    Present(String),
+    Missing,
+    Unknown,
+}
+
+impl TextTemplate {
+    // Syntax is `${name:filter1:filter2|default}`, filters and default being optional, and `$$` for a literal `$`.
+    pub fn parse(text: &str) -> Result<Self, TextTemplateError> {
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let mut position = 0;
+
+        while let Some(offset) = text[position..].find('$') {
+            let start = position + offset;
+            literal.push_str(&text[position..start]);
+
+            match text[start + 1..].chars().next() {
+                Some('$') => {
+                    literal.push('$');
+                    position = start + 2;
+                },
+                Some('{') => {
+                    let end = start + text[start..].find('}').ok_or(TextTemplateError::UnclosedPlaceholder(start))?;
+                    // Placeholders end at the first closing brace, so an opening one inside would be cut in half
+                    if text[start + 2..end].contains('{') {
+                        return Err(TextTemplateError::NestedBrace(start));
+                    }
+                    if !literal.is_empty() {
+                        parts.push(TextTemplatePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(TextTemplatePart::Placeholder(Placeholder::parse(&text[start..=end], start)?));
+                    position = end + 1;
+                },
+                _ => {
+                    literal.push('$');
+                    position = start + 1;
+                }
+            }
+        }
+
+        literal.push_str(&text[position..]);
+        if !literal.is_empty() {
+            parts.push(TextTemplatePart::Literal(literal));
+        }
+
+        Ok(Self(parts))
+    }
+
+    pub fn placeholder_names(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().filter_map(|part| match part {
+            TextTemplatePart::Placeholder(placeholder) => Some(placeholder.name.as_str()),
+            TextTemplatePart::Literal(_) => None,
+        })
+    }
+
+    // Unknown placeholders without a default are kept as written, missing ones without a default render as nothing.
+    pub fn render<F>(&self, resolve: F) -> String where F: Fn(&str) -> TextTemplateValue {
+        let mut output = String::new();
+
+        for part in &self.0 {
+            match part {
+                TextTemplatePart::Literal(text) => output.push_str(text),
+                TextTemplatePart::Placeholder(placeholder) => match (resolve(&placeholder.name), &placeholder.default) {
+                    (TextTemplateValue::Present(value), _) => output.push_str(&placeholder.apply_filters(value)),
+                    (_, Some(default)) => output.push_str(&placeholder.apply_filters(default.clone())),
+                    (TextTemplateValue::Missing, None) => {},
+                    (TextTemplateValue::Unknown, None) => output.push_str(&placeholder.source),
+                }
+            }
+        }
+
+        output
+    }
+}
+
+impl Placeholder {
+    fn parse(source: &str, position: usize) -> Result<Self, TextTemplateError> {
+        let content = &source[2..source.len() - 1];
+        let (specification, default) = match content.split_once('|') {
+            Some((specification, default)) => (specification, Some(default.to_string())),
+            None => (content, None),
+        };
+        let mut specification = specification.split(':');
+        let name = specification.next().unwrap_or_default().trim();
+
+        if name.is_empty() {
+            return Err(TextTemplateError::MissingName(position));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            name: name.to_string(),
+            filters: specification.map(TextTemplateFilter::parse).collect::<Result<Vec<TextTemplateFilter>, TextTemplateError>>()?,
+            default,
+        })
+    }
+
+    fn apply_filters(&self, value: String) -> String {
+        self.filters.iter().fold(value, |value, filter| filter.apply(value))
+    }
+}
+
+impl TextTemplateFilter {
+    fn parse(filter: &str) -> Result<Self, TextTemplateError> {
+        let filter = filter.trim();
+        match filter {
+            "ordinal" => Ok(TextTemplateFilter::Ordinal),
+            "upper" => Ok(TextTemplateFilter::Upper),
+            "lower" => Ok(TextTemplateFilter::Lower),
+            _ => filter.strip_prefix("truncate(")
+                .and_then(|length| length.strip_suffix(')'))
+                .and_then(|length| length.trim().parse::<usize>().ok())
+                .map(TextTemplateFilter::Truncate)
+                .ok_or_else(|| TextTemplateError::InvalidFilter(filter.to_string()))
+        }
+    }
+
+    fn apply(&self, value: String) -> String {
+        match self {
+            TextTemplateFilter::Ordinal => match value.parse::<u64>() {
+                Ok(number) => format!("{}{}", number, ordinal_suffix(number)),
+                Err(_) => value
+            },
+            TextTemplateFilter::Upper => value.to_uppercase(),
+            TextTemplateFilter::Lower => value.to_lowercase(),
+            TextTemplateFilter::Truncate(length) => value.chars().take(*length).collect(),
+        }
+    }
+}
+
+fn ordinal_suffix(number: u64) -> &'static str {
+    match (number % 10, number % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_replaces_present_placeholders() {
+        assert_eq!(render("Hello ${user}, ${count} times", &[("user", "Pmyl"), ("count", "3")]), String::from("Hello Pmyl, 3 times"));
+    }
+
+    #[test]
+    fn render_keeps_unknown_placeholders_and_lone_dollars_as_written() {
+        assert_eq!(render("${not} keyword ${existing} $ 5$", &[]), String::from("${not} keyword ${existing} $ 5$"));
+    }
+
+    #[test]
+    fn render_double_dollar_is_a_literal_dollar() {
+        assert_eq!(render("$${user} costs 5$$", &[("user", "Pmyl")]), String::from("${user} costs 5$"));
+    }
+
+    #[test]
+    fn render_missing_placeholder_without_default_is_empty() {
+        assert_eq!(render("Hello ${target}!", &[("target", "")]), String::from("Hello !"));
+        assert_eq!(render("${target:upper}", &[("target", "")]), String::new());
+    }
+
+    #[test]
+    fn render_missing_or_unknown_placeholder_uses_default() {
+        assert_eq!(render("Hello ${target|someone}", &[("target", "")]), String::from("Hello someone"));
+        assert_eq!(render("Hello ${not|some: one}", &[]), String::from("Hello some: one"));
+    }
+
+    #[test]
+    fn render_applies_filters_in_order() {
+        assert_eq!(render("${count:ordinal}", &[("count", "1")]), String::from("1st"));
+        assert_eq!(render("${count:ordinal} ${other:ordinal}", &[("count", "12"), ("other", "23")]), String::from("12th 23rd"));
+        assert_eq!(render("${user:upper}", &[("user", "Pmyl")]), String::from("PMYL"));
+        assert_eq!(render("${user:lower:truncate(2)}", &[("user", "Pmyl")]), String::from("pm"));
+        assert_eq!(render("${target:upper|someone}", &[("target", "")]), String::from("SOMEONE"));
+    }
+
+    #[test]
+    fn render_ordinal_filter_on_text_keeps_it() {
+        assert_eq!(render("${user:ordinal}", &[("user", "Pmyl")]), String::from("Pmyl"));
+    }
+
+    #[test]
+    fn render_does_not_interpolate_values() {
+        assert_eq!(render("Hi ${user}", &[("user", "${count}"), ("count", "3")]), String::from("Hi ${count}"));
+    }
+
+    #[test]
+    fn parse_invalid_template_errors() {
+        assert_eq!(TextTemplate::parse("Hello ${user"), Err(TextTemplateError::UnclosedPlaceholder(6)));
+        assert_eq!(TextTemplate::parse("Hello ${|someone}"), Err(TextTemplateError::MissingName(6)));
+        assert_eq!(TextTemplate::parse("${user:shout}"), Err(TextTemplateError::InvalidFilter(String::from("shout"))));
+        assert_eq!(TextTemplate::parse("${user:truncate(many)}"), Err(TextTemplateError::InvalidFilter(String::from("truncate(many)"))));
+    }
+
+    #[test]
+    fn placeholder_names_lists_the_placeholders_in_order() {
+        let template = TextTemplate::parse("Hi ${user:upper}, $${count} ${target|someone} ${user}").unwrap();
+
+        assert_eq!(template.placeholder_names().collect::<Vec<&str>>(), vec!["user", "target", "user"]);
+    }
+
+    #[test]
+    fn parse_brace_inside_placeholder_errors() {
+        assert_eq!(TextTemplate::parse("Hello ${target|{nobody}}"), Err(TextTemplateError::NestedBrace(6)));
+        assert_eq!(TextTemplate::parse("Hello ${{target}"), Err(TextTemplateError::NestedBrace(6)));
+        assert!(TextTemplate::parse("Hello {friend} ${target|nobody}}").is_ok());
+    }
+
+    fn render(text: &str, values: &[(&str, &str)]) -> String {
+        TextTemplate::parse(text).unwrap().render(|name| match values.iter().find(|(value_name, _)| *value_name == name) {
+            Some((_, "")) => TextTemplateValue::Missing,
+            Some((_, value)) => TextTemplateValue::Present(value.to_string()),
+            None => TextTemplateValue::Unknown,
+        })
+    }
+}
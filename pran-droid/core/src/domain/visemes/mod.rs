@@ -0,0 +1,2 @@
+pub mod viseme_mapping;
+pub mod mouth_track;
\ No newline at end of file
@@ -0,0 +1,121 @@
+use crate::domain::emotions::emotion::MouthPositionName;
+use crate::domain::visemes::viseme_mapping::VisemeMapping;
+
+const ARPABET_VOWELS: [&str; 15] = ["AA", "AE", "AH", "AO", "AW", "AY", "EH", "ER", "EY", "IH", "IY", "OW", "OY", "UH", "UW"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimedMouthPosition {
+    pub position: MouthPositionName,
+    pub start_ms: u32,
+    pub duration_ms: u32,
+}
+
+// How long each mouth position is held, depending on the kind of phoneme it comes from
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhonemeDurations {
+    pub vowel_ms: u32,
+    pub consonant_ms: u32,
+    pub pause_ms: u32,
+}
+
+impl Default for PhonemeDurations {
+    fn default() -> Self {
+        PhonemeDurations { vowel_ms: 110, consonant_ms: 70, pause_ms: 150 }
+    }
+}
+
+impl PhonemeDurations {
+    fn of(&self, phoneme: &str) -> u32 {
+        let phoneme = phoneme.trim_end_matches(|character: char| character.is_ascii_digit()).to_uppercase();
+        if ARPABET_VOWELS.contains(&phoneme.as_str()) {
+            self.vowel_ms
+        } else if phoneme.chars().any(char::is_alphabetic) {
+            self.consonant_ms
+        } else {
+            self.pause_ms
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MouthTrackModel {
+    pub mapping: VisemeMapping,
+    pub durations: PhonemeDurations,
+}
+
+impl MouthTrackModel {
+    // Repeated positions are joined, so the mouth is only redrawn when it changes
+    pub fn mouth_track(&self, phonemes: &[String]) -> Vec<TimedMouthPosition> {
+        let mut track: Vec<TimedMouthPosition> = vec![];
+        let mut start_ms = 0;
+
+        for phoneme in phonemes {
+            let duration_ms = self.durations.of(phoneme);
+            for position in self.mapping.mouth_positions(phoneme) {
+                match track.last_mut() {
+                    Some(last) if last.position == *position => last.duration_ms += duration_ms,
+                    _ => track.push(TimedMouthPosition { position: position.clone(), start_ms, duration_ms }),
+                }
+                start_ms += duration_ms;
+            }
+        }
+
+        track
+    }
+}
+
+pub fn mouth_track_duration_ms(track: &[TimedMouthPosition]) -> u32 {
+    track.last().map(|position| position.start_ms + position.duration_ms).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    fn phonemes(phonemes: &[&str]) -> Vec<String> {
+        phonemes.iter().map(|phoneme| String::from(*phoneme)).collect()
+    }
+
+    fn timed(position: MouthPositionName, start_ms: u32, duration_ms: u32) -> TimedMouthPosition {
+        TimedMouthPosition { position, start_ms, duration_ms }
+    }
+
+    #[test]
+    fn mouth_track_times_each_position_by_kind_of_phoneme() {
+        let model = MouthTrackModel::default();
+
+        let track = model.mouth_track(&phonemes(&["HH", "AH0", "L", "OW1", "."]));
+
+        assert_eq!(track, vec![
+            timed(MouthPositionName::Ah, 0, 180),
+            timed(MouthPositionName::L, 180, 70),
+            timed(MouthPositionName::Oh, 250, 110),
+            timed(MouthPositionName::Idle, 360, 300),
+        ]);
+        assert_eq!(mouth_track_duration_ms(&track), 660);
+    }
+
+    #[test]
+    fn mouth_track_uses_the_configured_mapping_and_durations() {
+        let model = MouthTrackModel {
+            mapping: VisemeMapping::new(HashMap::from([(String::from("P"), vec![MouthPositionName::P1, MouthPositionName::P2])])),
+            durations: PhonemeDurations { vowel_ms: 100, consonant_ms: 40, pause_ms: 200 },
+        };
+
+        let track = model.mouth_track(&phonemes(&["P", "unknown", "P"]));
+
+        assert_eq!(track, vec![
+            timed(MouthPositionName::P1, 0, 40),
+            timed(MouthPositionName::P2, 40, 40),
+            timed(MouthPositionName::P1, 80, 40),
+            timed(MouthPositionName::P2, 120, 40),
+        ]);
+    }
+
+    #[test]
+    fn mouth_track_is_empty_without_phonemes() {
+        assert_eq!(MouthTrackModel::default().mouth_track(&[]), vec![]);
+        assert_eq!(mouth_track_duration_ms(&[]), 0);
+    }
+}
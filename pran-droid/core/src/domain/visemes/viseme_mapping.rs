@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use crate::domain::emotions::emotion::MouthPositionName;
+
+// Maps ARPAbet phonemes to the mouth positions drawn while saying them
+#[derive(Clone, Debug, PartialEq)]
+pub struct VisemeMapping {
+    phonemes: HashMap<String, Vec<MouthPositionName>>,
+}
+
+const CMU_VISEMES: [(&str, &[MouthPositionName]); 41] = [
+    // consonants
+    ("B", &[MouthPositionName::B]),
+    ("CH", &[MouthPositionName::S]),
+    ("D", &[MouthPositionName::L]),
+    ("DH", &[MouthPositionName::L]),
+    ("F", &[MouthPositionName::FV]),
+    ("G", &[MouthPositionName::S]),
+    ("HH", &[MouthPositionName::Ah]),
+    ("JH", &[MouthPositionName::S]),
+    ("K", &[MouthPositionName::K]),
+    ("L", &[MouthPositionName::L]),
+    ("M", &[MouthPositionName::B]),
+    ("N", &[MouthPositionName::S]),
+    ("NG", &[MouthPositionName::S]),
+    ("P", &[MouthPositionName::P1, MouthPositionName::P2]),
+    ("R", &[MouthPositionName::Ur]),
+    ("S", &[MouthPositionName::S]),
+    ("SH", &[MouthPositionName::S]),
+    ("T", &[MouthPositionName::S]),
+    ("TH", &[MouthPositionName::S]),
+    ("V", &[MouthPositionName::FV]),
+    ("W", &[MouthPositionName::Ur]),
+    ("Z", &[MouthPositionName::S]),
+    ("ZH", &[MouthPositionName::S]),
+    // vowels
+    ("AA", &[MouthPositionName::Oh]),
+    ("AE", &[MouthPositionName::Ee]),
+    ("AH", &[MouthPositionName::Ah]),
+    ("AO", &[MouthPositionName::Oh]),
+    ("AW", &[MouthPositionName::Ah]),
+    ("AY", &[MouthPositionName::Ah]),
+    ("EH", &[MouthPositionName::Ee]),
+    ("ER", &[MouthPositionName::Ur]),
+    ("EY", &[MouthPositionName::Ee]),
+    ("IH", &[MouthPositionName::Ee]),
+    ("IY", &[MouthPositionName::Ee]),
+    ("OW", &[MouthPositionName::Oh]),
+    ("OY", &[MouthPositionName::Oh]),
+    ("UH", &[MouthPositionName::Ur]),
+    ("UW", &[MouthPositionName::Ur]),
+    ("Y", &[MouthPositionName::Ee]),
+    // punctuation
+    (",", &[MouthPositionName::Idle]),
+    (".", &[MouthPositionName::Idle, MouthPositionName::Idle]),
+];
+
+impl Default for VisemeMapping {
+    // Same table the overlays used before the brain sent mouth tracks
+    fn default() -> Self {
+        VisemeMapping::new(CMU_VISEMES.iter()
+            .map(|(phoneme, positions)| (String::from(*phoneme), positions.to_vec()))
+            .collect())
+    }
+}
+
+impl VisemeMapping {
+    pub fn new(phonemes: HashMap<String, Vec<MouthPositionName>>) -> Self {
+        VisemeMapping { phonemes: phonemes.into_iter().map(|(phoneme, positions)| (normalise_phoneme(&phoneme), positions)).collect() }
+    }
+
+    // Droids can replace the positions of some phonemes and keep the rest of the table
+    pub fn with_overrides(mut self, overrides: HashMap<String, Vec<MouthPositionName>>) -> Self {
+        for (phoneme, positions) in overrides {
+            self.phonemes.insert(normalise_phoneme(&phoneme), positions);
+        }
+        self
+    }
+
+    // Unknown phonemes have no mouth position
+    pub fn mouth_positions(&self, phoneme: &str) -> &[MouthPositionName] {
+        self.phonemes.get(&normalise_phoneme(phoneme)).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+impl TryFrom<HashMap<String, Vec<String>>> for VisemeMapping {
+    type Error = ();
+
+    fn try_from(phonemes: HashMap<String, Vec<String>>) -> Result<Self, Self::Error> {
+        Ok(VisemeMapping::new(phonemes.into_iter()
+            .map(|(phoneme, positions)| Ok((phoneme, positions.iter().map(MouthPositionName::try_from).collect::<Result<Vec<MouthPositionName>, ()>>()?)))
+            .collect::<Result<HashMap<String, Vec<MouthPositionName>>, ()>>()?))
+    }
+}
+
+// Phonemisers may add the stress digit to vowels, the mouth is the same
+fn normalise_phoneme(phoneme: &str) -> String {
+    phoneme.trim_end_matches(|character: char| character.is_ascii_digit()).to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viseme_mapping_default_maps_cmu_phonemes() {
+        let mapping = VisemeMapping::default();
+
+        assert_eq!(mapping.mouth_positions("P"), &[MouthPositionName::P1, MouthPositionName::P2]);
+        assert_eq!(mapping.mouth_positions("ah1"), &[MouthPositionName::Ah]);
+        assert_eq!(mapping.mouth_positions("."), &[MouthPositionName::Idle, MouthPositionName::Idle]);
+        assert_eq!(mapping.mouth_positions("Q"), &[]);
+    }
+
+    #[test]
+    fn viseme_mapping_overrides_replace_only_the_given_phonemes() {
+        let mapping = VisemeMapping::default().with_overrides(HashMap::from([(String::from("hh"), vec![MouthPositionName::Idle])]));
+
+        assert_eq!(mapping.mouth_positions("HH"), &[MouthPositionName::Idle]);
+        assert_eq!(mapping.mouth_positions("B"), &[MouthPositionName::B]);
+    }
+
+    #[test]
+    fn viseme_mapping_try_from_rejects_unknown_mouth_positions() {
+        assert_eq!(
+            VisemeMapping::try_from(HashMap::from([(String::from("AA"), vec![String::from("ah")])])),
+            Ok(VisemeMapping::new(HashMap::from([(String::from("AA"), vec![MouthPositionName::Ah])])))
+        );
+        assert_eq!(VisemeMapping::try_from(HashMap::from([(String::from("AA"), vec![String::from("wide")])])), Err(()));
+    }
+}
@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+use std::sync::Mutex;
+use crate::domain::credentials::twitch_credentials::TwitchCredentials;
+use crate::domain::credentials::twitch_credentials_repository::{CredentialsSaveError, TwitchCredentialsRepository};
+
+#[derive(Default)]
+pub struct InMemoryTwitchCredentialsRepository {
+    credentials: Mutex<Option<TwitchCredentials>>,
+}
+
+impl InMemoryTwitchCredentialsRepository {
+    pub fn new() -> InMemoryTwitchCredentialsRepository {
+        InMemoryTwitchCredentialsRepository { credentials: Mutex::new(None) }
+    }
+}
+
+#[async_trait]
+impl TwitchCredentialsRepository for InMemoryTwitchCredentialsRepository {
+    async fn get(&self) -> Option<TwitchCredentials> {
+        self.credentials.lock().ok()?.clone()
+    }
+
+    async fn save(&self, credentials: &TwitchCredentials) -> Result<(), CredentialsSaveError> {
+        let mut lock = self.credentials.lock().map_err(|_| CredentialsSaveError::Unexpected)?;
+        *lock = Some(credentials.clone());
+
+        Ok(())
+    }
+}
@@ -0,0 +1 @@
+pub mod in_memory_twitch_credentials_repository;
\ No newline at end of file
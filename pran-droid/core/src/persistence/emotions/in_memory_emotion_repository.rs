@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use std::sync::{Arc, Mutex};
 use crate::domain::emotions::emotion::{Emotion, EmotionId, EmotionName};
-use crate::domain::emotions::emotion_repository::{EmotionInsertError, EmotionRepository, EmotionUpdateError};
+use crate::domain::emotions::emotion_repository::{EmotionDeleteError, EmotionGetAllError, EmotionInsertError, EmotionRepository, EmotionUpdateError};
 use crate::persistence::id_generation::id_generation::{IdGenerator, IdGeneratorInMemoryIncremental, IdGeneratorUuid};
 
 pub struct InMemoryEmotionRepository {
@@ -60,6 +60,10 @@ impl EmotionRepository for InMemoryEmotionRepository {
         self.emotions.lock().unwrap().to_vec()
     }
 
+    async fn try_get_all(&self) -> Result<Vec<Emotion>, EmotionGetAllError> {
+        Ok(self.get_all().await)
+    }
+
     async fn exists(&self, id: &EmotionId) -> bool {
         self.emotions.lock().unwrap().iter().any(|stored_emotion| &stored_emotion.id == id)
     }
@@ -71,6 +75,17 @@ impl EmotionRepository for InMemoryEmotionRepository {
     async fn exists_with_name(&self, name: &EmotionName) -> bool {
         self.emotions.lock().unwrap().iter().any(|stored_emotion| &stored_emotion.name == name)
     }
+
+    async fn delete(&self, id: &EmotionId) -> Result<(), EmotionDeleteError> {
+        let mut lock = self.emotions.lock().unwrap();
+        if let Some(index) = lock.iter().position(|stored_emotion| stored_emotion.id == *id) {
+            lock.remove(index);
+
+            return Ok(())
+        }
+
+        Err(EmotionDeleteError::Missing)
+    }
 }
 
 #[cfg(test)]
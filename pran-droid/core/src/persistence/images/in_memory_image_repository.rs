@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use std::sync::Mutex;
 use crate::domain::images::image::{Image, ImageId};
-use crate::domain::images::image_repository::{ImageRepository, InsertError};
+use crate::domain::images::image_repository::{DeleteError, GetAllError, ImageRepository, InsertError};
 
 pub struct InMemoryImageRepository {
     images: Mutex<Vec<Image>>,
@@ -24,6 +24,10 @@ impl ImageRepository for InMemoryImageRepository {
         self.images.lock().unwrap().to_vec()
     }
 
+    async fn try_get_all(&self) -> Result<Vec<Image>, GetAllError> {
+        Ok(self.get_all().await)
+    }
+
     async fn has(&self, id: &ImageId) -> bool {
         let lock = self.images.lock().unwrap();
         lock.iter().any(|image| image.id == *id)
@@ -43,4 +47,16 @@ impl ImageRepository for InMemoryImageRepository {
 
         Ok(())
     }
+
+    async fn delete(&self, id: &ImageId) -> Result<(), DeleteError> {
+        let mut lock = match self.images.lock() {
+            Ok(lock) => lock,
+            Err(_) => return Err(DeleteError::Unexpected)
+        };
+
+        let index = lock.iter().position(|stored_image| stored_image.id == *id).ok_or(DeleteError::Missing)?;
+        lock.remove(index);
+
+        Ok(())
+    }
 }
@@ -1,4 +1,5 @@
 pub mod reactions;
 pub mod images;
 pub mod emotions;
-pub mod id_generation;
\ No newline at end of file
+pub mod id_generation;
+pub mod credentials;
\ No newline at end of file
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use std::sync::{Arc, Mutex};
 use crate::domain::reactions::reaction_definition::{ReactionDefinition, ReactionDefinitionId, ReactionTrigger};
-use crate::domain::reactions::reaction_definition_repository::{ReactionDefinitionRepository, ReactionInsertError, ReactionUpdateError};
+use crate::domain::reactions::reaction_definition_repository::{ReactionDefinitionRepository, ReactionDeleteError, ReactionGetAllError, ReactionInsertError, ReactionUpdateError};
 use crate::persistence::id_generation::id_generation::{IdGenerator, IdGeneratorInMemoryIncremental, IdGeneratorUuid};
 
 pub struct InMemoryReactionRepository {
@@ -43,7 +43,7 @@ impl ReactionDefinitionRepository for InMemoryReactionRepository {
     async fn exists_with_trigger(&self, trigger: &ReactionTrigger) -> bool {
         let lock = self.reactions.lock().unwrap();
 
-        lock.iter().any(|stored_reaction| stored_reaction.triggers.iter().any(|stored_trigger| stored_trigger == trigger))
+        lock.iter().any(|stored_reaction| stored_reaction.triggers.iter().any(|stored_trigger| stored_trigger.conflicts_with(trigger)))
     }
 
     async fn other_exists_with_trigger(&self, trigger: &ReactionTrigger, excluded_reaction_definition_id: &ReactionDefinitionId) -> bool {
@@ -51,7 +51,7 @@ impl ReactionDefinitionRepository for InMemoryReactionRepository {
 
         lock.iter()
             .filter(|stored_reaction| &stored_reaction.id != excluded_reaction_definition_id)
-            .any(|stored_reaction| stored_reaction.triggers.iter().any(|stored_trigger| stored_trigger == trigger))
+            .any(|stored_reaction| stored_reaction.triggers.iter().any(|stored_trigger| stored_trigger.conflicts_with(trigger)))
     }
 
     async fn get(&self, id: &ReactionDefinitionId) -> Option<ReactionDefinition> {
@@ -62,6 +62,10 @@ impl ReactionDefinitionRepository for InMemoryReactionRepository {
         self.reactions.lock().unwrap().to_vec()
     }
 
+    async fn try_get_all(&self) -> Result<Vec<ReactionDefinition>, ReactionGetAllError> {
+        Ok(self.get_all().await)
+    }
+
     async fn update(&self, reaction: &ReactionDefinition) -> Result<(), ReactionUpdateError> {
         let mut lock = self.reactions.lock().unwrap();
         if let Some(index) = lock.iter().position(|stored_reaction| stored_reaction.id == reaction.id) {
@@ -73,6 +77,17 @@ impl ReactionDefinitionRepository for InMemoryReactionRepository {
 
         Err(ReactionUpdateError::Missing)
     }
+
+    async fn delete(&self, id: &ReactionDefinitionId) -> Result<(), ReactionDeleteError> {
+        let mut lock = self.reactions.lock().unwrap();
+        if let Some(index) = lock.iter().position(|stored_reaction| stored_reaction.id == *id) {
+            lock.remove(index);
+
+            return Ok(())
+        }
+
+        Err(ReactionDeleteError::Missing)
+    }
 }
 
 #[cfg(test)]
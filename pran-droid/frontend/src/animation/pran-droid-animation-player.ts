@@ -14,6 +14,10 @@ export class PranDroidAnimationPlayer {
     this._playerController = playerController;
   }
 
+  public loadImages(images: [id: string, url: string][]): Promise<void> {
+    return this._animatorManager.loadImages(images);
+  }
+
   public async play(run: AnimationRun): Promise<void> {
     this._lastRun?.stop();
     this._playerController.stop();
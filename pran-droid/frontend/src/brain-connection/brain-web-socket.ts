@@ -1,3 +1,4 @@
+import { EmotionDefinition } from '../droid/droid-builder';
 import { CompositeTalkingReaction, ReactionType, TalkingReaction } from '../droid/reaction';
 import { PranDroidSkip } from '../droid/skip';
 
@@ -12,6 +13,31 @@ export interface BrainMovingReaction {
 
 export type DroidBrainReaction = { steps: (BrainMovingReaction | TalkingReaction | CompositeTalkingReaction)[] };
 
+// Reactions carrying the emotions and images the overlay did not receive yet, or that changed since
+export type ResolvedDroidBrainReaction = DroidBrainReaction & {
+  emotions: (EmotionDefinition & { hash: string })[],
+  images: { hash: string, id: string, url: string }[]
+};
+
+const PROTOCOL_VERSION: number = 1;
+
+type BrainEvent =
+  | { type: 'Reaction', reactionId: number, reaction: DroidBrainReaction }
+  | { type: 'ResolvedReaction', reactionId: number, reaction: ResolvedDroidBrainReaction }
+  | { type: 'Status', status: { overlays: string[], playingReactionId: number | null } }
+  | { type: 'Catalogue', catalogue: unknown }
+  | { type: 'ConfigChanged', catalogue: unknown }
+  | { type: 'Pong', nonce: number | null }
+  | { type: 'Error', message: string }
+  | { type: 'RecentReactions', reactions: { reactionId: number, reaction: DroidBrainReaction, startedMsAgo: number }[] };
+
+type OverlayMessage =
+  | { type: 'Hello', name: string, capabilities: string[], channels?: string[], token?: string }
+  | { type: 'ReactionStarted', reactionId: number }
+  | { type: 'ReactionFinished', reactionId: number }
+  | { type: 'RequestCatalogue' }
+  | { type: 'Ping', nonce: number | null };
+
 class WebSocketConnectionTool {
   private static readonly DELAY_TIME: number = 10000;
   private readonly _init: () => void;
@@ -31,22 +57,42 @@ class WebSocketConnectionTool {
   }
 }
 
+// The overlay page can be pointed to a remote brain with ?brain=wss://host:port&channel=scene&token=secret&replay=5
+function brainWebSocketUrl(): string {
+  const parameters = new URLSearchParams(window.location.search);
+  const url = new URL(parameters.get('brain') || 'ws://localhost:8080');
+  const channel = parameters.get('channel');
+  const token = parameters.get('token');
+  if (channel) {
+    url.pathname = `/${channel}`;
+  }
+  if (token) {
+    url.searchParams.set('token', token);
+  }
+  const replay = parameters.get('replay');
+  if (replay) {
+    url.searchParams.set('replay', replay);
+  }
+
+  return url.toString();
+}
+
 export class BrainWebSocket {
   private _websocket: WebSocket;
   private _connectionTool: WebSocketConnectionTool;
 
-  constructor(onReaction: (reaction: DroidBrainReaction) => unknown) {
-    this._connectionTool = new WebSocketConnectionTool(() => this._initConnection(onReaction));
+  constructor(onReaction: (reaction: DroidBrainReaction) => unknown, onResolvedReaction: (reaction: ResolvedDroidBrainReaction) => unknown) {
+    this._connectionTool = new WebSocketConnectionTool(() => this._initConnection(onReaction, onResolvedReaction));
     this._connectionTool.connect();
   }
 
-  private _initConnection(onReaction: (reaction: DroidBrainReaction) => unknown) {
+  private _initConnection(onReaction: (reaction: DroidBrainReaction) => unknown, onResolvedReaction: (reaction: ResolvedDroidBrainReaction) => unknown) {
     console.log('Connecting to websocket');
-    this._websocket = new WebSocket('ws://localhost:8080');
+    this._websocket = new WebSocket(brainWebSocketUrl());
 
     this._websocket.addEventListener('open', () => {
       console.log('Connected to websocket');
-      this._websocket.send('Hello Server!');
+      this._send({ type: 'Hello', name: 'droid', capabilities: ['Reactions', 'ResolvedReactions'] });
     });
 
     this._websocket.addEventListener('close', () => {
@@ -56,12 +102,35 @@ export class BrainWebSocket {
 
     this._websocket.addEventListener('message', function(event) {
       try {
-        let reaction = JSON.parse(event.data);
-        console.log('Input received from websocket', reaction);
-        onReaction(reaction);
+        let brainEvent: { version: number } & BrainEvent = JSON.parse(event.data);
+        console.log('Input received from websocket', brainEvent);
+        if (brainEvent.version !== PROTOCOL_VERSION) {
+          console.error(`Unsupported brain protocol version ${brainEvent.version}`);
+          return;
+        }
+
+        switch (brainEvent.type) {
+          case 'Reaction':
+            onReaction(brainEvent.reaction);
+            break;
+          case 'ResolvedReaction':
+            onResolvedReaction(brainEvent.reaction);
+            break;
+          // Finished reactions come oldest first and before the playing one, which the brain sends already cut to what is left of it
+          case 'RecentReactions':
+            brainEvent.reactions.forEach(recentReaction => onReaction(recentReaction.reaction));
+            break;
+          case 'Error':
+            console.error('Error received from the brain', brainEvent.message);
+            break;
+        }
       } catch (e) {
         console.error("Message received from websocket, error occurred", e);
       }
     });
   }
+
+  private _send(message: OverlayMessage): void {
+    this._websocket.send(JSON.stringify({ version: PROTOCOL_VERSION, ...message }));
+  }
 }
\ No newline at end of file
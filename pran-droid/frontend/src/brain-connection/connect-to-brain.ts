@@ -1,7 +1,23 @@
+import { buildEmotionRange } from '../droid/droid-builder';
 import { PranDroid } from '../droid/droid';
 import { BrainWebSocket } from './brain-web-socket';
 import { reactionToSteps } from './response-parsers';
 
 export function connectToBrain(pranDroid: PranDroid) {
-  new BrainWebSocket(reaction => pranDroid.react(reactionToSteps(reaction)));
-}
\ No newline at end of file
+  // Definitions of a resolved reaction load before it is queued, so reactions are chained to keep their order
+  let lastReaction: Promise<unknown> = Promise.resolve();
+  const queue = (step: () => unknown) => lastReaction = lastReaction
+    .then(step)
+    .catch(e => console.error('Could not queue the reaction from the brain', e));
+
+  new BrainWebSocket(
+    reaction => queue(() => pranDroid.react(reactionToSteps(reaction))),
+    reaction => queue(async () => {
+      await pranDroid.updateDefinitions(
+        buildEmotionRange(reaction.emotions),
+        reaction.images.map(image => [image.id, image.url] as [string, string])
+      );
+      pranDroid.react(reactionToSteps(reaction));
+    })
+  );
+}
@@ -0,0 +1,58 @@
+import { ReactionType } from '../droid/reaction';
+import { SkipType } from '../droid/skip';
+import { DroidBrainReaction } from './brain-web-socket';
+import { reactionToSteps } from './response-parsers';
+
+describe('response-parsers', () => {
+  describe('reactionToSteps', () => {
+    it('should map talking steps', () => {
+      const brainReaction: DroidBrainReaction = {
+        steps: [
+          { type: ReactionType.Talking, emotion: 'happy', phonemes: ['HH', 'AH0'], bubble: 'hello', skip: { type: SkipType.AfterStep, extraMs: 100 } }
+        ]
+      };
+
+      expect(reactionToSteps(brainReaction)).toEqual([
+        { type: ReactionType.Talking, emotion: 'happy', phonemes: ['HH', 'AH0'], mouthTrack: undefined, bubble: 'hello', skip: { type: SkipType.AfterStep, extraMs: 100 } }
+      ]);
+    });
+
+    it('should map composite talking steps to their talking parts', () => {
+      const brainReaction: DroidBrainReaction = {
+        steps: [
+          {
+            type: ReactionType.CompositeTalking,
+            reactions: [
+              { type: ReactionType.Talking, emotion: 'happy', phonemes: ['HH'], bubble: 'hello ' },
+              {
+                type: ReactionType.Talking,
+                emotion: 'sad',
+                phonemes: ['B'],
+                mouthTrack: [{ position: 'b', startMs: 0, durationMs: 70 }],
+                bubble: 'bye',
+                skip: { type: SkipType.AfterTime, ms: 500 }
+              }
+            ]
+          }
+        ]
+      };
+
+      expect(reactionToSteps(brainReaction)).toEqual([
+        {
+          type: ReactionType.CompositeTalking,
+          reactions: [
+            { type: ReactionType.Talking, emotion: 'happy', phonemes: ['HH'], mouthTrack: undefined, bubble: 'hello ', skip: undefined },
+            {
+              type: ReactionType.Talking,
+              emotion: 'sad',
+              phonemes: ['B'],
+              mouthTrack: [{ position: 'b', startMs: 0, durationMs: 70 }],
+              bubble: 'bye',
+              skip: { type: SkipType.AfterTime, ms: 500 }
+            }
+          ]
+        }
+      ]);
+    });
+  });
+});
@@ -16,19 +16,29 @@ export function reactionToSteps(brainReaction: DroidBrainReaction): PranDroidRea
           skip: step.skip
         };
       case ReactionType.Talking:
+        return getTalkingReaction(step);
+      case ReactionType.CompositeTalking:
         return {
-          type: ReactionType.Talking,
-          emotion: step.emotion,
-          phonemes: step.phonemes,
-          bubble: step.bubble,
-          skip: step.skip
-        } as TalkingReaction;
+          type: ReactionType.CompositeTalking,
+          reactions: step.reactions.map(getTalkingReaction)
+        };
       default:
-        throw new Error("unhandled step type " + step.type);
+        throw new Error("unhandled step type " + (step as { type: string }).type);
     }
   });
 }
 
+function getTalkingReaction(step: TalkingReaction): TalkingReaction {
+  return {
+    type: ReactionType.Talking,
+    emotion: step.emotion,
+    phonemes: step.phonemes,
+    mouthTrack: step.mouthTrack,
+    bubble: step.bubble,
+    skip: step.skip
+  };
+}
+
 function getAnimation(animation: BrainAnimation): AnimationRun {
   return StepAnimationRun.animating(SingleAnimationStepper.create({
     fps: 60,
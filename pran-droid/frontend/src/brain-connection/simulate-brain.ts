@@ -1,8 +1,14 @@
 import { PranDroid } from '../droid/droid';
+import { DroidBrainReaction } from './brain-web-socket';
 import { reactionToSteps } from './response-parsers';
 
-export async function simulateBrainMessage(pranDroid: PranDroid, message: string) {
-  const reaction = await fetch(
+interface BrainSimulation {
+  reaction: DroidBrainReaction | null;
+  chatMessages: { text: string, replyToMessageId: string | null }[];
+}
+
+export async function simulateBrainMessage(pranDroid: PranDroid, message: string): Promise<string[]> {
+  const simulation: BrainSimulation = await fetch(
     `/api/brain/simulation/message`,
     {
       method: 'POST',
@@ -11,13 +17,11 @@ export async function simulateBrainMessage(pranDroid: PranDroid, message: string
     })
     .then(x => x.json());
 
-  if (!!reaction) {
-    pranDroid.react(reactionToSteps(reaction));
-  }
+  return playSimulation(pranDroid, simulation);
 }
 
-export async function simulateBrainRedeem(pranDroid: PranDroid, id: string) {
-  const reaction = await fetch(
+export async function simulateBrainRedeem(pranDroid: PranDroid, id: string): Promise<string[]> {
+  const simulation: BrainSimulation = await fetch(
     `/api/brain/simulation/action`,
     {
       method: 'POST',
@@ -26,7 +30,14 @@ export async function simulateBrainRedeem(pranDroid: PranDroid, id: string) {
     })
     .then(x => x.json());
 
-  if (!!reaction) {
-    pranDroid.react(reactionToSteps(reaction));
+  return playSimulation(pranDroid, simulation);
+}
+
+// Returns the messages the droid would have sent to the stream chat
+function playSimulation(pranDroid: PranDroid, simulation: BrainSimulation): string[] {
+  if (!!simulation.reaction && simulation.reaction.steps.length > 0) {
+    pranDroid.react(reactionToSteps(simulation.reaction));
   }
-}
\ No newline at end of file
+
+  return simulation.chatMessages.map(message => message.text);
+}
@@ -9,7 +9,7 @@ import { animationToTimelineActions } from '../helpers/animation-to-timeline-act
 import { retryFetch } from '../helpers/retry-fetch';
 import { SpeechBubble } from '../speech-bubble/speech-bubble';
 import { PranDroid } from './droid';
-import { ConfigurableEmotion, EmotionLayer } from './emotion';
+import { ConfigurableEmotion, Emotion, EmotionLayer } from './emotion';
 
 export async function buildDroid(pranCanvas: Container, speechBubble: SpeechBubble): Promise<PranDroid> {
   const animationPlayer = await setupPranDroidAnimation(pranCanvas);
@@ -41,15 +41,21 @@ async function setupPranDroidAnimation(pranCanvas: Container): Promise<PranDroid
   return new PranDroidAnimationPlayer(animator, animatorManager, playerController);
 }
 
+export interface EmotionDefinition {
+  id: string;
+  name: string;
+  layers: ({ type: 'Mouth', mouthMapping: { [key: string]: string } } | { type: 'Animation', frames: { frameStart: number, frameEnd: number, imageId: string }[]})[];
+}
+
 async function setupEmotions(pranDroid: PranDroid): Promise<void> {
-  const emotions: {
-    id: string,
-    name: string,
-    layers: ({ type: 'Mouth', mouthMapping: { [key: string]: string } } | { type: 'Animation', frames: { frameStart: number, frameEnd: number, imageId: string }[]})[],
-  }[] = (await retryFetch("/api/emotions").then(r => r.json())).data;
+  const emotions: EmotionDefinition[] = (await retryFetch("/api/emotions").then(r => r.json())).data;
   console.log("Emotions", emotions);
 
-  pranDroid.setEmotionRange(emotions.reduce((acc, emotion) => {
+  pranDroid.setEmotionRange(buildEmotionRange(emotions));
+}
+
+export function buildEmotionRange(emotions: EmotionDefinition[]): { [emotion: string]: Emotion } {
+  return emotions.reduce((acc, emotion) => {
     acc[emotion.id] = new ConfigurableEmotion(emotion.layers.map(layer => {
       switch (layer.type) {
         case 'Mouth':
@@ -60,7 +66,7 @@ async function setupEmotions(pranDroid: PranDroid): Promise<void> {
     }));
 
     return acc;
-  }, {}));
+  }, {});
 }
 
 // Temporary idle animation, this is going to come from the API when the feature has been added
@@ -28,6 +28,12 @@ export class PranDroid {
     this._emotionRange = emotionRange;
   }
 
+  // Emotions and images can change while the droid runs, the new ones are used from the next reaction
+  public async updateDefinitions(emotions: { [emotion: string]: Emotion }, images: [id: string, url: string][]): Promise<void> {
+    await this._animationPlayer.loadImages(images);
+    this._emotionRange = { ...this._emotionRange, ...emotions };
+  }
+
   public start(): void {
     this._stayIdling();
     this._isReacting = false;
@@ -82,7 +88,7 @@ export class PranDroid {
   private async _executeTalkReaction(reaction: TalkingReaction) {
     const emotion = this._getEmotion(reaction.emotion);
     const speechResult = this._showBubble(reaction);
-    const animationExecution = this._animationPlayer.play(emotion.speak(reaction.phonemes, speechResult.durationMs));
+    const animationExecution = this._animationPlayer.play(emotion.speak(reaction.phonemes, speechResult.durationMs, reaction.mouthTrack));
     await this._waitReactionTime(reaction, speechResult, animationExecution);
   }
 
@@ -3,6 +3,7 @@ import { cmuPhonemesMap, MapOutput, phonemesMapper } from 'pran-phonemes-fronten
 import { AnimationRun } from '../animation/run/animation-run';
 import { StepAnimationRun } from '../animation/run/step/step-animation-run';
 import { SingleAnimationStepper } from '../animation/run/step/stepper/single-animation-stepper';
+import { MouthTrackPosition } from './reaction';
 
 export const enum EmotionLayer {
   Mouth,
@@ -12,7 +13,7 @@ export const enum EmotionLayer {
 export type EmotionLayers = ({ type: EmotionLayer.Mouth, mouthMapping?: { [key: string]: string } } | { type: EmotionLayer.Animation, animation: () => ManagerTimelineAction[] })[];
 
 export interface Emotion {
-  speak(phonemes: string[], durationMs: number): AnimationRun;
+  speak(phonemes: string[], durationMs: number, mouthTrack?: MouthTrackPosition[]): AnimationRun;
 }
 
 export class ConfigurableEmotion implements Emotion {
@@ -22,13 +23,15 @@ export class ConfigurableEmotion implements Emotion {
     this._emotionLayers = emotionLayers;
   }
 
-  public speak(phonemes: string[], durationMs: number): AnimationRun {
+  public speak(phonemes: string[], durationMs: number, mouthTrack?: MouthTrackPosition[]): AnimationRun {
     return StepAnimationRun.animating(SingleAnimationStepper.create({
       fps: 60,
       layers: this._emotionLayers.map(layer => {
         switch (layer.type) {
           case EmotionLayer.Mouth:
-            return this._createMouthLayer(phonemes, durationMs, layer.mouthMapping);
+            return mouthTrack?.length
+              ? this._createTimedMouthLayer(mouthTrack, layer.mouthMapping)
+              : this._createMouthLayer(phonemes, durationMs, layer.mouthMapping);
           case EmotionLayer.Animation:
             return { actions: layer.animation(), loop: true }
         }
@@ -36,6 +39,25 @@ export class ConfigurableEmotion implements Emotion {
     }));
   }
 
+  // The brain already timed each mouth position, frames come from its rounded boundaries so the track does not drift
+  private _createTimedMouthLayer(mouthTrack: MouthTrackPosition[], mouthMapping: { [p: string]: string } | undefined) {
+    const talkingActions: ManagerTimelineAction[] = mouthTrack.flatMap(timedPosition => {
+      const frames: number = Math.round((timedPosition.startMs + timedPosition.durationMs) * MS_TO_FRAMES) - Math.round(timedPosition.startMs * MS_TO_FRAMES);
+      if (frames === 0) {
+        return [];
+      }
+
+      const imageId = mouthMapping ? mouthMapping[timedPosition.position] : timedPosition.position;
+      const actions: ManagerTimelineAction[] = [imageId ? drawId(imageId) : clear()];
+      actions.push(wait(frames - 1));
+
+      return actions;
+    });
+    talkingActions.push(drawId(mouthMapping ? mouthMapping['idle'] : 'idle'));
+
+    return talkingActions;
+  }
+
   private _createMouthLayer(phonemes: string[], durationMs: number, mouthMapping: { [p: string]: string } | undefined) {
     const mouthMovementsMapping: MapOutput[] = phonemesMapper(phonemes, cmuPhonemesMap);
     let totalFrames: number = durationMs * MS_TO_FRAMES;
@@ -19,10 +19,17 @@ export interface CompositeTalkingReaction {
   reactions: TalkingReaction[];
 }
 
+export interface MouthTrackPosition {
+  position: string;
+  startMs: number;
+  durationMs: number;
+}
+
 export interface TalkingReaction {
   type: ReactionType.Talking;
   emotion: string;
   phonemes: string[];
+  mouthTrack?: MouthTrackPosition[];
   bubble?: string | { text: string; letterByLetter: boolean; };
   skip?: PranDroidSkip;
 }
@@ -19,7 +19,13 @@ export const previewModal = inlineComponent<ModalContentInputs<void> & { reactio
   const speechBubbleCanvas: Container = Container.CreateEmptyElement('canvas');
   const speechBubble = new SpeechBubble(speechBubbleCanvas.componentElement as HTMLCanvasElement);
   let pranDroid: PranDroid,
-    redeems: string[] = [];
+    redeems: string[] = [],
+    chatMessages: string[] = [];
+
+  function showChatMessages(messages: string[]) {
+    chatMessages = messages;
+    controls.changed();
+  }
 
   (async() => {
     pranDroid = await buildDroid(pranCanvas, speechBubble);
@@ -47,7 +53,7 @@ export const previewModal = inlineComponent<ModalContentInputs<void> & { reactio
         .el('div', 'preview-modal_rewards')
           .cmp(dropdown, {
             buttons: redeems.map(redeem => ({ id: redeem, text: redeem })),
-            onSelect: id => simulateBrainRedeem(pranDroid, id),
+            onSelect: id => simulateBrainRedeem(pranDroid, id).then(showChatMessages),
             position: 'Top'
           })
         .endEl()
@@ -55,11 +61,14 @@ export const previewModal = inlineComponent<ModalContentInputs<void> & { reactio
         .el('div', 'preview-modal_button-container')
           .el('button', 'button preview-modal_send-message-button').attr('type', 'submit').text('SEND').endEl()
         .endEl()
-      .endEl();
+      .endEl()
+      .el('div', 'preview-modal_chat');
+    chatMessages.forEach(message => r.el('span', 'preview-modal_chat-message').text(message).endEl());
+    r.endEl();
 
     return e => onClick(e, '.preview-modal_send-message-button', ev => (
       ev.preventDefault(),
-      simulateBrainMessage(pranDroid, (e.querySelector('#preview-modal_message-input') as HTMLInputElement).value),
+      simulateBrainMessage(pranDroid, (e.querySelector('#preview-modal_message-input') as HTMLInputElement).value).then(showChatMessages),
       (e.querySelector('#preview-modal_message-input') as HTMLInputElement).value = ''
     ));
   };
@@ -1,14 +1,11 @@
-use std::collections::HashMap;
 use async_trait::async_trait;
-use serde::{Serialize, Deserialize};
 use serde_json::{Map, Value};
 use uuid::Uuid;
-use pran_droid_core::domain::emotions::emotion::{EmotionId, EmotionLayer, EmotionName, MouthPositionName};
-use pran_droid_core::domain::images::image::ImageId;
+use pran_droid_core::domain::emotions::emotion::{EmotionId, EmotionName};
 use pran_droid_core::domain::emotions::emotion::{Emotion};
 use crate::deta::{Base, Deta, Query, InsertError as DetaInsertError, PutError, QueryAll};
-use pran_droid_core::domain::emotions::emotion_repository::{EmotionRepository, EmotionInsertError, EmotionUpdateError};
-use crate::animations::animation::{AnimationStorage, into_animation_domain, into_animation_storage};
+use pran_droid_core::domain::emotions::emotion_repository::{EmotionRepository, EmotionDeleteError, EmotionGetAllError, EmotionInsertError, EmotionUpdateError};
+use crate::emotions::emotion_storage::EmotionStorage;
 
 pub struct DetaEmotionRepository {
     base: Base,
@@ -31,57 +28,6 @@ impl DetaEmotionRepository {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct EmotionStorage {
-    key: String,
-    name: String,
-    layers: Vec<EmotionLayerStorage>
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-enum EmotionLayerStorage {
-    Animation(AnimationStorage),
-    Mouth { mouth_mapping: HashMap<String, String> }
-}
-
-impl Into<Emotion> for EmotionStorage {
-    fn into(self) -> Emotion {
-        Emotion {
-            id: EmotionId(self.key),
-            name: EmotionName(self.name),
-            animation: self.layers.iter().map(into_layer_domain).collect(),
-        }
-    }
-}
-
-impl From<&Emotion> for EmotionStorage {
-    fn from(emotion: &Emotion) -> Self {
-        Self {
-            key: emotion.id.0.clone(),
-            name: emotion.name.0.clone(),
-            layers: emotion.animation.iter().map(into_layer_storage).collect()
-        }
-    }
-}
-
-fn into_layer_domain(layer: &EmotionLayerStorage) -> EmotionLayer {
-    match layer {
-        EmotionLayerStorage::Animation(animation) => EmotionLayer::Animation(into_animation_domain(animation)),
-        EmotionLayerStorage::Mouth { mouth_mapping } => EmotionLayer::Mouth {
-            mouth_mapping: mouth_mapping.into_iter().map(|(pos, id)| (TryInto::<MouthPositionName>::try_into(pos).unwrap(), ImageId(id.clone()))).collect()
-        },
-    }
-}
-
-fn into_layer_storage(layer: &EmotionLayer) -> EmotionLayerStorage {
-    match layer {
-        EmotionLayer::Animation(animation) => EmotionLayerStorage::Animation(into_animation_storage(animation)),
-        EmotionLayer::Mouth { mouth_mapping } => EmotionLayerStorage::Mouth {
-            mouth_mapping: mouth_mapping.into_iter().map(|(pos, id)| (pos.into(), id.0.clone())).collect()
-        },
-    }
-}
-
 #[async_trait]
 impl EmotionRepository for DetaEmotionRepository {
     fn next_id(&self) -> EmotionId {
@@ -112,11 +58,13 @@ impl EmotionRepository for DetaEmotionRepository {
     }
 
     async fn get_all(&self) -> Vec<Emotion> {
+        self.try_get_all().await.expect("Unexpected error")
+    }
+
+    async fn try_get_all(&self) -> Result<Vec<Emotion>, EmotionGetAllError> {
         self.base.query_all::<EmotionStorage>(QueryAll::default()).await
-            .expect("Unexpected error")
-            .into_iter()
-            .map(Into::into)
-            .collect()
+            .map_err(|error| EmotionGetAllError::Unexpected(format!("{:?}", error)))
+            .map(|items| items.into_iter().map(Into::into).collect())
     }
 
     async fn exists(&self, id: &EmotionId) -> bool {
@@ -130,4 +78,13 @@ impl EmotionRepository for DetaEmotionRepository {
     async fn exists_with_name(&self, name: &EmotionName) -> bool {
         self.fetch_one_by_name(name).await.is_some()
     }
+
+    async fn delete(&self, id: &EmotionId) -> Result<(), EmotionDeleteError> {
+        if !self.exists(id).await {
+            return Err(EmotionDeleteError::Missing);
+        }
+
+        self.base.delete(id.0.as_str()).await
+            .map_err(|_| EmotionDeleteError::Unexpected)
+    }
 }
\ No newline at end of file
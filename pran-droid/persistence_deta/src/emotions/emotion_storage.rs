@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use pran_droid_core::domain::emotions::emotion::{Emotion, EmotionId, EmotionLayer, EmotionName, MouthPositionName};
+use pran_droid_core::domain::images::image::ImageId;
+use crate::animations::animation::{AnimationStorage, into_animation_domain, into_animation_storage};
+
+// Shared by every persistence, emotions are stored as this JSON document
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EmotionStorage {
+    pub key: String,
+    pub name: String,
+    layers: Vec<EmotionLayerStorage>
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+enum EmotionLayerStorage {
+    Animation(AnimationStorage),
+    Mouth { mouth_mapping: HashMap<String, String> }
+}
+
+impl From<EmotionStorage> for Emotion {
+    fn from(storage: EmotionStorage) -> Self {
+        Emotion {
+            id: EmotionId(storage.key),
+            name: EmotionName(storage.name),
+            animation: storage.layers.iter().map(into_layer_domain).collect(),
+        }
+    }
+}
+
+impl From<&Emotion> for EmotionStorage {
+    fn from(emotion: &Emotion) -> Self {
+        Self {
+            key: emotion.id.0.clone(),
+            name: emotion.name.0.clone(),
+            layers: emotion.animation.iter().map(into_layer_storage).collect()
+        }
+    }
+}
+
+fn into_layer_domain(layer: &EmotionLayerStorage) -> EmotionLayer {
+    match layer {
+        EmotionLayerStorage::Animation(animation) => EmotionLayer::Animation(into_animation_domain(animation)),
+        EmotionLayerStorage::Mouth { mouth_mapping } => EmotionLayer::Mouth {
+            mouth_mapping: mouth_mapping.iter().map(|(pos, id)| (TryInto::<MouthPositionName>::try_into(pos).unwrap(), ImageId(id.clone()))).collect()
+        },
+    }
+}
+
+fn into_layer_storage(layer: &EmotionLayer) -> EmotionLayerStorage {
+    match layer {
+        EmotionLayer::Animation(animation) => EmotionLayerStorage::Animation(into_animation_storage(animation)),
+        EmotionLayer::Mouth { mouth_mapping } => EmotionLayerStorage::Mouth {
+            mouth_mapping: mouth_mapping.iter().map(|(pos, id)| (pos.into(), id.0.clone())).collect()
+        },
+    }
+}
@@ -1 +1,2 @@
-pub mod deta_emotion_repository;
\ No newline at end of file
+pub mod deta_emotion_repository;
+pub mod emotion_storage;
\ No newline at end of file
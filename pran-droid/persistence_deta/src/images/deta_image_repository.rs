@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use serde::{Serialize, Deserialize};
 use pran_droid_core::domain::images::image::{Image, ImageId, ImageUrl};
 use crate::deta::{Base, Deta, InsertError as DetaInsertError, QueryAll};
-use pran_droid_core::domain::images::image_repository::{ImageRepository, InsertError};
+use pran_droid_core::domain::images::image_repository::{DeleteError, GetAllError, ImageRepository, InsertError};
 
 pub struct DetaImageRepository {
     base: Base
@@ -39,11 +39,13 @@ impl ImageRepository for DetaImageRepository {
     }
 
     async fn get_all(&self) -> Vec<Image> {
+        self.try_get_all().await.expect("Unexpected error")
+    }
+
+    async fn try_get_all(&self) -> Result<Vec<Image>, GetAllError> {
         self.base.query_all::<ImageStorage>(QueryAll::default()).await
-            .expect("Unexpected error")
-            .into_iter()
-            .map(Into::into)
-            .collect()
+            .map_err(|error| GetAllError::Unexpected(format!("{:?}", error)))
+            .map(|items| items.into_iter().map(Into::into).collect())
     }
 
     async fn has(&self, id: &ImageId) -> bool {
@@ -59,4 +61,13 @@ impl ImageRepository for DetaImageRepository {
             })
             .map(|_| ())
     }
+
+    async fn delete(&self, id: &ImageId) -> Result<(), DeleteError> {
+        if !self.has(id).await {
+            return Err(DeleteError::Missing);
+        }
+
+        self.base.delete(id.0.as_str()).await
+            .map_err(|_| DeleteError::Unexpected)
+    }
 }
\ No newline at end of file
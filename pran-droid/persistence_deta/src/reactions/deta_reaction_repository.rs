@@ -1,12 +1,9 @@
 use async_trait::async_trait;
-use serde::{Serialize, Deserialize};
 use uuid::Uuid;
-use pran_droid_core::domain::emotions::emotion::EmotionId;
-use pran_droid_core::domain::reactions::reaction::Milliseconds;
-use pran_droid_core::domain::reactions::reaction_definition::{ChatCommandTrigger, MovingReactionStepDefinition, ReactionDefinition, ReactionDefinitionId, ReactionStepDefinition, ReactionStepSkipDefinition, ReactionStepMessageAlternativeDefinition, ReactionStepMessageAlternativesDefinition, ReactionStepMessageDefinition, ReactionTrigger, TalkingReactionStepDefinition};
+use pran_droid_core::domain::reactions::reaction_definition::{ReactionDefinition, ReactionDefinitionId, ReactionTrigger};
 use crate::deta::{Base, Deta, Query, InsertError as DetaInsertError, PutError, QueryAll};
-use pran_droid_core::domain::reactions::reaction_definition_repository::{ReactionDefinitionRepository, ReactionInsertError, ReactionUpdateError};
-use crate::animations::animation::{AnimationStorage, into_animation_domain, into_animation_storage};
+use pran_droid_core::domain::reactions::reaction_definition_repository::{ReactionDefinitionRepository, ReactionDeleteError, ReactionGetAllError, ReactionInsertError, ReactionUpdateError};
+use crate::reactions::reaction_storage::ReactionStorage;
 
 pub struct DetaReactionRepository {
     base: Base,
@@ -19,7 +16,7 @@ impl DetaReactionRepository {
 
     fn triggers_contain(&self, triggers: Vec<ReactionTrigger>, trigger_to_search: &ReactionTrigger) -> bool {
         for trigger in triggers {
-            if &trigger == trigger_to_search {
+            if trigger.conflicts_with(trigger_to_search) {
                 return true
             }
         }
@@ -28,178 +25,6 @@ impl DetaReactionRepository {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ReactionStorage {
-    key: String,
-    triggers: Vec<ReactionTriggerStorage>,
-    steps: Vec<ReactionStepStorage>,
-    is_disabled: bool,
-    count: u32,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(tag = "type")]
-enum ReactionTriggerStorage {
-    #[serde(rename = "chat_command")]
-    ChatCommand { command: String },
-    #[serde(rename = "chat_keyword")]
-    ChatKeyword { command: String },
-    #[serde(rename = "action")]
-    Action { id: String, name: String },
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-enum ReactionStepStorage {
-    Moving { animation: AnimationStorage, skip: ReactionSkipStorage },
-    Talking { emotion_id: String, skip: ReactionSkipStorage, alternatives: Vec<ReactionStepMessageAlternativeStorage> },
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ReactionStepMessageAlternativeStorage {
-    probability: Option<f32>,
-    message: ReactionStepMessageStorage
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(tag = "type")]
-enum ReactionStepMessageStorage {
-    #[serde(rename = "instant")]
-    Instant { text: String },
-    #[serde(rename = "letter_by_letter")]
-    LetterByLetter { text: String }
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-enum ReactionSkipStorage {
-    ImmediatelyAfter,
-    AfterMilliseconds(u16),
-    AfterStepWithExtraMilliseconds(u16)
-}
-
-impl Into<ReactionDefinition> for ReactionStorage {
-    fn into(self) -> ReactionDefinition { (&self).into() }
-}
-
-impl From<&ReactionStorage> for ReactionDefinition {
-    fn from(storage: &ReactionStorage) -> ReactionDefinition {
-        ReactionDefinition {
-            id: ReactionDefinitionId(storage.key.clone()),
-            steps: storage.steps.iter().map(into_step_domain).collect(),
-            triggers: storage.triggers.iter().map(into_trigger_domain).collect(),
-            is_disabled: storage.is_disabled,
-            count: storage.count
-        }
-    }
-}
-
-impl From<&ReactionDefinition> for ReactionStorage {
-    fn from(reaction: &ReactionDefinition) -> Self {
-        Self {
-            key: reaction.id.0.clone(),
-            triggers: reaction.triggers.iter().map(into_trigger_storage).collect(),
-            steps: reaction.steps.iter().map(into_step_storage).collect(),
-            is_disabled: reaction.is_disabled,
-            count: reaction.count,
-        }
-    }
-}
-
-fn into_trigger_storage(trigger: &ReactionTrigger) -> ReactionTriggerStorage {
-    match trigger {
-        ReactionTrigger::ChatCommand(chat_command) => ReactionTriggerStorage::ChatCommand { command: chat_command.text.clone() },
-        ReactionTrigger::ChatKeyword(chat_keyword) => ReactionTriggerStorage::ChatKeyword { command: chat_keyword.text.clone() },
-        ReactionTrigger::Action(action_trigger) => ReactionTriggerStorage::Action { id: action_trigger.id.clone(), name: action_trigger.name.clone() },
-    }
-}
-
-fn into_trigger_domain(trigger: &ReactionTriggerStorage) -> ReactionTrigger {
-    match trigger {
-        ReactionTriggerStorage::ChatCommand { command } => ReactionTrigger::ChatCommand(ChatCommandTrigger { text: command.clone() }),
-        ReactionTriggerStorage::ChatKeyword { command } => ReactionTrigger::new_chat_keyword(command.clone()).unwrap(),
-        ReactionTriggerStorage::Action { id, name } => ReactionTrigger::new_action(id.clone(), name.clone()).unwrap(),
-    }
-}
-
-fn into_step_domain(step: &ReactionStepStorage) -> ReactionStepDefinition {
-    match step {
-        ReactionStepStorage::Moving { skip, animation } => ReactionStepDefinition::Moving(MovingReactionStepDefinition {
-            skip: into_skip_domain(skip),
-            animation: into_animation_domain(animation)
-        }),
-        ReactionStepStorage::Talking { skip, emotion_id, alternatives: text } => ReactionStepDefinition::Talking(TalkingReactionStepDefinition {
-            skip: into_skip_domain(skip),
-            emotion_id: EmotionId(emotion_id.clone()),
-            alternatives: into_text_alternatives_domain(text)
-        })
-    }
-}
-
-fn into_step_storage(step: &ReactionStepDefinition) -> ReactionStepStorage {
-    match step {
-        ReactionStepDefinition::Moving(moving) => ReactionStepStorage::Moving {
-            skip: into_skip_storage(&moving.skip),
-            animation: into_animation_storage(&moving.animation)
-        },
-        ReactionStepDefinition::Talking(talking) => ReactionStepStorage::Talking {
-            skip: into_skip_storage(&talking.skip),
-            emotion_id: talking.emotion_id.0.clone(),
-            alternatives: into_text_alternatives_storage(&talking.alternatives)
-        },
-        ReactionStepDefinition::CompositeTalking(_) => todo!("Implement when CompositeTalking is done")
-    }
-}
-
-fn into_skip_storage(skip: &ReactionStepSkipDefinition) -> ReactionSkipStorage {
-    match skip {
-        ReactionStepSkipDefinition::ImmediatelyAfter => ReactionSkipStorage::ImmediatelyAfter,
-        ReactionStepSkipDefinition::AfterMilliseconds(ms) => ReactionSkipStorage::AfterMilliseconds(ms.0),
-        ReactionStepSkipDefinition::AfterStepWithExtraMilliseconds(ms) => ReactionSkipStorage::AfterStepWithExtraMilliseconds(ms.0)
-    }
-}
-
-fn into_skip_domain(skip: &ReactionSkipStorage) -> ReactionStepSkipDefinition {
-    match skip {
-        ReactionSkipStorage::ImmediatelyAfter => ReactionStepSkipDefinition::ImmediatelyAfter,
-        ReactionSkipStorage::AfterMilliseconds(ms) => ReactionStepSkipDefinition::AfterMilliseconds(Milliseconds(*ms)),
-        ReactionSkipStorage::AfterStepWithExtraMilliseconds(ms) => ReactionStepSkipDefinition::AfterStepWithExtraMilliseconds(Milliseconds(*ms))
-    }
-}
-
-fn into_text_alternatives_storage(alternatives: &ReactionStepMessageAlternativesDefinition) -> Vec<ReactionStepMessageAlternativeStorage> {
-    alternatives.0
-        .iter()
-        .map(|alternative| ReactionStepMessageAlternativeStorage {
-            message: into_text_storage(&alternative.message),
-            probability: alternative.probability
-        })
-        .collect()
-}
-
-fn into_text_storage(text: &ReactionStepMessageDefinition) -> ReactionStepMessageStorage {
-    match text {
-        ReactionStepMessageDefinition::Instant(text) => ReactionStepMessageStorage::Instant { text: text.clone() },
-        ReactionStepMessageDefinition::LetterByLetter(text) => ReactionStepMessageStorage::LetterByLetter { text: text.clone() },
-    }
-}
-
-fn into_text_domain(text: &ReactionStepMessageStorage) -> ReactionStepMessageDefinition {
-    match text {
-        ReactionStepMessageStorage::Instant { text } => ReactionStepMessageDefinition::Instant(text.clone()),
-        ReactionStepMessageStorage::LetterByLetter { text } => ReactionStepMessageDefinition::LetterByLetter(text.clone()),
-    }
-}
-
-fn into_text_alternatives_domain(alternatives: &Vec<ReactionStepMessageAlternativeStorage>) -> ReactionStepMessageAlternativesDefinition {
-    ReactionStepMessageAlternativesDefinition(alternatives
-        .iter()
-        .map(|alternative| ReactionStepMessageAlternativeDefinition {
-            message: into_text_domain(&alternative.message),
-            probability: alternative.probability
-        })
-        .collect()
-    )
-}
-
 #[async_trait]
 impl ReactionDefinitionRepository for DetaReactionRepository {
     fn next_id(&self) -> ReactionDefinitionId {
@@ -246,11 +71,13 @@ impl ReactionDefinitionRepository for DetaReactionRepository {
     }
 
     async fn get_all(&self) -> Vec<ReactionDefinition> {
+        self.try_get_all().await.expect("Unexpected error")
+    }
+
+    async fn try_get_all(&self) -> Result<Vec<ReactionDefinition>, ReactionGetAllError> {
         self.base.query_all::<ReactionStorage>(QueryAll::default()).await
-            .expect("Unexpected error")
-            .into_iter()
-            .map(Into::into)
-            .collect()
+            .map_err(|error| ReactionGetAllError::Unexpected(format!("{:?}", error)))
+            .map(|items| items.into_iter().map(Into::into).collect())
     }
 
     async fn update(&self, reaction: &ReactionDefinition) -> Result<(), ReactionUpdateError> {
@@ -261,4 +88,13 @@ impl ReactionDefinitionRepository for DetaReactionRepository {
             })
             .map(|_| ())
     }
+
+    async fn delete(&self, id: &ReactionDefinitionId) -> Result<(), ReactionDeleteError> {
+        if self.base.get::<ReactionStorage>(id.0.as_str()).await.is_err() {
+            return Err(ReactionDeleteError::Missing);
+        }
+
+        self.base.delete(id.0.as_str()).await
+            .map_err(|_| ReactionDeleteError::Unexpected)
+    }
 }
\ No newline at end of file
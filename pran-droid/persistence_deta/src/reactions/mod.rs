@@ -1 +1,2 @@
-pub mod deta_reaction_repository;
\ No newline at end of file
+pub mod deta_reaction_repository;
+pub mod reaction_storage;
\ No newline at end of file
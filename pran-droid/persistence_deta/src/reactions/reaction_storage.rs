@@ -0,0 +1,394 @@
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use pran_droid_core::domain::emotions::emotion::EmotionId;
+use pran_droid_core::domain::reactions::reaction::Milliseconds;
+use pran_droid_core::domain::reactions::reaction_definition::{ChatMessageReactionStepDefinition, ChatTriggerMatchOptions, MovingReactionStepDefinition, OverlayTarget, ReactionCondition, ReactionCooldown, ReactionDefinition, ReactionDefinitionId, ReactionPermissionLevel, ReactionStepDefinition, ReactionStepSkipDefinition, ReactionStepMessageAlternativeDefinition, ReactionStepMessageAlternativesDefinition, ReactionStepMessageDefinition, ReactionTrigger, StreamEventKind, TalkingReactionStepDefinition};
+use crate::animations::animation::{AnimationStorage, into_animation_domain, into_animation_storage};
+
+// Shared by every persistence, reactions are stored as this JSON document
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReactionStorage {
+    pub key: String,
+    triggers: Vec<ReactionTriggerStorage>,
+    steps: Vec<ReactionStepStorage>,
+    is_disabled: bool,
+    count: u32,
+    #[serde(default)]
+    cooldown: ReactionCooldownStorage,
+    #[serde(default)]
+    permission_level: ReactionPermissionLevelStorage,
+    #[serde(default)]
+    overlay_targets: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ReactionCooldownStorage {
+    global_ms: u64,
+    per_user_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ChatTriggerMatchOptionsStorage {
+    case_sensitive: bool,
+    punctuation_as_boundary: bool,
+    normalise_unicode: bool,
+}
+
+impl Default for ChatTriggerMatchOptionsStorage {
+    fn default() -> Self {
+        into_match_options_storage(&ChatTriggerMatchOptions::default())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+enum ReactionPermissionLevelStorage {
+    #[default]
+    #[serde(rename = "everyone")]
+    Everyone,
+    #[serde(rename = "subscribers")]
+    Subscribers,
+    #[serde(rename = "vips")]
+    Vips,
+    #[serde(rename = "mods")]
+    Mods,
+    #[serde(rename = "broadcaster")]
+    Broadcaster,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum ReactionTriggerStorage {
+    #[serde(rename = "chat_command")]
+    ChatCommand { command: String, #[serde(default)] options: ChatTriggerMatchOptionsStorage },
+    #[serde(rename = "chat_keyword")]
+    ChatKeyword { command: String, #[serde(default)] options: ChatTriggerMatchOptionsStorage },
+    #[serde(rename = "chat_pattern")]
+    ChatPattern { pattern: String },
+    #[serde(rename = "action")]
+    Action { id: String, name: String },
+    #[serde(rename = "action_range")]
+    ActionRange { name: String, min: u32, max: Option<u32> },
+    #[serde(rename = "stream_event")]
+    StreamEvent { kind: StreamEventKindStorage },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum StreamEventKindStorage {
+    #[serde(rename = "subscription")]
+    Subscription,
+    #[serde(rename = "resubscription")]
+    Resubscription,
+    #[serde(rename = "gift_subscription")]
+    GiftSubscription,
+    #[serde(rename = "mystery_gift")]
+    MysteryGift,
+    #[serde(rename = "raid")]
+    Raid,
+    #[serde(rename = "follow")]
+    Follow,
+    #[serde(rename = "hype_train_start")]
+    HypeTrainStart,
+    #[serde(rename = "hype_train_level_up")]
+    HypeTrainLevelUp,
+    #[serde(rename = "hype_train_end")]
+    HypeTrainEnd,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum ReactionStepStorage {
+    Moving { animation: AnimationStorage, skip: ReactionSkipStorage },
+    Talking { emotion_id: String, skip: ReactionSkipStorage, alternatives: Vec<ReactionStepMessageAlternativeStorage> },
+    CompositeTalking { parts: Vec<TalkingReactionStepPartStorage> },
+    ChatMessage { alternatives: Vec<ReactionStepMessageAlternativeStorage>, #[serde(default)] reply: bool },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TalkingReactionStepPartStorage {
+    emotion_id: String,
+    skip: ReactionSkipStorage,
+    alternatives: Vec<ReactionStepMessageAlternativeStorage>
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ReactionStepMessageAlternativeStorage {
+    probability: Option<f32>,
+    message: ReactionStepMessageStorage,
+    #[serde(default)]
+    conditions: Vec<ReactionConditionStorage>
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum ReactionConditionStorage {
+    #[serde(rename = "count_multiple_of")]
+    CountMultipleOf { count: u32 },
+    #[serde(rename = "count_equals")]
+    CountEquals { count: u32 },
+    #[serde(rename = "minimum_permission_level")]
+    MinimumPermissionLevel { permission_level: ReactionPermissionLevelStorage },
+    #[serde(rename = "has_target")]
+    HasTarget,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum ReactionStepMessageStorage {
+    #[serde(rename = "instant")]
+    Instant { text: String },
+    #[serde(rename = "letter_by_letter")]
+    LetterByLetter { text: String }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum ReactionSkipStorage {
+    ImmediatelyAfter,
+    AfterMilliseconds(u16),
+    AfterStepWithExtraMilliseconds(u16)
+}
+
+impl From<ReactionStorage> for ReactionDefinition {
+    fn from(storage: ReactionStorage) -> ReactionDefinition { (&storage).into() }
+}
+
+impl From<&ReactionStorage> for ReactionDefinition {
+    fn from(storage: &ReactionStorage) -> ReactionDefinition {
+        ReactionDefinition {
+            id: ReactionDefinitionId(storage.key.clone()),
+            steps: storage.steps.iter().map(into_step_domain).collect(),
+            triggers: storage.triggers.iter().map(into_trigger_domain).collect(),
+            is_disabled: storage.is_disabled,
+            count: storage.count,
+            cooldown: ReactionCooldown {
+                global: Duration::from_millis(storage.cooldown.global_ms),
+                per_user: Duration::from_millis(storage.cooldown.per_user_ms),
+            },
+            permission_level: into_permission_level_domain(&storage.permission_level),
+            overlay_targets: storage.overlay_targets.iter().map(|target| OverlayTarget(target.clone())).collect(),
+        }
+    }
+}
+
+impl From<&ReactionDefinition> for ReactionStorage {
+    fn from(reaction: &ReactionDefinition) -> Self {
+        Self {
+            key: reaction.id.0.clone(),
+            triggers: reaction.triggers.iter().map(into_trigger_storage).collect(),
+            steps: reaction.steps.iter().map(into_step_storage).collect(),
+            is_disabled: reaction.is_disabled,
+            count: reaction.count,
+            cooldown: ReactionCooldownStorage {
+                global_ms: reaction.cooldown.global.as_millis() as u64,
+                per_user_ms: reaction.cooldown.per_user.as_millis() as u64,
+            },
+            permission_level: into_permission_level_storage(&reaction.permission_level),
+            overlay_targets: reaction.overlay_targets.iter().map(|target| target.0.clone()).collect(),
+        }
+    }
+}
+
+fn into_permission_level_storage(permission_level: &ReactionPermissionLevel) -> ReactionPermissionLevelStorage {
+    match permission_level {
+        ReactionPermissionLevel::Everyone => ReactionPermissionLevelStorage::Everyone,
+        ReactionPermissionLevel::Subscribers => ReactionPermissionLevelStorage::Subscribers,
+        ReactionPermissionLevel::Vips => ReactionPermissionLevelStorage::Vips,
+        ReactionPermissionLevel::Mods => ReactionPermissionLevelStorage::Mods,
+        ReactionPermissionLevel::Broadcaster => ReactionPermissionLevelStorage::Broadcaster,
+    }
+}
+
+fn into_permission_level_domain(permission_level: &ReactionPermissionLevelStorage) -> ReactionPermissionLevel {
+    match permission_level {
+        ReactionPermissionLevelStorage::Everyone => ReactionPermissionLevel::Everyone,
+        ReactionPermissionLevelStorage::Subscribers => ReactionPermissionLevel::Subscribers,
+        ReactionPermissionLevelStorage::Vips => ReactionPermissionLevel::Vips,
+        ReactionPermissionLevelStorage::Mods => ReactionPermissionLevel::Mods,
+        ReactionPermissionLevelStorage::Broadcaster => ReactionPermissionLevel::Broadcaster,
+    }
+}
+
+fn into_match_options_storage(options: &ChatTriggerMatchOptions) -> ChatTriggerMatchOptionsStorage {
+    ChatTriggerMatchOptionsStorage {
+        case_sensitive: options.case_sensitive,
+        punctuation_as_boundary: options.punctuation_as_boundary,
+        normalise_unicode: options.normalise_unicode,
+    }
+}
+
+fn into_match_options_domain(options: &ChatTriggerMatchOptionsStorage) -> ChatTriggerMatchOptions {
+    ChatTriggerMatchOptions {
+        case_sensitive: options.case_sensitive,
+        punctuation_as_boundary: options.punctuation_as_boundary,
+        normalise_unicode: options.normalise_unicode,
+    }
+}
+
+fn into_trigger_storage(trigger: &ReactionTrigger) -> ReactionTriggerStorage {
+    match trigger {
+        ReactionTrigger::ChatCommand(chat_command) => ReactionTriggerStorage::ChatCommand { command: chat_command.text.clone(), options: into_match_options_storage(&chat_command.options) },
+        ReactionTrigger::ChatKeyword(chat_keyword) => ReactionTriggerStorage::ChatKeyword { command: chat_keyword.text.clone(), options: into_match_options_storage(&chat_keyword.options) },
+        ReactionTrigger::ChatPattern(chat_pattern) => ReactionTriggerStorage::ChatPattern { pattern: chat_pattern.pattern.clone() },
+        ReactionTrigger::Action(action_trigger) => ReactionTriggerStorage::Action { id: action_trigger.id.clone(), name: action_trigger.name.clone() },
+        ReactionTrigger::ActionRange(action_range_trigger) => ReactionTriggerStorage::ActionRange { name: action_range_trigger.name.clone(), min: action_range_trigger.min, max: action_range_trigger.max },
+        ReactionTrigger::StreamEvent(stream_event_trigger) => ReactionTriggerStorage::StreamEvent { kind: into_stream_event_kind_storage(&stream_event_trigger.kind) },
+    }
+}
+
+fn into_trigger_domain(trigger: &ReactionTriggerStorage) -> ReactionTrigger {
+    match trigger {
+        ReactionTriggerStorage::ChatCommand { command, options } => ReactionTrigger::new_chat_command_with_options(command.clone(), into_match_options_domain(options)).unwrap(),
+        ReactionTriggerStorage::ChatKeyword { command, options } => ReactionTrigger::new_chat_keyword_with_options(command.clone(), into_match_options_domain(options)).unwrap(),
+        ReactionTriggerStorage::ChatPattern { pattern } => ReactionTrigger::new_chat_pattern(pattern.clone()).unwrap(),
+        ReactionTriggerStorage::Action { id, name } => ReactionTrigger::new_action(id.clone(), name.clone()).unwrap(),
+        ReactionTriggerStorage::ActionRange { name, min, max } => ReactionTrigger::new_action_range(name.clone(), *min, *max).unwrap(),
+        ReactionTriggerStorage::StreamEvent { kind } => ReactionTrigger::new_stream_event(into_stream_event_kind_domain(kind)),
+    }
+}
+
+fn into_stream_event_kind_storage(kind: &StreamEventKind) -> StreamEventKindStorage {
+    match kind {
+        StreamEventKind::Subscription => StreamEventKindStorage::Subscription,
+        StreamEventKind::Resubscription => StreamEventKindStorage::Resubscription,
+        StreamEventKind::GiftSubscription => StreamEventKindStorage::GiftSubscription,
+        StreamEventKind::MysteryGift => StreamEventKindStorage::MysteryGift,
+        StreamEventKind::Raid => StreamEventKindStorage::Raid,
+        StreamEventKind::Follow => StreamEventKindStorage::Follow,
+        StreamEventKind::HypeTrainStart => StreamEventKindStorage::HypeTrainStart,
+        StreamEventKind::HypeTrainLevelUp => StreamEventKindStorage::HypeTrainLevelUp,
+        StreamEventKind::HypeTrainEnd => StreamEventKindStorage::HypeTrainEnd,
+    }
+}
+
+fn into_stream_event_kind_domain(kind: &StreamEventKindStorage) -> StreamEventKind {
+    match kind {
+        StreamEventKindStorage::Subscription => StreamEventKind::Subscription,
+        StreamEventKindStorage::Resubscription => StreamEventKind::Resubscription,
+        StreamEventKindStorage::GiftSubscription => StreamEventKind::GiftSubscription,
+        StreamEventKindStorage::MysteryGift => StreamEventKind::MysteryGift,
+        StreamEventKindStorage::Raid => StreamEventKind::Raid,
+        StreamEventKindStorage::Follow => StreamEventKind::Follow,
+        StreamEventKindStorage::HypeTrainStart => StreamEventKind::HypeTrainStart,
+        StreamEventKindStorage::HypeTrainLevelUp => StreamEventKind::HypeTrainLevelUp,
+        StreamEventKindStorage::HypeTrainEnd => StreamEventKind::HypeTrainEnd,
+    }
+}
+
+fn into_step_domain(step: &ReactionStepStorage) -> ReactionStepDefinition {
+    match step {
+        ReactionStepStorage::Moving { skip, animation } => ReactionStepDefinition::Moving(MovingReactionStepDefinition {
+            skip: into_skip_domain(skip),
+            animation: into_animation_domain(animation)
+        }),
+        ReactionStepStorage::Talking { skip, emotion_id, alternatives: text } => ReactionStepDefinition::Talking(TalkingReactionStepDefinition {
+            skip: into_skip_domain(skip),
+            emotion_id: EmotionId(emotion_id.clone()),
+            alternatives: into_text_alternatives_domain(text)
+        }),
+        ReactionStepStorage::CompositeTalking { parts } => ReactionStepDefinition::CompositeTalking(parts.iter()
+            .map(|part| TalkingReactionStepDefinition {
+                skip: into_skip_domain(&part.skip),
+                emotion_id: EmotionId(part.emotion_id.clone()),
+                alternatives: into_text_alternatives_domain(&part.alternatives)
+            })
+            .collect()),
+        ReactionStepStorage::ChatMessage { alternatives, reply } => ReactionStepDefinition::ChatMessage(ChatMessageReactionStepDefinition {
+            alternatives: into_text_alternatives_domain(alternatives),
+            reply: *reply
+        })
+    }
+}
+
+fn into_step_storage(step: &ReactionStepDefinition) -> ReactionStepStorage {
+    match step {
+        ReactionStepDefinition::Moving(moving) => ReactionStepStorage::Moving {
+            skip: into_skip_storage(&moving.skip),
+            animation: into_animation_storage(&moving.animation)
+        },
+        ReactionStepDefinition::Talking(talking) => ReactionStepStorage::Talking {
+            skip: into_skip_storage(&talking.skip),
+            emotion_id: talking.emotion_id.0.clone(),
+            alternatives: into_text_alternatives_storage(&talking.alternatives)
+        },
+        ReactionStepDefinition::CompositeTalking(parts) => ReactionStepStorage::CompositeTalking {
+            parts: parts.iter().map(|part| TalkingReactionStepPartStorage {
+                skip: into_skip_storage(&part.skip),
+                emotion_id: part.emotion_id.0.clone(),
+                alternatives: into_text_alternatives_storage(&part.alternatives)
+            }).collect()
+        },
+        ReactionStepDefinition::ChatMessage(chat_message) => ReactionStepStorage::ChatMessage {
+            alternatives: into_text_alternatives_storage(&chat_message.alternatives),
+            reply: chat_message.reply
+        }
+    }
+}
+
+fn into_skip_storage(skip: &ReactionStepSkipDefinition) -> ReactionSkipStorage {
+    match skip {
+        ReactionStepSkipDefinition::ImmediatelyAfter => ReactionSkipStorage::ImmediatelyAfter,
+        ReactionStepSkipDefinition::AfterMilliseconds(ms) => ReactionSkipStorage::AfterMilliseconds(ms.0),
+        ReactionStepSkipDefinition::AfterStepWithExtraMilliseconds(ms) => ReactionSkipStorage::AfterStepWithExtraMilliseconds(ms.0)
+    }
+}
+
+fn into_skip_domain(skip: &ReactionSkipStorage) -> ReactionStepSkipDefinition {
+    match skip {
+        ReactionSkipStorage::ImmediatelyAfter => ReactionStepSkipDefinition::ImmediatelyAfter,
+        ReactionSkipStorage::AfterMilliseconds(ms) => ReactionStepSkipDefinition::AfterMilliseconds(Milliseconds(*ms)),
+        ReactionSkipStorage::AfterStepWithExtraMilliseconds(ms) => ReactionStepSkipDefinition::AfterStepWithExtraMilliseconds(Milliseconds(*ms))
+    }
+}
+
+fn into_text_alternatives_storage(alternatives: &ReactionStepMessageAlternativesDefinition) -> Vec<ReactionStepMessageAlternativeStorage> {
+    alternatives.0
+        .iter()
+        .map(|alternative| ReactionStepMessageAlternativeStorage {
+            message: into_text_storage(&alternative.message),
+            probability: alternative.probability,
+            conditions: alternative.conditions.iter().map(into_condition_storage).collect()
+        })
+        .collect()
+}
+
+fn into_text_storage(text: &ReactionStepMessageDefinition) -> ReactionStepMessageStorage {
+    match text {
+        ReactionStepMessageDefinition::Instant(text) => ReactionStepMessageStorage::Instant { text: text.clone() },
+        ReactionStepMessageDefinition::LetterByLetter(text) => ReactionStepMessageStorage::LetterByLetter { text: text.clone() },
+    }
+}
+
+fn into_text_domain(text: &ReactionStepMessageStorage) -> ReactionStepMessageDefinition {
+    match text {
+        ReactionStepMessageStorage::Instant { text } => ReactionStepMessageDefinition::Instant(text.clone()),
+        ReactionStepMessageStorage::LetterByLetter { text } => ReactionStepMessageDefinition::LetterByLetter(text.clone()),
+    }
+}
+
+fn into_text_alternatives_domain(alternatives: &[ReactionStepMessageAlternativeStorage]) -> ReactionStepMessageAlternativesDefinition {
+    ReactionStepMessageAlternativesDefinition(alternatives
+        .iter()
+        .map(|alternative| ReactionStepMessageAlternativeDefinition {
+            message: into_text_domain(&alternative.message),
+            probability: alternative.probability,
+            conditions: alternative.conditions.iter().map(into_condition_domain).collect()
+        })
+        .collect()
+    )
+}
+
+fn into_condition_storage(condition: &ReactionCondition) -> ReactionConditionStorage {
+    match condition {
+        ReactionCondition::CountMultipleOf(count) => ReactionConditionStorage::CountMultipleOf { count: *count },
+        ReactionCondition::CountEquals(count) => ReactionConditionStorage::CountEquals { count: *count },
+        ReactionCondition::MinimumPermissionLevel(permission_level) => ReactionConditionStorage::MinimumPermissionLevel { permission_level: into_permission_level_storage(permission_level) },
+        ReactionCondition::HasTarget => ReactionConditionStorage::HasTarget,
+    }
+}
+
+fn into_condition_domain(condition: &ReactionConditionStorage) -> ReactionCondition {
+    match condition {
+        ReactionConditionStorage::CountMultipleOf { count } => ReactionCondition::CountMultipleOf(*count),
+        ReactionConditionStorage::CountEquals { count } => ReactionCondition::CountEquals(*count),
+        ReactionConditionStorage::MinimumPermissionLevel { permission_level } => ReactionCondition::MinimumPermissionLevel(into_permission_level_domain(permission_level)),
+        ReactionConditionStorage::HasTarget => ReactionCondition::HasTarget,
+    }
+}
@@ -0,0 +1,110 @@
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use pran_droid_core::domain::credentials::twitch_credentials::TwitchCredentials;
+use pran_droid_core::domain::credentials::twitch_credentials_repository::{CredentialsSaveError, TwitchCredentialsRepository};
+
+pub struct FileTwitchCredentialsRepository {
+    path: PathBuf
+}
+
+impl FileTwitchCredentialsRepository {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct TwitchCredentialsStorage {
+    access_token: String,
+    refresh_token: String,
+    expires_at_seconds: u64,
+}
+
+#[async_trait]
+impl TwitchCredentialsRepository for FileTwitchCredentialsRepository {
+    async fn get(&self) -> Option<TwitchCredentials> {
+        let content = tokio::fs::read(&self.path).await.ok()?;
+
+        serde_json::from_slice::<TwitchCredentialsStorage>(&content).ok()
+            .and_then(into_twitch_credentials_domain)
+    }
+
+    async fn save(&self, credentials: &TwitchCredentials) -> Result<(), CredentialsSaveError> {
+        let content = serde_json::to_vec_pretty(&into_twitch_credentials_storage(credentials))
+            .map_err(|_| CredentialsSaveError::Unexpected)?;
+        if let Some(parent) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.map_err(|_| CredentialsSaveError::Unexpected)?;
+        }
+
+        // Written aside then renamed so a crash never leaves half written credentials
+        let temporary_path = self.path.with_extension("tmp");
+        tokio::fs::write(&temporary_path, content).await.map_err(|_| CredentialsSaveError::Unexpected)?;
+        tokio::fs::rename(&temporary_path, &self.path).await.map_err(|_| CredentialsSaveError::Unexpected)
+    }
+}
+
+fn into_twitch_credentials_domain(storage: TwitchCredentialsStorage) -> Option<TwitchCredentials> {
+    TwitchCredentials::new(
+        storage.access_token,
+        storage.refresh_token,
+        SystemTime::UNIX_EPOCH + Duration::from_secs(storage.expires_at_seconds)
+    ).ok()
+}
+
+fn into_twitch_credentials_storage(credentials: &TwitchCredentials) -> TwitchCredentialsStorage {
+    TwitchCredentialsStorage {
+        access_token: credentials.access_token.clone(),
+        refresh_token: credentials.refresh_token.clone(),
+        expires_at_seconds: credentials.expires_at.duration_since(SystemTime::UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn get_credentials_without_file_returns_none() {
+        let directory = tempfile::tempdir().unwrap();
+        let repository = FileTwitchCredentialsRepository::new(directory.path().join("credentials.json"));
+
+        assert!(repository.get().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn save_credentials_writes_them_to_file_and_get_reads_them_back() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("twitch").join("credentials.json");
+        let repository = FileTwitchCredentialsRepository::new(&path);
+        let credentials = TwitchCredentials::new(String::from("access"), String::from("refresh"), SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)).unwrap();
+
+        repository.save(&credentials).await.unwrap();
+
+        assert_eq!(repository.get().await, Some(credentials));
+        assert!(path.exists());
+        assert!(!path.with_extension("tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn save_credentials_replaces_previous_ones() {
+        let directory = tempfile::tempdir().unwrap();
+        let repository = FileTwitchCredentialsRepository::new(directory.path().join("credentials.json"));
+        repository.save(&TwitchCredentials::new(String::from("old"), String::from("old_refresh"), SystemTime::UNIX_EPOCH).unwrap()).await.unwrap();
+        let refreshed = TwitchCredentials::new(String::from("new"), String::from("new_refresh"), SystemTime::UNIX_EPOCH + Duration::from_secs(60)).unwrap();
+
+        repository.save(&refreshed).await.unwrap();
+
+        assert_eq!(repository.get().await, Some(refreshed));
+    }
+
+    #[tokio::test]
+    async fn get_credentials_from_corrupted_file_returns_none() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("credentials.json");
+        tokio::fs::write(&path, "not json").await.unwrap();
+
+        assert!(FileTwitchCredentialsRepository::new(path).get().await.is_none());
+    }
+}
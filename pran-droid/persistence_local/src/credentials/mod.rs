@@ -0,0 +1 @@
+pub mod file_twitch_credentials_repository;
\ No newline at end of file
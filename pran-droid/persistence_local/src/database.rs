@@ -0,0 +1,102 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use rusqlite::{Connection, ErrorCode};
+
+// The api and the brain open the same file, writers wait for each other instead of failing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const MIGRATIONS: [&str; 1] = [
+    "CREATE TABLE reactions (
+        key TEXT PRIMARY KEY NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE emotions (
+        key TEXT PRIMARY KEY NOT NULL,
+        name TEXT NOT NULL UNIQUE,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE images (
+        key TEXT PRIMARY KEY NOT NULL,
+        url TEXT NOT NULL
+    );",
+];
+
+pub struct Database {
+    connection: Mutex<Connection>
+}
+
+impl Database {
+    pub fn open<P: AsRef<Path>>(path: P) -> rusqlite::Result<Self> {
+        Self::migrated(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::migrated(Connection::open_in_memory()?)
+    }
+
+    pub(crate) fn connection(&self) -> MutexGuard<'_, Connection> {
+        self.connection.lock().unwrap()
+    }
+
+    // SQLite calls block, they run on the blocking pool to keep the async runtime free
+    pub(crate) async fn run<T: Send + 'static>(self: &Arc<Self>, query: impl FnOnce(&Connection) -> T + Send + 'static) -> T {
+        let database = Arc::clone(self);
+        tokio::task::spawn_blocking(move || query(&database.connection())).await.expect("Database query panicked")
+    }
+
+    fn migrated(mut connection: Connection) -> rusqlite::Result<Self> {
+        connection.busy_timeout(BUSY_TIMEOUT)?;
+        // In memory databases stay in memory journal mode, the pragma returns the mode applied
+        connection.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+        migrate(&mut connection)?;
+
+        Ok(Self { connection: Mutex::new(connection) })
+    }
+}
+
+fn migrate(connection: &mut Connection) -> rusqlite::Result<()> {
+    let version: i64 = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", (index + 1) as i64)?;
+        transaction.commit()?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn is_constraint_violation(error: &rusqlite::Error) -> bool {
+    matches!(error, rusqlite::Error::SqliteFailure(failure, _) if failure.code == ErrorCode::ConstraintViolation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_new_database_applies_all_migrations() {
+        let database = Database::open_in_memory().unwrap();
+
+        let version: i64 = database.connection().pragma_query_value(None, "user_version", |row| row.get(0)).unwrap();
+
+        assert_eq!(version, MIGRATIONS.len() as i64);
+    }
+
+    #[test]
+    fn open_existing_database_does_not_reapply_migrations() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("pran_droid.sqlite");
+        Database::open(&path).unwrap()
+            .connection()
+            .execute("INSERT INTO images (key, url) VALUES ('image', 'api/images/image')", [])
+            .unwrap();
+
+        let database = Database::open(&path).unwrap();
+
+        let count: i64 = database.connection().query_row("SELECT COUNT(*) FROM images", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+    }
+}
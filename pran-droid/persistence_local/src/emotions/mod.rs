@@ -0,0 +1 @@
+pub mod sqlite_emotion_repository;
\ No newline at end of file
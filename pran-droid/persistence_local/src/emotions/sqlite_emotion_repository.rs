@@ -0,0 +1,131 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use rusqlite::{OptionalExtension, params};
+use uuid::Uuid;
+use pran_droid_core::domain::emotions::emotion::{Emotion, EmotionId, EmotionName};
+use pran_droid_core::domain::emotions::emotion_repository::{EmotionRepository, EmotionDeleteError, EmotionGetAllError, EmotionInsertError, EmotionUpdateError};
+use pran_droid_persistence_deta::emotions::emotion_storage::EmotionStorage;
+use crate::database::{Database, is_constraint_violation};
+
+pub struct SqliteEmotionRepository {
+    database: Arc<Database>
+}
+
+impl SqliteEmotionRepository {
+    pub fn new(database: Arc<Database>) -> Self {
+        Self { database }
+    }
+
+    async fn fetch_one(&self, query: &'static str, value: &str) -> Option<Emotion> {
+        let value = value.to_string();
+        self.database.run(move |connection| connection.query_row(query, params![value], |row| row.get::<_, String>(0)).optional()).await
+            .expect("Unexpected error")
+            .map(into_emotion_domain)
+    }
+}
+
+fn into_emotion_domain(data: String) -> Emotion {
+    serde_json::from_str::<EmotionStorage>(&data).expect("Corrupted emotion data").into()
+}
+
+fn into_emotion_data(emotion: &Emotion) -> String {
+    serde_json::to_string(&EmotionStorage::from(emotion)).expect("Unexpected error")
+}
+
+#[async_trait]
+impl EmotionRepository for SqliteEmotionRepository {
+    fn next_id(&self) -> EmotionId {
+        EmotionId(Uuid::new_v4().to_string())
+    }
+
+    async fn insert(&self, emotion: &Emotion) -> Result<(), EmotionInsertError> {
+        let (key, name, data) = (emotion.id.0.clone(), emotion.name.0.clone(), into_emotion_data(emotion));
+        self.database.run(move |connection| connection.execute("INSERT INTO emotions (key, name, data) VALUES (?1, ?2, ?3)", params![key, name, data])).await
+            .map_err(|error| if is_constraint_violation(&error) { EmotionInsertError::Conflict } else { EmotionInsertError::Unexpected(error.to_string()) })
+            .map(|_| ())
+    }
+
+    async fn update(&self, emotion: &Emotion) -> Result<(), EmotionUpdateError> {
+        let (key, name, data) = (emotion.id.0.clone(), emotion.name.0.clone(), into_emotion_data(emotion));
+        match self.database.run(move |connection| connection.execute("UPDATE emotions SET name = ?2, data = ?3 WHERE key = ?1", params![key, name, data])).await {
+            Ok(0) | Err(_) => Err(EmotionUpdateError::Missing),
+            Ok(_) => Ok(())
+        }
+    }
+
+    async fn get(&self, id: &EmotionId) -> Option<Emotion> {
+        self.fetch_one("SELECT data FROM emotions WHERE key = ?1", &id.0).await
+    }
+
+    async fn get_all(&self) -> Vec<Emotion> {
+        self.try_get_all().await.expect("Unexpected error")
+    }
+
+    async fn try_get_all(&self) -> Result<Vec<Emotion>, EmotionGetAllError> {
+        let emotions = self.database.run(|connection| {
+            let mut statement = connection.prepare("SELECT data FROM emotions")?;
+            let emotions = statement.query_map([], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<String>>>();
+            emotions
+        }).await.map_err(|error| EmotionGetAllError::Unexpected(error.to_string()))?;
+
+        Ok(emotions.into_iter().map(into_emotion_domain).collect())
+    }
+
+    async fn exists(&self, id: &EmotionId) -> bool {
+        self.get(id).await.is_some()
+    }
+
+    async fn get_by_name(&self, name: &EmotionName) -> Option<Emotion> {
+        self.fetch_one("SELECT data FROM emotions WHERE name = ?1", &name.0).await
+    }
+
+    async fn exists_with_name(&self, name: &EmotionName) -> bool {
+        self.get_by_name(name).await.is_some()
+    }
+
+    async fn delete(&self, id: &EmotionId) -> Result<(), EmotionDeleteError> {
+        let key = id.0.clone();
+        match self.database.run(move |connection| connection.execute("DELETE FROM emotions WHERE key = ?1", params![key])).await {
+            Ok(0) => Err(EmotionDeleteError::Missing),
+            Ok(_) => Ok(()),
+            Err(_) => Err(EmotionDeleteError::Unexpected)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use pran_droid_core::domain::emotions::emotion::{EmotionLayer, MouthPositionName};
+    use pran_droid_core::domain::images::image::ImageId;
+    use super::*;
+
+    #[tokio::test]
+    async fn insert_emotion_can_be_retrieved_by_id_and_name() {
+        let repository = SqliteEmotionRepository::new(Arc::new(Database::open_in_memory().unwrap()));
+        let emotion = Emotion {
+            id: repository.next_id(),
+            name: EmotionName(String::from("happy")),
+            animation: vec![EmotionLayer::Mouth { mouth_mapping: HashMap::from([(MouthPositionName::Ah, ImageId(String::from("ah")))]) }],
+        };
+
+        repository.insert(&emotion).await.unwrap();
+
+        let by_id = repository.get(&emotion.id).await.unwrap();
+        let by_name = repository.get_by_name(&emotion.name).await.unwrap();
+        assert_eq!(by_id.id, emotion.id);
+        assert_eq!(by_name.id, emotion.id);
+        assert!(matches!(&by_id.animation[0], EmotionLayer::Mouth { mouth_mapping } if mouth_mapping.get(&MouthPositionName::Ah) == Some(&ImageId(String::from("ah")))));
+    }
+
+    #[tokio::test]
+    async fn insert_emotion_with_existing_name_conflicts() {
+        let repository = SqliteEmotionRepository::new(Arc::new(Database::open_in_memory().unwrap()));
+        repository.insert(&Emotion { id: repository.next_id(), name: EmotionName(String::from("happy")), animation: vec![] }).await.unwrap();
+
+        let result = repository.insert(&Emotion { id: repository.next_id(), name: EmotionName(String::from("happy")), animation: vec![] }).await;
+
+        assert!(matches!(result, Err(EmotionInsertError::Conflict)));
+    }
+}
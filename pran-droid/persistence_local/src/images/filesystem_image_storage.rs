@@ -0,0 +1,96 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use async_trait::async_trait;
+use pran_droid_core::domain::images::image::{ImageId, ImageUrl};
+use pran_droid_core::domain::images::image_storage::{ImageData, ImageStorage, StorageDeleteError, StorageSaveError};
+
+const URL_PREFIX: &str = "api/images/";
+
+pub struct FilesystemImageStorage {
+    base_path: PathBuf
+}
+
+impl FilesystemImageStorage {
+    pub fn new<P: Into<PathBuf>>(base_path: P) -> Self {
+        Self { base_path: base_path.into() }
+    }
+
+    fn file_path(&self, file_name: &str) -> Option<PathBuf> {
+        if file_name.is_empty() || file_name == "." || file_name == ".." || file_name.contains(['/', '\\']) {
+            return None;
+        }
+
+        Some(self.base_path.join(format!("{}.png", file_name)))
+    }
+
+    fn file_path_from_url(&self, url: &ImageUrl) -> Option<PathBuf> {
+        url.0.strip_prefix(URL_PREFIX).and_then(|file_name| self.file_path(file_name))
+    }
+}
+
+#[async_trait]
+impl ImageStorage for FilesystemImageStorage {
+    async fn get(&self, url: &ImageUrl) -> Option<ImageData> {
+        let path = self.file_path_from_url(url)?;
+
+        tokio::fs::read(path).await.map(ImageData).ok()
+    }
+
+    async fn save(&self, id: &ImageId, data: &ImageData) -> Result<ImageUrl, StorageSaveError> {
+        let path = self.file_path(&id.0).ok_or(StorageSaveError::Unexpected)?;
+        tokio::fs::create_dir_all(&self.base_path).await.map_err(|_| StorageSaveError::Unexpected)?;
+
+        tokio::fs::write(path, &data.0).await
+            .map(|_| ImageUrl(format!("{}{}", URL_PREFIX, id.0)))
+            .map_err(|_| StorageSaveError::Unexpected)
+    }
+
+    async fn delete(&self, url: &ImageUrl) -> Result<(), StorageDeleteError> {
+        let path = self.file_path_from_url(url).ok_or(StorageDeleteError::Missing)?;
+
+        tokio::fs::remove_file(path).await
+            .map_err(|error| match error.kind() {
+                ErrorKind::NotFound => StorageDeleteError::Missing,
+                _ => StorageDeleteError::Unexpected
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn save_image_writes_it_to_base_path_and_returns_api_url() {
+        let directory = tempfile::tempdir().unwrap();
+        let storage = FilesystemImageStorage::new(directory.path().join("images"));
+
+        let url = storage.save(&ImageId(String::from("image")), &ImageData(vec![1, 2, 3])).await.unwrap();
+
+        assert_eq!(url.0, "api/images/image");
+        assert_eq!(storage.get(&url).await.map(|data| data.0), Some(vec![1, 2, 3]));
+        assert!(directory.path().join("images").join("image.png").exists());
+    }
+
+    #[tokio::test]
+    async fn save_image_with_id_escaping_base_path_errors() {
+        let directory = tempfile::tempdir().unwrap();
+        let storage = FilesystemImageStorage::new(directory.path());
+
+        let result = storage.save(&ImageId(String::from("../image")), &ImageData(vec![1])).await;
+
+        assert!(matches!(result, Err(StorageSaveError::Unexpected)));
+    }
+
+    #[tokio::test]
+    async fn delete_image_removes_file_and_reports_missing_afterwards() {
+        let directory = tempfile::tempdir().unwrap();
+        let storage = FilesystemImageStorage::new(directory.path());
+        let url = storage.save(&ImageId(String::from("image")), &ImageData(vec![1])).await.unwrap();
+
+        storage.delete(&url).await.unwrap();
+
+        assert!(storage.get(&url).await.is_none());
+        assert!(matches!(storage.delete(&url).await, Err(StorageDeleteError::Missing)));
+    }
+}
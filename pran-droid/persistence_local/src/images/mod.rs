@@ -0,0 +1,2 @@
+pub mod sqlite_image_repository;
+pub mod filesystem_image_storage;
\ No newline at end of file
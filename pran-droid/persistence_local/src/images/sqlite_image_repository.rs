@@ -0,0 +1,62 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use rusqlite::{OptionalExtension, params, Row};
+use pran_droid_core::domain::images::image::{Image, ImageId, ImageUrl};
+use pran_droid_core::domain::images::image_repository::{DeleteError, GetAllError, ImageRepository, InsertError};
+use crate::database::{Database, is_constraint_violation};
+
+pub struct SqliteImageRepository {
+    database: Arc<Database>
+}
+
+impl SqliteImageRepository {
+    pub fn new(database: Arc<Database>) -> Self {
+        Self { database }
+    }
+}
+
+fn into_image(row: &Row<'_>) -> rusqlite::Result<Image> {
+    Ok(Image { id: ImageId(row.get(0)?), url: ImageUrl(row.get(1)?) })
+}
+
+#[async_trait]
+impl ImageRepository for SqliteImageRepository {
+    async fn get(&self, id: &ImageId) -> Option<Image> {
+        let key = id.0.clone();
+        self.database.run(move |connection| connection.query_row("SELECT key, url FROM images WHERE key = ?1", params![key], into_image).optional()).await
+            .expect("Unexpected error")
+    }
+
+    async fn get_all(&self) -> Vec<Image> {
+        self.try_get_all().await.expect("Unexpected error")
+    }
+
+    async fn try_get_all(&self) -> Result<Vec<Image>, GetAllError> {
+        self.database.run(|connection| {
+            let mut statement = connection.prepare("SELECT key, url FROM images")?;
+            let images = statement.query_map([], into_image)?
+                .collect::<rusqlite::Result<Vec<Image>>>();
+            images
+        }).await.map_err(|error| GetAllError::Unexpected(error.to_string()))
+    }
+
+    async fn has(&self, id: &ImageId) -> bool {
+        self.get(id).await.is_some()
+    }
+
+    async fn insert(&self, image: &Image) -> Result<(), InsertError> {
+        let (key, url) = (image.id.0.clone(), image.url.0.clone());
+        self.database.run(move |connection| connection.execute("INSERT INTO images (key, url) VALUES (?1, ?2)", params![key, url])).await
+            .map_err(|error| if is_constraint_violation(&error) { InsertError::Conflict } else { InsertError::Unexpected })
+            .map(|_| ())
+    }
+
+    async fn delete(&self, id: &ImageId) -> Result<(), DeleteError> {
+        let key = id.0.clone();
+        match self.database.run(move |connection| connection.execute("DELETE FROM images WHERE key = ?1", params![key])).await {
+            Ok(0) => Err(DeleteError::Missing),
+            Ok(_) => Ok(()),
+            Err(_) => Err(DeleteError::Unexpected)
+        }
+    }
+}
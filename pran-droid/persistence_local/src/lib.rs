@@ -0,0 +1,6 @@
+pub mod database;
+pub mod images;
+pub mod reactions;
+pub mod emotions;
+
+pub mod credentials;
\ No newline at end of file
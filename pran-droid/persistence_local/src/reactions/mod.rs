@@ -0,0 +1 @@
+pub mod sqlite_reaction_repository;
\ No newline at end of file
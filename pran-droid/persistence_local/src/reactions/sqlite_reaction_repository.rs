@@ -0,0 +1,235 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use rusqlite::{Connection, OptionalExtension, params};
+use uuid::Uuid;
+use pran_droid_core::domain::reactions::reaction_definition::{ReactionDefinition, ReactionDefinitionId, ReactionTrigger};
+use pran_droid_core::domain::reactions::reaction_definition_repository::{ReactionDefinitionRepository, ReactionDeleteError, ReactionGetAllError, ReactionInsertError, ReactionUpdateError};
+use pran_droid_persistence_deta::reactions::reaction_storage::ReactionStorage;
+use crate::database::{Database, is_constraint_violation};
+
+pub struct SqliteReactionRepository {
+    database: Arc<Database>,
+}
+
+impl SqliteReactionRepository {
+    pub fn new(database: Arc<Database>) -> Self {
+        Self { database }
+    }
+
+    async fn fetch_all(&self) -> rusqlite::Result<Vec<ReactionStorage>> {
+        self.database.run(fetch_all).await
+    }
+}
+
+fn fetch_all(connection: &Connection) -> rusqlite::Result<Vec<ReactionStorage>> {
+    let mut statement = connection.prepare("SELECT data FROM reactions")?;
+    let reactions = statement.query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+
+    Ok(reactions.iter().map(|data| into_reaction_storage(data)).collect())
+}
+
+fn into_reaction_storage(data: &str) -> ReactionStorage {
+    serde_json::from_str(data).expect("Corrupted reaction data")
+}
+
+fn into_reaction_data(reaction: &ReactionDefinition) -> String {
+    serde_json::to_string(&ReactionStorage::from(reaction)).expect("Unexpected error")
+}
+
+#[async_trait]
+impl ReactionDefinitionRepository for SqliteReactionRepository {
+    fn next_id(&self) -> ReactionDefinitionId {
+        ReactionDefinitionId(Uuid::new_v4().to_string())
+    }
+
+    async fn insert(&self, reaction: &ReactionDefinition) -> Result<(), ReactionInsertError> {
+        let (key, data) = (reaction.id.0.clone(), into_reaction_data(reaction));
+        self.database.run(move |connection| connection.execute("INSERT INTO reactions (key, data) VALUES (?1, ?2)", params![key, data])).await
+            .map_err(|error| if is_constraint_violation(&error) { ReactionInsertError::Conflict } else { ReactionInsertError::Unexpected })
+            .map(|_| ())
+    }
+
+    async fn exists_with_trigger(&self, trigger: &ReactionTrigger) -> bool {
+        self.fetch_all().await
+            .expect("Unexpected error")
+            .iter()
+            .map(Into::<ReactionDefinition>::into)
+            .any(|reaction| reaction.triggers.iter().any(|stored_trigger| stored_trigger.conflicts_with(trigger)))
+    }
+
+    async fn other_exists_with_trigger(&self, trigger: &ReactionTrigger, excluded_reaction_definition_id: &ReactionDefinitionId) -> bool {
+        self.fetch_all().await
+            .expect("Unexpected error")
+            .iter()
+            .filter(|reaction| reaction.key != excluded_reaction_definition_id.0)
+            .map(Into::<ReactionDefinition>::into)
+            .any(|reaction| reaction.triggers.iter().any(|stored_trigger| stored_trigger.conflicts_with(trigger)))
+    }
+
+    async fn get(&self, id: &ReactionDefinitionId) -> Option<ReactionDefinition> {
+        let key = id.0.clone();
+        self.database.run(move |connection| connection.query_row("SELECT data FROM reactions WHERE key = ?1", params![key], |row| row.get::<_, String>(0)).optional()).await
+            .expect("Unexpected error")
+            .map(|data| into_reaction_storage(&data).into())
+    }
+
+    async fn get_all(&self) -> Vec<ReactionDefinition> {
+        self.try_get_all().await.expect("Unexpected error")
+    }
+
+    async fn try_get_all(&self) -> Result<Vec<ReactionDefinition>, ReactionGetAllError> {
+        self.fetch_all().await
+            .map_err(|error| ReactionGetAllError::Unexpected(error.to_string()))
+            .map(|reactions| reactions.into_iter().map(Into::into).collect())
+    }
+
+    async fn update(&self, reaction: &ReactionDefinition) -> Result<(), ReactionUpdateError> {
+        let (key, data) = (reaction.id.0.clone(), into_reaction_data(reaction));
+        match self.database.run(move |connection| connection.execute("UPDATE reactions SET data = ?2 WHERE key = ?1", params![key, data])).await {
+            Ok(0) | Err(_) => Err(ReactionUpdateError::Missing),
+            Ok(_) => Ok(())
+        }
+    }
+
+    async fn delete(&self, id: &ReactionDefinitionId) -> Result<(), ReactionDeleteError> {
+        let key = id.0.clone();
+        match self.database.run(move |connection| connection.execute("DELETE FROM reactions WHERE key = ?1", params![key])).await {
+            Ok(0) => Err(ReactionDeleteError::Missing),
+            Ok(_) => Ok(()),
+            Err(_) => Err(ReactionDeleteError::Unexpected)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use pran_droid_core::domain::emotions::emotion::EmotionId;
+    use pran_droid_core::domain::reactions::reaction::Milliseconds;
+    use pran_droid_core::domain::reactions::reaction_definition::{ChatMessageReactionStepDefinition, OverlayTarget, ReactionCondition, ReactionCooldown, ReactionPermissionLevel, ReactionStepDefinition, ReactionStepSkipDefinition, ReactionStepMessageAlternativeDefinition, ReactionStepMessageAlternativesDefinition, ReactionStepMessageDefinition, StreamEventKind, TalkingReactionStepDefinition};
+    use super::*;
+
+    #[tokio::test]
+    async fn insert_reaction_round_trips_steps_and_triggers() {
+        let repository = SqliteReactionRepository::new(Arc::new(Database::open_in_memory().unwrap()));
+        let reaction = create_reaction(&repository, "!hello");
+
+        repository.insert(&reaction).await.unwrap();
+
+        let stored = repository.get(&reaction.id).await.unwrap();
+        assert_eq!(stored.triggers, reaction.triggers);
+        assert_eq!(stored.steps.len(), 1);
+        assert_eq!(stored.cooldown, reaction.cooldown);
+        assert!(repository.exists_with_trigger(&reaction.triggers[0]).await);
+        assert!(!repository.other_exists_with_trigger(&reaction.triggers[0], &reaction.id).await);
+    }
+
+    #[tokio::test]
+    async fn insert_reaction_round_trips_alternative_conditions() {
+        let repository = SqliteReactionRepository::new(Arc::new(Database::open_in_memory().unwrap()));
+        let mut reaction = create_reaction(&repository, "!hello");
+        let conditions = vec![
+            ReactionCondition::CountMultipleOf(100),
+            ReactionCondition::CountEquals(1000),
+            ReactionCondition::MinimumPermissionLevel(ReactionPermissionLevel::Mods),
+            ReactionCondition::HasTarget,
+        ];
+        if let ReactionStepDefinition::Talking(talking_step) = &mut reaction.steps[0] {
+            talking_step.alternatives.0[0].conditions = conditions.clone();
+        }
+
+        repository.insert(&reaction).await.unwrap();
+
+        let stored = repository.get(&reaction.id).await.unwrap();
+        assert!(matches!(&stored.steps[0], ReactionStepDefinition::Talking(talking_step) if talking_step.alternatives.0[0].conditions == conditions));
+    }
+
+    #[tokio::test]
+    async fn insert_reaction_round_trips_action_range_and_stream_event_triggers() {
+        let repository = SqliteReactionRepository::new(Arc::new(Database::open_in_memory().unwrap()));
+        let mut reaction = create_reaction(&repository, "!hello");
+        reaction.triggers = vec![
+            ReactionTrigger::new_action_range(String::from("bits"), 100, Some(499)).unwrap(),
+            ReactionTrigger::new_action_range(String::from("bits"), 500, None).unwrap(),
+            ReactionTrigger::new_stream_event(StreamEventKind::Raid),
+        ];
+
+        repository.insert(&reaction).await.unwrap();
+
+        let stored = repository.get(&reaction.id).await.unwrap();
+        assert_eq!(stored.triggers, reaction.triggers);
+    }
+
+    #[tokio::test]
+    async fn insert_reaction_round_trips_chat_message_steps() {
+        let repository = SqliteReactionRepository::new(Arc::new(Database::open_in_memory().unwrap()));
+        let mut reaction = create_reaction(&repository, "!hello");
+        reaction.steps = vec![ReactionStepDefinition::ChatMessage(ChatMessageReactionStepDefinition {
+            alternatives: ReactionStepMessageAlternativesDefinition::new_single(ReactionStepMessageDefinition::Instant(String::from("hi ${user}"))),
+            reply: true,
+        })];
+
+        repository.insert(&reaction).await.unwrap();
+
+        let stored = repository.get(&reaction.id).await.unwrap();
+        assert!(matches!(&stored.steps[0], ReactionStepDefinition::ChatMessage(chat_message_step)
+            if chat_message_step.reply && chat_message_step.alternatives.0[0].message.get_text() == "hi ${user}"));
+    }
+
+    #[tokio::test]
+    async fn insert_reaction_round_trips_overlay_targets() {
+        let repository = SqliteReactionRepository::new(Arc::new(Database::open_in_memory().unwrap()));
+        let mut reaction = create_reaction(&repository, "!hello");
+        reaction.overlay_targets = vec![OverlayTarget(String::from("main-scene")), OverlayTarget(String::from("alerts"))];
+
+        repository.insert(&reaction).await.unwrap();
+
+        let stored = repository.get(&reaction.id).await.unwrap();
+        assert_eq!(stored.overlay_targets, reaction.overlay_targets);
+    }
+
+    #[tokio::test]
+    async fn update_and_delete_not_existing_reaction_report_missing() {
+        let repository = SqliteReactionRepository::new(Arc::new(Database::open_in_memory().unwrap()));
+        let reaction = create_reaction(&repository, "!hello");
+
+        assert!(matches!(repository.update(&reaction).await, Err(ReactionUpdateError::Missing)));
+        assert!(matches!(repository.delete(&reaction.id).await, Err(ReactionDeleteError::Missing)));
+    }
+
+    #[tokio::test]
+    async fn delete_reaction_removes_only_that_reaction() {
+        let repository = SqliteReactionRepository::new(Arc::new(Database::open_in_memory().unwrap()));
+        let hello = create_reaction(&repository, "!hello");
+        let bye = create_reaction(&repository, "!bye");
+        repository.insert(&hello).await.unwrap();
+        repository.insert(&bye).await.unwrap();
+
+        repository.delete(&hello.id).await.unwrap();
+
+        assert!(repository.get(&hello.id).await.is_none());
+        assert_eq!(repository.get_all().await.len(), 1);
+    }
+
+    fn create_reaction(repository: &SqliteReactionRepository, command: &str) -> ReactionDefinition {
+        ReactionDefinition {
+            id: repository.next_id(),
+            is_disabled: false,
+            triggers: vec![ReactionTrigger::new_chat_command(String::from(command)).unwrap()],
+            steps: vec![ReactionStepDefinition::Talking(TalkingReactionStepDefinition {
+                emotion_id: EmotionId(String::from("happy")),
+                skip: ReactionStepSkipDefinition::AfterMilliseconds(Milliseconds(100)),
+                alternatives: ReactionStepMessageAlternativesDefinition(vec![ReactionStepMessageAlternativeDefinition {
+                    message: ReactionStepMessageDefinition::Instant(String::from("hello")),
+                    probability: None,
+                    conditions: vec![],
+                }]),
+            })],
+            count: 0,
+            cooldown: ReactionCooldown { global: Duration::from_secs(1), per_user: Duration::from_secs(2) },
+            permission_level: ReactionPermissionLevel::Everyone,
+            overlay_targets: vec![],
+        }
+    }
+}